@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::config_options::{
+    ConfigOptions, IncludeResolution, MultilineMargin, SpecStrictness, UrlOptions,
+};
+use crate::load_report::{LoadReport, LoadWarning};
+use crate::parser::loader;
+use crate::raw::raw_object::RawObject;
+use crate::resolve_options::{ResolveOptions, SelfReference};
+use crate::syntax::Syntax;
+use crate::value::Value;
+
+/// The parts of [`ConfigOptions`] that change how a file is loaded. The `compare`
+/// function is recorded by the order it puts the syntaxes in. Loads with classpath
+/// providers, URL handlers, resolvers, external roots or merge strategies, which can't be
+/// compared, aren't cached at all.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    loader: &'static str,
+    files: Vec<PathBuf>,
+    use_system_environment: bool,
+    classpath: Vec<String>,
+    max_nesting_depth: usize,
+    max_include_depth: usize,
+    extensions: Vec<Syntax>,
+    /// The syntaxes sorted with [`ConfigOptions::compare`].
+    order: Vec<Syntax>,
+    resolve: ResolveKey,
+    multiline_margin: MultilineMargin,
+    extended_numbers: bool,
+    non_finite_numbers: bool,
+    include_resolution: IncludeResolution,
+    expand_home: bool,
+    cache_includes: bool,
+    url_options: UrlOptions,
+    limits: [usize; 4],
+}
+
+/// The parts of [`ConfigOptions::resolve`] that change how a loaded file is resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ResolveKey {
+    use_system_environment: bool,
+    allow_unresolved: bool,
+    max_substitution_depth: usize,
+    /// The overrides and the defaults for missing substitutions rendered as HOCON, sorted
+    /// by path.
+    overrides: Vec<(String, String)>,
+    defaults_for_missing: Vec<(String, String)>,
+    self_reference: SelfReference,
+    environment: Option<BTreeMap<String, String>>,
+    spec_strictness: SpecStrictness,
+    intern_keys: bool,
+}
+
+impl ResolveKey {
+    /// Returns `None` if `options` can't be compared.
+    fn new(options: &ResolveOptions) -> Option<ResolveKey> {
+        if options.resolver.is_some()
+            || options.external_root.is_some()
+            || options.merge_strategies.is_some()
+        {
+            return None;
+        }
+        let render = |values: &HashMap<String, Value>| {
+            let mut values = values
+                .iter()
+                .map(|(path, value)| (path.clone(), value.to_hocon_string()))
+                .collect::<Vec<_>>();
+            values.sort();
+            values
+        };
+        Some(ResolveKey {
+            use_system_environment: options.use_system_environment,
+            allow_unresolved: options.allow_unresolved,
+            max_substitution_depth: options.max_substitution_depth,
+            overrides: render(&options.overrides),
+            defaults_for_missing: render(&options.defaults_for_missing),
+            self_reference: options.self_reference,
+            environment: options.environment.as_deref().cloned(),
+            spec_strictness: options.spec_strictness,
+            intern_keys: options.intern_keys,
+        })
+    }
+}
+
+struct CacheEntry {
+    /// Every file read for the value, included ones too, and the files optional includes
+    /// looked up without finding anything, with their modification times.
+    sources: Vec<(PathBuf, Option<SystemTime>)>,
+    value: Value,
+}
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, CacheEntry>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, CacheEntry>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Returns the cached value of `path` if none of the files it was loaded from changed
+/// since it was cached, otherwise loads `path` with `load`, resolves it and caches the
+/// result.
+///
+/// `loader` tells apart the functions that load a path differently. Paths that are not
+/// found on disk, e.g. classpath resources, are never cached, and neither are values with
+/// a source that isn't a file, e.g. an included URL. Neither are loads with
+/// [`ConfigOptions::classpath_providers`] or [`ConfigOptions::url_handlers`], which can't
+/// be compared, so any include may resolve differently with others, or with a resolver,
+/// an external root or merge strategies in [`ConfigOptions::resolve`], which can't be
+/// compared either.
+pub(crate) fn get_or_load<F>(
+    loader: &'static str,
+    path: &Path,
+    options: &ConfigOptions,
+    load: F,
+) -> crate::Result<Value>
+where
+    F: FnOnce() -> crate::Result<RawObject<'static>>,
+{
+    let resolve = |object| Config::resolve_object::<Value>(object, options);
+    let Some(resolve_key) = ResolveKey::new(&options.resolve) else {
+        return resolve(load()?);
+    };
+    if !options.classpath_providers.is_empty() || !options.url_handlers.is_empty() {
+        return resolve(load()?);
+    }
+    let Ok(files) = loader::config_files(path, &options.extensions) else {
+        return resolve(load()?);
+    };
+    let files = files
+        .into_iter()
+        .map(|file| std::fs::canonicalize(&file).unwrap_or(file))
+        .collect::<Vec<_>>();
+    let mut order = vec![Syntax::Hocon, Syntax::Json, Syntax::Properties];
+    order.sort_by(|a, b| (options.compare)(a, b));
+    let key = CacheKey {
+        loader,
+        files: files.clone(),
+        use_system_environment: options.use_system_environment,
+        classpath: options.classpath.to_vec(),
+        max_nesting_depth: options.max_nesting_depth,
+        max_include_depth: options.max_include_depth,
+        extensions: options.extensions.to_vec(),
+        order,
+        resolve: resolve_key,
+        multiline_margin: options.multiline_margin,
+        extended_numbers: options.extended_numbers,
+        non_finite_numbers: options.non_finite_numbers,
+        include_resolution: options.include_resolution,
+        expand_home: options.expand_home,
+        cache_includes: options.cache_includes,
+        url_options: options.url_options.clone(),
+        limits: [
+            options.max_input_bytes,
+            options.max_object_entries,
+            options.max_array_len,
+            options.max_nodes,
+        ],
+    };
+    {
+        let cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = cache.get(&key)
+            && entry
+                .sources
+                .iter()
+                .all(|(file, modified)| self::modified(file) == *modified)
+        {
+            return Ok(entry.value.clone());
+        }
+    }
+    let object = load()?;
+    let sources = sources(&object, &files);
+    let value = resolve(object)?;
+    if let Some(sources) = sources {
+        let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+        let entry = CacheEntry {
+            sources,
+            value: value.clone(),
+        };
+        cache.insert(key, entry);
+    }
+    Ok(value)
+}
+
+/// The files `object` was loaded from with their modification times: `files`, the files
+/// of its fields and the files looked up by its optional includes that found nothing.
+/// `None` if a field comes from a source that isn't a file.
+fn sources(object: &RawObject, files: &[PathBuf]) -> Option<Vec<(PathBuf, Option<SystemTime>)>> {
+    let report = LoadReport::new(object, Default::default());
+    let mut sources = files.to_vec();
+    for source in &report.sources {
+        let file = PathBuf::from(&*source.name());
+        if !file.is_file() {
+            return None;
+        }
+        sources.push(std::fs::canonicalize(&file).unwrap_or(file));
+    }
+    for warning in report.warnings {
+        if let LoadWarning::MissingInclude { searched, .. } = warning {
+            sources.extend(searched.into_iter().map(PathBuf::from));
+        }
+    }
+    sources.sort();
+    sources.dedup();
+    let sources = sources
+        .into_iter()
+        .map(|file| {
+            let modified = modified(&file);
+            (file, modified)
+        })
+        .collect();
+    Some(sources)
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    std::fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+/// Removes every cached value.
+pub(crate) fn invalidate() {
+    cache().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
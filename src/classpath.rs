@@ -0,0 +1,307 @@
+//! Sources of classpath resources besides the directories of
+//! [`crate::ConfigOptions::classpath`], e.g. configuration files embedded in the binary the
+//! way a `reference.conf` ships inside a JVM jar.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+/// Looks up classpath resources by their relative path, e.g. `reference.conf`.
+///
+/// Resources are probed like files: for a path without a known extension, the path with
+/// each of [`crate::ConfigOptions::extensions`] appended is read.
+///
+/// The async loaders, e.g. `Config::load_async`, call providers on the thread
+/// polling the load, so providers must not block: serve resources from memory, and give
+/// directories on disk as [`crate::ConfigOptions::classpath`], which is read without
+/// blocking.
+pub trait ClasspathProvider: Debug {
+    /// Returns the contents of the resource at `path`, or `None` if there is none.
+    fn read(&self, path: &Path) -> std::io::Result<Option<Cow<'static, [u8]>>>;
+
+    /// Like [`ClasspathProvider::read`], but reads at most `limit` bytes: the contents of a
+    /// longer resource are cut short. The loader asks for one byte more than
+    /// [`crate::ConfigOptions::max_input_bytes`] allows, to tell a resource that is too
+    /// large without reading it whole. By default, the resource is read whole.
+    fn read_limited(&self, path: &Path, limit: u64) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+        let _ = limit;
+        self.read(path)
+    }
+
+    /// Returns the name of the resource at `path`, recorded as the origin of its values.
+    /// Names must be unique across providers, they identify the resource while loading.
+    fn name(&self, path: &Path) -> String;
+
+    /// The directory on disk the resources are read from, if any. Resources of a
+    /// directory are loaded like files, e.g. their relative includes are resolved next to
+    /// them, rather than through [`ClasspathProvider::read`].
+    fn directory(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Resources in a directory on disk, like an entry of [`crate::ConfigOptions::classpath`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryClasspath(pub PathBuf);
+
+impl ClasspathProvider for DirectoryClasspath {
+    fn read(&self, path: &Path) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+        match std::fs::read(self.0.join(path)) {
+            Ok(contents) => Ok(Some(contents.into())),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn name(&self, path: &Path) -> String {
+        self.0.join(path).display().to_string()
+    }
+
+    fn directory(&self) -> Option<&Path> {
+        Some(&self.0)
+    }
+}
+
+/// Resources compiled into the binary, e.g. with `include_bytes!` or collected from an
+/// `include_dir!` directory. Their origins are named `embedded:<path>`.
+///
+/// # Example
+/// ```rust
+/// use std::rc::Rc;
+/// use hocon_rs::classpath::EmbeddedClasspath;
+/// use hocon_rs::{Config, ConfigOptions, Value};
+///
+/// let embedded = EmbeddedClasspath::new().with_file("reference.conf", b"port = 8080");
+/// let options = ConfigOptions {
+///     classpath_providers: Rc::new(vec![Rc::new(embedded)]),
+///     ..Default::default()
+/// };
+/// let value: Value = Config::load("reference", Some(options)).unwrap();
+/// assert_eq!(value.get_by_path(["port"]), Some(&Value::from(8080)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedClasspath {
+    files: HashMap<PathBuf, &'static [u8]>,
+}
+
+impl EmbeddedClasspath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the resource at `path`, relative to the root of the classpath.
+    pub fn with_file(mut self, path: impl Into<PathBuf>, contents: &'static [u8]) -> Self {
+        self.insert(path, contents);
+        self
+    }
+
+    /// Adds the resource at `path`, replacing the one added before.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, contents: &'static [u8]) {
+        self.files.insert(path.into(), contents);
+    }
+}
+
+impl<P> FromIterator<(P, &'static [u8])> for EmbeddedClasspath
+where
+    P: Into<PathBuf>,
+{
+    fn from_iter<T: IntoIterator<Item = (P, &'static [u8])>>(iter: T) -> Self {
+        let files = iter
+            .into_iter()
+            .map(|(path, contents)| (path.into(), contents))
+            .collect();
+        EmbeddedClasspath { files }
+    }
+}
+
+impl ClasspathProvider for EmbeddedClasspath {
+    fn read(&self, path: &Path) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+        Ok(self
+            .files
+            .get(path)
+            .map(|contents| Cow::Borrowed(*contents)))
+    }
+
+    fn name(&self, path: &Path) -> String {
+        format!("embedded:{}", path.display())
+    }
+}
+
+/// Resources inside a zip or jar archive, read without extracting it. Their origins are
+/// named `<archive>!/<entry>`, like the resources of a JVM jar.
+///
+/// Entries of [`crate::ConfigOptions::classpath`] with a `.zip` or `.jar` extension are
+/// read as archives too.
+#[cfg(feature = "zip")]
+#[derive(Debug)]
+pub struct ArchiveClasspath {
+    path: PathBuf,
+    archive: std::cell::RefCell<zip::ZipArchive<std::fs::File>>,
+}
+
+#[cfg(feature = "zip")]
+impl ArchiveClasspath {
+    /// Opens the archive at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let archive = zip::ZipArchive::new(std::fs::File::open(&path)?)?;
+        Ok(ArchiveClasspath {
+            path,
+            archive: archive.into(),
+        })
+    }
+
+    /// Returns `true` if the classpath entry `path` is a zip or jar archive.
+    pub(crate) fn is_archive(path: &Path) -> bool {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        extension.is_some_and(|e| e.eq_ignore_ascii_case("zip") || e.eq_ignore_ascii_case("jar"))
+            && path.is_file()
+    }
+
+    /// The name of the entry at `path`, whose components are always separated by `/`.
+    fn entry_name(path: &Path) -> String {
+        let components = path.components().filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        });
+        components.collect::<Vec<_>>().join("/")
+    }
+}
+
+#[cfg(feature = "zip")]
+impl ClasspathProvider for ArchiveClasspath {
+    fn read(&self, path: &Path) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+        self.read_limited(path, u64::MAX)
+    }
+
+    /// The size recorded in the archive isn't trusted: the entry is read in chunks until
+    /// `limit`, so a forged size can't make it allocate more.
+    fn read_limited(&self, path: &Path, limit: u64) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+        use std::io::Read;
+
+        let mut archive = self.archive.borrow_mut();
+        let entry = match archive.by_name(&Self::entry_name(path)) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let mut contents = vec![];
+        entry.take(limit).read_to_end(&mut contents)?;
+        Ok(Some(contents.into()))
+    }
+
+    fn name(&self, path: &Path) -> String {
+        format!("{}!/{}", self.path.display(), Self::entry_name(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use crate::classpath::{ClasspathProvider, DirectoryClasspath, EmbeddedClasspath};
+    use crate::{Config, ConfigOptions, Result, Value};
+
+    #[test]
+    fn test_classpath_providers() -> Result<()> {
+        let embedded: EmbeddedClasspath = [
+            (
+                "reference.conf",
+                b"a = 1\nb = 1\ninclude \"nested/defaults\"".as_slice(),
+            ),
+            ("nested/defaults.json", br#"{"c": 1}"#.as_slice()),
+        ]
+        .into_iter()
+        .collect();
+        let providers: Vec<Rc<dyn ClasspathProvider>> = vec![
+            Rc::new(DirectoryClasspath(PathBuf::from("resources"))),
+            Rc::new(embedded),
+        ];
+        let options = ConfigOptions {
+            classpath_providers: Rc::new(providers),
+            ..Default::default()
+        };
+        let config = Config::load_raw("reference", Some(options.clone()))?;
+        let origin = config.provenance("c").unwrap().origin.unwrap();
+        assert_eq!(
+            origin.source.as_ref().unwrap().to_string(),
+            "embedded:nested/defaults.json"
+        );
+        let value: Value = Config::load("reference", Some(options.clone()))?;
+        let expected: Value = Config::parse_str("a = 1, b = 1, c = 1", None)?;
+        assert_eq!(value, expected);
+        // The directory comes first and has the file.
+        let value: Value = Config::load("base", Some(options.clone()))?;
+        assert_eq!(value, Config::load("resources/base", None)?);
+        assert!(Config::load::<Value>("missing", Some(options)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_classpath_providers_not_cached() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.conf");
+        std::fs::write(&path, "include classpath(\"reference\")")?;
+        for port in [8080, 9090] {
+            let contents = format!("port = {port}").leak().as_bytes();
+            let embedded = EmbeddedClasspath::new().with_file("reference.conf", contents);
+            let options = ConfigOptions {
+                classpath_providers: Rc::new(vec![Rc::new(embedded)]),
+                cache: true,
+                ..Default::default()
+            };
+            let value: Value = Config::load(&path, Some(options))?;
+            assert_eq!(value.get_by_path(["port"]), Some(&Value::from(port)));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_archive_classpath() -> Result<()> {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir()?;
+        let jar = dir.path().join("app.jar");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&jar)?);
+        let options = zip::write::SimpleFileOptions::default();
+        writer
+            .start_file("reference.conf", options)
+            .map_err(std::io::Error::from)?;
+        writer.write_all(b"a = 1\ninclude \"nested/defaults\"")?;
+        writer
+            .start_file("nested/defaults.json", options)
+            .map_err(std::io::Error::from)?;
+        writer.write_all(br#"{"b": 2}"#)?;
+        writer.finish().map_err(std::io::Error::from)?;
+
+        let options = ConfigOptions {
+            classpath: Rc::new(vec![jar.display().to_string()]),
+            ..Default::default()
+        };
+        let config = Config::load_raw("reference", Some(options.clone()))?;
+        let origin = config.provenance("b").unwrap().origin.unwrap();
+        let name = format!("{}!/nested/defaults.json", jar.display());
+        assert_eq!(origin.source.as_ref().unwrap().to_string(), name);
+        let value: Value = Config::load("reference", Some(options.clone()))?;
+        assert_eq!(value, Config::parse_str::<Value>("a = 1, b = 2", None)?);
+
+        let archive = super::ArchiveClasspath::open(&jar)?;
+        assert!(archive.read("missing.conf".as_ref())?.is_none());
+        let read = archive.read_limited("reference.conf".as_ref(), 3)?;
+        assert_eq!(read.as_deref(), Some(b"a =".as_slice()));
+
+        let options = ConfigOptions {
+            max_input_bytes: 8,
+            ..options
+        };
+        let error = Config::load::<Value>("reference", Some(options)).unwrap_err();
+        assert!(matches!(
+            error,
+            crate::Error::InputTooLarge { max_bytes: 8 }
+        ));
+        Ok(())
+    }
+}
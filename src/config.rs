@@ -1,27 +1,56 @@
-use std::collections::HashMap;
-
-use crate::config_options::ConfigOptions;
+use crate::cache;
+use crate::config_builder::ConfigBuilder;
+use crate::config_options::{ConfigOptions, DirectoryOptions};
+use crate::error::ValidationProblem;
+use crate::fingerprint::Fingerprint;
+use crate::load_report::LoadReport;
+use crate::merge::memo::Memo;
 use crate::merge::object::Object as MObject;
 use crate::merge::value::Value as MValue;
+use crate::origin::{Layer, Origin, Provenance};
 use crate::parser::loader::{self, load_from_path, parse_hocon};
-use crate::parser::read::{StrRead, StreamRead};
+use crate::parser::read::{CopyRead, StrRead, StreamRead};
+use crate::path::render_path;
+use crate::policy::{Policy, Severity};
+use crate::raw::comment::Comment;
 use crate::raw::raw_object::RawObject;
 use crate::raw::raw_string::RawString;
 use crate::raw::raw_value::RawValue;
+use crate::raw::substitution::Substitution;
 use crate::raw::{field::ObjectField, include::Inclusion};
-use crate::value::Value;
+use crate::render::{IoWriter, RenderOptions, Renderer, SaveOptions, write_atomic};
+use crate::resolution_trace::ResolutionTrace;
+use crate::resolve_options::ResolveOptions;
+use crate::scoped_config::ScopedConfig;
+use crate::value::{Map, Value};
 use derive_more::{Deref, DerefMut};
 use serde::de::DeserializeOwned;
 
+/// A parsed configuration whose substitutions are not resolved yet.
+///
+/// This is what parsing and loading without a target type produce (e.g.
+/// [UnresolvedConfig::parse_str_raw] or [UnresolvedConfig::load_raw]). It can be inspected,
+/// extended and merged, and turns into a [ResolvedConfig](crate::ResolvedConfig) with typed getters once resolved:
+///
+/// ```rust
+/// use hocon_rs::{ResolvedConfig, UnresolvedConfig};
+///
+/// let unresolved = UnresolvedConfig::parse_str_raw("port = 80, url = \"http://x:\"${port}", None).unwrap();
+/// let config: ResolvedConfig = unresolved.resolve().unwrap();
+/// assert_eq!(config.get_string("url").unwrap(), "http://x:80");
+/// ```
 #[derive(Debug, Clone, PartialEq, Deref, DerefMut)]
-pub struct Config {
+pub struct UnresolvedConfig {
     #[deref]
     #[deref_mut]
-    object: RawObject,
-    options: ConfigOptions,
+    pub(crate) object: RawObject<'static>,
+    pub(crate) options: ConfigOptions,
 }
 
-impl Config {
+/// The name [UnresolvedConfig] had before the resolved and unresolved stages were split.
+pub type Config = UnresolvedConfig;
+
+impl UnresolvedConfig {
     pub fn new(options: Option<ConfigOptions>) -> Self {
         Self {
             object: Default::default(),
@@ -29,6 +58,11 @@ impl Config {
         }
     }
 
+    /// Returns a [ConfigBuilder] that composes a configuration from several sources.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
     pub fn load<T>(
         path: impl AsRef<std::path::Path>,
         options: Option<ConfigOptions>,
@@ -36,22 +70,721 @@ impl Config {
     where
         T: DeserializeOwned,
     {
-        let raw = loader::load(&path, options.unwrap_or_default(), None)?;
+        let options = options.unwrap_or_default();
+        if options.cache {
+            let value = cache::get_or_load("load", path.as_ref(), &options, || {
+                loader::load(&path, options.clone(), None)
+            })?;
+            return T::deserialize(value);
+        }
+        let raw = loader::load(&path, options.clone(), None)?;
         tracing::debug!("path: {} raw obj: {}", path.as_ref().display(), raw);
-        Self::resolve_object::<T>(raw)
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Loads `path` like [Config::load] and returns, along with the result, a [LoadReport]
+    /// of how it was loaded. The cache of [ConfigOptions::cache] is not used.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ConfigOptions, Value};
+    ///
+    /// let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+    /// let (_, report) = Config::load_with_report::<Value>("include.conf", Some(options)).unwrap();
+    /// assert!(report.stats.includes > 0);
+    /// assert!(report.sources.len() > 1);
+    /// ```
+    pub fn load_with_report<T>(
+        path: impl AsRef<std::path::Path>,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<(T, LoadReport)>
+    where
+        T: DeserializeOwned,
+    {
+        Self::load_reported(options, |options| {
+            loader::load(&path, options.clone(), None)
+        })
+    }
+
+    /// Loads several configuration files and merges them in order, later files win.
+    ///
+    /// Each path is looked up like in [Config::load]. Substitutions are resolved once
+    /// after all files are merged, so a file may refer to values defined in another one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ConfigOptions, Value};
+    ///
+    /// let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+    /// let paths = ["load_all_base.conf", "load_all_override.conf"];
+    /// let value: Value = Config::load_all(paths, Some(options)).unwrap();
+    /// assert_eq!(value.get_by_path(["url"]).unwrap().as_str(), Some("http://example.com:8080"));
+    /// ```
+    pub fn load_all<I, P, T>(paths: I, options: Option<ConfigOptions>) -> crate::Result<T>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<std::path::Path>,
+        T: DeserializeOwned,
+    {
+        let options = options.unwrap_or_default();
+        let raw = loader::load_all(paths, options.clone(), None)?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Loads `path` and overlays the profile specific file and the local file next to it.
+    ///
+    /// For `application.conf` and the profile `dev`, `application.dev.conf` and then
+    /// `application.local.conf` are merged on top if they exist. Overlays are looked up
+    /// like `path` itself, and substitutions are resolved once after merging.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ConfigOptions, Value};
+    ///
+    /// let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+    /// let value: Value = Config::load_with_profile("profile.conf", "dev", Some(options)).unwrap();
+    /// assert_eq!(value.get_by_path(["url"]).unwrap().as_str(), Some("http://dev.example.com:9000"));
+    /// ```
+    pub fn load_with_profile<T>(
+        path: impl AsRef<std::path::Path>,
+        profile: &str,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let overlays = [profile, "local"];
+        let options = options.unwrap_or_default();
+        let raw = loader::load_with_overlays(path, &overlays, options.clone(), None)?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Loads every configuration file in the directory `dir`, like a `conf.d` directory.
+    ///
+    /// Files are merged in lexicographic order of their paths, so later files win, and
+    /// resolved once at the end. By default only `*.conf` files directly inside `dir` are
+    /// loaded, see [DirectoryOptions].
+    pub fn load_dir<T>(
+        dir: impl AsRef<std::path::Path>,
+        dir_options: Option<DirectoryOptions>,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let dir_options = dir_options.unwrap_or_default();
+        let options = options.unwrap_or_default();
+        let raw = loader::load_dir(dir, &dir_options, options.clone(), None)?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Loads the configuration at `path` like [Config::load], but keeps it unresolved so it
+    /// can be inspected (e.g. with [Config::get_origin]) or extended before resolving.
+    pub fn load_raw(
+        path: impl AsRef<std::path::Path>,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<Config> {
+        let options = options.unwrap_or_default();
+        let object = loader::load(&path, options.clone(), None)?;
+        Ok(Config { object, options })
+    }
+
+    /// Parses a HOCON string like [Config::parse_str], but keeps it unresolved.
+    pub fn parse_str_raw(s: &str, options: Option<ConfigOptions>) -> crate::Result<Config> {
+        let options = options.unwrap_or_default();
+        let read = CopyRead::new(s.as_bytes());
+        let object = parse_hocon(read, options.clone(), None)?;
+        Ok(Config { object, options })
+    }
+
+    /// Returns where the value at `path` was defined.
+    ///
+    /// `path` is a dot separated path such as `"a.b"`. Returns `None` if the path doesn't
+    /// exist, is shadowed by a later non-object value, or was added programmatically.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw("a {\n  b = 1\n}", None).unwrap();
+    /// let origin = config.get_origin("a.b").unwrap();
+    /// assert_eq!(origin.position.unwrap().line, 2);
+    /// ```
+    pub fn get_origin(&self, path: &str) -> Option<&Origin> {
+        let path = crate::path::Path::from_str(path).ok()?;
+        self.object.get_origin(&path)
+    }
+
+    /// Returns a view of the configuration rooted at the dot separated `path`, for
+    /// libraries that are handed the whole configuration but only read their own
+    /// namespace. See [ScopedConfig].
+    pub fn scoped<'a>(&'a self, path: &'a str) -> ScopedConfig<'a> {
+        ScopedConfig::new(self, path)
+    }
+
+    /// Returns which layer contributed the value that wins at the dot separated `path`.
+    ///
+    /// This follows the same precedence as resolving, so when several files, includes,
+    /// fallbacks, environment variables or overrides define `path`, the one that wins is
+    /// reported. Returns `None` if the path doesn't exist, or is shadowed by a later
+    /// non-object value. Overrides passed in [ResolveOptions] are only applied when
+    /// resolving and are not reported.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    /// use hocon_rs::origin::Layer;
+    ///
+    /// let config = Config::builder()
+    ///     .add_str("port = 80, host = localhost")
+    ///     .add_override("port", 8080)
+    ///     .build_raw()
+    ///     .unwrap();
+    /// assert_eq!(config.provenance("port").unwrap().layer, Layer::Override);
+    /// assert_eq!(config.provenance("host").unwrap().layer, Layer::String);
+    /// ```
+    pub fn provenance(&self, path: &str) -> Option<Provenance> {
+        let path = crate::path::Path::from_str(path).ok()?;
+        let origin = self.object.find_definition(&path)?.cloned();
+        let layer = origin
+            .as_ref()
+            .map_or(Layer::Programmatic, |origin| origin.layer);
+        let same_content = match &origin {
+            Some(Origin {
+                source: Some(source),
+                same_content,
+                ..
+            }) => {
+                let mut same_content = same_content.clone();
+                self.object.same_content_after(source, &mut same_content);
+                same_content
+            }
+            _ => vec![],
+        };
+        Some(Provenance {
+            layer,
+            origin,
+            same_content,
+        })
+    }
+
+    /// Returns the documentation of the field that wins at the dot separated `path`: the
+    /// comments on the lines directly above it, one line per comment, without the comment
+    /// marker and the space after it. Returns `None` if the path doesn't exist or the field
+    /// has no such comments.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw("# The port to listen on.\nport = 80", None).unwrap();
+    /// assert_eq!(config.doc_comment("port").as_deref(), Some("The port to listen on."));
+    /// ```
+    pub fn doc_comment(&self, path: &str) -> Option<String> {
+        let path = crate::path::Path::from_str(path).ok()?;
+        let (object, index) = self.object.find_field(&path)?;
+        let comments = object.leading_comments(index);
+        if comments.is_empty() {
+            return None;
+        }
+        let lines = comments
+            .iter()
+            .map(|comment| comment.strip_prefix(' ').unwrap_or(comment))
+            .collect::<Vec<_>>();
+        Some(lines.join("\n"))
+    }
+
+    /// Replaces the comments directly above the field that wins at `path` with `doc`,
+    /// written as one `#` comment per line. An empty `doc` removes them.
+    ///
+    /// # Errors
+    /// - [crate::Error::PathNotFound] if nothing is defined at `path`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, RenderOptions};
+    ///
+    /// let mut config = Config::parse_str_raw("port = 80", None).unwrap();
+    /// config.set_doc_comment("port", "The port to listen on.").unwrap();
+    /// let rendered = config.render(&RenderOptions::default());
+    /// assert!(rendered.starts_with("# The port to listen on.\n"));
+    /// ```
+    pub fn set_doc_comment(&mut self, path: &str, doc: &str) -> crate::Result<()> {
+        let key = crate::path::Path::from_str(path)?;
+        let (object, index) = self
+            .object
+            .find_field_mut(&key)
+            .ok_or_else(|| crate::error::Error::PathNotFound(path.to_string()))?;
+        let comments = doc.lines().map(|line| match line {
+            "" => Comment::hash(""),
+            line => Comment::hash(format!(" {line}")),
+        });
+        object.set_leading_comments(index, comments);
+        Ok(())
+    }
+
+    /// Extracts the object at `path` as a new [Config] with the same options.
+    ///
+    /// A copy of the whole configuration is resolved first (see [Config::resolve_partial]),
+    /// so substitutions that point outside the section are already replaced and the
+    /// returned config can be resolved on its own. Substitutions that cannot be resolved
+    /// yet are kept, the ones pointing into the section are rewritten relative to it.
+    ///
+    /// # Errors
+    /// - [crate::Error::PathNotFound] if nothing is defined at `path`.
+    /// - [crate::Error::InvalidConversion] if the value at `path` is not an object.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let config = Config::parse_str_raw("port = 80\nserver { port = ${port} }", None).unwrap();
+    /// let server: Value = config.get_config("server").unwrap().resolve().unwrap();
+    /// assert_eq!(server.get_by_path(["port"]), Some(&Value::from(80)));
+    /// ```
+    pub fn get_config(&self, path: &str) -> crate::Result<Config> {
+        let options = ResolveOptions {
+            allow_unresolved: true,
+            ..Default::default()
+        };
+        let resolved = self.clone().resolve_partial(&options)?;
+        let key = crate::path::Path::from_str(path)?;
+        match resolved.object.get_by_path(&key) {
+            Some(RawValue::Object(object)) => {
+                let prefix = key.iter().map(|p| p.first.to_string()).collect::<Vec<_>>();
+                let mut object = RawValue::Object(object.clone());
+                Self::relativize_substitutions(&mut object, &prefix)?;
+                let RawValue::Object(object) = object else {
+                    unreachable!("relativize never changes the value type")
+                };
+                Ok(Config {
+                    object,
+                    options: self.options.clone(),
+                })
+            }
+            Some(other) => Err(crate::error::Error::InvalidConversion {
+                from: other.ty(),
+                to: "object",
+            }),
+            None => Err(crate::error::Error::PathNotFound(path.to_string())),
+        }
+    }
+
+    /// Strips `prefix` from the paths of all substitutions inside `value` that point into it.
+    fn relativize_substitutions(value: &mut RawValue, prefix: &[String]) -> crate::Result<()> {
+        match value {
+            RawValue::Object(object) => {
+                for field in object.iter_mut() {
+                    if let ObjectField::KeyValue { value, .. } = field {
+                        Self::relativize_substitutions(value, prefix)?;
+                    }
+                }
+            }
+            RawValue::Array(array) => {
+                for value in array.iter_mut() {
+                    Self::relativize_substitutions(value, prefix)?;
+                }
+            }
+            RawValue::Substitution(substitution) => {
+                let path = substitution.path.as_path();
+                if path.len() > prefix.len() && path.iter().zip(prefix).all(|(a, b)| a == b) {
+                    let relative = path[prefix.len()..]
+                        .iter()
+                        .map(|p| RawString::quoted(p.to_string()))
+                        .collect();
+                    substitution.path = RawString::path_expression(relative);
+                }
+            }
+            RawValue::Concat(_) => {
+                let RawValue::Concat(concat) = std::mem::replace(value, RawValue::Null) else {
+                    unreachable!()
+                };
+                let (mut values, spaces) = concat.into_inner();
+                for value in values.iter_mut() {
+                    Self::relativize_substitutions(value, prefix)?;
+                }
+                *value = RawValue::concat(values, spaces)?;
+            }
+            RawValue::AddAssign(add_assign) => {
+                Self::relativize_substitutions(add_assign, prefix)?;
+            }
+            RawValue::Boolean(_)
+            | RawValue::Null
+            | RawValue::String(_)
+            | RawValue::Number(_)
+            | RawValue::NonFinite(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Resolves a copy of this configuration and returns all of its leaf entries as
+    /// `(path, value)` pairs, see [Value::entry_set].
+    pub fn entry_set(&self) -> crate::Result<Vec<(String, Value)>> {
+        let value: Value = self.clone().resolve()?;
+        let entries = value
+            .entry_set()
+            .into_iter()
+            .map(|(path, value)| (path, value.clone()))
+            .collect();
+        Ok(entries)
+    }
+
+    /// Checks that every entry of this configuration also exists in `reference` with a
+    /// compatible type.
+    ///
+    /// Both configurations are resolved for the check. A string in the reference accepts
+    /// any scalar, a number or boolean accepts a string that converts to it, and null is
+    /// compatible with everything. If `restrict_to_paths` is not empty, only entries under
+    /// these path expressions are checked.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::Validation`] listing every violation with its origin, or
+    /// [`crate::Error::InvalidPathExpression`] if one of `restrict_to_paths` is invalid.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let reference = Config::parse_str_raw("port = 80, host = localhost", None).unwrap();
+    /// let config = Config::parse_str_raw("port = abc, hots = example.com", None).unwrap();
+    /// let error = config.check_valid(&reference, &[]).unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid configuration: hots: not defined in the reference (<string>:1:12); \
+    ///      port: expected Number, found String (<string>:1:0)"
+    /// );
+    /// ```
+    pub fn check_valid(&self, reference: &Config, restrict_to_paths: &[&str]) -> crate::Result<()> {
+        let restrict_to_paths = restrict_to_paths
+            .iter()
+            .map(|path| Ok(crate::path::Path::from_str(path)?.keys()))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let restrict_to_paths = restrict_to_paths
+            .iter()
+            .map(|keys| keys.iter().map(String::as_str).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let value: Value = self.clone().resolve()?;
+        let reference: Value = reference.clone().resolve()?;
+        let mut problems = vec![];
+        if let (Value::Object(value), Value::Object(reference)) = (&value, &reference) {
+            self.check_object(
+                value,
+                reference,
+                &restrict_to_paths,
+                &mut vec![],
+                &mut problems,
+            );
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            problems.sort_by(|a, b| a.path.cmp(&b.path));
+            Err(crate::Error::Validation { problems })
+        }
+    }
+
+    fn check_object<'a>(
+        &self,
+        value: &'a Map,
+        reference: &Map,
+        restrict_to_paths: &[Vec<&str>],
+        path: &mut Vec<&'a str>,
+        problems: &mut Vec<ValidationProblem>,
+    ) {
+        for (key, value) in value {
+            path.push(key);
+            let checked = restrict_to_paths.is_empty()
+                || restrict_to_paths.iter().any(|r| path.starts_with(r));
+            let ancestor = restrict_to_paths.iter().any(|r| r.starts_with(path));
+            let problem = match (value, reference.get(key.as_str())) {
+                (Value::Object(value), Some(Value::Object(expected))) => {
+                    if checked || ancestor {
+                        self.check_object(value, expected, restrict_to_paths, path, problems);
+                    }
+                    None
+                }
+                (value, Some(expected)) if self.is_compatible(value, expected) => None,
+                (value, Some(expected)) => {
+                    Some(format!("expected {}, found {}", expected.ty(), value.ty()))
+                }
+                (_, None) => Some("not defined in the reference".to_string()),
+            };
+            if let Some(problem) = problem
+                && checked
+            {
+                let origin = crate::path::Path::from_iter(path.iter())
+                    .ok()
+                    .and_then(|p| self.object.closest_origin(&p).cloned());
+                problems.push(ValidationProblem {
+                    path: render_path(path.iter().copied()),
+                    problem,
+                    origin,
+                });
+            }
+            path.pop();
+        }
+    }
+
+    fn is_compatible(&self, value: &Value, expected: &Value) -> bool {
+        match (value, expected) {
+            _ if value.is_null() || expected.is_null() => true,
+            (Value::Object(_) | Value::Array(_), _) | (_, Value::Object(_) | Value::Array(_)) => {
+                value.ty() == expected.ty()
+            }
+            (_, Value::String(_)) => true,
+            (Value::String(_), Value::Number(_)) => value.as_f64().is_some(),
+            (Value::String(_), Value::Boolean(_)) => value.lenient().as_boolean().is_some(),
+            _ => value.ty() == expected.ty(),
+        }
+    }
+
+    /// Returns the origin that best matches `path`, see [RawObject::closest_origin].
+    ///
+    /// `path` may contain array indices as reported by deserialization errors, e.g.
+    /// `"servers[0].port"`, the search then starts at the array itself (`"servers"`).
+    /// The [Origin::span] of the result covers the entry to point users at.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw("a {\n  b = [1, x]\n}", None).unwrap();
+    /// let origin = config.closest_origin("a.b[1]").unwrap();
+    /// let span = origin.span().unwrap();
+    /// assert_eq!((span.start.line, span.start.column), (2, 2));
+    /// assert_eq!((span.end.line, span.end.column), (2, 12));
+    /// ```
+    pub fn closest_origin(&self, path: &str) -> Option<&Origin> {
+        let path = crate::path::strip_indices(path);
+        let path = crate::path::Path::from_str(path).ok()?;
+        self.object.closest_origin(&path)
+    }
+
+    /// Fills in the origin of an [crate::Error::AtPath] returned when deserializing this
+    /// configuration, other errors are returned as they are.
+    ///
+    /// The origin points at the failing array element if the array was parsed, otherwise at
+    /// the closest field that has an origin. [Config::get] does this on its own, while
+    /// [Config::resolve] consumes the configuration, so keep a copy to annotate its errors.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use hocon_rs::{Config, Error};
+    ///
+    /// let config = Config::parse_str_raw("ports = [\n  80,\n  http\n]", None).unwrap();
+    /// let error = config.clone().resolve::<HashMap<String, Vec<u16>>>().unwrap_err();
+    /// let Error::AtPath { path, origin, .. } = config.annotate_error(error) else {
+    ///     panic!("expected an error with a path");
+    /// };
+    /// assert_eq!(path, "ports[1]");
+    /// assert_eq!(origin.unwrap().position.unwrap().line, 3);
+    /// ```
+    pub fn annotate_error(&self, error: crate::Error) -> crate::Error {
+        match error {
+            crate::Error::AtPath {
+                path,
+                error,
+                origin: None,
+            } => {
+                let origin = crate::path::parse_error_path(&path)
+                    .and_then(|segments| self.object.origin_at(&segments))
+                    .map(Box::new);
+                crate::Error::AtPath {
+                    path,
+                    error,
+                    origin,
+                }
+            }
+            error => error,
+        }
+    }
+
+    /// Nests the whole configuration under the dot separated `path`, a HOCON path
+    /// expression where keys containing dots are quoted like `a."b.c"`.
+    ///
+    /// Substitutions are kept as written, they still refer to paths from the root. Fails
+    /// with [`crate::Error::InvalidPathExpression`] if `path` isn't a path expression.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let config = Config::parse_str_raw("port = 8080", None).unwrap();
+    /// let value: Value = config.at_path("a.\"b.c\"").unwrap().resolve().unwrap();
+    /// assert_eq!(value.get_by_path(["a", "b.c", "port"]), Some(&Value::from(8080)));
+    /// ```
+    pub fn at_path(self, path: &str) -> crate::Result<Config> {
+        let Config { object, options } = self;
+        let keys = crate::path::Path::from_str(path)?.keys();
+        let object = keys.into_iter().rev().fold(object, |object, key| {
+            RawObject::new(vec![ObjectField::key_value(
+                RawString::quoted(key),
+                RawValue::Object(object),
+            )])
+        });
+        Ok(Config { object, options })
+    }
+
+    /// Nests the whole configuration under `key`, which is taken literally, dots included.
+    ///
+    /// Substitutions are kept as written, they still refer to paths from the root.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let config = Config::parse_str_raw("port = 8080", None).unwrap();
+    /// let value: Value = config.at_key("server").resolve().unwrap();
+    /// assert_eq!(value.get_by_path(["server", "port"]), Some(&Value::from(8080)));
+    /// ```
+    pub fn at_key(self, key: impl Into<String>) -> Config {
+        let Config { object, options } = self;
+        let key: String = key.into();
+        let field = ObjectField::key_value(RawString::quoted(key), RawValue::Object(object));
+        Config {
+            object: RawObject::new(vec![field]),
+            options,
+        }
+    }
+
+    /// Layers this configuration on top of `fallback`, like HOCON's `withFallback`.
+    ///
+    /// The layers are merged before substitutions are resolved, so `a += x` and
+    /// self-referential substitutions such as `a = ${a} [x]` can extend values that are
+    /// only defined in `fallback`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let defaults = Config::parse_str_raw("plugins = [core]", None).unwrap();
+    /// let config = Config::parse_str_raw("plugins += extra", None).unwrap();
+    /// let value: Value = config.with_fallback(defaults).resolve().unwrap();
+    /// assert_eq!(value.get_by_path(["plugins"]).unwrap().as_array().unwrap().len(), 2);
+    /// ```
+    pub fn with_fallback(self, fallback: Config) -> Config {
+        Config {
+            object: RawObject::merge(fallback.object, self.object),
+            options: self.options,
+        }
+    }
+
+    /// Computes a [Fingerprint] of the resolved configuration and the files it was loaded
+    /// from, e.g. to record which configuration a deployment started with.
+    ///
+    /// The digest covers the resolved value rendered with sorted keys, so it doesn't depend
+    /// on formatting or field order, plus the name and contents of every contributing
+    /// source. The contents are the ones read while loading, sources are not read again.
+    ///
+    /// # Errors
+    /// Fails if the configuration can't be resolved.
+    pub fn fingerprint(&self) -> crate::Result<Fingerprint> {
+        let value: Value = self.clone().resolve()?;
+        Ok(Fingerprint::new(&self.object, &value))
+    }
+
+    /// Renders the unresolved configuration as HOCON or JSON, see [`RenderOptions`].
+    ///
+    /// Field order, include statements and substitutions are kept as they are.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.object.render(options)
+    }
+
+    /// Renders the unresolved configuration like [Config::render], writing it to `writer` as
+    /// it goes instead of building a [String] first. `writer` is not buffered, wrap it in a
+    /// [std::io::BufWriter] if it makes a system call per write.
+    ///
+    /// # Errors
+    /// - [crate::Error::Io] if writing fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, RenderOptions};
+    ///
+    /// let config = Config::parse_str_raw("a = 1", None).unwrap();
+    /// let mut out = Vec::new();
+    /// config.write_to(&mut out, &RenderOptions::default()).unwrap();
+    /// assert_eq!(out, b"a = 1");
+    /// ```
+    pub fn write_to<W: std::io::Write>(
+        &self,
+        writer: W,
+        options: &RenderOptions,
+    ) -> crate::Result<()> {
+        let mut writer = IoWriter::new(writer);
+        let result =
+            Renderer::with_options(&mut writer, options.clone()).render_root_raw(&self.object);
+        writer.finish(result)
+    }
+
+    /// Renders the unresolved configuration as HOCON and writes it to `path`, the same as
+    /// [Config::save_with_options] with [SaveOptions::backup] set to `backup`, without
+    /// preserving the permissions of the file it replaces.
+    pub fn render_to_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        backup: bool,
+    ) -> crate::Result<()> {
+        let options = SaveOptions {
+            backup,
+            preserve_permissions: false,
+            ..Default::default()
+        };
+        self.save_with_options(path, &options)
+    }
+
+    /// Saves the unresolved configuration to `path` with the default [SaveOptions]: rendered
+    /// like [Config::render] and written atomically, keeping the permissions of the file
+    /// it replaces.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("app.conf");
+    /// let mut config = Config::parse_str_raw("port = 80", None).unwrap();
+    /// config.add_kv("host", Value::from("localhost"));
+    /// config.save(&path).unwrap();
+    /// let saved = Config::load_raw(&path, None).unwrap();
+    /// assert_eq!(saved.get::<String>("host").unwrap(), "localhost");
+    /// ```
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> crate::Result<()> {
+        self.save_with_options(path, &SaveOptions::default())
+    }
+
+    /// Renders the unresolved configuration and writes it to `path` as configured by
+    /// `options`.
+    ///
+    /// The text is written to a temporary file next to `path` first and then renamed over
+    /// it, so readers and crashes never observe a partially written file. If `path` is a
+    /// symbolic link, the file it points to is replaced and the link is kept.
+    pub fn save_with_options(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: &SaveOptions,
+    ) -> crate::Result<()> {
+        let rendered = self.render(&options.render);
+        write_atomic(
+            path.as_ref(),
+            rendered.as_bytes(),
+            options.backup,
+            options.preserve_permissions,
+        )
     }
 
     pub fn add_kv<K, V>(&mut self, key: K, value: V) -> &mut Self
     where
-        K: Into<RawString>,
-        V: Into<RawValue>,
+        K: Into<RawString<'static>>,
+        V: Into<RawValue<'static>>,
     {
         let field = ObjectField::key_value(key, value);
         self.object.push(field);
         self
     }
 
-    pub fn add_include(&mut self, inclusion: Inclusion) -> &mut Self {
+    pub fn add_include(&mut self, inclusion: Inclusion<'static>) -> &mut Self {
         let field = ObjectField::inclusion(inclusion);
         self.object.push(field);
         self
@@ -60,7 +793,7 @@ impl Config {
     pub fn add_kvs<I, V>(&mut self, kvs: I) -> &mut Self
     where
         I: IntoIterator<Item = (String, V)>,
-        V: Into<RawValue>,
+        V: Into<RawValue<'static>>,
     {
         let fields = kvs
             .into_iter()
@@ -69,8 +802,8 @@ impl Config {
         self
     }
 
-    pub fn add_object(&mut self, object: RawObject) -> &mut Self {
-        self.object.extend(object.0);
+    pub fn add_object(&mut self, object: RawObject<'_>) -> &mut Self {
+        self.object.extend(object.into_owned().0);
         self
     }
 
@@ -78,7 +811,434 @@ impl Config {
     where
         T: DeserializeOwned,
     {
-        Self::resolve_object(self.object)
+        Self::resolve_object(self.object, &self.options)
+    }
+
+    /// Resolves only the value at `path` and deserializes it into `T`.
+    /// `path` is a HOCON path expression, so keys containing dots are quoted like
+    /// `a."b.c"`.
+    ///
+    /// Unlike [Config::resolve], only the substitutions reachable from `path` are resolved,
+    /// which is much cheaper for large configurations when just a few values are needed, and
+    /// doesn't fail because of broken substitutions elsewhere. The configuration itself is
+    /// left unresolved. Like [Config::resolve], it uses the options the configuration was
+    /// loaded with.
+    ///
+    /// # Errors
+    /// - [crate::Error::PathNotFound] if nothing is defined at `path`.
+    /// - Any error raised while resolving the substitutions `path` depends on.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw(
+    ///     "port = 80\nserver { port = ${port} }\nbroken = ${missing}",
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let port: u16 = config.get("server.port").unwrap();
+    /// assert_eq!(port, 80);
+    /// ```
+    pub fn get<T>(&self, path: &str) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.get_with_options(path, &self.options.resolve)
+    }
+
+    /// Like [Config::get], resolving with the given [ResolveOptions].
+    pub fn get_with_options<T>(&self, path: &str, options: &ResolveOptions) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let external = Self::with_external_root(options)?;
+        let options = external.as_ref().unwrap_or(options);
+        let keys = crate::path::Path::from_str(path)?.keys();
+        let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+        let mut memo = Memo::new(options.clone());
+        let value = Self::merge_object(self.object.clone(), &memo)?
+            .substitute_path(&keys, &mut memo)?
+            .ok_or_else(|| crate::error::Error::PathNotFound(path.to_string()))?;
+        if value.is_unmerged() {
+            return Err(crate::error::Error::ResolveIncomplete);
+        }
+        T::deserialize(value).map_err(|error| {
+            let error = keys
+                .iter()
+                .rev()
+                .fold(error, |error, key| error.at_key(key));
+            self.annotate_error(error)
+        })
+    }
+
+    /// Resolves the configuration into a [Value].
+    ///
+    /// Same as [Config::resolve] into a [Value], but the resolved tree is converted directly instead of
+    /// going through serde.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let config = Config::parse_str_raw("a = 1, b = ${a}", None).unwrap();
+    /// let value = config.resolve_value().unwrap();
+    /// assert_eq!(value.get_by_path(["b"]), Some(&Value::from(1)));
+    /// ```
+    pub fn resolve_value(self) -> crate::Result<Value> {
+        let options = self.options.resolve.clone();
+        self.resolve_value_with_options(&options)
+            .map(|(value, _)| value)
+    }
+
+    /// Resolves the configuration like [Config::resolve_value] and converts it to JSON.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw("name = app, url = \"http://\"${name}", None).unwrap();
+    /// let json = config.to_json().unwrap();
+    /// assert_eq!(json["url"], "http://app");
+    /// ```
+    pub fn to_json(self) -> crate::Result<serde_json::Value> {
+        Ok(self.resolve_value()?.into_json())
+    }
+
+    /// Resolves the configuration into a [Value] with the given [ResolveOptions], along with
+    /// the paths that were left unresolved.
+    ///
+    /// The paths are only ever non-empty with [ResolveOptions::allow_unresolved] set: the
+    /// fields that depend on a missing substitution are then left out of the value, and
+    /// such array elements are `null`. Use [Config::resolve_partial] to keep them as
+    /// substitutions instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ResolveOptions, Value};
+    ///
+    /// let config = Config::parse_str_raw("a = 1, b = ${missing}, c = [${a}, ${missing}]", None).unwrap();
+    /// let options = ResolveOptions { allow_unresolved: true, ..Default::default() };
+    /// let (value, unresolved) = config.resolve_value_with_options(&options).unwrap();
+    /// assert_eq!(unresolved, ["b", "c.1"]);
+    /// assert!(value.get_by_path(["b"]).is_none());
+    /// assert_eq!(value.get_by_path(["c"]), Some(&Value::from(vec![Value::from(1), Value::Null])));
+    /// ```
+    pub fn resolve_value_with_options(
+        self,
+        options: &ResolveOptions,
+    ) -> crate::Result<(Value, Vec<String>)> {
+        let value = Self::substitute_object(self.object, options)?;
+        if !options.allow_unresolved {
+            return Ok((value.try_into()?, vec![]));
+        }
+        let mut unresolved = vec![];
+        let value = Value::from_partially_resolved(value, &mut vec![], &mut unresolved)
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        Ok((value, unresolved))
+    }
+
+    /// Resolves the configuration with the given [ResolveOptions] and deserializes it into `T`.
+    pub fn resolve_with_options<T>(self, options: &ResolveOptions) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Self::resolve_object_with_options(self.object, options)
+    }
+
+    /// Resolves the configuration like [Config::resolve_with_options] and returns, along with
+    /// the result, how every substitution was resolved: where its value was found and what
+    /// it was.
+    ///
+    /// Recording the trace has a cost, so it is only done by this method. It answers "why
+    /// did this key end up with that value" without enabling `TRACE` logs globally.
+    ///
+    /// The trace also captures the environment variables that were consulted. Passing them
+    /// back as [ResolveOptions::environment] reproduces the resolution elsewhere, e.g. a
+    /// production configuration on a developer machine.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ResolveOptions, Value};
+    /// use hocon_rs::resolution_trace::SubstitutionSource;
+    ///
+    /// let config = Config::parse_str_raw("host = localhost, url = \"http://\"${host}", None).unwrap();
+    /// let (_, trace) = config.resolve_traced::<Value>(&ResolveOptions::default()).unwrap();
+    /// let step = trace.steps_for("url").next().unwrap();
+    /// assert_eq!(step.substitution, "${host}");
+    /// assert_eq!(step.source, SubstitutionSource::Configuration);
+    /// assert_eq!(step.value.as_deref(), Some("localhost"));
+    /// ```
+    pub fn resolve_traced<T>(self, options: &ResolveOptions) -> crate::Result<(T, ResolutionTrace)>
+    where
+        T: DeserializeOwned,
+    {
+        let (value, trace) = Self::substitute_object_traced(self.object, options, true)?;
+        if value.is_unmerged() {
+            return Err(crate::error::Error::ResolveIncomplete);
+        }
+        Ok((T::deserialize(value)?, trace))
+    }
+
+    /// Resolves the configuration like [Config::resolve_with_options] and returns, along with
+    /// the result, a [LoadReport] of its sources, warnings, provenance, resolution trace and
+    /// statistics.
+    ///
+    /// This reports configurations from every entry point, e.g. [Config::load_all] or the
+    /// [ConfigBuilder], by loading them with their `*_raw` variant first.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ResolveOptions, Value};
+    ///
+    /// let config = Config::parse_str_raw("a = 1, b = ${?missing}, include \"none.conf\"", None).unwrap();
+    /// let (_, report) = config.resolve_with_report::<Value>(&ResolveOptions::default()).unwrap();
+    /// assert_eq!(report.provenance.keys().collect::<Vec<_>>(), ["a"]);
+    /// let warnings = report.warnings.iter().map(ToString::to_string).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     warnings,
+    ///     [
+    ///         "include \"none.conf\" found nothing at 1:24, searched: [none.conf]",
+    ///         "b: ${?missing} found nothing",
+    ///     ]
+    /// );
+    /// ```
+    pub fn resolve_with_report<T>(self, options: &ResolveOptions) -> crate::Result<(T, LoadReport)>
+    where
+        T: DeserializeOwned,
+    {
+        self.resolve_reported(options, std::time::Duration::ZERO)
+    }
+
+    fn resolve_reported<T>(
+        self,
+        options: &ResolveOptions,
+        load_time: std::time::Duration,
+    ) -> crate::Result<(T, LoadReport)>
+    where
+        T: DeserializeOwned,
+    {
+        let start = std::time::Instant::now();
+        let (value, trace) = Self::substitute_object_traced(self.object.clone(), options, true)?;
+        if value.is_unmerged() {
+            return Err(crate::error::Error::ResolveIncomplete);
+        }
+        let value = <Value as serde::Deserialize>::deserialize(value)?;
+        let mut report = LoadReport::new(&self.object, trace);
+        report.stats.resolve_time = start.elapsed();
+        report.stats.load_time = load_time;
+        for (path, _) in value.entry_set() {
+            if let Some(provenance) = self.provenance(&path) {
+                report.provenance.insert(path, provenance);
+            }
+            report.stats.entries += 1;
+        }
+        Ok((T::deserialize(value)?, report))
+    }
+
+    /// Resolves the configuration, looking up substitutions that it doesn't define in
+    /// `source`, and deserializes it into `T`.
+    ///
+    /// `source` is resolved on its own first. It is consulted before the environment
+    /// variables, and is not merged into the result, so values like secrets only end up
+    /// where they are referenced. Keys of a [std::collections::HashMap] source are taken
+    /// literally, `"db.password"` is found by `${db.password}` as well.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let config = Config::parse_str_raw("db.password = ${secrets.db_password}", None).unwrap();
+    /// let secrets = HashMap::from([("secrets.db_password".to_string(), Value::from("hunter2"))]);
+    /// let value: Value = config.resolve_with(secrets).unwrap();
+    /// assert_eq!(value.get_by_path(["db", "password"]), Some(&Value::from("hunter2")));
+    /// assert!(value.get_by_path(["secrets"]).is_none());
+    /// ```
+    pub fn resolve_with<T>(self, source: impl Into<Config>) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let options = ResolveOptions {
+            external_root: Some(std::rc::Rc::new(source.into())),
+            ..Default::default()
+        };
+        self.resolve_with_options(&options)
+    }
+
+    /// Resolves the configuration, checks it against `policies` and deserializes it into `T`.
+    ///
+    /// Violations with [Severity::Error] make this fail with
+    /// [crate::Error::PolicyViolation], the other ones are logged.
+    pub fn resolve_with_policies<T>(self, policies: &[&dyn Policy]) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let value: Value = self.resolve()?;
+        let (errors, others): (Vec<_>, Vec<_>) = value
+            .check_policies(policies)
+            .into_iter()
+            .partition(|violation| violation.severity == Severity::Error);
+        for violation in others {
+            tracing::warn!("{violation}");
+        }
+        if !errors.is_empty() {
+            return Err(crate::error::Error::PolicyViolation { violations: errors });
+        }
+        T::deserialize(value)
+    }
+
+    /// Resolves as many substitutions as possible and returns the result as a new [Config].
+    ///
+    /// With [ResolveOptions::allow_unresolved] set, substitutions that cannot be found are
+    /// kept, so the returned config can be extended (e.g. with [Config::add_kv]) and resolved
+    /// again. Field origins are not preserved.
+    pub fn resolve_partial(self, options: &ResolveOptions) -> crate::Result<Config> {
+        let value = Self::substitute_object(self.object, options)?;
+        let object = match value.into_raw()? {
+            Some(RawValue::Object(object)) => object,
+            _ => RawObject::default(),
+        };
+        Ok(Config {
+            object,
+            options: self.options,
+        })
+    }
+
+    /// Returns `true` if the configuration contains no substitutions, e.g. after
+    /// [Config::resolve_partial] found all of them.
+    pub fn is_fully_resolved(&self) -> bool {
+        self.object.substitutions().is_empty()
+    }
+
+    /// Lists the substitutions left in the configuration, in document order, with the
+    /// path of the field or array element each one appears in.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ResolveOptions};
+    ///
+    /// let config = Config::parse_str_raw("a = 1, b = ${a}, c = [${missing}]", None).unwrap();
+    /// let options = ResolveOptions { allow_unresolved: true, ..Default::default() };
+    /// let config = config.resolve_partial(&options).unwrap();
+    /// assert!(!config.is_fully_resolved());
+    /// let unresolved = config.unresolved_substitutions();
+    /// assert_eq!(unresolved[0].0, "c.0");
+    /// assert_eq!(unresolved[0].1.to_string(), "${missing}");
+    /// ```
+    pub fn unresolved_substitutions(&self) -> Vec<(String, &Substitution<'static>)> {
+        self.object.substitutions()
+    }
+
+    /// Checks, without resolving, that every required substitution refers to a path that
+    /// may exist: one that some layer defines, or a parent of which is itself a
+    /// substitution, or one that `options` can provide through
+    /// [ResolveOptions::overrides], [ResolveOptions::external_root],
+    /// [ResolveOptions::resolver] or the environment variables. This catches broken
+    /// references even in parts of the configuration the application never reads.
+    ///
+    /// The check is lenient: a reference that passes may still fail to resolve, e.g. when
+    /// the path is only defined by an earlier value that a later one replaces.
+    ///
+    /// # Errors
+    /// Returns [`crate::Error::Validation`] listing every broken reference with the path
+    /// and origin of the field it appears in.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, ResolveOptions};
+    ///
+    /// let config =
+    ///     Config::parse_str_raw("a = 1, b = ${a}, c = [${x}, ${?y}], d = ${e.f}", None).unwrap();
+    /// let error = config.check_substitutions(&ResolveOptions::default()).unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Invalid configuration: c.0: ${x} refers to an undefined path (<string>:1:17); \
+    ///      d: ${e.f} refers to an undefined path (<string>:1:36)"
+    /// );
+    /// ```
+    pub fn check_substitutions(&self, options: &ResolveOptions) -> crate::Result<()> {
+        let mut problems = vec![];
+        for (path, substitution) in self.object.substitutions() {
+            if substitution.is_optional() || self.may_provide(substitution, options)? {
+                continue;
+            }
+            problems.push(ValidationProblem {
+                origin: self.closest_origin(&path).cloned(),
+                path,
+                problem: format!("{substitution} refers to an undefined path"),
+            });
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::Error::Validation { problems })
+        }
+    }
+
+    /// Returns an indented tree of the configuration after all layers are merged and
+    /// before substitutions are resolved, with the merge state of every object and array.
+    /// For the tree as written, see [RawObject::debug_tree].
+    ///
+    /// Overrides in `options` are not merged in, see [Config::resolve_with_options].
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw("a { b = 1 }\na.c = ${a.b}", None).unwrap();
+    /// assert_eq!(
+    ///     config.debug_merge_tree().unwrap(),
+    ///     "object (unmerged)\n\
+    ///     \x20 a = object (unmerged)\n\
+    ///     \x20   b = number 1\n\
+    ///     \x20   c = substitution ${a.b}\n"
+    /// );
+    /// ```
+    pub fn debug_merge_tree(&self) -> crate::Result<String> {
+        let memo = Memo::new(self.options.resolve.clone());
+        let object = Self::merge_object(self.object.clone(), &memo)?;
+        Ok(MValue::Object(object).debug_tree())
+    }
+
+    fn may_provide(
+        &self,
+        substitution: &Substitution,
+        options: &ResolveOptions,
+    ) -> crate::Result<bool> {
+        let keys = substitution.path().as_path();
+        let path = crate::path::Path::from_iter(keys.iter())?;
+        if self.object.may_define(&path) {
+            return Ok(true);
+        }
+        let full_path = keys.join(".");
+        // An override of the path, of a parent or of a child provides it.
+        let overridden = options.overrides.keys().any(|key| {
+            crate::path::Path::from_str(key).is_ok_and(|key| {
+                key.keys()
+                    .iter()
+                    .zip(keys.iter())
+                    .all(|(key, path)| key == path)
+            })
+        });
+        if overridden
+            || options
+                .external_root
+                .as_ref()
+                .is_some_and(|root| root.object.may_define(&path))
+            || options
+                .resolver
+                .as_ref()
+                .is_some_and(|resolver| resolver(&full_path).is_some())
+        {
+            return Ok(true);
+        }
+        let in_environment = options.use_system_environment
+            && match &options.environment {
+                Some(environment) => environment.contains_key(&full_path),
+                None => std::env::var_os(&full_path).is_some(),
+            };
+        Ok(in_environment)
     }
 
     pub fn parse_file<T>(
@@ -88,26 +1248,62 @@ impl Config {
     where
         T: DeserializeOwned,
     {
-        let raw = load_from_path(path, opts.unwrap_or_default(), None)?;
-        Self::resolve_object::<T>(raw)
+        let options = opts.unwrap_or_default();
+        if options.cache {
+            let value = cache::get_or_load("parse_file", path.as_ref(), &options, || {
+                load_from_path(&path, options.clone(), None)
+            })?;
+            return T::deserialize(value);
+        }
+        let raw = load_from_path(path, options.clone(), None)?;
+        Self::resolve_object::<T>(raw, &options)
     }
 
-    #[cfg(feature = "urls_includes")]
+    /// Like [Config::parse_file], but also returns a [LoadReport], see
+    /// [Config::load_with_report].
+    pub fn parse_file_with_report<T>(
+        path: impl AsRef<std::path::Path>,
+        opts: Option<ConfigOptions>,
+    ) -> crate::Result<(T, LoadReport)>
+    where
+        T: DeserializeOwned,
+    {
+        Self::load_reported(opts, |options| load_from_path(&path, options.clone(), None))
+    }
+
+    /// Clears the process wide cache used when [ConfigOptions::cache] is set.
+    ///
+    /// Cached results are reloaded when one of the files they were read from is modified,
+    /// but included files, classpath resources and environment variables are not watched.
+    /// Call this after changing any of them.
+    ///
+    /// The responses of included URLs cached in memory, see [crate::UrlCache::Memory], are
+    /// cleared as well.
+    pub fn invalidate_caches() {
+        cache::invalidate();
+        #[cfg(feature = "urls_includes")]
+        crate::parser::url_cache::invalidate();
+    }
+
+    /// Loads the configuration at `url` and the sources it includes. `file:` URLs are read
+    /// from the file system and URLs with a scheme of [ConfigOptions::url_handlers] by their
+    /// handler. Other URLs are fetched over HTTP, which needs the `urls_includes` feature.
     pub fn parse_url<T>(url: impl AsRef<str>, opts: Option<ConfigOptions>) -> crate::Result<T>
     where
         T: DeserializeOwned,
     {
         use std::str::FromStr;
         let url = url::Url::from_str(url.as_ref())?;
-        let raw = loader::load_from_url(url, opts.unwrap_or_default().into(), None)?;
-        Self::resolve_object::<T>(raw)
+        let options = opts.unwrap_or_default();
+        let raw = loader::load_from_url(url, options.clone(), None)?;
+        Self::resolve_object::<T>(raw, &options)
     }
 
     pub fn parse_map<T>(values: std::collections::HashMap<String, Value>) -> crate::Result<T>
     where
         T: DeserializeOwned,
     {
-        fn into_raw(value: Value) -> RawValue {
+        fn into_raw(value: Value) -> RawValue<'static> {
             match value {
                 Value::Object(object) => {
                     let len = object.len();
@@ -126,16 +1322,21 @@ impl Config {
                 Value::Null => RawValue::Null,
                 Value::String(string) => {
                     let s = RawString::path_expression(
-                        string.split('.').map(RawString::quoted).collect(),
+                        string
+                            .split('.')
+                            .map(|s| RawString::quoted(s.to_string()))
+                            .collect(),
                     );
                     RawValue::String(s)
                 }
                 Value::Number(number) => RawValue::Number(number),
+                Value::NonFinite(non_finite) => RawValue::NonFinite(non_finite),
+                Value::Shared(shared) => into_raw(std::sync::Arc::unwrap_or_clone(shared)),
             }
         }
-        let raw = into_raw(Value::Object(HashMap::from_iter(values)));
+        let raw = into_raw(Value::Object(Map::from_iter(values)));
         if let RawValue::Object(raw_obj) = raw {
-            Self::resolve_object::<T>(raw_obj)
+            Self::resolve_object::<T>(raw_obj, &ConfigOptions::default())
         } else {
             unreachable!("raw should always be an object");
         }
@@ -146,9 +1347,24 @@ impl Config {
         T: DeserializeOwned,
     {
         let read = StrRead::new(s);
-        let raw = parse_hocon(read, options.unwrap_or_default(), None)?;
+        let options = options.unwrap_or_default();
+        let raw = parse_hocon(read, options.clone(), None)?;
         tracing::debug!("raw obj: {}", raw);
-        Self::resolve_object::<T>(raw)
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Like [Config::parse_str], but also returns a [LoadReport], see
+    /// [Config::load_with_report].
+    pub fn parse_str_with_report<T>(
+        s: &str,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<(T, LoadReport)>
+    where
+        T: DeserializeOwned,
+    {
+        Self::load_reported(options, |options| {
+            parse_hocon(CopyRead::new(s.as_bytes()), options.clone(), None)
+        })
     }
 
     pub fn parse_reader<R, T>(rdr: R, options: Option<ConfigOptions>) -> crate::Result<T>
@@ -156,28 +1372,212 @@ impl Config {
         R: std::io::Read,
         T: DeserializeOwned,
     {
-        let read = StreamRead::new(rdr);
-        let raw = parse_hocon(read, options.unwrap_or_default(), None)?;
-        Self::resolve_object::<T>(raw)
+        let options = options.unwrap_or_default();
+        let read = StreamRead::new(loader::limit_reader(rdr, &options));
+        let raw = parse_hocon(read, options.clone(), None)?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Like [Config::parse_reader], but also returns a [LoadReport], see
+    /// [Config::load_with_report].
+    pub fn parse_reader_with_report<R, T>(
+        rdr: R,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<(T, LoadReport)>
+    where
+        R: std::io::Read,
+        T: DeserializeOwned,
+    {
+        Self::load_reported(options, |options| {
+            let read = StreamRead::new(loader::limit_reader(rdr, options));
+            parse_hocon(read, options.clone(), None)
+        })
+    }
+
+    /// Parses standard input to the end, like [Config::parse_reader], so a program can read
+    /// its configuration from a pipe, e.g. `cat app.conf | mytool`. The limits of
+    /// `options`, e.g. [ConfigOptions::max_input_bytes], apply.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_stdin(None).unwrap();
+    /// ```
+    pub fn parse_stdin<T>(options: Option<ConfigOptions>) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Self::parse_reader(std::io::stdin().lock(), options)
+    }
+
+    /// Like [Config::parse_reader], but reads `reader` without blocking, for use inside
+    /// async runtimes. Included files and URLs are loaded without blocking as well, but
+    /// archives in [ConfigOptions::classpath] and [ConfigOptions::classpath_providers] are
+    /// read on the runtime's thread, see [crate::classpath::ClasspathProvider].
+    ///
+    /// The input is read to the end before it is parsed. The returned future is not `Send`,
+    /// as [ConfigOptions] isn't.
+    ///
+    /// # Example
+    /// ```rust
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let reader = "a = 1, b = ${a}".as_bytes();
+    /// let value: Value = Config::parse_async_reader(reader, None).await.unwrap();
+    /// assert_eq!(value.get_by_path(["b"]), Some(&Value::from(1)));
+    /// # });
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn parse_async_reader<R, T>(
+        reader: R,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<T>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+        T: DeserializeOwned,
+    {
+        let options = options.unwrap_or_default();
+        let raw = crate::parser::async_loader::parse_reader(reader, &options).await?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Like [Config::load], but reads the files and the ones they include without blocking.
+    /// [ConfigOptions::cache] is ignored. Classpath providers are read on the runtime's
+    /// thread, like in [Config::parse_async_reader].
+    #[cfg(feature = "tokio")]
+    pub async fn load_async<T>(
+        path: impl AsRef<std::path::Path>,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let options = options.unwrap_or_default();
+        let raw = crate::parser::async_loader::load(path.as_ref(), &options).await?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Like [Config::parse_url], but fetches the URL and the sources it includes without
+    /// blocking. Independent includes are fetched concurrently.
+    #[cfg(feature = "tokio")]
+    pub async fn parse_url_async<T>(
+        url: impl AsRef<str>,
+        options: Option<ConfigOptions>,
+    ) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        use std::str::FromStr;
+        let url = url::Url::from_str(url.as_ref())?;
+        let options = options.unwrap_or_default();
+        let raw = crate::parser::async_loader::parse_url(url, &options).await?;
+        Self::resolve_object::<T>(raw, &options)
+    }
+
+    /// Loads a configuration with `load` and resolves it like the loading functions do,
+    /// reporting both steps.
+    fn load_reported<T>(
+        options: Option<ConfigOptions>,
+        load: impl FnOnce(&ConfigOptions) -> crate::Result<RawObject<'static>>,
+    ) -> crate::Result<(T, LoadReport)>
+    where
+        T: DeserializeOwned,
+    {
+        let options = options.unwrap_or_default();
+        let start = std::time::Instant::now();
+        let object = load(&options)?;
+        let load_time = start.elapsed();
+        let resolve_options = options.resolve.clone();
+        Config { object, options }.resolve_reported(&resolve_options, load_time)
+    }
+
+    /// Resolves `object` with the [ConfigOptions::resolve] of the `options` it was loaded
+    /// with.
+    pub(crate) fn resolve_object<T>(object: RawObject, options: &ConfigOptions) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Self::resolve_object_with_options(object, &options.resolve)
     }
 
-    fn resolve_object<T>(object: RawObject) -> crate::Result<T>
+    fn resolve_object_with_options<T>(
+        object: RawObject,
+        options: &ResolveOptions,
+    ) -> crate::Result<T>
     where
         T: DeserializeOwned,
     {
-        let object = MObject::from_raw(None, object)?;
-        let mut value = MValue::Object(object);
-        tracing::debug!("merged value: {value}");
-        value.resolve()?;
+        let value = Self::substitute_object(object, options)?;
         if value.is_unmerged() {
             return Err(crate::error::Error::ResolveIncomplete);
         }
         T::deserialize(value)
     }
+
+    /// Resolves [ResolveOptions::external_root] and turns it into a resolver that is
+    /// consulted before the one in `options`.
+    fn with_external_root(options: &ResolveOptions) -> crate::Result<Option<ResolveOptions>> {
+        let Some(root) = &options.external_root else {
+            return Ok(None);
+        };
+        let root: Value = Config::clone(root).resolve()?;
+        let fallback = options.resolver.clone();
+        let resolver = move |path: &str| {
+            let keys = path.split('.').collect::<Vec<_>>();
+            root.get_by_path(&keys)
+                .or_else(|| root.as_object()?.get(path))
+                .cloned()
+                .or_else(|| fallback.as_ref()?(path))
+        };
+        Ok(Some(ResolveOptions {
+            resolver: Some(std::rc::Rc::new(resolver)),
+            external_root: None,
+            ..options.clone()
+        }))
+    }
+
+    /// Applies [ResolveOptions::overrides] and merges the object with the options of
+    /// `memo`, ready to be resolved.
+    fn merge_object(mut object: RawObject, memo: &Memo) -> crate::Result<MObject> {
+        for (path, value) in memo.options.overrides.iter() {
+            let keys = crate::path::Path::from_str(path)?.keys();
+            let key = RawString::path_expression(keys.into_iter().map(RawString::quoted).collect());
+            object.push(ObjectField::key_value(key, value.clone()));
+        }
+        let mut object = MObject::from_raw(None, object, memo)?;
+        object.apply_merge_strategies(None, memo)?;
+        Ok(object)
+    }
+
+    fn substitute_object(object: RawObject, options: &ResolveOptions) -> crate::Result<MValue> {
+        Self::substitute_object_traced(object, options, false).map(|(value, _)| value)
+    }
+
+    /// Like [Config::substitute_object], recording the resolved substitutions if `trace`
+    /// is set.
+    fn substitute_object_traced(
+        object: RawObject,
+        options: &ResolveOptions,
+        trace: bool,
+    ) -> crate::Result<(MValue, ResolutionTrace)> {
+        let external = Self::with_external_root(options)?;
+        let options = external.as_ref().unwrap_or(options);
+        let mut memo = Memo::new(options.clone());
+        let object = Self::merge_object(object, &memo)?;
+        let mut value = MValue::Object(object);
+        tracing::debug!("merged value:\n{}", value.debug_tree());
+        if trace {
+            memo.trace = Some(ResolutionTrace::default());
+        }
+        value.resolve(&mut memo)?;
+        Ok((value, memo.trace.unwrap_or_default()))
+    }
 }
 
-impl From<RawObject> for Config {
-    fn from(value: RawObject) -> Self {
+impl From<RawObject<'static>> for Config {
+    fn from(value: RawObject<'static>) -> Self {
         Config {
             object: value,
             options: Default::default(),
@@ -206,8 +1606,12 @@ impl From<std::collections::HashMap<String, Value>> for Config {
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use crate::Result;
     use crate::error::Error;
+    use crate::number::NonFinite;
+    use crate::resolve_options::ResolveOptions;
     use crate::{config::Config, config_options::ConfigOptions, value::Value};
     use rstest::rstest;
 
@@ -256,12 +1660,17 @@ mod tests {
     #[case("resources/empty.conf", "resources/empty.json")]
     #[case("resources/base.conf", "resources/base.json")]
     #[case("resources/add_assign.conf", "resources/add_assign_expected.json")]
+    #[case(
+        "resources/add_assign_include.conf",
+        "resources/add_assign_include.json"
+    )]
     #[case("resources/concat.conf", "resources/concat.json")]
     #[case("resources/concat2.conf", "resources/concat2.json")]
     #[case("resources/concat3.conf", "resources/concat3.json")]
     #[case("resources/concat4.conf", "resources/concat4.json")]
     #[case("resources/concat5.conf", "resources/concat5.json")]
     #[case("resources/include.conf", "resources/include.json")]
+    #[case("resources/include_json.conf", "resources/include_json.json")]
     #[case("resources/comment.conf", "resources/comment.json")]
     #[case("resources/substitution.conf", "resources/substitution.json")]
     #[case("resources/substitution3.conf", "resources/substitution3.json")]
@@ -270,8 +1679,7 @@ mod tests {
         #[case] hocon: impl AsRef<std::path::Path>,
         #[case] json: impl AsRef<std::path::Path>,
     ) -> Result<()> {
-        let mut options = ConfigOptions::default();
-        options.classpath = vec!["resources".to_string()].into();
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
         let value = Config::load::<Value>(hocon, Some(options))?;
         let f = std::fs::File::open(json)?;
         let expected_value: serde_json::Value = serde_json::from_reader(f)?;
@@ -280,30 +1688,1099 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_origin() -> Result<()> {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let config = Config::load_raw("resources/include.conf", Some(options))?;
+        let origin = config.get_origin("a").unwrap();
+        assert_eq!(
+            &*origin.source.as_ref().unwrap().name(),
+            "resources/include.conf"
+        );
+        assert!(origin.include_chain.is_empty());
+        let origin = config.get_origin("a.y").unwrap();
+        assert_eq!(
+            &*origin.source.as_ref().unwrap().name(),
+            "resources/foo.conf"
+        );
+        assert_eq!(origin.include_chain.len(), 1);
+        let config = Config::parse_str_raw("a.b = 1\na { c = 2 }\nd = ${a}", None)?;
+        let origin = config.get_origin("a.b").unwrap();
+        assert_eq!(origin.source, None);
+        assert_eq!(origin.position.unwrap().line, 1);
+        let origin = config.get_origin("a.c").unwrap();
+        assert_eq!(origin.position.unwrap().line, 2);
+        assert_eq!(origin.position.unwrap().column, 4);
+        assert!(config.get_origin("a.e").is_none());
+        let config = Config::parse_str_raw("a.b = 1\na = 2", None)?;
+        assert!(config.get_origin("a.b").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_closest_origin() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "a { b = 1 }\na { \"b.c\" = [{ d = 1 }] }\n\"e[0]\" = 2",
+            None,
+        )?;
+        let line = |path: &str| {
+            config
+                .closest_origin(path)
+                .map(|o| o.position.unwrap().line)
+        };
+        assert_eq!(line("a.b"), Some(1));
+        assert_eq!(line("a.\"b.c\"[0].d"), Some(2));
+        assert_eq!(line("a.b.c"), Some(1));
+        assert_eq!(line("\"e[0]\""), Some(3));
+        assert_eq!(line("x[0]"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_provenance() -> Result<()> {
+        use crate::origin::Layer;
+
+        let options = ConfigOptions::new(true, vec!["resources".to_string()]);
+        let mut config = Config::builder()
+            .options(options)
+            .add_file("resources/include.conf")
+            .add_str("b = 1, c = 2")
+            .add_override("c", 3)
+            .build_raw()?
+            .with_fallback(Config::parse_str_raw("d = 4", None)?);
+        config.add_kv("e", Value::from(5));
+
+        let provenance = config.provenance("a").unwrap();
+        assert_eq!(provenance.layer, Layer::File);
+        let provenance = config.provenance("a.y").unwrap();
+        assert_eq!(provenance.layer, Layer::Include);
+        assert_eq!(
+            &*provenance.origin.unwrap().source.as_ref().unwrap().name(),
+            "resources/foo.conf"
+        );
+        assert_eq!(config.provenance("b").unwrap().layer, Layer::String);
+        let provenance = config.provenance("c").unwrap();
+        assert_eq!(provenance.layer, Layer::Override);
+        assert_eq!(provenance.to_string(), "override c (override)");
+        assert_eq!(config.provenance("d").unwrap().layer, Layer::String);
+        let provenance = config.provenance("e").unwrap();
+        assert_eq!(provenance.layer, Layer::Programmatic);
+        assert!(provenance.origin.is_none());
+        assert!(config.provenance("missing").is_none());
+
+        let (name, _) = std::env::vars().next().unwrap();
+        let provenance = config.provenance(&crate::path::render_key(&name)).unwrap();
+        assert_eq!(provenance.layer, Layer::Environment);
+        assert_eq!(
+            provenance.origin.unwrap().to_string(),
+            format!("environment variable {name}")
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("services.*.image", &["services.web.image", "services.db.image", "services.web.image"])]
+    #[case("**.image", &[
+        "image",
+        "services.web.image",
+        "services.db.image",
+        "services.web.image",
+    ])]
+    #[case("services.**", &[
+        "services",
+        "services.web",
+        "services.web.image",
+        "services.db.image",
+        "services.web",
+        "services.web.image",
+        "services.web.tag",
+    ])]
+    #[case("*.web", &["services.web", "services.web"])]
+    #[case("image.*", &[])]
+    fn test_select(#[case] selector: &str, #[case] expected: &[&str]) -> Result<()> {
+        let config = Config::parse_str_raw(
+            "image = base\nservices {\n  web { image = ${image} }\n  db.image = \"db:2.0\"\n}\nservices.web { image = \"web:1.1\", tag = latest }",
+            None,
+        )?;
+        let selections = config.select(selector);
+        let paths = selections
+            .iter()
+            .map(|s| s.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_include() -> Result<()> {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let config = Config::load_raw("resources/include.conf", Some(options))?;
+        let selections = config.select("a.x");
+        assert_eq!(selections.len(), 2);
+        assert_eq!(
+            &*selections[0]
+                .origin()
+                .unwrap()
+                .source
+                .as_ref()
+                .unwrap()
+                .name(),
+            "resources/foo.conf"
+        );
+        assert_eq!(selections[1].value().to_string(), "42");
+        assert_eq!(selections[1].span().unwrap().start.line, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_content_includes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let lib = dir.path().join("lib.conf");
+        let copy = dir.path().join("copy.conf");
+        let nested = dir.path().join("nested.conf");
+        std::fs::write(&nested, "m = 2")?;
+        for path in [&lib, &copy] {
+            let contents = format!(
+                "k = 1\nn {{ include file({:?}) }}",
+                nested.display().to_string()
+            );
+            std::fs::write(path, contents)?;
+        }
+        let main = dir.path().join("main.conf");
+        std::fs::write(
+            &main,
+            format!(
+                "x {{ include file({:?}) }}\ny {{ include file({:?}) }}",
+                lib.display().to_string(),
+                copy.display().to_string()
+            ),
+        )?;
+        let config = Config::load_raw(&main, None)?;
+
+        use crate::origin::SourceId;
+
+        let lib_source = SourceId::new(&lib.display().to_string());
+        let copy_source = SourceId::new(&copy.display().to_string());
+        let provenance = config.provenance("y.k").unwrap();
+        let origin = provenance.origin.clone().unwrap();
+        assert_eq!(origin.source, Some(copy_source.clone()));
+        assert_eq!(origin.include_chain, vec![copy_source.clone()]);
+        assert_eq!(provenance.same_content, vec![lib_source.clone()]);
+        assert!(
+            provenance
+                .to_string()
+                .ends_with(&format!("(include, same content as {})", lib.display()))
+        );
+        let origin = config.provenance("y.n.m").unwrap().origin.unwrap();
+        assert_eq!(origin.include_chain[0], copy_source);
+        let provenance = config.provenance("x.k").unwrap();
+        assert_eq!(provenance.origin.unwrap().source, Some(lib_source));
+        assert_eq!(provenance.same_content, [copy_source]);
+        // Another load doesn't know about the duplicates of this one.
+        let config = Config::load_raw(&lib, None)?;
+        assert!(config.provenance("k").unwrap().same_content.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cached.conf");
+        std::fs::write(&path, "a = 1")?;
+        let modified = std::fs::metadata(&path)?.modified()?;
+        let options = ConfigOptions {
+            cache: true,
+            ..Default::default()
+        };
+        let load = || -> Result<Value> { Config::parse_file(&path, Some(options.clone())) };
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(1)));
+
+        std::fs::write(&path, "a = 2")?;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified)?;
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(1)));
+        let uncached: Value = Config::parse_file(&path, None)?;
+        assert_eq!(uncached.get_by_path(["a"]), Some(&Value::from(2)));
+
+        Config::invalidate_caches();
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(2)));
+
+        std::fs::write(&path, "a = 3")?;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified + std::time::Duration::from_secs(1))?;
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(3)));
+
+        std::fs::write(&path, "a = 4")?;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified + std::time::Duration::from_secs(1))?;
+        let other = ConfigOptions {
+            cache_includes: false,
+            ..options.clone()
+        };
+        let value: Value = Config::parse_file(&path, Some(other))?;
+        assert_eq!(value.get_by_path(["a"]), Some(&Value::from(4)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_includes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.conf");
+        let included = dir.path().join("base.conf");
+        std::fs::write(&path, "include \"base.conf\"\ninclude \"local.conf\"")?;
+        std::fs::write(&included, "a = 1")?;
+        let modified = std::fs::metadata(&included)?.modified()?;
+        let options = ConfigOptions {
+            cache: true,
+            ..Default::default()
+        };
+        let load = || -> Result<Value> { Config::load(&path, Some(options.clone())) };
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(1)));
+
+        std::fs::write(&included, "a = 2")?;
+        std::fs::File::options()
+            .write(true)
+            .open(&included)?
+            .set_modified(modified + std::time::Duration::from_secs(1))?;
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(2)));
+
+        std::fs::write(dir.path().join("local.conf"), "a = 3")?;
+        assert_eq!(load()?.get_by_path(["a"]), Some(&Value::from(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fingerprint() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("base.conf");
+        let app = dir.path().join("app.conf");
+        std::fs::write(&base, "port = 80")?;
+        std::fs::write(&app, "include \"base.conf\"\nhost = localhost")?;
+        let options = ConfigOptions::new(false, vec![dir.path().display().to_string()]);
+        let fingerprint = Config::load_raw(&app, Some(options.clone()))?.fingerprint()?;
+        let names = fingerprint
+            .sources
+            .iter()
+            .map(|source| source.name.clone())
+            .collect::<Vec<_>>();
+        let mut expected = vec![app.display().to_string(), base.display().to_string()];
+        expected.sort();
+        assert_eq!(names, expected);
+        assert!(fingerprint.sources.iter().all(|s| s.digest.is_some()));
+        assert_eq!(fingerprint.digest.len(), 64);
+
+        let loaded = Config::load_raw(&app, Some(options.clone()))?;
+        assert_eq!(loaded.fingerprint()?, fingerprint);
+
+        std::fs::write(&base, "port = 80 # unchanged value")?;
+        assert_eq!(loaded.fingerprint()?, fingerprint);
+        let changed = Config::load_raw(&app, Some(options))?.fingerprint()?;
+        assert_ne!(changed.digest, fingerprint.digest);
+
+        let a = Config::parse_str_raw("a = 1, b { c = x }", None)?.fingerprint()?;
+        let b = Config::parse_str_raw("b.c = x\na: 1", None)?.fingerprint()?;
+        assert_eq!(a, b);
+        assert!(a.sources.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unresolved_substitutions() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "a = 1, b = ${a}\nc { d = \"x\"${?e}, f += ${g} }\nh = [1, {i = ${a}}]",
+            None,
+        )?;
+        assert!(!config.is_fully_resolved());
+        let unresolved = config
+            .unresolved_substitutions()
+            .into_iter()
+            .map(|(path, substitution)| format!("{path} {substitution}"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unresolved,
+            ["b ${a}", "c.d ${?e}", "c.f ${g}", "h.1.i ${a}"]
+        );
+
+        let options = ResolveOptions {
+            allow_unresolved: true,
+            ..Default::default()
+        };
+        let config = config.resolve_partial(&options)?;
+        let unresolved = config
+            .unresolved_substitutions()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(unresolved, ["c.f.0"]);
+        let mut config = config;
+        config.add_kv("g", Value::from(2));
+        let config = config.resolve_partial(&options)?;
+        assert!(config.is_fully_resolved());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get() -> Result<()> {
+        let config = Config::parse_str_raw(
+            r#"
+            defaults { port = ${port}, hosts = [a] }
+            port = 80
+            server = ${defaults} { host = local }
+            broken = ${missing}
+            "#,
+            None,
+        )?;
+        assert_eq!(config.get::<u16>("server.port")?, 80);
+        assert_eq!(config.get::<Vec<String>>("server.hosts")?, ["a"]);
+        let server: Value = config.get("server")?;
+        assert_eq!(server.get_by_path(["port"]), Some(&Value::from(80)));
+        assert_eq!(server.get_by_path(["host"]), Some(&Value::from("local")));
+        assert!(matches!(
+            config.get::<Value>("server.missing"),
+            Err(Error::PathNotFound(_))
+        ));
+        assert!(matches!(
+            config.get::<Value>("broken"),
+            Err(Error::SubstitutionNotFound(_))
+        ));
+        assert!(config.clone().resolve::<Value>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_quoted_key() -> Result<()> {
+        let config = Config::parse_str_raw(r#"a { "b.c" = 1, b.c = 2 }"#, None)?;
+        assert_eq!(config.get::<i64>(r#"a."b.c""#)?, 1);
+        assert_eq!(config.get::<i64>("a.b.c")?, 2);
+        assert!(matches!(
+            config.get::<i64>("a..b"),
+            Err(Error::InvalidPathExpression(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_environment_snapshot() -> Result<()> {
+        use std::collections::BTreeMap;
+
+        let environment = |vars: &[(&str, &str)]| ResolveOptions {
+            environment: Some(
+                vars.iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect::<BTreeMap<_, _>>()
+                    .into(),
+            ),
+            ..Default::default()
+        };
+        let config = Config::parse_str_raw(
+            "stage = ${HOCON_RS_SNAPSHOT_TEST}\nregion = ${?HOCON_RS_SNAPSHOT_MISSING}",
+            None,
+        )?;
+        let options = environment(&[("HOCON_RS_SNAPSHOT_TEST", "production"), ("UNUSED", "x")]);
+        let (expected, trace) = config.clone().resolve_traced::<Value>(&options)?;
+        assert_eq!(
+            trace.environment,
+            BTreeMap::from([(
+                "HOCON_RS_SNAPSHOT_TEST".to_string(),
+                "production".to_string()
+            )])
+        );
+
+        // Replaying the recorded variables gives the same result.
+        let options = ResolveOptions {
+            environment: Some(trace.environment.into()),
+            ..Default::default()
+        };
+        let value: Value = config.clone().resolve_with_options(&options)?;
+        value.assert_deep_eq(&expected, "$");
+        assert_eq!(value.get_by_path(["region"]), None);
+
+        let options = environment(&[
+            ("HOCON_RS_SNAPSHOT_TEST", "local"),
+            ("HOCON_RS_SNAPSHOT_MISSING", "eu"),
+        ]);
+        let value: Value = config.resolve_with_options(&options)?;
+        assert_eq!(value.get_by_path(["stage"]), Some(&Value::from("local")));
+        assert_eq!(value.get_by_path(["region"]), Some(&Value::from("eu")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_traced() -> Result<()> {
+        use crate::resolution_trace::SubstitutionSource;
+
+        let config = Config::parse_str_raw(
+            r#"
+            base = { port = 80 }
+            server = ${base} { url = "http://"${host}":"${base.port} }
+            host = localhost
+            env = ${HOCON_RS_TRACE_TEST}
+            secret = ${secrets.token}
+            region = ${?missing}
+            "#,
+            None,
+        )?;
+        let options = ResolveOptions {
+            overrides: std::collections::HashMap::from([(
+                "host".to_string(),
+                Value::from("example.com"),
+            )])
+            .into(),
+            environment: Some(
+                std::collections::BTreeMap::from([(
+                    "HOCON_RS_TRACE_TEST".to_string(),
+                    "from-env".to_string(),
+                )])
+                .into(),
+            ),
+            ..ResolveOptions::with_resolver(|path| {
+                (path == "secrets.token").then(|| Value::from("t0k3n"))
+            })
+        };
+        let (value, trace) = config.resolve_traced::<Value>(&options)?;
+        assert_eq!(
+            value.get_by_path(["server", "url"]),
+            Some(&Value::from("http://example.com:80"))
+        );
+
+        let sources = |path: &'static str| {
+            trace
+                .steps_for(path)
+                .map(|step| {
+                    (
+                        step.substitution.as_str(),
+                        step.source,
+                        step.value.as_deref(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sources("server"),
+            [(
+                "${base}",
+                SubstitutionSource::Configuration,
+                Some("{port: 80}")
+            )]
+        );
+        let url = sources("server.url");
+        assert!(url.contains(&("${host}", SubstitutionSource::Override, Some("example.com"))));
+        assert!(url.contains(&(
+            "${base.port}",
+            SubstitutionSource::Configuration,
+            Some("80")
+        )));
+        assert_eq!(
+            sources("env"),
+            [(
+                "${HOCON_RS_TRACE_TEST}",
+                SubstitutionSource::Environment,
+                Some("from-env")
+            )]
+        );
+        assert_eq!(
+            sources("secret"),
+            [(
+                "${secrets.token}",
+                SubstitutionSource::Resolver,
+                Some("t0k3n")
+            )]
+        );
+        assert_eq!(
+            sources("region"),
+            [("${?missing}", SubstitutionSource::NotFound, None)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_with() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "user = ${secrets.user}, password = ${secrets.password}, port = 80, url = ${?port}",
+            None,
+        )?;
+        let secrets = Config::parse_str_raw(
+            "secrets { user = admin, password = hunter2 }, port = 1",
+            None,
+        )?;
+        let value: Value = config.clone().resolve_with(secrets)?;
+        assert_eq!(value.get_by_path(["user"]), Some(&Value::from("admin")));
+        assert_eq!(
+            value.get_by_path(["password"]),
+            Some(&Value::from("hunter2"))
+        );
+        assert_eq!(value.get_by_path(["url"]), Some(&Value::from(80)));
+        assert!(!value.has_path("secrets"));
+
+        let error = config
+            .clone()
+            .resolve_with::<Value>(std::collections::HashMap::new())
+            .unwrap_err();
+        assert!(matches!(error, Error::SubstitutionNotFound { .. }));
+
+        let options = ResolveOptions {
+            external_root: Some(Config::parse_str_raw("secrets.user = root", None)?.into()),
+            ..ResolveOptions::with_resolver(|path| Some(Value::from(format!("resolver {path}"))))
+        };
+        let value: Value = config.resolve_with_options(&options)?;
+        assert_eq!(value.get_by_path(["user"]), Some(&Value::from("root")));
+        assert_eq!(
+            value.get_by_path(["password"]),
+            Some(&Value::from("resolver secrets.password"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_options() -> Result<()> {
+        use crate::ResolveOptions;
+
+        let config = Config::parse_str_raw("a = ${b}\nc = ${?HOME}", None)?;
+        let options = ResolveOptions {
+            use_system_environment: false,
+            ..ResolveOptions::with_resolver(|path| (path == "b").then(|| Value::from(1)))
+        };
+        let value: Value = config.clone().resolve_with_options(&options)?;
+        assert_eq!(value.get_by_path(["a"]), Some(&Value::from(1)));
+        assert_eq!(value.get_by_path(["c"]), None);
+
+        let mut overrides = std::collections::HashMap::new();
+        overrides.insert("b".to_string(), Value::from("x"));
+        let options = ResolveOptions {
+            overrides: overrides.into(),
+            ..Default::default()
+        };
+        let value: Value = config.resolve_with_options(&options)?;
+        assert_eq!(value.get_by_path(["a"]), Some(&Value::from("x")));
+
+        let config = Config::parse_str_raw("a = ${\"b.c\"}", None)?;
+        let overrides = std::collections::HashMap::from([("\"b.c\"".to_string(), Value::from(1))]);
+        let options = ResolveOptions {
+            overrides: overrides.into(),
+            ..Default::default()
+        };
+        let value: Value = config.clone().resolve_with_options(&options)?;
+        assert_eq!(value.get_by_path(["a"]), Some(&Value::from(1)));
+        assert_eq!(value.get_by_path(["b.c"]), Some(&Value::from(1)));
+        let overrides = std::collections::HashMap::from([("b..c".to_string(), Value::from(1))]);
+        let options = ResolveOptions {
+            overrides: overrides.into(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.resolve_with_options::<Value>(&options),
+            Err(Error::InvalidPathExpression(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_partial() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "a = 1\nb = ${a}\nc = ${x} ${a}\nd { e = ${x}, f = ${a} }\ng = [${x}]",
+            None,
+        )?;
+        let options = crate::ResolveOptions::new(false, true);
+        let mut config = config.resolve_partial(&options)?;
+        config.add_kv("x", Value::from("hi"));
+        let value: Value = config.resolve()?;
+        assert_eq!(value.get_by_path(["b"]), Some(&Value::from(1)));
+        assert_eq!(value.get_by_path(["c"]), Some(&Value::from("hi 1")));
+        assert_eq!(value.get_by_path(["d", "e"]), Some(&Value::from("hi")));
+        assert_eq!(value.get_by_path(["d", "f"]), Some(&Value::from(1)));
+        assert_eq!(
+            value.get_by_path(["g"]),
+            Some(&Value::from(vec![Value::from("hi")]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_value() -> Result<()> {
+        let config = Config::load_raw("resources/demo.conf", None)?;
+        assert_eq!(config.clone().resolve_value()?, config.resolve::<Value>()?);
+
+        let config = Config::parse_str_raw(
+            "a = 1\nb = ${a}\nc = ${x} ${a}\nd { e = ${x}, f = ${a} }\ng = [${x}]",
+            None,
+        )?;
+        assert!(config.clone().resolve_value().is_err());
+        let options = crate::ResolveOptions::new(false, true);
+        let (value, unresolved) = config.resolve_value_with_options(&options)?;
+        assert_eq!(unresolved, ["c", "d.e", "g.0"]);
+        let expected: Value = Config::parse_str("a = 1, b = 1, d { f = 1 }, g = [null]", None)?;
+        assert_eq!(value, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_json() -> Result<()> {
+        let config = Config::load_raw("resources/demo.conf", None)?;
+        let expected: serde_json::Value = config.clone().resolve()?;
+        assert_eq!(config.to_json()?, expected);
+        assert!(
+            Config::parse_str_raw("a = ${missing}", None)?
+                .to_json()
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_spec_strictness() -> Result<()> {
+        let input = "a = [1]\na = { \"$merge\" = append, value = [2] }";
+        let strategies = Rc::new(crate::merge_strategy::MergeStrategies::builtin());
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                merge_strategies: Some(strategies.clone()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let value: Value = Config::parse_str(input, Some(options.clone()))?;
+        assert_eq!(value, Config::parse_str::<Value>("a = [1, 2]", None)?);
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                spec_strictness: crate::SpecStrictness::Strict,
+                ..options.resolve.clone()
+            },
+            ..options
+        };
+        let expected: Value = Config::parse_str(
+            "a { \"$merge\" = append, value = [2] }",
+            Some(options.clone()),
+        )?;
+        let value: Value = Config::parse_str(input, Some(options.clone()))?;
+        assert_eq!(value, expected);
+        let config = Config::parse_str_raw(input, Some(options.clone()))?;
+        assert_eq!(config.clone().resolve::<Value>()?, expected);
+        assert_eq!(config.get::<String>(r#"a."$merge""#)?, "append");
+        let resolve_options = ResolveOptions {
+            merge_strategies: Some(strategies),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.get_with_options::<Vec<i32>>("a", &resolve_options)?,
+            [1, 2]
+        );
+
+        let options = ConfigOptions {
+            extended_numbers: true,
+            non_finite_numbers: true,
+            ..options
+        };
+        let value: Value = Config::parse_str("a = 0xFF, b = NaN", Some(options.clone()))?;
+        assert_eq!(value.get_by_path(["a"]), Some(&Value::from("0xFF")));
+        assert_eq!(value.get_by_path(["b"]), Some(&Value::from("NaN")));
+        Ok(())
+    }
+
+    /// The strings typesafe-config's `DefaultTransformer` turns into booleans, matched
+    /// case-sensitively, and some it rejects with `ConfigException.WrongType`.
+    #[rstest]
+    #[case("true", true)]
+    #[case("yes", true)]
+    #[case("on", true)]
+    #[case("false", true)]
+    #[case("no", true)]
+    #[case("off", true)]
+    #[case("True", false)]
+    #[case("YES", false)]
+    #[case("1", false)]
+    #[case("enabled", false)]
+    fn test_boolean_strings_like_typesafe_config(
+        #[case] input: &str,
+        #[case] boolean: bool,
+    ) -> Result<()> {
+        let reference = Config::parse_str_raw("enabled = true", None)?;
+        for spec_strictness in [
+            crate::SpecStrictness::Extended,
+            crate::SpecStrictness::Strict,
+        ] {
+            let options = ConfigOptions {
+                resolve: ResolveOptions {
+                    spec_strictness,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let input = format!("enabled = {}", serde_json::to_string(input)?);
+            let config = Config::parse_str_raw(&input, Some(options))?;
+            assert_eq!(config.check_valid(&reference, &[]).is_ok(), boolean);
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("0xFF", Value::from(255))]
+    #[case("0Xff_ff", Value::from(65535))]
+    #[case("-0x10", Value::from(-16))]
+    #[case("-0x8000000000000000", Value::from(i64::MIN))]
+    #[case("0xFFFFFFFFFFFFFFFF", Value::Number(u64::MAX.into()))]
+    #[case("1_000_000", Value::from(1_000_000))]
+    #[case("-1_000.5", Value::from(-1000.5))]
+    #[case("0x1FFFFFFFFFFFFFFFF", Value::from("0x1FFFFFFFFFFFFFFFF"))]
+    #[case("0x", Value::from("0x"))]
+    #[case("0x_1", Value::from("0x_1"))]
+    #[case("1__0", Value::from("1__0"))]
+    #[case("1_", Value::from("1_"))]
+    #[case("_1", Value::from("_1"))]
+    #[case("0x10 px", Value::from("0x10 px"))]
+    #[case("[0x10, 1_0]", Value::array(vec![Value::from(16), Value::from(10)]))]
+    fn test_extended_numbers(#[case] input: &str, #[case] expected: Value) -> Result<()> {
+        let options = ConfigOptions {
+            extended_numbers: true,
+            ..Default::default()
+        };
+        let value: Value = Config::parse_str(&format!("a = {input}"), Some(options))?;
+        assert_eq!(value.get_by_path(["a"]), Some(&expected));
+        let value: Value = Config::parse_str(&format!("a = {input}"), None)?;
+        if matches!(expected, Value::Number(_)) {
+            assert_eq!(value.get_by_path(["a"]), Some(&Value::from(input)));
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("Infinity", Value::NonFinite(NonFinite::Infinity))]
+    #[case("-Infinity", Value::NonFinite(NonFinite::NegativeInfinity))]
+    #[case("NaN", Value::NonFinite(NonFinite::NaN))]
+    #[case("infinity", Value::from("infinity"))]
+    #[case("\"NaN\"", Value::from("NaN"))]
+    #[case("Infinity ms", Value::from("Infinity ms"))]
+    #[case("[NaN, 1]", Value::array(vec![Value::NonFinite(NonFinite::NaN), Value::from(1)]))]
+    fn test_non_finite_numbers(#[case] input: &str, #[case] expected: Value) -> Result<()> {
+        let options = ConfigOptions {
+            non_finite_numbers: true,
+            ..Default::default()
+        };
+        let raw = Config::parse_str_raw(&format!("a = {input}"), Some(options.clone()))?;
+        let value: Value = Config::parse_str(&format!("a = {input}"), Some(options))?;
+        assert_eq!(value.get_by_path(["a"]), Some(&expected));
+        // JSON has no non-finite numbers, all conversions to JSON write them as `null`.
+        let json = value.clone().into_json();
+        let rendered = value.render(&crate::RenderOptions::json());
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&rendered)?, json);
+        if let Value::NonFinite(non_finite) = expected {
+            assert_eq!(json["a"], serde_json::Value::Null);
+            let rendered = raw.render(&crate::RenderOptions::json());
+            assert_eq!(serde_json::from_str::<serde_json::Value>(&rendered)?, json);
+            let value: Value = Config::parse_str(&format!("a = {input}"), None)?;
+            assert_eq!(value.get_by_path(["a"]), Some(&Value::from(input)));
+            let float = value.get_by_path(["a"]).and_then(Value::as_f64_strict);
+            assert_eq!(float, None);
+            let options = ConfigOptions {
+                non_finite_numbers: true,
+                ..Default::default()
+            };
+            let float: f64 = Config::parse_str::<std::collections::HashMap<String, f64>>(
+                &format!("a = {input}"),
+                Some(options),
+            )?["a"];
+            assert_eq!(float.to_bits(), non_finite.as_f64().to_bits());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_intern_keys() -> Result<()> {
+        let input = "a { host = x, port = 1 }\nb = ${a} { port = 2 }\nc.host = ${b.host}";
+        let expected: Value = Config::parse_str(input, None)?;
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                intern_keys: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let value: Value = Config::parse_str(input, Some(options))?;
+        assert_eq!(value, expected);
+        let resolve_options = ResolveOptions {
+            intern_keys: true,
+            ..Default::default()
+        };
+        let config = Config::parse_str_raw(input, None)?;
+        assert_eq!(
+            config.get_with_options::<i32>("b.port", &resolve_options)?,
+            2
+        );
+        let key = "connection_timeout_milliseconds";
+        let input = format!("a.{key} = 1\nb {{ {key} = 2 }}");
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                intern_keys: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let value = Config::parse_str_raw(&input, Some(options))?.resolve_value()?;
+        let key_of = |object: &str| {
+            let object = value.get_by_path([object]).and_then(Value::as_object);
+            object
+                .and_then(|object| object.get_key_value(key))
+                .map(|(key, _)| key.as_ptr())
+        };
+        assert_eq!(key_of("a"), key_of("b"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_options_resolve() -> Result<()> {
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                overrides: std::rc::Rc::new(std::collections::HashMap::from([(
+                    "port".to_string(),
+                    Value::from(9090),
+                )])),
+                self_reference: crate::SelfReference::Error,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let input = "port = 80, url = \"http://localhost:\"${port}";
+        let value: Value = Config::parse_str(input, Some(options.clone()))?;
+        assert_eq!(value.get_by_path(["port"]), Some(&Value::from(9090)));
+        let config = Config::parse_str_raw(input, Some(options.clone()))?;
+        assert_eq!(config.get::<String>("url")?, "http://localhost:9090");
+        assert_eq!(config.resolve::<Value>()?, value);
+        let result = Config::parse_str::<Value>("a = [1], a = ${a} [2]", Some(options));
+        assert!(matches!(result, Err(Error::SelfReferenceNotAllowed { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_defaults_for_missing() -> Result<()> {
+        let options = ConfigOptions {
+            use_system_environment: false,
+            resolve: ResolveOptions {
+                defaults_for_missing: std::rc::Rc::new(std::collections::HashMap::from([
+                    ("HOCON_RS_TEST_PORT".to_string(), Value::from(8080)),
+                    ("a".to_string(), Value::from(2)),
+                    ("b".to_string(), Value::from(3)),
+                ])),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let input =
+            "a = 1\nx = ${?a}\nport = ${?HOCON_RS_TEST_PORT}\nhosts = [h, ${?HOCON_RS_TEST_HOST}]";
+        let value: Value = Config::parse_str(input, Some(options.clone()))?;
+        let expected: Value =
+            Config::parse_str("a = 1, x = 1, port = 8080, hosts = [h, null]", None)?;
+        assert_eq!(value, expected);
+
+        let config = Config::parse_str_raw(input, Some(options.clone()))?;
+        assert_eq!(config.resolve::<Value>()?, expected);
+
+        let result = Config::parse_str::<Value>("y = ${b}", Some(options.clone()));
+        assert!(matches!(result, Err(Error::SubstitutionNotFound(_))));
+
+        let resolve_options = ResolveOptions {
+            defaults_for_missing: options.resolve.defaults_for_missing.clone(),
+            ..Default::default()
+        };
+        let config = Config::parse_str_raw("port = ${?HOCON_RS_TEST_PORT}", None)?;
+        let (_, trace) = config.resolve_traced::<Value>(&resolve_options)?;
+        let step = trace.steps_for("port").next().unwrap();
+        assert_eq!(
+            step.source,
+            crate::resolution_trace::SubstitutionSource::Default
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_config() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "db { host = localhost, port = ${?PORT}, url = ${db.host}\":\"${missing} }\na = 1",
+            None,
+        )?;
+        let mut db = config.get_config("db")?;
+        db.add_kv("missing", Value::from(5432));
+        let db: Value = db.resolve()?;
+        assert_eq!(db.get_by_path(["host"]), Some(&Value::from("localhost")));
+        assert_eq!(
+            db.get_by_path(["url"]),
+            Some(&Value::from("localhost:5432"))
+        );
+        assert!(matches!(
+            config.get_config("a"),
+            Err(Error::InvalidConversion { to: "object", .. })
+        ));
+        assert!(matches!(
+            config.get_config("b"),
+            Err(Error::PathNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_source() -> Result<()> {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Error>();
+
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let error =
+            Config::load::<Value>("resources/include_invalid.conf", Some(options)).unwrap_err();
+        let Error::Include { error, .. } = error else {
+            panic!("expected an include error, got {error:?}");
+        };
+        let Error::Parse {
+            origin, position, ..
+        } = *error
+        else {
+            panic!("expected a parse error, got {error:?}");
+        };
+        assert_eq!(&*origin.unwrap().name(), "resources/invalid_syntax.conf");
+        assert_eq!((position.line, position.column), (2, 6));
+
+        let error = Config::parse_str::<Value>("a = ]", None).unwrap_err();
+        assert!(error.to_string().starts_with("Parse error at <string>:1:4"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_json_is_strict() -> Result<()> {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let error = Config::load::<Value>("resources/include_invalid_json.conf", Some(options))
+            .unwrap_err();
+        let Error::Include { error, .. } = error else {
+            panic!("expected an include error, got {error:?}");
+        };
+        assert!(matches!(*error, Error::Serde(_)), "got {error:?}");
+        Ok(())
+    }
+
     #[test]
     fn test_max_depth() -> Result<()> {
         let error = Config::load::<Value>("resources/max_depth.conf", None)
             .err()
             .unwrap();
-        assert!(matches!(error, Error::RecursionDepthExceeded { .. }));
+        assert!(matches!(error, Error::NestingDepthExceeded { .. }));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(3, 3, false)]
+    #[case(3, 2, true)]
+    fn test_max_include_depth(
+        #[case] files: usize,
+        #[case] max_include_depth: usize,
+        #[case] exceeded: bool,
+    ) -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = |i: usize| dir.path().join(format!("{i}.conf"));
+        std::fs::write(path(files), "a = 1")?;
+        for i in 0..files {
+            let include = path(i + 1).display().to_string();
+            std::fs::write(path(i), format!("include file({include:?})"))?;
+        }
+        // A deep chain of includes doesn't nest objects.
+        let options = ConfigOptions {
+            max_include_depth,
+            max_nesting_depth: 1,
+            ..Default::default()
+        };
+        let result = Config::load::<Value>(path(0), Some(options));
+        if exceeded {
+            let mut error = result.err().unwrap();
+            while let Error::Include { error: inner, .. } = error {
+                error = *inner;
+            }
+            assert!(matches!(
+                error,
+                Error::IncludeDepthExceeded { max_depth: 2 }
+            ));
+        } else {
+            assert_eq!(
+                result?,
+                Value::object_from_iter([("a".to_string(), Value::Number(1.into()))])
+            );
+        }
         Ok(())
     }
 
     #[test]
     fn test_include_cycle() -> Result<()> {
-        let mut options = ConfigOptions::default();
-        options.classpath = vec!["resources".to_string()].into();
-        let error = Config::load::<Value>("resources/include_cycle.conf", Some(options))
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let mut error = Config::load::<Value>("resources/include_cycle.conf", Some(options))
             .err()
             .unwrap();
         assert!(matches!(error, Error::Include { .. }));
+        while let Error::Include { error: inner, .. } = error {
+            error = *inner;
+        }
+        let Error::IncludeCycle { chain } = &error else {
+            panic!("expected an include cycle, got {error}");
+        };
+        let file = |name: &str| std::path::Path::new("resources").join(name);
+        let sources = chain
+            .iter()
+            .map(|site| site.source.as_ref().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            ["include_cycle", "include_cycle2", "include_cycle3"].map(|name| file(&format!(
+                "{name}.conf"
+            ))
+            .display()
+            .to_string())
+        );
+        assert_eq!(chain[2].path, "include_cycle.conf");
+        assert_eq!(
+            chain[2].position,
+            Some(crate::parser::read::Position::new(1, 0))
+        );
+        let message = format!(
+            "Include cycle: {}:1:0 includes \"include_cycle2.conf\" -> ",
+            file("include_cycle.conf").display()
+        );
+        assert!(error.to_string().starts_with(&message), "{error}");
+        assert!(error.to_string().ends_with(" (cycle closed)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_cycle_locations() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let conf = dir.path().join("conf");
+        std::fs::create_dir_all(conf.join("sub"))?;
+        // The same path in another directory is another file.
+        std::fs::write(conf.join("common.conf"), "include \"sub/x.conf\"\na = 1")?;
+        std::fs::write(conf.join("sub/x.conf"), "include \"common.conf\"\nb = 1")?;
+        std::fs::write(conf.join("sub/common.conf"), "c = 1")?;
+        let value: Value = Config::load(conf.join("common.conf"), None)?;
+        assert_eq!(value, Config::parse_str("a = 1, b = 1, c = 1", None)?);
+
+        // Different paths to the same file.
+        std::fs::write(conf.join("a.conf"), "include \"sub/b.conf\"")?;
+        std::fs::write(conf.join("sub/b.conf"), "include \"../../conf/a.conf\"")?;
+        let mut error = Config::load::<Value>(conf.join("a.conf"), None)
+            .err()
+            .unwrap();
+        while let Error::Include { error: inner, .. } = error {
+            error = *inner;
+        }
+        let Error::IncludeCycle { chain } = error else {
+            panic!("expected an include cycle, got {error}");
+        };
+        let paths = chain
+            .iter()
+            .map(|site| site.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["sub/b.conf", "../../conf/a.conf"]);
         Ok(())
     }
 
     #[test]
     fn test_substitution_cycle() -> Result<()> {
-        let mut options = ConfigOptions::default();
-        options.classpath = vec!["resources".to_string()].into();
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
         let error = Config::load::<Value>("resources/substitution_cycle.conf", Some(options))
             .err()
             .unwrap();
@@ -313,12 +2790,345 @@ mod tests {
 
     #[test]
     fn test_substitution_not_found() -> Result<()> {
-        let mut options = ConfigOptions::default();
-        options.classpath = vec!["resources".to_string()].into();
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
         let error = Config::load::<Value>("resources/substitution2.conf", Some(options))
             .err()
             .unwrap();
-        assert!(matches!(error, Error::SubstitutionNotFound { .. }));
+        assert!(matches!(error, Error::SelfReferenceNotFound { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_fallback() -> Result<()> {
+        let defaults = Config::parse_str_raw("a = [1]\nb { c = [x] }", None)?;
+        let config = Config::parse_str_raw("a += 2\nb.c = ${b.c} [y]", None)?;
+        let value: Value = config.with_fallback(defaults).resolve()?;
+        assert_eq!(
+            value.get_by_path(["a"]),
+            Some(&Value::Array(vec![Value::from(1), Value::from(2)]))
+        );
+        assert_eq!(
+            value.get_by_path(["b", "c"]),
+            Some(&Value::Array(vec![Value::from("x"), Value::from("y")]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extensions() -> Result<()> {
+        use crate::syntax::Syntax;
+
+        let mut options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let config = Config::load_raw("resources/base", Some(options.clone()))?;
+        let origin = config.get_origin("a").unwrap();
+        assert_eq!(
+            &*origin.source.as_ref().unwrap().name(),
+            "resources/base.json"
+        );
+
+        options.extensions = vec![Syntax::Hocon].into();
+        let config = Config::load_raw("base", Some(options.clone()))?;
+        let origin = config.get_origin("a").unwrap();
+        assert_eq!(
+            &*origin.source.as_ref().unwrap().name(),
+            "resources/base.conf"
+        );
+
+        options.extensions = vec![Syntax::Properties].into();
+        let error = Config::load_raw("base", Some(options)).unwrap_err();
+        assert_eq!(
+            error.searched_locations().unwrap(),
+            ["base.properties", "resources/base.properties"]
+        );
+        assert!(
+            error
+                .to_string()
+                .starts_with("No configuration file was found")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_all() -> Result<()> {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let value: Value = Config::load_all(
+            ["load_all_base.conf", "load_all_override.conf"],
+            Some(options),
+        )?;
+        assert_eq!(
+            value.get_by_path(["host"]),
+            Some(&Value::from("example.com"))
+        );
+        assert_eq!(value.get_by_path(["port"]), Some(&Value::from(8080)));
+        assert_eq!(
+            value.get_by_path(["url"]),
+            Some(&Value::from("http://example.com:8080"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_with_profile() -> Result<()> {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let value: Value = Config::load_with_profile("profile.conf", "prod", Some(options))?;
+        assert_eq!(
+            value.get_by_path(["url"]),
+            Some(&Value::from("http://localhost:9000"))
+        );
+        let value: Value = Config::load_with_profile("resources/profile", "dev", None)?;
+        assert_eq!(
+            value.get_by_path(["url"]),
+            Some(&Value::from("http://dev.example.com:9000"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_dir() -> Result<()> {
+        use crate::DirectoryOptions;
+        use crate::syntax::Syntax;
+
+        let value: Value = Config::load_dir("resources/conf.d", None, None)?;
+        assert_eq!(value.get_by_path(["name"]), Some(&Value::from("override")));
+        assert_eq!(
+            value.get_by_path(["greeting"]),
+            Some(&Value::from("hello override"))
+        );
+        assert_eq!(value.get_by_path(["nested"]), None);
+
+        let dir_options = DirectoryOptions::new(true, vec![Syntax::Hocon, Syntax::Json]);
+        let value: Value = Config::load_dir("resources/conf.d", Some(dir_options), None)?;
+        assert_eq!(value.get_by_path(["name"]), Some(&Value::from("nested")));
+        assert_eq!(value.get_by_path(["nested"]), Some(&Value::Boolean(true)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_valid() -> Result<()> {
+        let reference =
+            Config::parse_str_raw("a = 1\nb { c = true, d = text }\ne = [1]\nf = null", None)?;
+        let config = Config::parse_str_raw(
+            "a = \"2\"\nb { c = yes, d = 3 }\ne = [x]\nf { g = 1 }",
+            None,
+        )?;
+        config.check_valid(&reference, &[])?;
+
+        let config = Config::parse_str_raw("a = [1]\nb { c = 1, x = 1 }\ne = 1", None)?;
+        let Err(Error::Validation { problems }) = config.check_valid(&reference, &[]) else {
+            panic!("expected a validation error");
+        };
+        let paths = problems.iter().map(|p| p.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["a", "b.c", "b.x", "e"]);
+        assert_eq!(
+            problems[1].origin.as_ref().unwrap().position.unwrap().line,
+            2
+        );
+
+        let Err(Error::Validation { problems }) = config.check_valid(&reference, &["b"]) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(problems.len(), 2);
+
+        let reference = Config::parse_str_raw("a { \"b.c\" = 1, b { c = 1 } }", None)?;
+        let config = Config::parse_str_raw("a { \"b.c\" = x, b { c = x } }", None)?;
+        let Err(Error::Validation { problems }) = config.check_valid(&reference, &["a.\"b.c\""])
+        else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(
+            config.check_valid(&reference, &["a..b"]),
+            Err(Error::InvalidPathExpression(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_substitutions() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "base { x = 1 }\na = ${base} { y = 2 }\nb = [${a.x}, ${a.y}, ${base.x}]\n\
+             c = ${b} ${z.d}\nd = { e = ${secret}, f = ${db.url}, g = ${HOME_DIR} }",
+            None,
+        )?;
+        let Err(Error::Validation { problems }) =
+            config.check_substitutions(&ResolveOptions::default())
+        else {
+            panic!("expected a validation error");
+        };
+        let paths = problems.iter().map(|p| p.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["c", "d.e", "d.f", "d.g"]);
+        assert_eq!(
+            problems[1].origin.as_ref().unwrap().position.unwrap().line,
+            5
+        );
+
+        let options = ResolveOptions {
+            use_system_environment: true,
+            environment: Some(
+                std::collections::BTreeMap::from([("HOME_DIR".to_string(), "/".to_string())])
+                    .into(),
+            ),
+            overrides: std::collections::HashMap::from([(
+                "db".to_string(),
+                Value::object_from_iter([("url".to_string(), Value::from("x"))]),
+            )])
+            .into(),
+            resolver: Some(std::rc::Rc::new(|path| {
+                (path == "secret").then(|| Value::from("s"))
+            })),
+            external_root: Some(Config::parse_str_raw("z.d = 1", None)?.into()),
+            ..Default::default()
+        };
+        config.check_substitutions(&options)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_reference() -> Result<()> {
+        use crate::{ResolveOptions, SelfReference};
+
+        let config = Config::parse_str_raw("a = [1]\na = ${a} [2]\nb = ${b} [3]", None)?;
+        let options = ResolveOptions {
+            use_system_environment: false,
+            ..ResolveOptions::with_resolver(|path| (path == "b").then(|| Value::Array(vec![])))
+        };
+        let value: Value = config.clone().resolve_with_options(&options)?;
+        assert_eq!(
+            value.get_by_path(["a"]),
+            Some(&Value::Array(vec![Value::from(1), Value::from(2)]))
+        );
+        assert_eq!(
+            value.get_by_path(["b"]),
+            Some(&Value::Array(vec![Value::from(3)]))
+        );
+
+        let error = config
+            .clone()
+            .resolve_with_options::<Value>(&ResolveOptions::new(false, false))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Self-referential substitution ${b} has no previous value, consulted: earlier definitions of b"
+        );
+
+        let options = ResolveOptions {
+            self_reference: SelfReference::Error,
+            ..ResolveOptions::new(false, false)
+        };
+        let error = config.resolve_with_options::<Value>(&options).unwrap_err();
+        assert!(matches!(error, Error::SelfReferenceNotAllowed { .. }));
+
+        // The error names the field, not the parts of the concatenation it resolves.
+        for (input, expected) in [
+            ("path = [a], path = ${path} [b]", "path"),
+            ("a = { x = 1 }, a = ${a} { y = 2 }", "a"),
+            ("s { a = [1], a = ${s.a} [2] }", "s.a"),
+        ] {
+            let error = Config::parse_str_raw(input, None)?
+                .resolve_with_options::<Value>(&options)
+                .unwrap_err();
+            match error {
+                Error::SelfReferenceNotAllowed { path, .. } => assert_eq!(path, expected),
+                error => panic!("unexpected error: {error}"),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_comment() -> Result<()> {
+        let input = "// Unrelated.\n\n// The server.\n// Second line.\nserver {\n  port = 80 // trailing\n}\n# Wins.\nserver.port = 8080\n";
+        let mut config = Config::parse_str_raw(input, None)?;
+        assert_eq!(
+            config.doc_comment("server").as_deref(),
+            Some("The server.\nSecond line.")
+        );
+        assert_eq!(config.doc_comment("server.port").as_deref(), Some("Wins."));
+        assert_eq!(config.doc_comment("missing"), None);
+
+        config.set_doc_comment("server.port", "Port.\nDefaults to 8080.")?;
+        config.set_doc_comment("server", "")?;
+        assert_eq!(config.doc_comment("server"), None);
+        assert_eq!(
+            config.doc_comment("server.port").as_deref(),
+            Some("Port.\nDefaults to 8080.")
+        );
+        assert!(matches!(
+            config.set_doc_comment("missing", "x"),
+            Err(Error::PathNotFound(_))
+        ));
+
+        let (object, index) = config
+            .object
+            .find_field(&crate::path::Path::from_str("server")?)
+            .unwrap();
+        let inner = match &object[index] {
+            crate::raw::field::ObjectField::KeyValue {
+                value: crate::raw::raw_value::RawValue::Object(inner),
+                ..
+            } => inner,
+            other => panic!("expected an object field, got {other:?}"),
+        };
+        assert_eq!(inner[0].comment().unwrap().content, " trailing");
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_report() -> Result<()> {
+        let input = "a = 1\nb { c = ${a}, d = ${?nope} }\ninclude \"missing.conf\"";
+        let (value, report) = Config::parse_str_with_report::<Value>(input, None)?;
+        assert_eq!(value, Config::parse_str::<Value>(input, None)?);
+        assert_eq!(report.provenance.keys().collect::<Vec<_>>(), ["a", "b.c"]);
+        assert_eq!(report.provenance["b.c"].layer, crate::origin::Layer::String);
+        assert_eq!(report.stats.fields, 4);
+        assert_eq!(report.stats.substitutions, 2);
+        assert_eq!(report.stats.entries, 2);
+        assert_eq!(report.stats.includes, 0);
+        assert!(report.sources.is_empty());
+        assert_eq!(report.warnings.len(), 2);
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let (_, report) = Config::load_with_report::<Value>("include.conf", Some(options.clone()))?;
+        let (_, expected) = Config::load_raw("include.conf", Some(options))?
+            .resolve_with_report::<Value>(&ResolveOptions::default())?;
+        assert_eq!(report.provenance, expected.provenance);
+        assert_eq!(report.sources, expected.sources);
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_not_found() -> Result<()> {
+        use crate::load_report::LoadWarning;
+
+        let dir = tempfile::tempdir()?;
+        let main = dir.path().join("main.conf");
+        std::fs::write(&main, "include \"local\"")?;
+        let classpath = dir.path().join("cp").display().to_string();
+        let options = ConfigOptions::new(false, vec![classpath.clone()]);
+        let searched = [
+            dir.path().join("local.conf").display().to_string(),
+            dir.path().join("local.json").display().to_string(),
+            dir.path().join("local.properties").display().to_string(),
+            "local.conf".to_string(),
+            "local.json".to_string(),
+            "local.properties".to_string(),
+            dir.path().join("cp/local.conf").display().to_string(),
+            dir.path().join("cp/local.json").display().to_string(),
+            dir.path().join("cp/local.properties").display().to_string(),
+        ];
+        let (_, report) = Config::load_with_report::<Value>(&main, Some(options.clone()))?;
+        match &report.warnings[..] {
+            [warning @ LoadWarning::MissingInclude { searched: s, .. }] => {
+                assert_eq!(s, &searched);
+                assert!(warning.to_string().contains(&searched.join(", ")));
+            }
+            warnings => panic!("expected a missing include, got {warnings:?}"),
+        }
+
+        std::fs::write(&main, "include required(\"local\")")?;
+        let error = Config::load::<Value>(&main, Some(options)).unwrap_err();
+        assert!(matches!(error, Error::Include { .. }));
+        assert_eq!(error.searched_locations().unwrap(), searched);
+        assert!(error.to_string().contains(&searched.join(", ")));
         Ok(())
     }
 }
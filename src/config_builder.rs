@@ -0,0 +1,326 @@
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+
+use crate::Result;
+use crate::config::Config;
+use crate::config_options::{ConfigOptions, DirectoryOptions};
+use crate::error::Error;
+use crate::origin::{Layer, Origin};
+use crate::parser::loader::{self, parse_hocon};
+use crate::parser::read::CopyRead;
+use crate::raw::field::ObjectField;
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_string::RawString;
+use crate::raw::raw_value::RawValue;
+use crate::value::Value;
+
+#[derive(Debug, Clone)]
+enum Source {
+    Str(String),
+    File { path: PathBuf, required: bool },
+    Dir(PathBuf, DirectoryOptions),
+    EnvPrefix(String),
+    Override(String, Value),
+    Config(Box<Config>),
+}
+
+/// Composes a configuration from several sources.
+///
+/// Sources are merged in the order they were added, so every source overrides the ones
+/// added before it. Substitutions are resolved once after everything is merged, which
+/// lets a file refer to values from the embedded defaults or the environment.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::{Config, Value};
+///
+/// const DEFAULTS: &str = "server { host = localhost, port = 8080 }";
+///
+/// let value: Value = Config::builder()
+///     .add_str(DEFAULTS)
+///     .add_optional_file("app.conf")
+///     .add_env_prefix("APP_")
+///     .add_override("server.port", 9090)
+///     .build()
+///     .unwrap();
+/// assert_eq!(value.get_by_path(["server", "port"]).unwrap().as_i64(), Some(9090));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    options: ConfigOptions,
+    sources: Vec<Source>,
+}
+
+impl ConfigBuilder {
+    pub fn new(options: Option<ConfigOptions>) -> Self {
+        Self {
+            options: options.unwrap_or_default(),
+            sources: vec![],
+        }
+    }
+
+    /// Sets the options used to find and parse every source.
+    ///
+    /// When [ConfigOptions::use_system_environment] is set, all environment variables are
+    /// merged below every source, like [Config::load] does.
+    pub fn options(mut self, options: ConfigOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds a HOCON string, e.g. defaults embedded with `include_str!`.
+    pub fn add_str(mut self, s: impl Into<String>) -> Self {
+        self.sources.push(Source::Str(s.into()));
+        self
+    }
+
+    /// Adds a configuration file, looked up like in [Config::load]. Building fails if it
+    /// doesn't exist.
+    pub fn add_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File {
+            path: path.into(),
+            required: true,
+        });
+        self
+    }
+
+    /// Adds a configuration file that is skipped if it doesn't exist.
+    pub fn add_optional_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::File {
+            path: path.into(),
+            required: false,
+        });
+        self
+    }
+
+    /// Adds every configuration file in `dir`, merged like in [Config::load_dir].
+    pub fn add_dir(
+        mut self,
+        dir: impl Into<PathBuf>,
+        dir_options: Option<DirectoryOptions>,
+    ) -> Self {
+        self.sources
+            .push(Source::Dir(dir.into(), dir_options.unwrap_or_default()));
+        self
+    }
+
+    /// Adds the environment variables starting with `prefix`.
+    ///
+    /// The prefix is stripped and the rest of the name is lowercased and turned into a
+    /// path: `_` separates keys, `__` stands for a literal `_` and `___` for `-`. With the
+    /// prefix `APP_`, `APP_DB_MAX__POOL___SIZE=10` sets `db.max_pool-size` to `10`.
+    /// Values that look like numbers or booleans are converted, everything else is a string.
+    ///
+    /// The variables are read from [`crate::ResolveOptions::environment`] of
+    /// [ConfigOptions::resolve] if set, otherwise from the process.
+    pub fn add_env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.sources.push(Source::EnvPrefix(prefix.into()));
+        self
+    }
+
+    /// Sets the value at `path`, overriding the sources added before. `path` is a HOCON path
+    /// expression, so keys containing dots are quoted like `a."b.c"`. An invalid path fails
+    /// the build with [`Error::InvalidPathExpression`].
+    pub fn add_override(mut self, path: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.sources
+            .push(Source::Override(path.into(), value.into()));
+        self
+    }
+
+    /// Adds an already parsed, unresolved configuration.
+    pub fn add_config(mut self, config: Config) -> Self {
+        self.sources.push(Source::Config(Box::new(config)));
+        self
+    }
+
+    /// Merges all sources without resolving substitutions.
+    pub fn build_raw(self) -> Result<Config> {
+        let options = ConfigOptions {
+            use_system_environment: false,
+            ..self.options.clone()
+        };
+        let mut object = if self.options.use_system_environment {
+            loader::parse_environments(&self.options)
+        } else {
+            RawObject::default()
+        };
+        for source in self.sources {
+            let raw = match source {
+                Source::Str(s) => parse_hocon(CopyRead::new(s.as_bytes()), options.clone(), None)?,
+                Source::File { path, required } => {
+                    match loader::load(&path, options.clone(), None) {
+                        Err(Error::Io(io))
+                            if !required && io.kind() == std::io::ErrorKind::NotFound =>
+                        {
+                            continue;
+                        }
+                        result => result?,
+                    }
+                }
+                Source::Dir(dir, dir_options) => {
+                    loader::load_dir(dir, &dir_options, options.clone(), None)?
+                }
+                Source::EnvPrefix(prefix) => env_with_prefix(&prefix, &options),
+                Source::Override(path, value) => {
+                    let keys = crate::path::Path::from_str(&path)?.keys();
+                    let key = RawString::path_expression(
+                        keys.into_iter().map(RawString::quoted).collect(),
+                    );
+                    let mut field = ObjectField::key_value(key, value);
+                    field.set_origin(Origin::synthetic(Layer::Override, Some(&path)));
+                    RawObject::new(vec![field])
+                }
+                Source::Config(config) => config.object,
+            };
+            object = RawObject::merge(object, raw);
+        }
+        Ok(Config {
+            object,
+            options: self.options,
+        })
+    }
+
+    /// Merges all sources, resolves the result and deserializes it into `T`.
+    pub fn build<T>(self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.build_raw()?.resolve()
+    }
+}
+
+fn env_with_prefix(prefix: &str, options: &ConfigOptions) -> RawObject<'static> {
+    let mut object = RawObject::default();
+    for (name, value) in loader::environment_vars(options) {
+        if let Some(suffix) = name.strip_prefix(prefix)
+            && !suffix.is_empty()
+        {
+            let key = RawString::path_expression(
+                env_name_to_path(suffix)
+                    .into_iter()
+                    .map(RawString::quoted)
+                    .collect(),
+            );
+            let mut field = ObjectField::key_value(key, env_value(value));
+            field.set_origin(Origin::synthetic(Layer::Environment, Some(&name)));
+            object.push(field);
+        }
+    }
+    object
+}
+
+/// Environment variables are always strings, so values that look like a number or a
+/// boolean are converted, the same way unquoted values in a HOCON file would be.
+fn env_value(value: String) -> RawValue<'static> {
+    match value.as_str() {
+        "true" => RawValue::Boolean(true),
+        "false" => RawValue::Boolean(false),
+        _ => match value.parse() {
+            Ok(number) => RawValue::Number(number),
+            Err(_) => RawValue::quoted_string(value),
+        },
+    }
+}
+
+fn env_name_to_path(name: &str) -> Vec<String> {
+    let mut keys = vec![];
+    let mut key = String::new();
+    let mut chars = name.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '_' {
+            key.extend(ch.to_lowercase());
+            continue;
+        }
+        let mut underscores = 1;
+        while chars.next_if_eq(&'_').is_some() {
+            underscores += 1;
+        }
+        match underscores {
+            1 => keys.push(std::mem::take(&mut key)),
+            2 => key.push('_'),
+            _ => key.push('-'),
+        }
+    }
+    keys.push(key);
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    use rstest::rstest;
+    use serde::Deserialize;
+
+    use crate::Result;
+    use crate::config::Config;
+    use crate::config_options::ConfigOptions;
+    use crate::error::Error;
+    use crate::resolve_options::ResolveOptions;
+    use crate::value::Value;
+
+    use super::env_name_to_path;
+
+    #[rstest]
+    #[case("DB_HOST", &["db", "host"])]
+    #[case("MAX__POOL", &["max_pool"])]
+    #[case("DB_POOL___SIZE", &["db", "pool-size"])]
+    fn test_env_name_to_path(#[case] name: &str, #[case] expected: &[&str]) {
+        assert_eq!(env_name_to_path(name), expected);
+    }
+
+    #[test]
+    fn test_builder() -> Result<()> {
+        #[derive(Deserialize)]
+        struct App {
+            host: String,
+            port: u16,
+            url: String,
+            workers: u32,
+            plugins: Vec<String>,
+        }
+
+        let environment = BTreeMap::from([
+            ("HOCON_RS_BUILDER_TEST_WORKERS".to_string(), "8".to_string()),
+            ("OTHER_WORKERS".to_string(), "2".to_string()),
+        ]);
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                environment: Some(Rc::new(environment)),
+                ..Default::default()
+            },
+            ..ConfigOptions::new(false, vec!["resources".to_string()])
+        };
+        let app: App = Config::builder()
+            .options(options)
+            .add_str("host = localhost, port = 80, workers = 1, plugins = [core]")
+            .add_file("load_all_override.conf")
+            .add_optional_file("missing.conf")
+            .add_env_prefix("HOCON_RS_BUILDER_TEST_")
+            .add_str("plugins += extra, url = \"http://\"${host}\":\"${port}")
+            .add_override("port", 9090)
+            .build()?;
+        assert_eq!(app.host, "example.com");
+        assert_eq!(app.port, 9090);
+        assert_eq!(app.url, "http://example.com:9090");
+        assert_eq!(app.workers, 8);
+        assert_eq!(app.plugins, ["core", "extra"]);
+
+        let missing = Config::builder()
+            .add_file("resources/missing.conf")
+            .build::<Value>();
+        assert!(missing.is_err());
+
+        let value: Value = Config::builder()
+            .add_str("a { \"b.c\" = 1, b { c = 2 } }")
+            .add_override("a.\"b.c\"", 3)
+            .build()?;
+        let expected: Value = Config::parse_str("a { \"b.c\" = 3, b { c = 2 } }", None)?;
+        assert_eq!(value, expected);
+        let invalid = Config::builder().add_override("a..b", 1).build::<Value>();
+        assert!(matches!(invalid, Err(Error::InvalidPathExpression(_))));
+        Ok(())
+    }
+}
@@ -1,23 +1,231 @@
-use std::{fmt::Debug, rc::Rc};
+use std::{collections::HashMap, fmt::Debug, path::PathBuf, rc::Rc, time::Duration};
 
+use crate::classpath::ClasspathProvider;
+use crate::resolve_options::ResolveOptions;
 use crate::syntax::Syntax;
+use crate::url_handler::UrlHandler;
 
-pub(crate) const MAX_DEPTH: usize = 64;
+pub(crate) const MAX_NESTING_DEPTH: usize = 64;
 
 pub(crate) const MAX_INCLUDE_DEPTH: usize = 64;
 
 pub type CompareFn = Rc<dyn Fn(&Syntax, &Syntax) -> std::cmp::Ordering>;
 
+/// Whether behavior beyond the [HOCON specification] is enabled, see
+/// [`crate::ResolveOptions::spec_strictness`].
+///
+/// [HOCON specification]: https://github.com/lightbend/config/blob/main/HOCON.md
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SpecStrictness {
+    /// Enable the extensions of this crate that are configured in the options:
+    ///
+    /// - Values tagged with `"$merge"` are merged by [`crate::ResolveOptions::merge_strategies`].
+    /// - [`ConfigOptions::extended_numbers`] and [`ConfigOptions::non_finite_numbers`].
+    /// - [`ConfigOptions::multiline_margin`] and [`ConfigOptions::expand_home`].
+    #[default]
+    Extended,
+    /// Behave like the specification and the JVM implementation, typesafe-config, so a
+    /// configuration resolves to the same value with both. The extensions listed for
+    /// [`SpecStrictness::Extended`] are disabled whatever the options say, e.g. a `"$merge"`
+    /// key is an ordinary key.
+    Strict,
+}
+
+/// How the indentation of triple-quoted strings is treated, see
+/// [`ConfigOptions::multiline_margin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MultilineMargin {
+    /// Keep the strings as written, as the specification requires.
+    #[default]
+    Keep,
+    /// Remove the indentation common to all lines that aren't blank, and the first and the
+    /// last line if they are blank, like Kotlin's `trimIndent`.
+    TrimIndent,
+    /// Remove the whitespace at the start of every line up to and including a `|`, like
+    /// Scala's `stripMargin`. Lines without a `|` after their indentation are kept.
+    StripMargin,
+}
+
+/// Where the relative paths of file includes are looked up, see
+/// [`ConfigOptions::include_resolution`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IncludeResolution {
+    /// In the directory of the including file first, as the specification requires, then
+    /// like [`IncludeResolution::WorkingDirectory`].
+    #[default]
+    RelativeToFile,
+    /// In the working directory, then in [`ConfigOptions::classpath`] for includes
+    /// without `file()`.
+    WorkingDirectory,
+}
+
+/// How included URLs are fetched with the `urls_includes` feature, see
+/// [`ConfigOptions::url_options`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct UrlOptions {
+    /// How long a request may take, from connecting until the whole response is read.
+    /// 30 seconds by default, `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// The most bytes read from one response before [`crate::Error::ResponseTooLarge`] is
+    /// returned. Unlimited by default, though [`ConfigOptions::max_input_bytes`] applies.
+    pub max_response_bytes: usize,
+    /// How many redirects are followed, `0` to follow none. 10 by default.
+    pub max_redirects: usize,
+    /// Headers sent with the requests to [`UrlOptions::header_origins`], e.g.
+    /// `("Authorization", "Bearer ...")`.
+    pub headers: Vec<(String, String)>,
+    /// The origins [`UrlOptions::headers`] are sent to, e.g. `https://config.example.com`.
+    /// Requests to other origins, e.g. redirects or URLs included by a fetched
+    /// configuration, are sent without them, so a configuration can't include a URL of
+    /// its choice to read the credentials. Empty by default.
+    pub header_origins: Vec<String>,
+    /// Where responses are cached, `None` by default. A cached response is revalidated
+    /// with its `ETag` and `Last-Modified` headers, so an unchanged config isn't sent again,
+    /// and is used as is when the server can't be reached or answers with a server error.
+    /// Responses are cached per URL and [`UrlOptions::headers`] sent to it.
+    pub cache: Option<UrlCache>,
+    /// How long after it was fetched or last revalidated a cached response is still used
+    /// when the server can't be reached. One day by default, `None` uses it at any age.
+    pub max_stale: Option<Duration>,
+}
+
+/// Where the responses of included URLs are cached, see [`UrlOptions::cache`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UrlCache {
+    /// In memory, shared by every load in the process until
+    /// [`crate::Config::invalidate_caches`] is called.
+    Memory,
+    /// In files in a directory, created if missing, so the responses outlive the process
+    /// and can be shared by several processes. The files are readable by their owner only.
+    Directory(PathBuf),
+}
+
+impl Default for UrlOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            max_response_bytes: usize::MAX,
+            max_redirects: 10,
+            headers: vec![],
+            header_origins: vec![],
+            cache: None,
+            max_stale: Some(Duration::from_secs(24 * 60 * 60)),
+        }
+    }
+}
+
+/// Leaves out the header values, which often hold credentials.
+impl Debug for UrlOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        f.debug_struct("UrlOptions")
+            .field("timeout", &self.timeout)
+            .field("max_response_bytes", &self.max_response_bytes)
+            .field("max_redirects", &self.max_redirects)
+            .field("headers", &headers)
+            .field("header_origins", &self.header_origins)
+            .field("cache", &self.cache)
+            .field("max_stale", &self.max_stale)
+            .finish()
+    }
+}
+
 #[derive(Clone)]
 pub struct ConfigOptions {
     pub use_system_environment: bool,
     pub compare: CompareFn,
+    /// The directories searched for classpath resources, in order. With the `zip` feature,
+    /// entries with a `.zip` or `.jar` extension are read as archives, see
+    /// `hocon_rs::classpath::ArchiveClasspath`.
     pub classpath: Rc<Vec<String>>,
-    pub max_depth: usize,
+    /// More sources of classpath resources, searched in order after the directories of
+    /// [`ConfigOptions::classpath`], e.g. an [`crate::classpath::EmbeddedClasspath`].
+    /// The async loaders call them without leaving the runtime's thread, so they must not
+    /// block, see [`ClasspathProvider`].
+    pub classpath_providers: Rc<Vec<Rc<dyn ClasspathProvider>>>,
+    /// How deeply objects and arrays may nest, across included files, before
+    /// [`crate::Error::NestingDepthExceeded`] is returned. Parsing recurses per level, so
+    /// this bounds the stack used.
+    pub max_nesting_depth: usize,
+    /// How deeply includes may nest, counting the included files only, before
+    /// [`crate::Error::IncludeDepthExceeded`] is returned.
     pub max_include_depth: usize,
+    /// The syntaxes tried, in order, for paths without a `.conf`, `.json` or
+    /// `.properties` extension. All files found are merged in the order given by
+    /// [`ConfigOptions::compare`].
+    pub extensions: Rc<Vec<Syntax>>,
+    /// Cache the results of [`crate::Config::load`] and [`crate::Config::parse_file`] in a
+    /// process wide cache, see [`crate::Config::invalidate_caches`]. Loads with
+    /// [`ConfigOptions::classpath_providers`], [`ConfigOptions::url_handlers`], or
+    /// [`crate::ResolveOptions::resolver`], [`crate::ResolveOptions::external_root`] or
+    /// [`crate::ResolveOptions::merge_strategies`] in [`ConfigOptions::resolve`] are not
+    /// cached, and neither are configurations that include a URL. A cached configuration is loaded again once one of the files it
+    /// was loaded from changes, included ones too.
+    pub cache: bool,
+    /// How substitutions are resolved when resolving while loading, e.g. in
+    /// [`crate::Config::load`], and by [`crate::Config::resolve`] and [`crate::Config::get`]
+    /// of a configuration loaded with these options. The methods that take
+    /// [`crate::ResolveOptions`] use the ones they are given instead.
+    ///
+    /// [`crate::ResolveOptions::spec_strictness`] and [`crate::ResolveOptions::intern_keys`]
+    /// apply to parsing as well.
+    pub resolve: ResolveOptions,
+    /// How the indentation of triple-quoted strings is treated, so embedded scripts and
+    /// queries don't carry the indentation of the file into the value. Kept by default.
+    pub multiline_margin: MultilineMargin,
+    /// Read unquoted values like `0xFF` and `1_000_000` as numbers instead of strings.
+    /// Hexadecimal numbers must fit in 64 bits and `_` is only allowed between digits.
+    /// Disabled by default.
+    pub extended_numbers: bool,
+    /// Read the unquoted values `Infinity`, `-Infinity` and `NaN` as [`crate::Value::NonFinite`]
+    /// floats instead of strings. Disabled by default.
+    pub non_finite_numbers: bool,
+    /// Parse a file or URL included several times while loading one configuration only
+    /// once, keyed by its canonical path or URL, and identical contents read from
+    /// different locations only once as well. Disable it if the same location may return
+    /// different contents during a load, every include is then read and parsed on its
+    /// own. Enabled by default.
+    pub cache_includes: bool,
+    /// How included URLs are fetched, e.g. their timeout and headers.
+    pub url_options: UrlOptions,
+    /// The handlers of custom URL schemes keyed by scheme, e.g. `"s3"`, used for
+    /// `include url(...)`, `include "..."` and [`crate::Config::parse_url`]. They work
+    /// without the `urls_includes` feature. See [`ConfigOptions::register_url_handler`].
+    pub url_handlers: Rc<HashMap<String, Rc<dyn UrlHandler>>>,
+    /// Where the relative paths of `include "..."` and `include file("...")` are looked
+    /// up. Relative to the including file by default.
+    pub include_resolution: IncludeResolution,
+    /// Expand a leading `~` in the paths of file includes to the home directory of the
+    /// user. Disabled by default.
+    pub expand_home: bool,
+    /// The most bytes read from all sources of one configuration, including included
+    /// files, before [`crate::Error::InputTooLarge`] is returned. Unlimited by default.
+    ///
+    /// This and the following limits bound the memory used for untrusted input. JSON and
+    /// properties files are only bounded by this limit.
+    pub max_input_bytes: usize,
+    /// The most fields and include statements of one object as written, before
+    /// [`crate::Error::TooManyObjectEntries`] is returned. Unlimited by default.
+    pub max_object_entries: usize,
+    /// The most elements of one array as written, before [`crate::Error::ArrayTooLong`] is
+    /// returned. Unlimited by default.
+    pub max_array_len: usize,
+    /// The most values parsed from all sources of one configuration, counting every value
+    /// nested in objects, arrays and concatenations, before [`crate::Error::TooManyNodes`]
+    /// is returned. Unlimited by default.
+    pub max_nodes: usize,
 }
 
 impl ConfigOptions {
+    /// Returns `true` unless [`crate::ResolveOptions::spec_strictness`] disables the extensions.
+    pub(crate) fn extended(&self) -> bool {
+        self.resolve.spec_strictness == SpecStrictness::Extended
+    }
+
     pub fn new(use_system_env: bool, classpath: Vec<String>) -> Self {
         Self {
             use_system_environment: use_system_env,
@@ -38,6 +246,17 @@ impl ConfigOptions {
             ..Default::default()
         }
     }
+
+    /// Reads URLs with `scheme`, e.g. `"s3"`, with `handler`, replacing the handler
+    /// registered for it before.
+    pub fn register_url_handler(
+        &mut self,
+        scheme: impl Into<String>,
+        handler: impl UrlHandler + 'static,
+    ) {
+        let scheme = scheme.into().to_ascii_lowercase();
+        Rc::make_mut(&mut self.url_handlers).insert(scheme, Rc::new(handler));
+    }
 }
 
 impl Default for ConfigOptions {
@@ -46,8 +265,24 @@ impl Default for ConfigOptions {
             use_system_environment: false,
             compare: Rc::new(Syntax::cmp),
             classpath: Default::default(),
-            max_depth: MAX_DEPTH,
+            classpath_providers: Default::default(),
+            max_nesting_depth: MAX_NESTING_DEPTH,
             max_include_depth: MAX_INCLUDE_DEPTH,
+            extensions: Rc::new(vec![Syntax::Hocon, Syntax::Json, Syntax::Properties]),
+            cache: false,
+            resolve: ResolveOptions::default(),
+            multiline_margin: MultilineMargin::default(),
+            extended_numbers: false,
+            non_finite_numbers: false,
+            cache_includes: true,
+            url_options: UrlOptions::default(),
+            url_handlers: Default::default(),
+            include_resolution: IncludeResolution::default(),
+            expand_home: false,
+            max_input_bytes: usize::MAX,
+            max_object_entries: usize::MAX,
+            max_array_len: usize::MAX,
+            max_nodes: usize::MAX,
         }
     }
 }
@@ -57,6 +292,24 @@ impl Debug for ConfigOptions {
         f.debug_struct("ConfigOptions")
             .field("use_system_environment", &self.use_system_environment)
             .field("classpath", &self.classpath)
+            .field("classpath_providers", &self.classpath_providers)
+            .field("max_nesting_depth", &self.max_nesting_depth)
+            .field("max_include_depth", &self.max_include_depth)
+            .field("extensions", &self.extensions)
+            .field("cache", &self.cache)
+            .field("resolve", &self.resolve)
+            .field("multiline_margin", &self.multiline_margin)
+            .field("extended_numbers", &self.extended_numbers)
+            .field("non_finite_numbers", &self.non_finite_numbers)
+            .field("cache_includes", &self.cache_includes)
+            .field("url_options", &self.url_options)
+            .field("url_handlers", &self.url_handlers)
+            .field("include_resolution", &self.include_resolution)
+            .field("expand_home", &self.expand_home)
+            .field("max_input_bytes", &self.max_input_bytes)
+            .field("max_object_entries", &self.max_object_entries)
+            .field("max_array_len", &self.max_array_len)
+            .field("max_nodes", &self.max_nodes)
             .finish_non_exhaustive()
     }
 }
@@ -66,7 +319,63 @@ impl PartialEq for ConfigOptions {
         self.use_system_environment == other.use_system_environment
             && Rc::ptr_eq(&self.compare, &other.compare)
             && self.classpath == other.classpath
+            && self.classpath_providers.len() == other.classpath_providers.len()
+            && self
+                .classpath_providers
+                .iter()
+                .zip(other.classpath_providers.iter())
+                .all(|(a, b)| Rc::ptr_eq(a, b))
+            && self.max_nesting_depth == other.max_nesting_depth
+            && self.max_include_depth == other.max_include_depth
+            && self.extensions == other.extensions
+            && self.cache == other.cache
+            && self.resolve == other.resolve
+            && self.multiline_margin == other.multiline_margin
+            && self.extended_numbers == other.extended_numbers
+            && self.non_finite_numbers == other.non_finite_numbers
+            && self.cache_includes == other.cache_includes
+            && self.url_options == other.url_options
+            && self.url_handlers.len() == other.url_handlers.len()
+            && self.url_handlers.iter().all(|(scheme, handler)| {
+                other
+                    .url_handlers
+                    .get(scheme)
+                    .is_some_and(|other| Rc::ptr_eq(handler, other))
+            })
+            && self.include_resolution == other.include_resolution
+            && self.expand_home == other.expand_home
+            && self.max_input_bytes == other.max_input_bytes
+            && self.max_object_entries == other.max_object_entries
+            && self.max_array_len == other.max_array_len
+            && self.max_nodes == other.max_nodes
     }
 }
 
 impl Eq for ConfigOptions {}
+
+/// Controls which files [`crate::Config::load_dir`] picks up from a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryOptions {
+    /// Also load files from subdirectories.
+    pub recursive: bool,
+    /// Only files with the extension of one of these syntaxes are loaded.
+    pub extensions: Vec<Syntax>,
+}
+
+impl DirectoryOptions {
+    pub fn new(recursive: bool, extensions: Vec<Syntax>) -> Self {
+        Self {
+            recursive,
+            extensions,
+        }
+    }
+}
+
+impl Default for DirectoryOptions {
+    fn default() -> Self {
+        Self {
+            recursive: false,
+            extensions: vec![Syntax::Hocon],
+        }
+    }
+}
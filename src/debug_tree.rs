@@ -0,0 +1,270 @@
+//! Indented tree dumps of raw and merged values, for debugging and bug reports.
+//!
+//! Every node is printed on a line of its own with its type, nested nodes are indented
+//! by two spaces. Raw fields show where they were defined, merged objects and arrays show
+//! whether they still contain values to resolve.
+
+use std::fmt::{Arguments, Write};
+
+use crate::merge::array::Array as MArray;
+use crate::merge::object::Object as MObject;
+use crate::merge::value::Value as MValue;
+use crate::origin::Origin;
+use crate::raw::field::ObjectField;
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_value::RawValue;
+
+#[derive(Default)]
+struct Tree {
+    out: String,
+    depth: usize,
+}
+
+impl Tree {
+    fn line(&mut self, args: Arguments) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+        self.out.write_fmt(args).unwrap();
+        self.out.push('\n');
+    }
+
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        f(self);
+        self.depth -= 1;
+    }
+
+    fn raw_fields(&mut self, object: &RawObject) {
+        for field in object.fields() {
+            match field {
+                ObjectField::KeyValue {
+                    key, value, origin, ..
+                } => {
+                    let location = origin.as_ref().map(location).unwrap_or_default();
+                    self.raw_value(&format!("{key} = "), value, &location);
+                }
+                ObjectField::Inclusion { inclusion, .. } => match inclusion.object() {
+                    Some(object) => {
+                        self.line(format_args!("{inclusion}"));
+                        self.nested(|tree| tree.raw_fields(object));
+                    }
+                    None => self.line(format_args!("{inclusion} (not loaded)")),
+                },
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
+            }
+        }
+    }
+
+    fn raw_value(&mut self, label: &str, value: &RawValue, location: &str) {
+        match value {
+            RawValue::Object(object) => {
+                self.line(format_args!("{label}object{location}"));
+                self.nested(|tree| tree.raw_fields(object));
+            }
+            RawValue::Array(array) => {
+                self.line(format_args!("{label}array{location}"));
+                self.nested(|tree| {
+                    for (index, value) in array.values().iter().enumerate() {
+                        let location = array
+                            .element_span(index)
+                            .map(|span| format!(" @ {span}"))
+                            .unwrap_or_default();
+                        tree.raw_value(&format!("[{index}] "), value, &location);
+                    }
+                });
+            }
+            RawValue::Concat(concat) => {
+                self.line(format_args!("{label}concat{location}"));
+                self.nested(|tree| {
+                    let spaces = concat.get_spaces();
+                    for (index, value) in concat.get_values().iter().enumerate() {
+                        if let Some(Some(space)) = index.checked_sub(1).map(|i| &spaces[i]) {
+                            tree.line(format_args!("space {space:?}"));
+                        }
+                        tree.raw_value("", value, "");
+                    }
+                });
+            }
+            RawValue::AddAssign(add_assign) => {
+                self.line(format_args!("{label}add_assign{location}"));
+                self.nested(|tree| tree.raw_value("", add_assign, ""));
+            }
+            RawValue::Boolean(_)
+            | RawValue::Null
+            | RawValue::String(_)
+            | RawValue::Number(_)
+            | RawValue::NonFinite(_)
+            | RawValue::Substitution(_) => {
+                self.line(format_args!("{label}{} {value}{location}", value.ty()));
+            }
+        }
+    }
+
+    fn merge_value(&mut self, label: &str, value: &MValue) {
+        match value {
+            MValue::Object(object) => {
+                let state = match object {
+                    MObject::Merged(_) => "merged",
+                    MObject::Unmerged(_) => "unmerged",
+                };
+                self.line(format_args!("{label}object ({state})"));
+                self.nested(|tree| {
+                    for (key, value) in object.iter() {
+                        tree.merge_value(&format!("{key} = "), &value.borrow());
+                    }
+                });
+            }
+            MValue::Array(array) => {
+                let (state, values) = match array {
+                    MArray::Merged(values) => ("merged", values),
+                    MArray::Unmerged(values) => ("unmerged", values),
+                };
+                self.line(format_args!("{label}array ({state})"));
+                self.nested(|tree| {
+                    for (index, value) in values.iter().enumerate() {
+                        tree.merge_value(&format!("[{index}] "), &value.borrow());
+                    }
+                });
+            }
+            MValue::Concat(concat) => {
+                self.line(format_args!("{label}concat"));
+                self.nested(|tree| {
+                    let spaces = concat.get_spaces();
+                    for (index, value) in concat.get_values().iter().enumerate() {
+                        if let Some(Some(space)) = index.checked_sub(1).map(|i| &spaces[i]) {
+                            tree.line(format_args!("space {space:?}"));
+                        }
+                        tree.merge_value("", &value.borrow());
+                    }
+                });
+            }
+            MValue::AddAssign(add_assign) => {
+                self.line(format_args!("{label}add_assign"));
+                self.nested(|tree| tree.merge_value("", &add_assign.0));
+            }
+            MValue::DelayReplacement(replacement) => {
+                self.line(format_args!("{label}delay_replacement"));
+                self.nested(|tree| {
+                    for value in replacement.iter() {
+                        tree.merge_value("", &value.borrow());
+                    }
+                });
+            }
+            MValue::None => self.line(format_args!("{label}none")),
+            MValue::Boolean(_)
+            | MValue::Null
+            | MValue::String(_)
+            | MValue::Number(_)
+            | MValue::NonFinite(_)
+            | MValue::Substitution(_) => {
+                self.line(format_args!("{label}{} {value}", value.ty()));
+            }
+        }
+    }
+}
+
+/// Formats where a field was defined, e.g. ` @ a.conf:1:0-1:5`.
+fn location(origin: &Origin) -> String {
+    let position = match (origin.span(), origin.position) {
+        (Some(span), _) => span.to_string(),
+        (None, Some(position)) => position.to_string(),
+        (None, None) => return format!(" @ {origin}"),
+    };
+    match &origin.source {
+        Some(source) => format!(" @ {source}:{position}"),
+        None => format!(" @ {position}"),
+    }
+}
+
+impl RawValue<'_> {
+    /// Returns an indented tree of this value for debugging, with the type of every node
+    /// and the span of every field and array element parsed from a source. Comments are
+    /// left out.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    /// use hocon_rs::raw::raw_object::RawObject;
+    /// use hocon_rs::raw::raw_value::RawValue;
+    ///
+    /// let config = Config::parse_str_raw("a = [1, ${b} ms]", None).unwrap();
+    /// let value = RawValue::Object(RawObject::new(config.fields().to_vec()));
+    /// assert_eq!(
+    ///     value.debug_tree(),
+    ///     "object\n\
+    ///     \x20 a = array @ 1:0-1:16\n\
+    ///     \x20   [0] number 1 @ 1:5-1:6\n\
+    ///     \x20   [1] concat @ 1:8-1:15\n\
+    ///     \x20     substitution ${b}\n\
+    ///     \x20     space \" \"\n\
+    ///     \x20     unquoted_string ms\n"
+    /// );
+    /// ```
+    pub fn debug_tree(&self) -> String {
+        let mut tree = Tree::default();
+        tree.raw_value("", self, "");
+        tree.out
+    }
+}
+
+impl RawObject<'_> {
+    /// Returns an indented tree of the fields of this object for debugging, see
+    /// [`RawValue::debug_tree`].
+    pub fn debug_tree(&self) -> String {
+        let mut tree = Tree::default();
+        tree.raw_fields(self);
+        tree.out
+    }
+}
+
+impl MValue {
+    /// Returns an indented tree of this value for debugging, with the merge state of every
+    /// object and array.
+    pub(crate) fn debug_tree(&self) -> String {
+        let mut tree = Tree::default();
+        tree.merge_value("", self);
+        tree.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use crate::config::Config;
+
+    #[test]
+    fn test_debug_tree() -> Result<()> {
+        let config = Config::parse_str_raw(
+            "a { b = 1 }\na.c += x\nd = ${a} { e = \"s\" }\ninclude \"missing.conf\"",
+            None,
+        )?;
+        assert_eq!(
+            config.object.debug_tree(),
+            "a = object @ 1:0-1:11\n\
+            \x20 b = number 1 @ 1:4-1:10\n\
+            a.c = add_assign @ 2:0-2:8\n\
+            \x20 unquoted_string x\n\
+            d = concat @ 3:0-3:20\n\
+            \x20 substitution ${a}\n\
+            \x20 space \" \"\n\
+            \x20 object\n\
+            \x20   e = quoted_string s @ 3:11-3:19\n\
+            include \"missing.conf\" (not loaded)\n"
+        );
+        assert_eq!(
+            config.debug_merge_tree()?,
+            "object (unmerged)\n\
+            \x20 a = object (unmerged)\n\
+            \x20   b = number 1\n\
+            \x20   c = array (merged)\n\
+            \x20     [0] string x\n\
+            \x20 d = concat\n\
+            \x20   substitution ${a}\n\
+            \x20   space \" \"\n\
+            \x20   object (merged)\n\
+            \x20     e = string s\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! Flattening of resolved values into dotenv (`.env`) files.
+//!
+//! Every leaf becomes a `NAME=value` line. Names are built from the keys of the path as
+//! configured by [`DotenvOptions`], array elements use their index as key. Values that
+//! contain anything but letters, digits and `_./:@,+-` are double quoted with `\`, `"`,
+//! `$` and newlines escaped, the quoting Docker Compose and most dotenv loaders read.
+
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+use crate::value::{Value, ordered_entries};
+
+/// Controls how [`Value::to_dotenv_string`] names the variables.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::{Config, DotenvOptions, EnvKeyMangling, Value};
+///
+/// let value: Value = Config::parse_str("db { max_pool-size = 10 }", None).unwrap();
+/// let options = DotenvOptions {
+///     prefix: "APP_".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(value.to_dotenv_string(&options), "APP_DB_MAX__POOL___SIZE=10\n");
+/// let plain = DotenvOptions {
+///     mangling: EnvKeyMangling::Plain,
+///     ..Default::default()
+/// };
+/// assert_eq!(value.to_dotenv_string(&plain), "DB_MAX_POOL_SIZE=10\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DotenvOptions {
+    /// Written in front of every name as is, e.g. `APP_`.
+    pub prefix: String,
+    /// How the keys of a path are turned into a name.
+    pub mangling: EnvKeyMangling,
+}
+
+/// Builds a variable name from the keys of a path, see [`EnvKeyMangling::Custom`].
+pub type EnvKeyFn = Rc<dyn Fn(&[&str]) -> String>;
+
+/// How [`DotenvOptions`] turns the keys of a path into a variable name.
+#[derive(Clone, Default)]
+pub enum EnvKeyMangling {
+    /// The inverse of [`crate::ConfigBuilder::add_env_prefix`]: keys are uppercased and
+    /// joined with `_`, a `_` in a key is written as `__` and a `-` as `___`, so the file
+    /// reads back into the same paths. Other characters that can't appear in a name are
+    /// replaced by `_`, and keys with uppercase letters read back lowercased.
+    #[default]
+    Escaped,
+    /// Keys are uppercased and joined with `_`, every character that can't appear in a
+    /// name is replaced by `_`. Reads nicer, but `a_b` and `a.b` get the same name.
+    Plain,
+    /// Builds the name, without the prefix, from the keys of the path.
+    Custom(EnvKeyFn),
+}
+
+impl Debug for EnvKeyMangling {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvKeyMangling::Escaped => write!(f, "Escaped"),
+            EnvKeyMangling::Plain => write!(f, "Plain"),
+            EnvKeyMangling::Custom(_) => write!(f, "Custom(<fn>)"),
+        }
+    }
+}
+
+impl EnvKeyMangling {
+    fn name(&self, keys: &[&str]) -> String {
+        let mangle_key = match self {
+            EnvKeyMangling::Escaped => escaped_key,
+            EnvKeyMangling::Plain => plain_key,
+            EnvKeyMangling::Custom(mangle) => return mangle(keys),
+        };
+        keys.iter()
+            .map(|key| mangle_key(key))
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+}
+
+fn escaped_key(key: &str) -> String {
+    let mut name = String::new();
+    for ch in key.chars() {
+        match ch {
+            '_' => name.push_str("__"),
+            '-' => name.push_str("___"),
+            ch => name.push(name_char(ch)),
+        }
+    }
+    name
+}
+
+fn plain_key(key: &str) -> String {
+    key.chars().map(name_char).collect()
+}
+
+fn name_char(ch: char) -> char {
+    if ch.is_ascii_alphanumeric() {
+        ch.to_ascii_uppercase()
+    } else {
+        '_'
+    }
+}
+
+/// Renders the leaves of `value` as dotenv lines, see the module documentation.
+pub(crate) fn render_dotenv(value: &Value, options: &DotenvOptions) -> String {
+    let mut rendered = String::new();
+    if let Value::Object(_) = value.unshared() {
+        render_entries(value, &mut vec![], options, &mut rendered);
+    }
+    rendered
+}
+
+fn render_entries(
+    value: &Value,
+    path: &mut Vec<String>,
+    options: &DotenvOptions,
+    out: &mut String,
+) {
+    match value.unshared() {
+        Value::Object(object) => {
+            for (key, value) in ordered_entries(object) {
+                path.push(key.to_string());
+                render_entries(value, path, options, out);
+                path.pop();
+            }
+        }
+        Value::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                path.push(index.to_string());
+                render_entries(element, path, options, out);
+                path.pop();
+            }
+        }
+        Value::Null => {}
+        Value::Boolean(boolean) => write_entry(path, &boolean.to_string(), options, out),
+        Value::Number(number) => write_entry(path, &number.to_string(), options, out),
+        Value::NonFinite(non_finite) => write_entry(path, &non_finite.to_string(), options, out),
+        Value::String(string) => write_entry(path, string, options, out),
+        Value::Shared(_) => unreachable!("unshared values are never shared"),
+    }
+}
+
+fn write_entry(path: &[String], value: &str, options: &DotenvOptions, out: &mut String) {
+    let keys = path.iter().map(String::as_str).collect::<Vec<_>>();
+    out.push_str(&options.prefix);
+    out.push_str(&options.mangling.name(&keys));
+    out.push('=');
+    let plain = value
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || "_./:@,+-".contains(ch));
+    if plain {
+        out.push_str(value);
+    } else {
+        out.push('"');
+        for ch in value.chars() {
+            match ch {
+                '\\' | '"' | '$' => {
+                    out.push('\\');
+                    out.push(ch);
+                }
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                ch => out.push(ch),
+            }
+        }
+        out.push('"');
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{DotenvOptions, EnvKeyMangling};
+    use crate::Result;
+    use crate::config::Config;
+    use crate::value::Value;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("a { b = 1, c = true }", "A_B=1\nA_C=true\n")]
+    #[case(
+        "servers = [{ host = x }, { host = y }]",
+        "SERVERS_0_HOST=x\nSERVERS_1_HOST=y\n"
+    )]
+    #[case("a = null, b = [], c = {}", "")]
+    #[case("a = \"\", b = \"http://x:80/y\"", "A=\nB=http://x:80/y\n")]
+    #[case("a = \"say \\\"hi\\\" $HOME\\n\"", "A=\"say \\\"hi\\\" \\$HOME\\n\"\n")]
+    #[case("\"a.b\" { c_d = 1 }", "A_B_C__D=1\n")]
+    fn test_render_dotenv(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let value: Value = Config::parse_str(input, None)?;
+        assert_eq!(value.to_dotenv_string(&DotenvOptions::default()), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dotenv_custom_mangling() -> Result<()> {
+        let value: Value = Config::parse_str("db { url = x }", None)?;
+        let options = DotenvOptions {
+            prefix: "APP.".to_string(),
+            mangling: EnvKeyMangling::Custom(Rc::new(|keys| keys.join("."))),
+        };
+        assert_eq!(value.to_dotenv_string(&options), "APP.db.url=x\n");
+        Ok(())
+    }
+}
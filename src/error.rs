@@ -1,5 +1,8 @@
 use std::fmt::Display;
 
+use crate::origin::{Origin, SourceId};
+use crate::parser::read::Position;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{0}")]
@@ -21,6 +24,16 @@ pub enum Error {
     },
     #[error("End of file")]
     Eof,
+    #[error(
+    "Parse error at {}:{position}: {error}",
+    origin.as_ref().map(|o| o.name()).as_deref().unwrap_or("<string>")
+    )]
+    Parse {
+        /// The file or URL being parsed, `None` for in-memory strings.
+        origin: Option<SourceId>,
+        position: Position,
+        error: Box<Error>,
+    },
     #[error("Cannot convert `{from}` to `{to}`")]
     InvalidConversion {
         from: &'static str,
@@ -44,10 +57,32 @@ pub enum Error {
         "Resolve incomplete. This should never happen outside this library. If you see this, it's a bug."
     )]
     ResolveIncomplete,
+    /// No longer returned, include cycles are reported as [`Error::IncludeCycle`].
     #[error("Circular include detected")]
     InclusionCycle,
     #[error("Object nesting depth exceeded the limit of {max_depth} levels")]
-    RecursionDepthExceeded { max_depth: usize },
+    NestingDepthExceeded { max_depth: usize },
+    #[error("Include depth exceeded the limit of {max_depth} levels")]
+    IncludeDepthExceeded { max_depth: usize },
+    #[error("Input exceeded the limit of {max_bytes} bytes")]
+    InputTooLarge { max_bytes: usize },
+    #[error("Response from {url} exceeded the limit of {max_bytes} bytes")]
+    ResponseTooLarge { url: String, max_bytes: usize },
+    #[error("Object exceeded the limit of {max_entries} entries")]
+    TooManyObjectEntries { max_entries: usize },
+    #[error("Array exceeded the limit of {max_len} elements")]
+    ArrayTooLong { max_len: usize },
+    #[error("Input exceeded the limit of {max_nodes} values")]
+    TooManyNodes { max_nodes: usize },
+    #[error(
+    "Include cycle: {} (cycle closed)",
+    chain.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" -> ")
+    )]
+    IncludeCycle {
+        /// The include statements from the root source to the one including a source
+        /// that is already being loaded.
+        chain: Vec<IncludeSite>,
+    },
     #[error("Inclusion: {inclusion} error: {error}")]
     Include {
         inclusion: String,
@@ -61,11 +96,54 @@ pub enum Error {
         current: String,
         backtrace: Vec<String>,
     },
+    #[error("Self-referential substitution {substitution} at {path} is not allowed")]
+    SelfReferenceNotAllowed { substitution: String, path: String },
+    #[error(
+    "Self-referential substitution {substitution} has no previous value, consulted: {}",
+    consulted.join(", ")
+    )]
+    SelfReferenceNotFound {
+        substitution: String,
+        consulted: Vec<String>,
+    },
     #[error("Substitution depth exceeded the limit of {max_depth} levels")]
     SubstitutionDepthExceeded { max_depth: usize },
     #[error("{0}")]
     Deserialize(String),
     #[error("{0}")]
+    Serialize(String),
+    #[error("Merge strategy {strategy} failed at {path}: {message}")]
+    MergeStrategy {
+        path: String,
+        strategy: String,
+        message: String,
+    },
+    #[error("Path {0} not found")]
+    PathNotFound(String),
+    #[error(
+    "Invalid value at {path}{}: {error}",
+    origin.as_ref().map(|o| format!(" ({o})")).unwrap_or_default()
+    )]
+    AtPath {
+        /// The path of the value, with array indices in brackets, e.g. `servers[0].port`.
+        path: String,
+        error: Box<Error>,
+        /// Where the value was defined, see [`crate::Config::annotate_error`].
+        origin: Option<Box<Origin>>,
+    },
+    #[error(
+    "Invalid configuration: {}",
+    problems.iter().map(|p| p.to_string()).collect::<Vec<_>>().join("; ")
+    )]
+    Validation { problems: Vec<ValidationProblem> },
+    #[error(
+    "Policy violations: {}",
+    violations.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; ")
+    )]
+    PolicyViolation {
+        violations: Vec<crate::policy::Violation>,
+    },
+    #[error("{0}")]
     JavaProperties(#[from] java_properties::PropertiesError),
     #[error("{0}")]
     UrlParse(#[from] url::ParseError),
@@ -76,6 +154,136 @@ pub enum Error {
     UrlsIncludesDisabled,
 }
 
+/// A single violation found by [`crate::Config::check_valid`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationProblem {
+    /// The rendered path of the offending entry, e.g. `a.b`.
+    pub path: String,
+    /// What is wrong with the entry.
+    pub problem: String,
+    /// Where the entry was defined, when known.
+    pub origin: Option<Origin>,
+}
+
+impl Display for ValidationProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.problem)?;
+        if let Some(origin) = &self.origin {
+            write!(f, " ({})", origin)?;
+        }
+        Ok(())
+    }
+}
+
+/// An include statement in the chain of an [`Error::IncludeCycle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeSite {
+    /// The file or URL holding the statement, `None` for in-memory strings.
+    pub source: Option<SourceId>,
+    /// Where the statement starts.
+    pub position: Option<Position>,
+    /// The included path or URL as written.
+    pub path: String,
+}
+
+impl Display for IncludeSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{}", source)?,
+            None => write!(f, "<string>")?,
+        }
+        if let Some(position) = &self.position {
+            write!(f, ":{}", position)?;
+        }
+        write!(f, " includes {:?}", self.path)
+    }
+}
+
+/// The locations looked up for a configuration that wasn't found, in order: files on
+/// disk, classpath resources and URLs. It is the source of the
+/// [`std::io::ErrorKind::NotFound`] errors of missing configurations, see
+/// [`Error::searched_locations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotFound {
+    pub searched: Vec<String>,
+}
+
+impl Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No configuration file was found, searched: [{}]",
+            self.searched.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+impl From<NotFound> for Error {
+    fn from(not_found: NotFound) -> Self {
+        Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, not_found))
+    }
+}
+
+impl Error {
+    /// Returns the locations searched for a configuration that wasn't found, also when it
+    /// was included, e.g. by `include required("app.conf")`.
+    pub fn searched_locations(&self) -> Option<&[String]> {
+        match self {
+            Error::Io(io) => io
+                .get_ref()?
+                .downcast_ref::<NotFound>()
+                .map(|not_found| not_found.searched.as_slice()),
+            Error::Include { error, .. } => error.searched_locations(),
+            _ => None,
+        }
+    }
+
+    /// Nests the error below `key`, prefixing the path of an [`Error::AtPath`].
+    pub(crate) fn at_key(self, key: &str) -> Error {
+        self.at_segment(crate::path::render_key(key).into_owned())
+    }
+
+    /// Nests the error below the array element at `index`.
+    pub(crate) fn at_index(self, index: usize) -> Error {
+        self.at_segment(format!("[{index}]"))
+    }
+
+    fn at_segment(self, segment: String) -> Error {
+        match self {
+            Error::AtPath {
+                path,
+                error,
+                origin,
+            } => {
+                let separator = if path.starts_with('[') { "" } else { "." };
+                Error::AtPath {
+                    path: format!("{segment}{separator}{path}"),
+                    error,
+                    origin,
+                }
+            }
+            // Not a problem of the value, but of the library.
+            Error::ResolveIncomplete => Error::ResolveIncomplete,
+            error => Error::AtPath {
+                path: segment,
+                error: Box::new(error),
+                origin: None,
+            },
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self::Serialize(msg.to_string())
+    }
+}
+
 impl serde::de::Error for Error {
     #[doc = r" Raised when there is general error when deserializing a type."]
     #[doc = r""]
@@ -0,0 +1,89 @@
+//! Fingerprints identifying exactly which configuration a process runs with.
+
+use std::fmt::{Display, Formatter, Write};
+
+use sha2::{Digest, Sha256};
+
+use crate::origin::Layer;
+use crate::raw::raw_object::RawObject;
+use crate::render::Renderer;
+use crate::value::Value;
+
+/// Identifies a resolved configuration and the sources it was built from, see
+/// [`crate::Config::fingerprint`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    /// The hex encoded SHA-256 of the canonical resolved value together with every
+    /// entry of [`Fingerprint::sources`].
+    pub digest: String,
+    /// The files and URLs that contributed an entry, sorted by name.
+    pub sources: Vec<SourceFingerprint>,
+}
+
+/// A source that contributed to a [`Fingerprint`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceFingerprint {
+    /// The file path or URL, as recorded in the [`crate::origin::Origin`] of its entries.
+    pub name: String,
+    /// The hex encoded SHA-256 of the contents read from the source while loading, see
+    /// [`crate::origin::SourceId::digest`].
+    pub digest: Option<String>,
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.digest)
+    }
+}
+
+impl Fingerprint {
+    pub(crate) fn new(object: &RawObject, value: &Value) -> Fingerprint {
+        let mut canonical = String::new();
+        Renderer::new(&mut canonical)
+            .render_root_value(value)
+            .expect("writing to a String never fails");
+        let sources = sources(object);
+        let mut hasher = Sha256::new();
+        hasher.update(canonical.as_bytes());
+        for source in &sources {
+            hasher.update([0]);
+            hasher.update(source.name.as_bytes());
+            hasher.update([0]);
+            hasher.update(source.digest.as_deref().unwrap_or_default().as_bytes());
+        }
+        Fingerprint {
+            digest: hex(&hasher.finalize()),
+            sources,
+        }
+    }
+}
+
+/// Collects the sources recorded in the origins of every field, with the digests of their
+/// contents recorded while loading. Environment variables and overrides are not sources,
+/// their values are covered by the canonical value.
+fn sources(object: &RawObject) -> Vec<SourceFingerprint> {
+    let mut sources = object
+        .select("**")
+        .into_iter()
+        .filter_map(|selection| selection.origin())
+        .filter(|origin| !matches!(origin.layer, Layer::Environment | Layer::Override))
+        .filter_map(|origin| origin.source.clone())
+        .collect::<Vec<_>>();
+    sources.sort();
+    sources.dedup();
+    sources
+        .into_iter()
+        .map(|source| SourceFingerprint {
+            name: source.name().to_string(),
+            digest: source.digest().map(|digest| hex(digest)),
+        })
+        .collect()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{byte:02x}").expect("writing to a String never fails");
+    }
+    hex
+}
@@ -1,7 +1,6 @@
-use super::Value;
+use crate::value::{Map, Value};
 use core::fmt::{self, Display};
 use core::ops;
-use std::collections::HashMap;
 
 /// A trait used to index into a HOCON [`Value`].
 ///
@@ -45,18 +44,19 @@ pub trait Index: private::Sealed {
 
 impl Index for usize {
     fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
-        match v {
+        match v.unshared() {
             Value::Array(vec) => vec.get(*self),
             _ => None,
         }
     }
     fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
-        match v {
+        match v.make_mut() {
             Value::Array(vec) => vec.get_mut(*self),
             _ => None,
         }
     }
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        let v = v.make_mut();
         match v {
             Value::Array(vec) => {
                 let len = vec.len();
@@ -74,23 +74,24 @@ impl Index for usize {
 
 impl Index for str {
     fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
-        match v {
+        match v.unshared() {
             Value::Object(map) => map.get(self),
             _ => None,
         }
     }
     fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
-        match v {
+        match v.make_mut() {
             Value::Object(map) => map.get_mut(self),
             _ => None,
         }
     }
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
+        let v = v.make_mut();
         if let Value::Null = v {
-            *v = Value::Object(HashMap::new());
+            *v = Value::Object(Map::new());
         }
         match v {
-            Value::Object(map) => map.entry(self.to_owned()).or_insert(Value::Null),
+            Value::Object(map) => map.get_or_insert_with(self, || Value::Null),
             _ => panic!("cannot access key {:?} in HOCON {}", self, Type(v)),
         }
     }
@@ -140,10 +141,11 @@ impl<'a> Display for Type<'a> {
         match *self.0 {
             Value::Null => f.write_str("null"),
             Value::Boolean(_) => f.write_str("boolean"),
-            Value::Number(_) => f.write_str("number"),
+            Value::Number(_) | Value::NonFinite(_) => f.write_str("number"),
             Value::String(_) => f.write_str("string"),
             Value::Array(_) => f.write_str("array"),
             Value::Object(_) => f.write_str("object"),
+            Value::Shared(ref shared) => Type(shared).fmt(f),
         }
     }
 }
@@ -182,7 +184,6 @@ where
 mod tests {
     use super::*;
     use crate::Config;
-    use std::collections::HashMap;
 
     const CONFIG: &str = r#"
 a = {
@@ -275,7 +276,7 @@ a = {
 
     #[test]
     fn test_index_mut_inserts_new_field() {
-        let mut value = Value::Object(HashMap::new());
+        let mut value = Value::Object(Map::new());
         value["new_field"] = Value::String("hi".into());
         assert_eq!(value["new_field"], Value::String("hi".into()));
     }
@@ -285,10 +286,10 @@ a = {
         let vals = vec![
             Value::Null,
             Value::Boolean(true),
-            Value::Number(serde_json::Number::from_f64(3.14).unwrap()),
+            Value::Number(serde_json::Number::from_f64(2.5).unwrap()),
             Value::String("abc".into()),
             Value::Array(vec![]),
-            Value::Object(HashMap::new()),
+            Value::Object(Map::new()),
         ];
         let expected = ["null", "boolean", "number", "string", "array", "object"];
         for (v, exp) in vals.into_iter().zip(expected) {
@@ -309,10 +310,7 @@ a = {
 
     #[test]
     fn test_str_index_into_mut_valid_and_invalid() {
-        let mut obj = Value::Object(HashMap::from([(
-            "x".to_string(),
-            Value::String("ok".into()),
-        )]));
+        let mut obj = Value::Object(Map::from([("x".to_string(), Value::String("ok".into()))]));
         let key = "x";
         assert!(key.index_into_mut(&mut obj).is_some());
         let mut non_obj = Value::Array(vec![]);
@@ -321,7 +319,7 @@ a = {
 
     #[test]
     fn test_string_index_into_mut_and_index_or_insert() {
-        let mut obj = Value::Object(HashMap::new());
+        let mut obj = Value::Object(Map::new());
         let k = "new".to_string();
         // index_into_mut
         assert!(k.index_into_mut(&mut obj).is_none());
@@ -332,7 +330,7 @@ a = {
 
     #[test]
     fn test_ref_index_into_mut_for_string() {
-        let mut obj = Value::Object(HashMap::from([("k".to_string(), Value::Number(10.into()))]));
+        let mut obj = Value::Object(Map::from([("k".to_string(), Value::Number(10.into()))]));
         let k = "k".to_string();
         let ref_k = &k;
         let result = ref_k.index_into_mut(&mut obj);
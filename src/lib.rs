@@ -1,32 +1,65 @@
 use ::serde::{Serialize, de::DeserializeOwned};
 
+mod cache;
+pub mod classpath;
 pub mod config;
+mod config_builder;
 mod config_options;
+mod debug_tree;
+mod dotenv;
 pub mod error;
+pub mod fingerprint;
 pub mod index;
+pub mod lint;
+pub mod load_report;
+pub mod map;
+pub mod merge_strategy;
+pub mod number;
 pub mod object;
+pub mod origin;
 pub mod parser;
 pub(crate) mod path;
+pub mod policy;
+mod properties;
 pub mod raw;
+mod render;
+pub mod resolution_trace;
+mod resolve_options;
+mod resolved_config;
+mod scoped_config;
 pub mod serde;
 pub mod syntax;
 pub mod transform;
+mod units;
+pub mod url_handler;
 pub mod value;
+pub mod value_string;
 
 mod merge {
     pub(crate) mod add_assign;
     pub(crate) mod array;
     pub(crate) mod concat;
     pub(crate) mod delay_replacement;
+    pub(crate) mod intern;
     pub(crate) mod memo;
     pub(crate) mod object;
     pub(crate) mod path;
     pub(crate) mod substitution;
     pub(crate) mod value;
 }
-pub use config::Config;
-pub use config_options::ConfigOptions;
+pub use config::{Config, UnresolvedConfig};
+pub use config_builder::ConfigBuilder;
+pub use config_options::{
+    ConfigOptions, DirectoryOptions, IncludeResolution, MultilineMargin, SpecStrictness, UrlCache,
+    UrlOptions,
+};
+pub use dotenv::{DotenvOptions, EnvKeyFn, EnvKeyMangling};
 pub use error::Error;
+pub use render::{RenderOptions, SaveOptions};
+pub use resolve_options::{ResolveOptions, ResolverFn, SelfReference};
+pub use resolved_config::ResolvedConfig;
+pub use scoped_config::ScopedConfig;
+pub use units::{parse_bytes, parse_duration};
 pub use value::Value;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,172 @@
+//! Checks of configuration source text, reported with their exact positions.
+//!
+//! Unlike [`crate::policy`], which inspects resolved values, lints look at the text as
+//! written, so they find problems that parsing hides.
+
+use std::fmt::{Display, Formatter};
+
+use crate::parser::read::Position;
+use crate::policy::Severity;
+
+/// A problem found in the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// Where the problem is. Columns count bytes, like the positions of the parser.
+    pub position: Position,
+    /// What is wrong.
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.position, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Unquoted,
+    Quoted,
+    Multiline,
+    Comment,
+}
+
+/// Finds characters that look like nothing or like a plain space, the usual cause of two
+/// configurations that look identical but behave differently:
+///
+/// - Zero width and bidirectional control characters anywhere but in comments, e.g.
+///   U+200B ZERO WIDTH SPACE in a key.
+/// - Byte order marks (U+FEFF) after the start of the text, e.g. left over from
+///   concatenating files.
+/// - No-break spaces like U+00A0 outside of quoted strings, where they separate values
+///   like a space but are kept in string concatenations, e.g. `a = foo\u{a0}bar`.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::lint::invisible_characters;
+///
+/// let lints = invisible_characters("\u{feff}a = 1\nb\u{200b} = \"x\u{a0}y\"\nc = x\u{a0}y");
+/// let lints = lints.iter().map(ToString::to_string).collect::<Vec<_>>();
+/// assert_eq!(
+///     lints,
+///     [
+///         "[warning] 2:1: zero width space (U+200B)",
+///         "[warning] 3:5: no-break space (U+00A0) outside of a quoted string",
+///     ]
+/// );
+/// ```
+pub fn invisible_characters(input: &str) -> Vec<Lint> {
+    let mut lints = vec![];
+    let mut context = Context::Unquoted;
+    let (mut line, mut start_of_line) = (1, 0);
+    let mut chars = input.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        let rest = &input[index..];
+        match (context, ch) {
+            (_, '\n') => {
+                line += 1;
+                start_of_line = index + 1;
+                if context == Context::Comment {
+                    context = Context::Unquoted;
+                }
+                continue;
+            }
+            (Context::Unquoted, '"') if rest.starts_with("\"\"\"") => {
+                context = Context::Multiline;
+                chars.nth(1);
+                continue;
+            }
+            (Context::Unquoted, '"') => {
+                context = Context::Quoted;
+                continue;
+            }
+            (Context::Unquoted, '#') => {
+                context = Context::Comment;
+                continue;
+            }
+            (Context::Unquoted, '/') if rest.starts_with("//") => {
+                context = Context::Comment;
+                continue;
+            }
+            (Context::Quoted, '\\') => {
+                chars.next_if(|&(_, next)| next.is_ascii() && next != '\n');
+                continue;
+            }
+            (Context::Quoted, '"') => {
+                context = Context::Unquoted;
+                continue;
+            }
+            (Context::Multiline, '"') if rest.starts_with("\"\"\"") => {
+                // The string ends with the last quote of a run, e.g. `""""` ends with `"`.
+                while chars.next_if(|&(_, next)| next == '"').is_some() {}
+                context = Context::Unquoted;
+                continue;
+            }
+            _ => {}
+        }
+        let message = match ch {
+            '\u{feff}' if index == 0 => continue,
+            '\u{feff}' => "byte order mark (U+FEFF) after the start of the text".to_string(),
+            _ if context == Context::Comment => continue,
+            '\u{a0}' | '\u{2007}' | '\u{202f}' if context == Context::Unquoted => {
+                format!("{} outside of a quoted string", describe(ch))
+            }
+            '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{ad}'
+            | '\u{180e}' => describe(ch),
+            _ => continue,
+        };
+        lints.push(Lint {
+            position: Position::new(line, index - start_of_line),
+            message,
+            severity: Severity::Warning,
+        });
+    }
+    lints
+}
+
+fn describe(ch: char) -> String {
+    let name = match ch {
+        '\u{a0}' => "no-break space",
+        '\u{2007}' => "figure space",
+        '\u{202f}' => "narrow no-break space",
+        '\u{200b}' => "zero width space",
+        '\u{200c}' => "zero width non-joiner",
+        '\u{200d}' => "zero width joiner",
+        '\u{2060}' => "word joiner",
+        '\u{ad}' => "soft hyphen",
+        '\u{180e}' => "mongolian vowel separator",
+        '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}' => {
+            "bidirectional control character"
+        }
+        _ => "invisible character",
+    };
+    format!("{name} (U+{:04X})", ch as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::invisible_characters;
+
+    #[rstest]
+    #[case("a = 1", &[])]
+    #[case("\u{feff}a = 1 # \u{200b}\u{a0}", &[])]
+    #[case("a = \"x\u{a0}\\\"\u{a0}\"", &[])]
+    #[case("a = \"\"\"x\u{a0}\"\"\"\"\u{a0}", &["1:14"])]
+    #[case("a = 1\n\u{feff}b = 2", &["2:0"])]
+    #[case("a = \"\\\u{200d}\" // \u{202e}", &["1:6"])]
+    #[case("a.b\u{2060}c = x\u{202f}y\u{ad}", &["1:3", "1:11", "1:15"])]
+    fn test_invisible_characters(#[case] input: &str, #[case] expected: &[&str]) {
+        let positions = invisible_characters(input)
+            .into_iter()
+            .map(|lint| lint.position.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(positions, expected);
+    }
+}
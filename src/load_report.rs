@@ -0,0 +1,179 @@
+//! Everything observed while loading a configuration, see [`crate::Config::load_with_report`].
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use crate::origin::{Layer, Provenance, SourceId};
+use crate::parser::read::Span;
+use crate::raw::field::ObjectField;
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_value::RawValue;
+use crate::resolution_trace::{ResolutionTrace, SubstitutionSource};
+
+/// What happened while a configuration was loaded and resolved, returned by the
+/// `*_with_report` variants of the load and parse functions and by
+/// [`crate::Config::resolve_with_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Things that didn't fail loading but may not be intended, in the order they were
+    /// found.
+    pub warnings: Vec<LoadWarning>,
+    /// The files and URLs that contributed a field, sorted by name. Environment variables and
+    /// overrides are not sources.
+    pub sources: Vec<SourceId>,
+    /// Where the value of every entry of the result came from, by path, see
+    /// [`crate::Config::provenance`] and [`crate::Value::entry_set`].
+    pub provenance: BTreeMap<String, Provenance>,
+    /// How every substitution was resolved, see [`crate::Config::resolve_traced`].
+    pub trace: ResolutionTrace,
+    pub stats: LoadStats,
+}
+
+/// Counts and timings of a [`LoadReport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadStats {
+    /// The key-value fields as written in every source, before merging.
+    pub fields: usize,
+    /// The include statements that loaded something.
+    pub includes: usize,
+    /// The resolved substitutions, the steps of [`LoadReport::trace`].
+    pub substitutions: usize,
+    /// The entries of the result, the leaves of the resolved value.
+    pub entries: usize,
+    /// How long reading and parsing took, zero when only resolving was reported.
+    pub load_time: Duration,
+    /// How long merging and resolving took.
+    pub resolve_time: Duration,
+}
+
+/// A warning of a [`LoadReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LoadWarning {
+    /// An include statement that isn't `required(...)` found nothing.
+    MissingInclude {
+        /// The include statement, e.g. `include "local.conf"`.
+        inclusion: String,
+        /// Where the statement was parsed from.
+        span: Option<Span>,
+        /// The files, classpath resources and URLs looked up, in order.
+        searched: Vec<String>,
+    },
+    /// An optional substitution, e.g. `${?HOME}`, found nothing, so it was dropped.
+    MissingSubstitution {
+        /// The path of the field the substitution appears in.
+        path: String,
+        /// The substitution as written.
+        substitution: String,
+    },
+}
+
+impl Display for LoadWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadWarning::MissingInclude {
+                inclusion,
+                span,
+                searched,
+            } => {
+                write!(f, "{inclusion} found nothing")?;
+                if let Some(span) = span {
+                    write!(f, " at {}", span.start)?;
+                }
+                if !searched.is_empty() {
+                    write!(f, ", searched: [{}]", searched.join(", "))?;
+                }
+                Ok(())
+            }
+            LoadWarning::MissingSubstitution { path, substitution } => {
+                write!(f, "{path}: {substitution} found nothing")
+            }
+        }
+    }
+}
+
+impl Display for LoadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let stats = &self.stats;
+        writeln!(
+            f,
+            "{} sources, {} fields, {} includes, {} substitutions, {} entries \
+             (loaded in {:?}, resolved in {:?})",
+            self.sources.len(),
+            stats.fields,
+            stats.includes,
+            stats.substitutions,
+            stats.entries,
+            stats.load_time,
+            stats.resolve_time,
+        )?;
+        for warning in &self.warnings {
+            writeln!(f, "warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+impl LoadReport {
+    /// Fills in what can be read from the unresolved `object`.
+    pub(crate) fn new(object: &RawObject, trace: ResolutionTrace) -> LoadReport {
+        let mut report = LoadReport::default();
+        report.collect(object);
+        report.sources.sort();
+        report.sources.dedup();
+        for step in &trace.steps {
+            if step.source == SubstitutionSource::NotFound {
+                report.warnings.push(LoadWarning::MissingSubstitution {
+                    path: step.path.clone(),
+                    substitution: step.substitution.clone(),
+                });
+            }
+        }
+        report.stats.substitutions = trace.steps.len();
+        report.trace = trace;
+        report
+    }
+
+    fn collect(&mut self, object: &RawObject) {
+        for field in object.iter() {
+            match field {
+                ObjectField::Inclusion { inclusion, .. } => match &inclusion.val {
+                    Some(object) => {
+                        self.stats.includes += 1;
+                        self.collect(object);
+                    }
+                    None => self.warnings.push(LoadWarning::MissingInclude {
+                        inclusion: inclusion.to_string(),
+                        span: inclusion.span,
+                        searched: inclusion.searched.clone(),
+                    }),
+                },
+                ObjectField::KeyValue { value, origin, .. } => {
+                    self.stats.fields += 1;
+                    if let Some(origin) = origin
+                        && !matches!(origin.layer, Layer::Environment | Layer::Override)
+                        && let Some(source) = &origin.source
+                    {
+                        self.sources.push(source.clone());
+                    }
+                    self.collect_value(value);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_value(&mut self, value: &RawValue) {
+        match value {
+            RawValue::Object(object) => self.collect(object),
+            RawValue::Array(array) => array.values().iter().for_each(|v| self.collect_value(v)),
+            RawValue::Concat(concat) => concat
+                .get_values()
+                .iter()
+                .for_each(|v| self.collect_value(v)),
+            RawValue::AddAssign(add_assign) => self.collect_value(add_assign),
+            _ => {}
+        }
+    }
+}
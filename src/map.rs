@@ -0,0 +1,272 @@
+//! The map of a [`Value::Object`].
+
+use std::fmt::{self, Debug, Formatter};
+use std::ops::{Index, IndexMut};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::value::Value;
+use crate::value_string::ValueString;
+
+#[cfg(not(feature = "preserve_order"))]
+type Inner = std::collections::HashMap<ValueString, Value>;
+#[cfg(feature = "preserve_order")]
+type Inner = indexmap::IndexMap<ValueString, Value>;
+
+/// The map of a [`Value::Object`], keyed by [`ValueString`].
+///
+/// By default the keys are hashed, so iterating, displaying and serializing objects
+/// visits the keys in an unspecified order that changes from run to run. With the
+/// `preserve_order` feature the keys are kept in the order they were inserted in, which
+/// makes every rendering path deterministic. Resolved configurations insert their keys in
+/// the order they were first defined in. The API is the same either way.
+///
+/// The APIs that flatten values, [`Value::entry_set`], [`Value::select`],
+/// [`Value::to_properties_string`] and [`Value::to_dotenv_string`], never depend on the
+/// hash order: they walk objects depth first, visiting the keys of each object in
+/// insertion order with `preserve_order` and sorted by key otherwise. Array elements are
+/// visited by index.
+#[derive(Clone, Default, PartialEq)]
+pub struct Map(pub(crate) Inner);
+
+impl Map {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty map with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Inner::with_capacity(capacity))
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns the value of `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    /// Returns the stored key and the value of `key`.
+    pub fn get_key_value(&self, key: &str) -> Option<(&ValueString, &Value)> {
+        self.0.get_key_value(key)
+    }
+
+    /// Returns the value of `key` mutably.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.0.get_mut(key)
+    }
+
+    /// Returns `true` if the map has a value for `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Sets the value of `key` and returns the previous one. A key that is already present
+    /// keeps its position.
+    pub fn insert(&mut self, key: impl Into<ValueString>, value: Value) -> Option<Value> {
+        self.0.insert(key.into(), value)
+    }
+
+    /// Removes `key` and returns its value. The remaining keys keep their order.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.0.remove(key);
+        #[cfg(feature = "preserve_order")]
+        return self.0.shift_remove(key);
+    }
+
+    /// Returns the value of `key`, inserting `default()` first if it has none.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: impl Into<ValueString>,
+        default: impl FnOnce() -> Value,
+    ) -> &mut Value {
+        self.0.entry(key.into()).or_insert_with(default)
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(&ValueString, &mut Value) -> bool) {
+        self.0.retain(|key, value| f(key, value))
+    }
+
+    /// Iterates over the entries.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Iterates over the entries with mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut(self.0.iter_mut())
+    }
+
+    /// Iterates over the keys.
+    pub fn keys(&self) -> Keys<'_> {
+        Keys(self.0.keys())
+    }
+
+    /// Iterates over the values.
+    pub fn values(&self) -> Values<'_> {
+        Values(self.0.values())
+    }
+
+    /// Iterates over the values mutably.
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
+        ValuesMut(self.0.values_mut())
+    }
+}
+
+impl Debug for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Index<&str> for Map {
+    type Output = Value;
+
+    /// Returns the value of `key`, panics if it has none.
+    fn index(&self, key: &str) -> &Value {
+        &self.0[key]
+    }
+}
+
+impl IndexMut<&str> for Map {
+    /// Returns the value of `key` mutably, panics if it has none.
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        self.0.get_mut(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Into<ValueString>> FromIterator<(K, Value)> for Map {
+    fn from_iter<T: IntoIterator<Item = (K, Value)>>(iter: T) -> Self {
+        Self(iter.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+}
+
+impl<K: Into<ValueString>> Extend<(K, Value)> for Map {
+    fn extend<T: IntoIterator<Item = (K, Value)>>(&mut self, iter: T) {
+        self.0.extend(iter.into_iter().map(|(k, v)| (k.into(), v)))
+    }
+}
+
+impl<K: Into<ValueString>, const N: usize> From<[(K, Value); N]> for Map {
+    fn from(entries: [(K, Value); N]) -> Self {
+        Self::from_iter(entries)
+    }
+}
+
+impl From<std::collections::HashMap<String, Value>> for Map {
+    fn from(map: std::collections::HashMap<String, Value>) -> Self {
+        Self::from_iter(map)
+    }
+}
+
+impl From<Map> for std::collections::HashMap<String, Value> {
+    fn from(map: Map) -> Self {
+        map.into_iter().map(|(k, v)| (k.into_string(), v)).collect()
+    }
+}
+
+impl Serialize for Map {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Map {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Inner::deserialize(deserializer).map(Self)
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (ValueString, Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a ValueString, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a ValueString, &'a mut Value);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+macro_rules! iterator {
+    ($(#[$doc:meta])* $name:ident<$($lt:lifetime)?>, $inner:ident, $item:ty) => {
+        $(#[$doc])*
+        pub struct $name<$($lt)?>(
+            #[cfg(not(feature = "preserve_order"))] std::collections::hash_map::$inner<$($lt,)? ValueString, Value>,
+            #[cfg(feature = "preserve_order")] indexmap::map::$inner<$($lt,)? ValueString, Value>,
+        );
+
+        impl<$($lt)?> Iterator for $name<$($lt)?> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<$($lt)?> ExactSizeIterator for $name<$($lt)?> {}
+
+        impl<$($lt)?> std::iter::FusedIterator for $name<$($lt)?> {}
+    };
+}
+
+iterator!(
+    /// The entries of a [`Map`], see [`Map::iter`].
+    Iter<'a>, Iter, (&'a ValueString, &'a Value)
+);
+iterator!(
+    /// The entries of a [`Map`] with mutable values, see [`Map::iter_mut`].
+    IterMut<'a>, IterMut, (&'a ValueString, &'a mut Value)
+);
+iterator!(
+    /// The keys of a [`Map`], see [`Map::keys`].
+    Keys<'a>, Keys, &'a ValueString
+);
+iterator!(
+    /// The values of a [`Map`], see [`Map::values`].
+    Values<'a>, Values, &'a Value
+);
+iterator!(
+    /// The mutable values of a [`Map`], see [`Map::values_mut`].
+    ValuesMut<'a>, ValuesMut, &'a mut Value
+);
+iterator!(
+    /// The entries of a [`Map`] by value, see [`Map::into_iter`].
+    IntoIter<>, IntoIter, (ValueString, Value)
+);
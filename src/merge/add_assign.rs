@@ -4,7 +4,7 @@ use derive_more::{Constructor, Deref, DerefMut};
 
 use crate::Result;
 use crate::{
-    merge::{path::RefPath, value::Value},
+    merge::{memo::Memo, path::RefPath, value::Value},
     raw::raw_value::RawValue,
 };
 
@@ -15,18 +15,19 @@ impl AddAssign {
     pub(crate) fn from_raw(
         parent: Option<&RefPath>,
         raw: crate::raw::add_assign::AddAssign,
+        memo: &Memo,
     ) -> crate::Result<Self> {
         let raw: RawValue = raw.into();
-        let value = Value::from_raw(parent, raw)?;
+        let value = Value::from_raw(parent, raw, memo)?;
         Ok(Self::new(value.into()))
     }
 
-    pub(crate) fn try_resolve(self, path: &RefPath) -> Result<Value> {
+    pub(crate) fn try_resolve(self, path: &RefPath, memo: &Memo) -> Result<Value> {
         let value = if self.is_merged() {
             *self.0
         } else {
             match *self.0 {
-                Value::Concat(concat) => concat.try_resolve(path)?,
+                Value::Concat(concat) => concat.try_resolve(path, memo)?,
                 other => other,
             }
         };
@@ -8,7 +8,7 @@ use tracing::trace;
 
 use crate::{
     join_format,
-    merge::{path::RefPath, value::Value},
+    merge::{memo::Memo, path::RefPath, value::Value},
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -29,10 +29,11 @@ impl Array {
     pub(crate) fn from_raw(
         parent: Option<&RefPath>,
         raw: crate::raw::raw_array::RawArray,
+        memo: &Memo,
     ) -> crate::Result<Self> {
         let mut values = Vec::with_capacity(raw.len());
         for val in raw.into_inner() {
-            let val = Value::from_raw(parent, val)?;
+            let val = Value::from_raw(parent, val, memo)?;
             values.push(RefCell::new(val));
         }
         Ok(Self::new(values))
@@ -2,7 +2,8 @@ use std::collections::VecDeque;
 use std::{cell::RefCell, fmt::Display};
 
 use crate::error::Error;
-use crate::merge::{path::RefPath, value::Value};
+use crate::merge::{memo::Memo, path::RefPath, value::Value};
+use crate::raw::space::Space;
 use crate::{Result, join_format};
 
 /// Represents a concatenation of evaluated HOCON values during the merge phase.
@@ -29,7 +30,7 @@ use crate::{Result, join_format};
 #[derive(Debug, Clone, PartialEq, Default)]
 pub(crate) struct Concat {
     values: VecDeque<RefCell<Value>>,
-    spaces: VecDeque<Option<String>>,
+    spaces: VecDeque<Option<Space>>,
 }
 
 impl Concat {
@@ -39,7 +40,7 @@ impl Concat {
     /// Returns `Error::InvalidConcat` if the invariant `values.len() != spaces.len() + 1` is violated.
     pub(crate) fn new(
         values: VecDeque<RefCell<Value>>,
-        spaces: VecDeque<Option<String>>,
+        spaces: VecDeque<Option<Space>>,
     ) -> Result<Self> {
         if values.len() != spaces.len() + 1 {
             return Err(Error::InvalidConcat(values.len(), spaces.len()));
@@ -48,7 +49,7 @@ impl Concat {
     }
 
     /// Constructs a minimal `Concat` with exactly two values and one optional space.
-    pub(crate) fn two(left: Value, space: Option<String>, right: Value) -> Self {
+    pub(crate) fn two(left: Value, space: Option<Space>, right: Value) -> Self {
         let values = VecDeque::from_iter([RefCell::new(left), RefCell::new(right)]);
         let spaces = VecDeque::from_iter([space]);
         Self { values, spaces }
@@ -65,12 +66,13 @@ impl Concat {
     pub(crate) fn from_raw(
         parent: Option<&RefPath>,
         raw: crate::raw::concat::Concat,
+        memo: &Memo,
     ) -> Result<Self> {
         let (raw_values, spaces) = raw.into_inner();
         let spaces = VecDeque::from_iter(spaces);
         let mut values = VecDeque::with_capacity(raw_values.len());
         for val in raw_values {
-            let val = Value::from_raw(parent, val)?;
+            let val = Value::from_raw(parent, val, memo)?;
             values.push_back(RefCell::new(val));
         }
         Self::new(values, spaces)
@@ -79,7 +81,7 @@ impl Concat {
     /// Appends a new value and its preceding space to the end of the concatenation.
     ///
     /// Maintains the invariant `values.len() == spaces.len() + 1`.
-    pub(crate) fn push_back(&mut self, space: Option<String>, val: RefCell<Value>) {
+    pub(crate) fn push_back(&mut self, space: Option<Space>, val: RefCell<Value>) {
         if self.values.is_empty() {
             debug_assert!(space.is_none());
             self.values.push_back(val);
@@ -91,7 +93,7 @@ impl Concat {
     }
 
     /// Removes and returns the last value with its preceding space (if any).
-    pub(crate) fn pop_back(&mut self) -> Option<(Option<String>, RefCell<Value>)> {
+    pub(crate) fn pop_back(&mut self) -> Option<(Option<Space>, RefCell<Value>)> {
         let v = self.values.pop_back();
         match v {
             Some(v) => {
@@ -114,7 +116,7 @@ impl Concat {
     }
 
     /// Removes and returns the first value with its following space (if any).
-    pub(crate) fn pop_front(&mut self) -> Option<(RefCell<Value>, Option<String>)> {
+    pub(crate) fn pop_front(&mut self) -> Option<(RefCell<Value>, Option<Space>)> {
         let v = self.values.pop_front();
         match v {
             Some(v) => {
@@ -137,7 +139,7 @@ impl Concat {
     }
 
     /// Inserts a new value and its following space at the beginning of the concatenation.
-    pub(crate) fn push_front(&mut self, val: RefCell<Value>, space: Option<String>) {
+    pub(crate) fn push_front(&mut self, val: RefCell<Value>, space: Option<Space>) {
         if self.values.is_empty() {
             debug_assert!(space.is_none());
             self.values.push_front(val);
@@ -158,6 +160,16 @@ impl Concat {
         &self.values
     }
 
+    /// Returns the spaces between the concatenated values.
+    pub(crate) fn get_spaces(&self) -> &VecDeque<Option<Space>> {
+        &self.spaces
+    }
+
+    /// Consumes the concatenation and returns its values and the spaces between them.
+    pub(crate) fn into_inner(self) -> (VecDeque<RefCell<Value>>, VecDeque<Option<Space>>) {
+        (self.values, self.spaces)
+    }
+
     /// Returns a mutable iterator over all value cells.
     pub(crate) fn values_mut(
         &mut self,
@@ -172,7 +184,7 @@ impl Concat {
     /// - **1 value** → returns that single value directly
     /// - **multiple values** → iteratively concatenates them using
     ///   `Value::concatenate`, preserving spaces between each.
-    pub(crate) fn try_resolve(mut self, path: &RefPath) -> Result<Value> {
+    pub(crate) fn try_resolve(mut self, path: &RefPath, memo: &Memo) -> Result<Value> {
         if self.values.is_empty() {
             Ok(Value::None)
         } else if self.values.len() == 1 {
@@ -183,7 +195,7 @@ impl Concat {
             let mut space = first_space;
             let mut first = first.into_inner();
             while let Some((second, second_space)) = self.pop_front() {
-                first = Value::concatenate(path, first, space, second.into_inner())?;
+                first = Value::concatenate(path, first, space, second.into_inner(), memo)?;
                 space = second_space;
             }
             Ok(first)
@@ -0,0 +1,51 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use crate::value_string::ValueString;
+
+/// Hands out the keys of the objects of a tree, sharing one allocation between equal keys,
+/// see [`crate::ResolveOptions::intern_keys`].
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    keys: RefCell<HashSet<ValueString>>,
+}
+
+impl Interner {
+    /// Returns `key`, or the equal key interned before it.
+    pub(crate) fn intern(&self, key: ValueString) -> ValueString {
+        let mut keys = self.keys.borrow_mut();
+        if let Some(interned) = keys.get(key.as_str()) {
+            return interned.clone();
+        }
+        let key = key.into_shared();
+        if key.is_shared() {
+            keys.insert(key.clone());
+        }
+        key
+    }
+
+    /// Like [Interner::intern], only copying `key` if it wasn't interned before.
+    pub(crate) fn intern_str(&self, key: &str) -> ValueString {
+        if let Some(interned) = self.keys.borrow().get(key) {
+            return interned.clone();
+        }
+        self.intern(key.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+
+    #[test]
+    fn test_intern() {
+        let keys = Interner::default();
+        let host = "h".repeat(32);
+        let (a, b) = (keys.intern_str(&host), keys.intern(host.clone().into()));
+        assert!(a.is_shared());
+        assert_eq!(a.as_ptr(), b.as_ptr());
+        let other = Interner::default().intern_str(&host);
+        assert_ne!(a.as_ptr(), other.as_ptr());
+        assert_eq!(a, other);
+    }
+}
@@ -1,4 +1,13 @@
+use std::fmt::Display;
+
+use crate::merge::intern::Interner;
+use crate::merge::path::RefPath;
+use crate::merge::substitution::Substitution;
 use crate::path::Path;
+use crate::raw::raw_string::CowStr;
+use crate::resolution_trace::{ResolutionTrace, SubstitutionSource, SubstitutionStep};
+use crate::resolve_options::ResolveOptions;
+use crate::value_string::ValueString;
 
 /// Tracks recursive substitutions during HOCON value resolution.
 ///
@@ -31,6 +40,7 @@ use crate::path::Path;
 ///   resolution chain. Used to detect recursion.
 /// - `substitution_counter`: Counts the total number of performed
 ///   substitutions, used for recursion depth control.
+/// - `options`: The user supplied options that control the resolution.
 #[derive(Debug, Default)]
 pub(crate) struct Memo {
     /// Stack of currently active substitution paths.
@@ -40,4 +50,84 @@ pub(crate) struct Memo {
     /// Counter to track the number of performed substitutions.
     /// Helps limit recursion depth to avoid stack overflow.
     pub(crate) substitution_counter: usize,
+
+    /// Options controlling environment fallback, depth limits and unresolved substitutions.
+    pub(crate) options: ResolveOptions,
+
+    /// The positions of the keys in the current path that address parts of concatenations
+    /// and delayed replacements rather than keys of the configuration, e.g. the `1` in
+    /// `server.1.url` for `server = ${base} { url = ... }`.
+    pub(crate) parts: Vec<usize>,
+
+    /// The resolved substitutions, only recorded if set.
+    pub(crate) trace: Option<ResolutionTrace>,
+
+    /// The keys of the merged objects, only interned with [ResolveOptions::intern_keys].
+    pub(crate) keys: Option<Interner>,
+}
+
+impl Memo {
+    pub(crate) fn new(options: ResolveOptions) -> Self {
+        Self {
+            tracker: vec![],
+            substitution_counter: 0,
+            parts: vec![],
+            trace: None,
+            keys: options.intern_keys.then(Interner::default),
+            options,
+        }
+    }
+
+    /// Returns `key` as the key of a merged object, interned if enabled.
+    pub(crate) fn key(&self, key: CowStr) -> ValueString {
+        match (&self.keys, key) {
+            (Some(keys), CowStr::Borrowed(key)) => keys.intern_str(key),
+            (Some(keys), CowStr::Owned(key)) => keys.intern(key),
+            (None, key) => key.into(),
+        }
+    }
+
+    /// Records a resolved substitution if tracing is enabled.
+    pub(crate) fn record(
+        &mut self,
+        path: &RefPath,
+        substitution: &Substitution,
+        source: SubstitutionSource,
+        value: Option<&dyn Display>,
+    ) {
+        if let Some(trace) = &mut self.trace {
+            let path = field_path(&self.parts, path);
+            trace.steps.push(SubstitutionStep::new(
+                path,
+                substitution.to_string(),
+                source,
+                value,
+            ));
+        }
+    }
+
+    /// Returns `path` without the keys that address parts of concatenations and delayed
+    /// replacements, i.e. the path of the field as written in the configuration.
+    pub(crate) fn field_path(&self, path: &RefPath) -> String {
+        field_path(&self.parts, path)
+    }
+
+    /// Records an environment variable a substitution was resolved from if tracing is
+    /// enabled.
+    pub(crate) fn record_environment(&mut self, name: &str, value: &str) {
+        if let Some(trace) = &mut self.trace {
+            trace
+                .environment
+                .insert(name.to_string(), value.to_string());
+        }
+    }
+}
+
+fn field_path(parts: &[usize], path: &RefPath) -> String {
+    path.keys()
+        .enumerate()
+        .filter(|(position, _)| !parts.contains(position))
+        .map(|(_, key)| key.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
 }
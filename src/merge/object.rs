@@ -5,33 +5,47 @@ use crate::merge::array::Array;
 use crate::merge::memo::Memo;
 use crate::merge::path::RefKey;
 use crate::merge::substitution::Substitution;
+use crate::merge_strategy;
 use crate::path::Key;
+use crate::raw::space::Space;
+use crate::resolution_trace::SubstitutionSource;
+use crate::resolve_options::{ResolveOptions, SelfReference};
+use crate::value_string::ValueString;
 use crate::{
     expect_variant,
     merge::{add_assign::AddAssign, path::RefPath, value::Value},
     path::Path,
-    raw::{field::ObjectField, raw_object::RawObject, raw_string::RawString, raw_value::RawValue},
+    raw::{
+        field::ObjectField,
+        raw_object::RawObject,
+        raw_string::{CowStr, RawString},
+        raw_value::RawValue,
+    },
 };
 use std::{
     cell::RefCell,
-    collections::BTreeMap,
     fmt::Display,
     ops::{Deref, DerefMut},
 };
 
 type V = RefCell<Value>;
 
-const MAX_SUBSTITUTION_DEPTH: usize = 32;
+/// The fields of an [`Object`]. With `preserve_order` they keep the order their keys were
+/// first defined in, otherwise they are sorted by key.
+#[cfg(not(feature = "preserve_order"))]
+pub(crate) type Fields = std::collections::BTreeMap<ValueString, V>;
+#[cfg(feature = "preserve_order")]
+pub(crate) type Fields = indexmap::IndexMap<ValueString, V>;
 
 /// Represents an intermediate state for a HOCON object during parsing and merging.
 ///
 /// This enum distinguishes between two states to optimize the resolution of substitutions:
 ///
-/// - `Merged(BTreeMap<String, V>)`: Indicates that all values within this object and its children
+/// - `Merged(Fields)`: Indicates that all values within this object and its children
 ///   have been fully resolved and merged. There are no remaining substitutions, concatenations,
 ///   or other complex structures that need further processing.
 ///
-/// - `Unmerged(BTreeMap<String, V>)`: Indicates that this object or its children may still
+/// - `Unmerged(Fields)`: Indicates that this object or its children may still
 ///   contain unresolved values, such as substitutions (`${...}`), concatenations (`Concat`),
 ///   or additions (`AddAssign`). The resolver must process these pending values before
 ///   the object is considered complete.
@@ -40,60 +54,104 @@ const MAX_SUBSTITUTION_DEPTH: usize = 32;
 /// objects, significantly reducing the scope of traversal and improving performance.
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum Object {
-    Merged(BTreeMap<String, V>),
-    Unmerged(BTreeMap<String, V>),
+    Merged(Fields),
+    Unmerged(Fields),
 }
 
 impl Object {
-    pub(crate) fn into_inner(self) -> BTreeMap<String, V> {
+    pub(crate) fn into_inner(self) -> Fields {
         match self {
             Object::Merged(values) | Object::Unmerged(values) => values,
         }
     }
 
-    pub(crate) fn from_raw(parent: Option<&RefPath>, obj: RawObject) -> crate::Result<Self> {
+    /// Removes `key`, keeping the order of the remaining fields.
+    pub(crate) fn remove(&mut self, key: &str) -> Option<V> {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.deref_mut().remove(key);
+        #[cfg(feature = "preserve_order")]
+        return self.deref_mut().shift_remove(key);
+    }
+
+    pub(crate) fn from_raw(
+        parent: Option<&RefPath>,
+        obj: RawObject,
+        memo: &Memo,
+    ) -> crate::Result<Self> {
         let mut root = Object::default();
         for field in obj.into_inner().into_iter() {
-            root.put_field(parent, field)?;
+            root.put_field(parent, field, memo)?;
         }
         Ok(root)
     }
 
-    fn put_field(&mut self, parent: Option<&RefPath>, field: ObjectField) -> crate::Result<()> {
+    fn put_field(
+        &mut self,
+        parent: Option<&RefPath>,
+        field: ObjectField,
+        memo: &Memo,
+    ) -> crate::Result<()> {
+        self.put_field0(parent, field, false, memo)
+    }
+
+    /// Merges a single field into this object.
+    ///
+    /// The fields of an inclusion are merged one by one into this object, as if they were
+    /// written in place of the include statement. This way `a += x` in an included file
+    /// appends to an `a` defined before the include. Substitutions in included fields are
+    /// fixed up relative to `parent`.
+    fn put_field0(
+        &mut self,
+        parent: Option<&RefPath>,
+        field: ObjectField,
+        included: bool,
+        memo: &Memo,
+    ) -> crate::Result<()> {
         match field {
             ObjectField::Inclusion { inclusion, .. } => {
                 if let Some(include_obj) = inclusion.val {
-                    let mut include_obj = Self::from_raw(parent, *include_obj)?;
-                    include_obj.fixup_substitution(parent)?;
-                    self.merge(include_obj, parent)?;
+                    for field in include_obj.into_inner() {
+                        self.put_field0(parent, field, true, memo)?;
+                    }
+                }
+            }
+            ObjectField::KeyValue { key, value, .. } => {
+                let mut expanded_obj = Self::new_obj_from_kv(parent, key, value, memo)?;
+                if included {
+                    expanded_obj.fixup_substitution(parent)?;
                 }
+                self.merge(expanded_obj, parent, memo)?;
             }
-            ObjectField::KeyValue { key, value, .. } => self.put_kv(parent, key, value)?,
-            ObjectField::NewlineComment(_) => {}
+            ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
         }
         Ok(())
     }
 
-    fn put_kv(
-        &mut self,
+    fn new_obj_from_kv(
         parent: Option<&RefPath>,
         key: RawString,
         value: RawValue,
-    ) -> crate::Result<()> {
-        let key_path = key.as_path();
-        let path = match parent {
-            Some(parent) => parent.join(RefPath::from_slice(&key_path)?),
-            None => RefPath::from_slice(&key_path)?,
+        memo: &Memo,
+    ) -> crate::Result<Object> {
+        let value = {
+            let key_path = key.as_path();
+            let path = match parent {
+                Some(parent) => parent.join(RefPath::from_slice(&key_path)?),
+                None => RefPath::from_slice(&key_path)?,
+            };
+            Value::from_raw(Some(&path), value, memo)?
         };
-        let expanded_obj =
-            Self::new_obj_from_path(&key_path, Value::from_raw(Some(&path), value)?)?;
-        self.merge(expanded_obj, parent)?;
-        Ok(())
+        Self::new_obj_from_path(key.into_keys(), value, memo)
     }
 
-    pub(crate) fn merge(&mut self, other: Self, parent: Option<&RefPath>) -> crate::Result<()> {
+    pub(crate) fn merge(
+        &mut self,
+        other: Self,
+        parent: Option<&RefPath>,
+        memo: &Memo,
+    ) -> crate::Result<()> {
         let both_merged = self.is_merged() && other.is_merged();
-        let other: BTreeMap<String, V> = other.into();
+        let other: Fields = other.into();
         for (k, v_right) in other {
             let sub_path = match parent {
                 None => RefPath::new(RefKey::Str(&k), None),
@@ -101,21 +159,33 @@ impl Object {
             };
             match self.get_mut(&k) {
                 Some(v_left) => match (v_left.get_mut(), v_right.into_inner()) {
+                    (l, r) if merge_strategy::is_tagged(&r, memo) => {
+                        let left = std::mem::take(l);
+                        *l = merge_strategy::apply(&sub_path, Some(left), r, memo)?;
+                    }
+                    (l, r) if merge_strategy::is_tagged(l, memo) => {
+                        let left = merge_strategy::apply(&sub_path, None, std::mem::take(l), memo)?;
+                        *l = Value::replace(&sub_path, left, r, memo)?;
+                        if let Value::Object(obj) = l {
+                            obj.resolve_add_assign();
+                        }
+                    }
                     (Value::Object(left_obj), Value::Object(right_obj)) => {
-                        left_obj.merge(right_obj, parent)?;
+                        left_obj.merge(right_obj, parent, memo)?;
                     }
                     (l, r) => {
                         let left = std::mem::take(l);
                         // Even if the value ends up merged after replacement,
                         // we still treat it as unmerged, to avoid complicating the merge-check logic.
-                        *l = Value::replace(&sub_path, left, r)?;
+                        *l = Value::replace(&sub_path, left, r, memo)?;
                         if let Value::Object(obj) = l {
                             obj.resolve_add_assign();
                         }
                     }
                 },
                 None => {
-                    let mut v_right = Value::replace(&sub_path, Value::None, v_right.into_inner())?;
+                    let mut v_right =
+                        Value::replace(&sub_path, Value::None, v_right.into_inner(), memo)?;
                     if let Value::Object(obj) = &mut v_right {
                         obj.resolve_add_assign();
                     }
@@ -132,6 +202,28 @@ impl Object {
         Ok(())
     }
 
+    /// Applies the tagged values that were never merged into an earlier definition, see
+    /// [`crate::merge_strategy`].
+    pub(crate) fn apply_merge_strategies(
+        &mut self,
+        parent: Option<&RefPath>,
+        memo: &Memo,
+    ) -> crate::Result<()> {
+        for (key, value) in self.iter_mut() {
+            let path = match parent {
+                None => RefPath::new(RefKey::Str(key), None),
+                Some(parent) => parent.join(RefPath::new(RefKey::Str(key), None)),
+            };
+            let value = value.get_mut();
+            if merge_strategy::is_tagged(value, memo) {
+                *value = merge_strategy::apply(&path, None, std::mem::take(value), memo)?;
+            } else if let Value::Object(object) = value {
+                object.apply_merge_strategies(Some(&path), memo)?;
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn resolve_add_assign(&mut self) {
         if self.is_merged() {
             return;
@@ -171,11 +263,11 @@ impl Object {
         matches!(self, Self::Unmerged(_))
     }
 
-    fn new_obj_from_path(path: &[&str], value: Value) -> crate::Result<Object> {
+    fn new_obj_from_path(path: Vec<CowStr>, value: Value, memo: &Memo) -> crate::Result<Object> {
         if enabled!(Level::TRACE) {
             trace!(
                 "create object from path: `{}` value: `{}`",
-                path.join("."),
+                path.iter().map(|key| &**key).collect::<Vec<_>>().join("."),
                 value
             );
         }
@@ -183,9 +275,9 @@ impl Object {
             return Err(Error::InvalidPathExpression("empty"));
         }
         let mut current = value;
-        for ele in path.iter().rev() {
+        for ele in path.into_iter().rev() {
             let mut obj = Object::default();
-            obj.insert(ele.to_string(), RefCell::new(current));
+            obj.insert(memo.key(ele), RefCell::new(current));
             current = Value::object(obj);
         }
         if let Value::Object(obj) = current {
@@ -248,7 +340,8 @@ impl Object {
                     | Value::Null
                     | Value::None
                     | Value::String(_)
-                    | Value::Number(_) => {}
+                    | Value::Number(_)
+                    | Value::NonFinite(_) => {}
                     // For substitutions, prepend the parent path to the substitution's path to make it
                     // relative to the root configuration.
                     Value::Substitution(substitution) => {
@@ -327,7 +420,7 @@ impl Object {
                 // Case 1: The path has more segments to traverse.
                 Some(path) => match (&path.first, &*root.borrow()) {
                     (Key::String(key), Value::Object(object)) => {
-                        match object.get(key) {
+                        match object.get(key.as_str()) {
                             Some(next_value) => {
                                 // Recursively call `get` on the next value in the path.
                                 get(next_value, path.next(), callback)
@@ -369,7 +462,7 @@ impl Object {
 
         // Start the recursive traversal from the top-level object.
         if let Key::String(key) = &path.first
-            && let Some(value) = self.get(key)
+            && let Some(value) = self.get(key.as_str())
         {
             get(value, path.next(), callback)
         } else {
@@ -413,7 +506,7 @@ impl Object {
     pub(crate) unsafe fn unsafe_get_by_path(&self, path: &Path) -> Option<&RefCell<Value>> {
         // Attempt to get the first value from the HashMap using the path's first key.
         if let Key::String(key) = &path.first
-            && let Some(value) = self.get(key)
+            && let Some(value) = self.get(key.as_str())
         {
             // Initialize the next path segment to traverse.
             let mut next = path.next();
@@ -431,7 +524,8 @@ impl Object {
                     // If there are more path segments, try to navigate deeper.
                     Some(n) => match (&n.first, &*value.borrow()) {
                         // Check if the current value is a `Value::Object` (i.e., a nested HashMap).
-                        (Key::String(key), Value::Object(object)) => match object.get(key) {
+                        (Key::String(key), Value::Object(object)) => match object.get(key.as_str())
+                        {
                             // If the next key exists, update the raw pointer and continue to the next path segment.
                             Some(value) => {
                                 raw = value as *const RefCell<Value>;
@@ -478,9 +572,14 @@ impl Object {
     /// # Substitution depth
     /// A `substitution_counter` in the provided `Memo` is incremented for each
     /// recursive call. This protects against cyclic substitutions by enforcing
-    /// a maximum substitution depth (`MAX_SUBSTITUTION_DEPTH`). If the depth
+    /// a maximum substitution depth (`ResolveOptions::max_substitution_depth`). If the depth
     /// exceeds the limit, an error is returned (`Error::SubstitutionDepthExceeded`).
     ///
+    /// # Unresolved substitutions
+    /// When `ResolveOptions::allow_unresolved` is set, the value is restored to its
+    /// original state if a required substitution inside it cannot be found, so the
+    /// caller can skip it and keep it for a later resolution (see [`Self::substitute_child`]).
+    ///
     /// # Borrowing rules
     /// Care must be taken with `RefCell<Value>` borrowing:
     /// - The function starts by taking an immutable borrow (`value.borrow()`).
@@ -518,12 +617,31 @@ impl Object {
         path: &RefPath,
         value: &RefCell<Value>,
         memo: &mut Memo,
+    ) -> crate::Result<()> {
+        if memo.options.allow_unresolved && !value.borrow().is_merged() {
+            let snapshot = value.borrow().clone();
+            let result = self.substitute_value0(path, value, memo);
+            if let Err(Error::SubstitutionNotFound(_) | Error::SelfReferenceNotFound { .. }) =
+                &result
+                && let Ok(mut value) = value.try_borrow_mut()
+            {
+                *value = snapshot;
+            }
+            return result;
+        }
+        self.substitute_value0(path, value, memo)
+    }
+
+    fn substitute_value0(
+        &self,
+        path: &RefPath,
+        value: &RefCell<Value>,
+        memo: &mut Memo,
     ) -> crate::Result<()> {
         memo.substitution_counter += 1;
-        if memo.substitution_counter > MAX_SUBSTITUTION_DEPTH {
-            return Err(Error::SubstitutionDepthExceeded {
-                max_depth: MAX_SUBSTITUTION_DEPTH,
-            });
+        let max_depth = memo.options.max_substitution_depth;
+        if memo.substitution_counter > max_depth {
+            return Err(Error::SubstitutionDepthExceeded { max_depth });
         }
         let value_ref = value.borrow();
         if value_ref.is_merged() {
@@ -536,7 +654,7 @@ impl Object {
                 let _enter = span.enter();
                 for (key, val) in object.iter() {
                     let sub_path = path.join(RefPath::new(RefKey::Str(key), None));
-                    self.substitute_value(&sub_path, val, memo)?;
+                    self.substitute_child(&sub_path, val, memo)?;
                 }
                 drop(value_ref);
                 // TODO
@@ -549,8 +667,12 @@ impl Object {
                 self.handle_array(path, array, memo)?;
                 drop(value_ref);
             }
-            Value::Boolean(_) | Value::Null | Value::None | Value::String(_) | Value::Number(_) => {
-            }
+            Value::Boolean(_)
+            | Value::Null
+            | Value::None
+            | Value::String(_)
+            | Value::Number(_)
+            | Value::NonFinite(_) => {}
             Value::Substitution(substitution) => {
                 let substitution = substitution.clone();
                 drop(value_ref);
@@ -615,11 +737,54 @@ impl Object {
         let _enter = span.enter();
         for (index, ele) in array.iter().enumerate() {
             let sub_path = path.join(RefPath::new(RefKey::Index(index), None));
-            self.substitute_value(&sub_path, ele, memo)?;
+            self.substitute_child(&sub_path, ele, memo)?;
         }
         Ok(())
     }
 
+    /// Substitutes a part of a concatenation or delayed replacement. `key` addresses the
+    /// part below `path`, but is not a key of the configuration, see [`Memo::parts`].
+    fn substitute_part(
+        &self,
+        path: &RefPath,
+        key: RefKey,
+        value: &RefCell<Value>,
+        memo: &mut Memo,
+    ) -> crate::Result<()> {
+        memo.parts.push(path.keys().count());
+        let result = self.substitute_value(&path.join(RefPath::new(key, None)), value, memo);
+        memo.parts.pop();
+        result
+    }
+
+    /// Substitutes an object field or an array element.
+    ///
+    /// If `ResolveOptions::allow_unresolved` is set and a required substitution inside the
+    /// child cannot be found, the child has already been restored by [`Self::substitute_value`],
+    /// so the error is swallowed and the resolution continues with the next sibling.
+    fn substitute_child(
+        &self,
+        path: &RefPath,
+        value: &RefCell<Value>,
+        memo: &mut Memo,
+    ) -> crate::Result<()> {
+        let tracker_len = memo.tracker.len();
+        let parts_len = memo.parts.len();
+        let substitution_counter = memo.substitution_counter;
+        match self.substitute_value(path, value, memo) {
+            Err(Error::SubstitutionNotFound(_) | Error::SelfReferenceNotFound { .. })
+                if memo.options.allow_unresolved =>
+            {
+                trace!("leave unresolved substitution at {}", path);
+                memo.tracker.truncate(tracker_len);
+                memo.parts.truncate(parts_len);
+                memo.substitution_counter = substitution_counter;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
     /// Resolves a single substitution node (`${...}`) into its concrete value.
     ///
     /// A substitution is a symbolic reference to another configuration path
@@ -631,13 +796,22 @@ impl Object {
     /// - **Path lookup**: Attempts to locate the referenced value in the current
     ///   configuration tree. If found, the referenced node is recursively resolved
     ///   (via [`substitute_value`]) before replacement.
-    /// - **Environment variables**: If the path is not found in the configuration,
-    ///   `std::env::var` is queried. On success, the substitution is replaced with
-    ///   a `Value::String` containing the environment variable's value.
+    /// - **Resolver hook**: If the path is not found in the configuration, the
+    ///   `ResolveOptions::resolver` is consulted first.
+    /// - **Environment variables**: If the path is still not found and
+    ///   `ResolveOptions::use_system_environment` is set, `std::env::var` is queried,
+    ///   or `ResolveOptions::environment` if it holds a snapshot.
+    ///   On success, the substitution is replaced with a `Value::String` containing
+    ///   the environment variable's value.
     /// - **Optional substitutions**: `${?foo}` will resolve to `Value::None` if the
     ///   key or environment variable does not exist.
     /// - **Required substitutions**: `${foo}` will produce an
     ///   [`Error::SubstitutionNotFound`] if the reference cannot be resolved.
+    /// - **Self-references**: A substitution referring to its own field (`a = ${a} [1]`)
+    ///   resolves to the previous value of the field, or is rejected with
+    ///   [`Error::SelfReferenceNotAllowed`], depending on `ResolveOptions::self_reference`.
+    ///   A missing previous value is reported with [`Error::SelfReferenceNotFound`],
+    ///   naming the layers that were consulted.
     /// - **Cycle detection**: Uses `memo.tracker` to detect circular references.
     ///   If a substitution resolves back into its own path,
     ///   [`Error::SubstitutionCycle`] is returned.
@@ -682,6 +856,14 @@ impl Object {
         let span = span!(Level::TRACE, "Substitution");
         let _enter = span.enter();
 
+        let self_reference = substitution.is_self_reference(path);
+        if self_reference && memo.options.self_reference == SelfReference::Error {
+            return Err(Error::SelfReferenceNotAllowed {
+                substitution: substitution.to_string(),
+                path: memo.field_path(path),
+            });
+        }
+
         // --- Cycle detection ---
         // Track the current path in `memo.tracker` to detect recursive references.
         // If this path already appears in the stack, we report a substitution cycle.
@@ -705,55 +887,81 @@ impl Object {
         // This makes it safe to call `unsafe_get_by_path` without risking UB.
         let target = unsafe { self.unsafe_get_by_path(&substitution.path) };
 
+        // Special case: a substitution directly referring to itself, `foo = ${foo}`.
+        // There is no previous value in the configuration, so only the external
+        // layers can provide one.
+        let target = target.filter(|target| {
+            &*substitution.path != path || !matches!(&*target.borrow(), Value::Substitution(_))
+        });
+
         match target {
             Some(target) => {
                 if enabled!(Level::TRACE) {
                     trace!("find substitution: {} -> {}", substitution, target.borrow());
                 }
 
-                // Special case: a substitution directly referring to itself.
-                // `${foo}` resolving to `foo = ${foo}` would cause infinite recursion.
-                if &*substitution.path == path
-                    && matches!(&*target.borrow(), Value::Substitution(_))
-                {
-                    return if substitution.optional {
-                        // Optional self-reference -> just set to None.
-                        *target.borrow_mut() = Value::None;
-                        Ok(())
-                    } else {
-                        Err(Error::SubstitutionCycle {
-                            current: substitution.to_string(),
-                            backtrace: vec![substitution.to_string()],
-                        })
-                    };
-                }
-
-                // Recursively resolve the referenced value before cloning it.
-                self.substitute_value(&RefPath::from(&substitution.path), target, memo)?;
+                // Recursively resolve the referenced value before cloning it. The parts of
+                // the current path don't apply to the path of the target.
+                let parts = std::mem::take(&mut memo.parts);
+                let result =
+                    self.substitute_value(&RefPath::from(&substitution.path), target, memo);
+                memo.parts = parts;
+                result?;
 
                 // Clone the resolved value to replace the current substitution.
                 let target_clone = target.borrow().clone();
                 if enabled!(Level::TRACE) {
                     trace!("set {} to {}", value.borrow(), target_clone);
                 }
+                if memo.trace.is_some() {
+                    let source = if Self::is_overridden(&substitution, &memo.options) {
+                        SubstitutionSource::Override
+                    } else {
+                        SubstitutionSource::Configuration
+                    };
+                    memo.record(path, &substitution, source, Some(&target_clone));
+                }
                 *value.borrow_mut() = target_clone;
             }
-            None => match std::env::var(substitution.full_path()) {
-                Ok(env_var) => {
-                    // If no in-memory value exists, check environment variables.
+            None => match Self::external_value(&substitution, memo)? {
+                Some((external, source)) => {
+                    // If no in-memory value exists, use the resolver or environment variables.
                     if enabled!(Level::TRACE) {
-                        trace!("set environment variable {} to {}", env_var, value.borrow());
+                        trace!("set external value {} to {}", external, value.borrow());
                     }
-                    *value.borrow_mut() = Value::string(env_var);
+                    memo.record(path, &substitution, source, Some(&external));
+                    *value.borrow_mut() = external;
                 }
-                Err(_) => {
+                None => {
                     // Missing substitution:
                     // - required substitutions produce an error
-                    // - optional ones resolve to `None`
-                    if !substitution.optional {
-                        return Err(Error::SubstitutionNotFound(substitution.to_string()));
-                    } else {
+                    // - optional ones resolve to their default or `None`
+                    let default = memo
+                        .options
+                        .defaults_for_missing
+                        .get(&substitution.full_path())
+                        .cloned();
+                    if substitution.optional
+                        && let Some(default) = default
+                    {
+                        let default = Value::from_raw(None, default.into(), memo)?;
+                        memo.record(
+                            path,
+                            &substitution,
+                            SubstitutionSource::Default,
+                            Some(&default),
+                        );
+                        *value.borrow_mut() = default;
+                    } else if substitution.optional {
+                        memo.record(path, &substitution, SubstitutionSource::NotFound, None);
                         *value.borrow_mut() = Value::None;
+                    } else if self_reference {
+                        return Err(Error::SelfReferenceNotFound {
+                            consulted: Self::consulted_layers(&substitution, &memo.options),
+                            substitution: substitution.to_string(),
+                        });
+                    } else {
+                        return Err(Error::SubstitutionNotFound(substitution.to_string()));
                     }
                 }
             },
@@ -764,9 +972,76 @@ impl Object {
         Ok(())
     }
 
+    /// Names the layers that were searched for the previous value of a self-referential
+    /// substitution, in lookup order.
+    fn consulted_layers(substitution: &Substitution, options: &ResolveOptions) -> Vec<String> {
+        let full_path = substitution.full_path();
+        let mut layers = vec![format!("earlier definitions of {full_path}")];
+        if options.resolver.is_some() {
+            layers.push("resolver".to_string());
+        }
+        if options.use_system_environment {
+            layers.push(format!("environment variable {full_path}"));
+        }
+        layers
+    }
+
+    /// Looks up a substitution that is not defined in the configuration, first with the
+    /// user supplied resolver and then in the environment variables, or the snapshot of them
+    /// in [`ResolveOptions::environment`].
+    fn external_value(
+        substitution: &Substitution,
+        memo: &mut Memo,
+    ) -> crate::Result<Option<(Value, SubstitutionSource)>> {
+        let options = &memo.options;
+        let full_path = substitution.full_path();
+        if let Some(resolver) = &options.resolver
+            && let Some(value) = resolver(&full_path)
+        {
+            let value = Value::from_raw(None, value.into(), memo)?;
+            return Ok(Some((value, SubstitutionSource::Resolver)));
+        }
+        if !options.use_system_environment {
+            return Ok(None);
+        }
+        let env_var = match &options.environment {
+            Some(environment) => environment.get(&full_path).cloned(),
+            None => std::env::var(&full_path).ok(),
+        };
+        match env_var {
+            Some(env_var) => {
+                memo.record_environment(&full_path, &env_var);
+                Ok(Some((
+                    Value::string(env_var),
+                    SubstitutionSource::Environment,
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns `true` if the value a substitution found in the configuration comes from
+    /// [`ResolveOptions::overrides`]. Overrides are merged last, so an override of the path,
+    /// or of a parent that defines the rest of the path, always wins.
+    fn is_overridden(substitution: &Substitution, options: &ResolveOptions) -> bool {
+        let path = substitution.path.keys();
+        options.overrides.iter().any(|(key, value)| {
+            let Ok(key) = crate::path::Path::from_str(key) else {
+                return false;
+            };
+            match path.strip_prefix(key.keys().as_slice()) {
+                Some([]) => true,
+                Some(rest) => value
+                    .get_by_path(rest.iter().map(String::as_str).collect::<Vec<_>>())
+                    .is_some(),
+                None => false,
+            }
+        })
+    }
+
     fn pop_value_from_concat(
         value: &RefCell<Value>,
-    ) -> Option<(Option<String>, RefCell<Value>, usize)> {
+    ) -> Option<(Option<Space>, RefCell<Value>, usize)> {
         let mut value_mut = value.borrow_mut();
         let concat = expect_variant!(value_mut, Value::Concat, mut);
         let len = concat.len();
@@ -834,17 +1109,15 @@ impl Object {
         match Self::pop_value_from_concat(value) {
             Some((space_last, last, last_index)) => {
                 // First resolve the last element (may contain substitutions itself)
-                let sub_path = path.join(RefPath::new(RefKey::Index(last_index), None));
-                self.substitute_value(&sub_path, &last, memo)?;
+                self.substitute_part(path, RefKey::Index(last_index), &last, memo)?;
 
                 // If the value is still a Concat, we can combine further
                 if matches!(&*value.borrow(), Value::Concat(_)) {
                     match Self::pop_value_from_concat(value) {
                         Some((space_second_last, second_last, second_last_index)) => {
                             // Resolve the second-to-last element
-                            let sub_path =
-                                path.join(RefPath::new(RefKey::Index(second_last_index), None));
-                            self.substitute_value(&sub_path, &second_last, memo)?;
+                            let key = RefKey::Index(second_last_index);
+                            self.substitute_part(path, key, &second_last, memo)?;
 
                             // Concatenate `second_last` and `last`
                             let last = last.into_inner();
@@ -853,13 +1126,13 @@ impl Object {
                                 second_last.into_inner(),
                                 space_last,
                                 last,
+                                memo,
                             )?;
                             let mut new_val = RefCell::new(new_val);
 
                             // Resolve any substitutions inside the concatenated result
-                            let sub_path =
-                                path.join(RefPath::new(RefKey::Str("concatenation"), None));
-                            self.substitute_value(&sub_path, &new_val, memo)?;
+                            let key = RefKey::Str("concatenation");
+                            self.substitute_part(path, key, &new_val, memo)?;
                             new_val.get_mut().try_become_merged();
 
                             if enabled!(Level::TRACE) {
@@ -877,8 +1150,8 @@ impl Object {
                                 v => {
                                     // If the node is not a concat anymore, collapse it into a single value
                                     let left = std::mem::take(v);
-                                    *v =
-                                        Value::concatenate(path, left, None, new_val.into_inner())?;
+                                    let right = new_val.into_inner();
+                                    *v = Value::concatenate(path, left, None, right, memo)?;
                                 }
                             }
 
@@ -898,8 +1171,9 @@ impl Object {
                 } else {
                     // If the node is no longer a Concat, concatenate it with the last element directly
                     let second_last = std::mem::take(&mut *value.borrow_mut());
+                    let last = last.into_inner();
                     let mut new_val =
-                        Value::concatenate(path, second_last, space_last, last.into_inner())?;
+                        Value::concatenate(path, second_last, space_last, last, memo)?;
                     new_val.try_become_merged();
                     if enabled!(Level::TRACE) {
                         trace!("set {} to {}", value.borrow(), new_val);
@@ -991,26 +1265,27 @@ impl Object {
         match Self::pop_value_from_delay_replacement(value) {
             Some((last, last_index)) => {
                 // Resolve substitutions in the last element
-                let sub_path = path.join(RefPath::new(RefKey::Index(last_index), None));
-                self.substitute_value(&sub_path, &last, memo)?;
+                self.substitute_part(path, RefKey::Index(last_index), &last, memo)?;
 
                 // If more elements remain in the DelayReplacement list, combine them
                 if matches!(&*value.borrow(), Value::DelayReplacement(_)) {
                     match Self::pop_value_from_delay_replacement(value) {
                         Some((second_last, second_last_index)) => {
-                            let sub_path =
-                                path.join(RefPath::new(RefKey::Index(second_last_index), None));
-                            self.substitute_value(&sub_path, &second_last, memo)?;
+                            let key = RefKey::Index(second_last_index);
+                            self.substitute_part(path, key, &second_last, memo)?;
 
                             // Merge second_last and last according to HOCON rules
-                            let new_val =
-                                Value::replace(path, second_last.into_inner(), last.into_inner())?;
+                            let new_val = Value::replace(
+                                path,
+                                second_last.into_inner(),
+                                last.into_inner(),
+                                memo,
+                            )?;
                             let mut new_val = RefCell::new(new_val);
 
                             // Resolve substitutions inside the merged value
-                            let sub_path =
-                                path.join(RefPath::new(RefKey::Str("replacement"), None));
-                            self.substitute_value(&sub_path, &new_val, memo)?;
+                            let key = RefKey::Str("replacement");
+                            self.substitute_part(path, key, &new_val, memo)?;
                             new_val.get_mut().try_become_merged();
 
                             if enabled!(Level::TRACE) {
@@ -1027,7 +1302,7 @@ impl Object {
                                 }
                                 v => {
                                     let left = std::mem::take(v);
-                                    *v = Value::replace(path, left, new_val.into_inner())?;
+                                    *v = Value::replace(path, left, new_val.into_inner(), memo)?;
                                 }
                             }
 
@@ -1047,7 +1322,7 @@ impl Object {
                 } else {
                     // If the node is no longer a DelayReplacement, merge the last element directly
                     let second_last = std::mem::take(&mut *value.borrow_mut());
-                    let mut new_val = Value::replace(path, second_last, last.into_inner())?;
+                    let mut new_val = Value::replace(path, second_last, last.into_inner(), memo)?;
                     new_val.try_become_merged();
                     if enabled!(Level::TRACE) {
                         trace!("set {} to {}", value.borrow(), new_val);
@@ -1069,11 +1344,72 @@ impl Object {
         Ok(())
     }
 
-    pub(crate) fn substitute(&self) -> crate::Result<()> {
-        let mut memo = Memo::default();
+    /// Converts a possibly partially resolved object back into a [`RawObject`].
+    ///
+    /// Keys are emitted as quoted strings so they are not split into paths again. A
+    /// `DelayReplacement` is emitted as repeated fields with the same key, which merge
+    /// back into the same `DelayReplacement`.
+    pub(crate) fn into_raw(self) -> crate::Result<RawObject<'static>> {
+        let mut fields = vec![];
+        for (key, value) in self.into_inner() {
+            match value.into_inner() {
+                Value::DelayReplacement(replacement) => {
+                    for value in replacement.into_inner() {
+                        if let Some(raw) = value.into_inner().into_raw()? {
+                            fields
+                                .push(ObjectField::key_value(RawString::quoted(key.clone()), raw));
+                        }
+                    }
+                }
+                value => {
+                    if let Some(raw) = value.into_raw()? {
+                        fields.push(ObjectField::key_value(RawString::quoted(key), raw));
+                    }
+                }
+            }
+        }
+        Ok(RawObject::new(fields))
+    }
+
+    /// Resolves only the value at `keys` and the substitutions it depends on, leaving the
+    /// rest of the object unresolved.
+    ///
+    /// Values on the way that are not objects yet, e.g. `a = ${b}` when `a.c` is requested,
+    /// are resolved first so they can be looked into. Returns `None` if nothing is defined
+    /// at `keys`.
+    pub(crate) fn substitute_path(
+        &self,
+        keys: &[&str],
+        memo: &mut Memo,
+    ) -> crate::Result<Option<Value>> {
+        for depth in 1..=keys.len() {
+            let prefix = &keys[..depth];
+            let path = Path::from_iter(prefix.iter())?;
+            // SAFETY: substitution only replaces values inside existing cells and never
+            // changes the structure of the tree, see `handle_substitution`.
+            let Some(value) = (unsafe { self.unsafe_get_by_path(&path) }) else {
+                return Ok(None);
+            };
+            let is_target = depth == keys.len();
+            if is_target || !matches!(&*value.borrow(), Value::Object(_)) {
+                let ref_path = RefPath::from_slice(prefix)?;
+                self.substitute_value(&ref_path, value, memo)?;
+                value.borrow_mut().try_become_merged();
+            }
+            if is_target {
+                let mut value = value.borrow().clone();
+                value.resolve_add_assign();
+                value.try_become_merged();
+                return Ok(Some(value).filter(|v| !matches!(v, Value::None)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn substitute(&self, memo: &mut Memo) -> crate::Result<()> {
         for (key, value) in self.iter() {
             let path = RefPath::new(RefKey::Str(key), None);
-            self.substitute_value(&path, value, &mut memo)?;
+            self.substitute_child(&path, value, memo)?;
             value.borrow_mut().try_become_merged();
         }
         Ok(())
@@ -1082,12 +1418,12 @@ impl Object {
 
 impl Default for Object {
     fn default() -> Self {
-        Object::Unmerged(BTreeMap::new())
+        Object::Unmerged(Fields::new())
     }
 }
 
 impl Deref for Object {
-    type Target = BTreeMap<String, V>;
+    type Target = Fields;
 
     fn deref(&self) -> &Self::Target {
         match self {
@@ -1104,7 +1440,7 @@ impl DerefMut for Object {
     }
 }
 
-impl From<Object> for BTreeMap<String, V> {
+impl From<Object> for Fields {
     fn from(val: Object) -> Self {
         match val {
             Object::Merged(object) | Object::Unmerged(object) => object,
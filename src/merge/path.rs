@@ -31,6 +31,11 @@ impl<'a> RefPath<'a> {
         self.remainder.as_deref()
     }
 
+    /// Returns the keys of the path, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &RefKey<'a>> {
+        std::iter::successors(Some(self), |p| p.next()).map(|p| &p.first)
+    }
+
     pub fn join(&self, path: RefPath<'a>) -> RefPath<'a> {
         let mut cloned = self.clone();
         let tail = cloned.tail_mut();
@@ -5,6 +5,7 @@ use std::{
 
 use derive_more::Constructor;
 
+use crate::merge::path::RefPath;
 use crate::path::{Key, Path};
 
 /// Represents a **HOCON substitution reference** in the merge phase.
@@ -74,6 +75,22 @@ impl Substitution {
             acc
         })
     }
+
+    /// Returns `true` if this substitution refers to the field at `path` or one of its
+    /// parents, e.g. `${a}` inside `a = ${a} [1]` or `a = { b = ${a} }`.
+    ///
+    /// Trailing keys of `path` that were added while resolving concatenations or
+    /// replacements are ignored, as only the leading keys are compared.
+    pub(crate) fn is_self_reference(&self, path: &RefPath) -> bool {
+        let mut next = Some(path);
+        for key in self.path.iter() {
+            match next {
+                Some(p) if p.first == key.first => next = p.next(),
+                _ => return false,
+            }
+        }
+        true
+    }
 }
 
 impl Display for Substitution {
@@ -93,13 +110,13 @@ impl Display for Substitution {
     }
 }
 
-impl From<crate::raw::substitution::Substitution> for Substitution {
+impl From<crate::raw::substitution::Substitution<'_>> for Substitution {
     /// Converts a raw substitution node from the parser into a merge-phase
     /// substitution with a resolved [`Path`].
     ///
     /// This conversion is part of the parsing pipeline where raw syntax trees
     /// are transformed into semantic configuration structures.
-    fn from(value: crate::raw::substitution::Substitution) -> Self {
+    fn from(value: crate::raw::substitution::Substitution<'_>) -> Self {
         let path = value.path.into_path().into();
         Self::new(path, value.optional)
     }
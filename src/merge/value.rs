@@ -1,5 +1,12 @@
 use tracing::trace;
 
+use crate::merge::memo::Memo;
+use crate::number::{NonFinite, write_number};
+use crate::raw::space::Space;
+use crate::raw::{
+    raw_string::RawString, raw_value::RawValue, substitution::Substitution as RawSubstitution,
+};
+use crate::value::ValueString;
 use crate::{
     error::Error,
     merge::{
@@ -42,8 +49,9 @@ pub(crate) enum Value {
     Null,
     #[default]
     None,
-    String(String),
+    String(ValueString),
     Number(serde_json::Number),
+    NonFinite(NonFinite),
     Substitution(Substitution),
     Concat(Concat),
     AddAssign(AddAssign),
@@ -59,7 +67,7 @@ impl Value {
         Value::Array(a.into())
     }
 
-    pub(crate) fn string(s: impl Into<String>) -> Value {
+    pub(crate) fn string(s: impl Into<ValueString>) -> Value {
         Value::String(s.into())
     }
 
@@ -95,7 +103,7 @@ impl Value {
             Value::Null => "null",
             Value::None => "none",
             Value::String(_) => "string",
-            Value::Number(_) => "number",
+            Value::Number(_) | Value::NonFinite(_) => "number",
             Value::Substitution(_) => "substitution",
             Value::Concat(_) => "concat",
             Value::AddAssign(_) => "add_assign",
@@ -107,9 +115,12 @@ impl Value {
         match self {
             Value::Object(object) => object.try_become_merged(),
             Value::Array(array) => array.try_become_merged(),
-            Value::Boolean(_) | Value::Null | Value::None | Value::String(_) | Value::Number(_) => {
-                true
-            }
+            Value::Boolean(_)
+            | Value::Null
+            | Value::None
+            | Value::String(_)
+            | Value::Number(_)
+            | Value::NonFinite(_) => true,
             Value::Substitution(_)
             | Value::Concat(_)
             | Value::AddAssign(_)
@@ -147,7 +158,12 @@ impl Value {
     /// - Deferred replacements (`DelayReplacement`) are used when the right value involves unresolved `Substitution`
     ///   or `Concat` to preserve dependencies for later resolution.
     /// - Trace logs are emitted for debugging the replacement operation and result.
-    pub(crate) fn replace(path: &RefPath, left: Value, right: Value) -> crate::Result<Value> {
+    pub(crate) fn replace(
+        path: &RefPath,
+        left: Value,
+        right: Value,
+        memo: &Memo,
+    ) -> crate::Result<Value> {
         // Log the replacement operation for debugging.
         trace!("replace: `{}`: `{}` <- `{}`", path, left, right);
 
@@ -156,7 +172,7 @@ impl Value {
             Value::Object(mut obj_left) => match right {
                 // Merge the right object into the left, respecting the path for conflict resolution.
                 Value::Object(right) => {
-                    obj_left.merge(right, Some(path))?;
+                    obj_left.merge(right, Some(path), memo)?;
                     Value::object(obj_left)
                 }
                 // Replace the left object with any primitive or array value.
@@ -165,7 +181,8 @@ impl Value {
                 | Value::Null
                 | Value::None
                 | Value::String(_)
-                | Value::Number(_) => right,
+                | Value::Number(_)
+                | Value::NonFinite(_) => right,
                 // Defer replacement if the right is a substitution, wrapping both values.
                 Value::Substitution(_) => {
                     let left = Value::object(obj_left);
@@ -173,11 +190,11 @@ impl Value {
                 }
                 // Attempt to resolve the right concat and merge or defer based on the result.
                 Value::Concat(concat) => {
-                    let try_resolved = concat.try_resolve(path)?;
+                    let try_resolved = concat.try_resolve(path, memo)?;
                     match try_resolved {
                         // Merge resolved object into the left object.
                         Value::Object(object) => {
-                            obj_left.merge(object, Some(path))?;
+                            obj_left.merge(object, Some(path), memo)?;
                             Value::object(obj_left)
                         }
                         // Defer if the concat resolves to another concat, prepending the left object.
@@ -213,13 +230,13 @@ impl Value {
                 }
                 // Attempt to resolve the right concat and handle the result.
                 Value::Concat(concat) => {
-                    let right = concat.try_resolve(path)?;
+                    let right = concat.try_resolve(path, memo)?;
                     match right {
                         // Concatenate arrays if the concat resolves to an array.
                         Value::Array(array) => {
                             let left = Value::Array(array_left);
                             let right = Value::Array(array);
-                            Self::concatenate(path, left, None, right)?
+                            Self::concatenate(path, left, None, right, memo)?
                         }
                         // Defer if the concat resolves to another concat.
                         Value::Concat(concat) => {
@@ -261,7 +278,7 @@ impl Value {
             Value::None => match right {
                 // Expand AddAssign to an array with the resolved value.
                 Value::AddAssign(add_assign) => {
-                    let value = add_assign.try_resolve(path)?;
+                    let value = add_assign.try_resolve(path, memo)?;
                     let array = if value.is_merged() {
                         Array::Merged(vec![RefCell::new(value)])
                     } else {
@@ -273,38 +290,40 @@ impl Value {
                 right => right,
             },
             // Handle replacement for primitive left values (boolean, string, number).
-            Value::Boolean(_) | Value::String(_) | Value::Number(_) => match right {
-                // Defer replacement if the right is a substitution.
-                Value::Substitution(_) => Value::delay_replacement([left, right]),
-                // Attempt to resolve the right concat and handle the result.
-                Value::Concat(concat) => {
-                    let right = concat.try_resolve(path)?;
-                    match right {
-                        // Defer if the concat resolves to another concat.
-                        Value::Concat(_) => Value::delay_replacement([left, right]),
-                        // AddAssign is invalid after concat resolution.
-                        Value::AddAssign(_) => {
-                            return Err(Error::ConcatenateDifferentType {
-                                path: path.to_string(),
-                                left_type: left.ty(),
-                                right_type: "add_assign",
-                            });
+            Value::Boolean(_) | Value::String(_) | Value::Number(_) | Value::NonFinite(_) => {
+                match right {
+                    // Defer replacement if the right is a substitution.
+                    Value::Substitution(_) => Value::delay_replacement([left, right]),
+                    // Attempt to resolve the right concat and handle the result.
+                    Value::Concat(concat) => {
+                        let right = concat.try_resolve(path, memo)?;
+                        match right {
+                            // Defer if the concat resolves to another concat.
+                            Value::Concat(_) => Value::delay_replacement([left, right]),
+                            // AddAssign is invalid after concat resolution.
+                            Value::AddAssign(_) => {
+                                return Err(Error::ConcatenateDifferentType {
+                                    path: path.to_string(),
+                                    left_type: left.ty(),
+                                    right_type: "add_assign",
+                                });
+                            }
+                            // Replace with the resolved value otherwise.
+                            other => other,
                         }
-                        // Replace with the resolved value otherwise.
-                        other => other,
                     }
+                    // Primitives cannot be replaced with AddAssign.
+                    Value::AddAssign(_) => {
+                        return Err(Error::ConcatenateDifferentType {
+                            path: path.to_string(),
+                            left_type: left.ty(),
+                            right_type: right.ty(),
+                        });
+                    }
+                    // Replace with any other right value.
+                    other => other,
                 }
-                // Primitives cannot be replaced with AddAssign.
-                Value::AddAssign(_) => {
-                    return Err(Error::ConcatenateDifferentType {
-                        path: path.to_string(),
-                        left_type: left.ty(),
-                        right_type: right.ty(),
-                    });
-                }
-                // Replace with any other right value.
-                other => other,
-            },
+            }
             // AddAssign cannot be the left value due to prior expansion during object merging.
             Value::AddAssign(_) => {
                 return Err(Error::ConcatenateDifferentType {
@@ -343,7 +362,7 @@ impl Value {
     /// # Parameters
     /// - `path`: The `RefPath` at which the concatenation is occurring, used for error reporting.
     /// - `left`: The left `Value` to concatenate.
-    /// - `space`: An optional `String` separator to insert between concatenated values (e.g., a space or empty string).
+    /// - `space`: An optional whitespace separator to insert between concatenated values (e.g., a space or empty string).
     /// - `right`: The right `Value` to concatenate.
     ///
     /// # Returns
@@ -358,8 +377,9 @@ impl Value {
     pub(crate) fn concatenate(
         path: &RefPath,
         left: Value,
-        space: Option<String>,
+        space: Option<Space>,
         right: Value,
+        memo: &Memo,
     ) -> crate::Result<Value> {
         trace!("concatenate: `{}`: `{}` <- `{}`", path, left, right);
 
@@ -370,7 +390,7 @@ impl Value {
                 Value::None => Value::object(left_obj),
                 // Merge right object into left object, respecting the path for conflict resolution.
                 Value::Object(right_obj) => {
-                    left_obj.merge(right_obj, Some(path))?;
+                    left_obj.merge(right_obj, Some(path), memo)?;
                     Value::object(left_obj)
                 }
                 // Objects cannot be concatenated with arrays, primitives, or AddAssign.
@@ -379,6 +399,7 @@ impl Value {
                 | Value::Boolean(_)
                 | Value::String(_)
                 | Value::Number(_)
+                | Value::NonFinite(_)
                 | Value::AddAssign(_) => {
                     return Err(Error::ConcatenateDifferentType {
                         path: path.to_string(),
@@ -418,14 +439,17 @@ impl Value {
                 // If a separator is provided, handle concatenation with primitives or None.
                 Some(space) => match right {
                     // For primitives, create a string starting with the separator.
-                    Value::Null | Value::Boolean(_) | Value::String(_) | Value::Number(_) => {
+                    Value::Null
+                    | Value::Boolean(_)
+                    | Value::String(_)
+                    | Value::Number(_)
+                    | Value::NonFinite(_) => {
                         let mut s = String::new();
-                        s.push_str(&space);
-                        write!(&mut s, "{right}").unwrap();
+                        write!(&mut s, "{space}{right}").unwrap();
                         Value::string(s)
                     }
                     // If right is None, return the separator as a string.
-                    Value::None => Value::string(space),
+                    Value::None => Value::string(space.to_string()),
                     // For substitutions, wrap in a Concat structure.
                     Value::Substitution(_) => Value::concat(Concat::two(left, Some(space), right)),
                     // Otherwise, return the right value unchanged.
@@ -435,13 +459,21 @@ impl Value {
                 _ => right,
             },
             // Handle concatenation of primitive types (null, boolean, string, number).
-            Value::Null | Value::Boolean(_) | Value::String(_) | Value::Number(_) => match right {
+            Value::Null
+            | Value::Boolean(_)
+            | Value::String(_)
+            | Value::Number(_)
+            | Value::NonFinite(_) => match right {
                 // Concatenate primitives into a single string, inserting the separator if provided.
-                Value::Boolean(_) | Value::Null | Value::String(_) | Value::Number(_) => {
+                Value::Boolean(_)
+                | Value::Null
+                | Value::String(_)
+                | Value::Number(_)
+                | Value::NonFinite(_) => {
                     let mut s = String::new();
                     write!(&mut s, "{left}").unwrap();
                     if let Some(space) = &space {
-                        s.push_str(space);
+                        write!(&mut s, "{space}").unwrap();
                     }
                     write!(&mut s, "{right}").unwrap();
                     Value::string(s)
@@ -451,7 +483,7 @@ impl Value {
                     let mut s = String::new();
                     write!(&mut s, "{left}").unwrap();
                     if let Some(space) = &space {
-                        s.push_str(space);
+                        write!(&mut s, "{space}").unwrap();
                     }
                     Value::string(s)
                 }
@@ -499,9 +531,12 @@ impl Value {
         match self {
             Value::Object(object) => object.is_merged(),
             Value::Array(array) => array.is_merged(),
-            Value::Boolean(_) | Value::String(_) | Value::Number(_) | Value::Null | Value::None => {
-                true
-            }
+            Value::Boolean(_)
+            | Value::String(_)
+            | Value::Number(_)
+            | Value::NonFinite(_)
+            | Value::Null
+            | Value::None => true,
             Value::Substitution(_)
             | Value::Concat(_)
             | Value::AddAssign(_)
@@ -550,9 +585,9 @@ impl Value {
         }
     }
 
-    pub(crate) fn resolve(&mut self) -> crate::Result<()> {
+    pub(crate) fn resolve(&mut self, memo: &mut Memo) -> crate::Result<()> {
         if let Value::Object(object) = self {
-            object.substitute()?;
+            object.substitute(memo)?;
         }
         self.resolve_add_assign();
         self.try_become_merged();
@@ -562,14 +597,15 @@ impl Value {
     pub(crate) fn from_raw(
         parent: Option<&RefPath>,
         raw: crate::raw::raw_value::RawValue,
+        memo: &Memo,
     ) -> crate::Result<Self> {
         let mut value = match raw {
             crate::raw::raw_value::RawValue::Object(raw_object) => {
-                let object = Object::from_raw(parent, raw_object)?;
+                let object = Object::from_raw(parent, raw_object, memo)?;
                 Value::object(object)
             }
             crate::raw::raw_value::RawValue::Array(raw_array) => {
-                let array = Array::from_raw(parent, raw_array)?;
+                let array = Array::from_raw(parent, raw_array, memo)?;
                 Value::array(array)
             }
             crate::raw::raw_value::RawValue::Boolean(b) => Value::Boolean(b),
@@ -578,21 +614,91 @@ impl Value {
                 Value::string(raw_string.to_string())
             }
             crate::raw::raw_value::RawValue::Number(number) => Value::number(number),
+            crate::raw::raw_value::RawValue::NonFinite(non_finite) => Value::NonFinite(non_finite),
             crate::raw::raw_value::RawValue::Substitution(substitution) => {
                 Value::substitution(substitution)
             }
             crate::raw::raw_value::RawValue::Concat(concat) => {
-                let concat = Concat::from_raw(parent, concat)?;
+                let concat = Concat::from_raw(parent, concat, memo)?;
                 Value::concat(concat)
             }
             crate::raw::raw_value::RawValue::AddAssign(add_assign) => {
-                let add_assign = AddAssign::from_raw(parent, add_assign)?;
+                let add_assign = AddAssign::from_raw(parent, add_assign, memo)?;
                 Value::add_assign(add_assign)
             }
         };
         value.try_become_merged();
         Ok(value)
     }
+
+    /// Converts a possibly partially resolved value back into a [`RawValue`], so it can be
+    /// merged and resolved again later.
+    ///
+    /// Pending substitutions, concatenations and add-assigns are kept as they are. A
+    /// `DelayReplacement` is only meaningful as an object field and is handled by
+    /// [`Object::into_raw`], here only its last value is kept.
+    ///
+    /// Returns `None` for `Value::None`, e.g. an optional substitution that resolved to nothing.
+    pub(crate) fn into_raw(self) -> crate::Result<Option<RawValue<'static>>> {
+        let raw = match self {
+            Value::Object(object) => RawValue::Object(object.into_raw()?),
+            Value::Array(array) => {
+                let mut values = vec![];
+                for ele in array.into_inner() {
+                    if let Some(raw) = ele.into_inner().into_raw()? {
+                        values.push(raw);
+                    }
+                }
+                RawValue::array(values)
+            }
+            Value::Boolean(b) => RawValue::Boolean(b),
+            Value::Null => RawValue::Null,
+            Value::None => return Ok(None),
+            Value::String(s) => RawValue::quoted_string(s),
+            Value::Number(n) => RawValue::Number(n),
+            Value::NonFinite(n) => RawValue::NonFinite(n),
+            Value::Substitution(substitution) => {
+                let path = substitution
+                    .path
+                    .iter()
+                    .map(|p| RawString::quoted(p.first.to_string()))
+                    .collect();
+                let path = RawString::path_expression(path);
+                RawValue::substitution(RawSubstitution::new(path, substitution.optional))
+            }
+            Value::Concat(concat) => {
+                let (values, spaces) = concat.into_inner();
+                let mut raw_values = vec![];
+                let mut raw_spaces = vec![];
+                let spaces = std::iter::once(None).chain(spaces);
+                for (value, space) in values.into_iter().zip(spaces) {
+                    if let Some(raw) = value.into_inner().into_raw()? {
+                        if !raw_values.is_empty() {
+                            raw_spaces.push(space);
+                        }
+                        raw_values.push(raw);
+                    }
+                }
+                match raw_values.len() {
+                    0 => return Ok(None),
+                    1 => raw_values.remove(0),
+                    _ => RawValue::concat(raw_values, raw_spaces)?,
+                }
+            }
+            Value::AddAssign(add_assign) => match (*add_assign.0).into_raw()? {
+                Some(raw) => RawValue::add_assign(raw),
+                None => return Ok(None),
+            },
+            Value::DelayReplacement(replacement) => {
+                let mut last = None;
+                for value in replacement.into_inner() {
+                    last = value.into_inner().into_raw()?.or(last);
+                }
+                return Ok(last);
+            }
+        };
+        Ok(Some(raw))
+    }
 }
 
 impl Display for Value {
@@ -604,7 +710,8 @@ impl Display for Value {
             Value::None => write!(f, "none"),
             Value::Null => write!(f, "null"),
             Value::String(string) => write!(f, "{string}"),
-            Value::Number(number) => write!(f, "{number}"),
+            Value::Number(number) => write_number(f, number),
+            Value::NonFinite(non_finite) => write!(f, "{non_finite}"),
             Value::Substitution(substitution) => write!(f, "{substitution}"),
             Value::Concat(concat) => write!(f, "{concat}"),
             Value::AddAssign(add_assign) => write!(f, "{add_assign}"),
@@ -0,0 +1,301 @@
+//! Custom merge behavior for tagged values.
+//!
+//! By default a later definition of a key replaces an earlier one, unless both are
+//! objects. With [`crate::ResolveOptions::merge_strategies`] set, a value shaped
+//! `{ "$merge" = <name>, value = ... }` is merged with the earlier definition by the
+//! [`MergeStrategy`] registered under `<name>` instead:
+//!
+//! ```hocon
+//! plugins = [core, metrics]
+//! plugins = { "$merge" = append, value = [tracing] }  # [core, metrics, tracing]
+//! ```
+//!
+//! The strategies `append` and `prepend` are built in, see [`MergeStrategies::builtin`].
+//! Both the earlier definition and the `value` must be plain values without
+//! substitutions, since strategies run while the configuration is merged, before
+//! substitutions are resolved.
+//!
+//! Without strategies, which is the default, and with [`crate::SpecStrictness::Strict`]
+//! tagged values are ordinary objects.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::config_options::SpecStrictness;
+use crate::error::Error;
+use crate::merge::memo::Memo;
+use crate::merge::path::RefPath;
+use crate::merge::value::Value as MValue;
+use crate::raw::raw_value::RawValue;
+use crate::value::Value;
+
+/// The key naming the strategy of a tagged value.
+pub const MERGE_TAG: &str = "$merge";
+/// The key holding the value of a tagged value.
+pub const MERGE_VALUE: &str = "value";
+
+/// Merges a tagged value into the value defined before it.
+///
+/// Closures taking the earlier value and the tagged `value` implement this trait.
+pub trait MergeStrategy {
+    /// Returns the merged value. `left` is `None` if the key wasn't defined before.
+    fn merge(&self, left: Option<Value>, right: Value) -> crate::Result<Value>;
+}
+
+impl<F> MergeStrategy for F
+where
+    F: Fn(Option<Value>, Value) -> crate::Result<Value>,
+{
+    fn merge(&self, left: Option<Value>, right: Value) -> crate::Result<Value> {
+        self(left, right)
+    }
+}
+
+/// The merge strategies tagged values are merged with, keyed by the name they are tagged
+/// with, see [`crate::ResolveOptions::merge_strategies`].
+///
+/// # Example
+/// ```rust
+/// use std::rc::Rc;
+/// use hocon_rs::merge_strategy::MergeStrategies;
+/// use hocon_rs::{Config, ConfigOptions, ResolveOptions, Value};
+///
+/// let strategies = MergeStrategies::builtin().with("sum", |left: Option<Value>, right: Value| {
+///     let left = left.and_then(|v| v.as_i64()).unwrap_or_default();
+///     Ok(Value::from(left + right.as_i64().unwrap_or_default()))
+/// });
+/// let options = ConfigOptions {
+///     resolve: ResolveOptions {
+///         merge_strategies: Some(Rc::new(strategies)),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// let input = r#"retries = 1, retries = { "$merge" = sum, value = 2 }"#;
+/// let value: Value = Config::parse_str(input, Some(options)).unwrap();
+/// assert_eq!(value.get_by_path(["retries"]), Some(&Value::from(3)));
+/// ```
+#[derive(Clone, Default)]
+pub struct MergeStrategies {
+    strategies: HashMap<String, Rc<dyn MergeStrategy>>,
+}
+
+impl MergeStrategies {
+    /// No strategies, every tagged value fails to merge with [`Error::MergeStrategy`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in strategies [`Append`] as `append` and [`Prepend`] as `prepend`.
+    pub fn builtin() -> Self {
+        Self::new().with("append", Append).with("prepend", Prepend)
+    }
+
+    /// Adds `strategy` for values tagged with `name`.
+    pub fn with(mut self, name: impl Into<String>, strategy: impl MergeStrategy + 'static) -> Self {
+        self.insert(name, strategy);
+        self
+    }
+
+    /// Adds `strategy` for values tagged with `name`, replacing the one added before,
+    /// including a built-in one.
+    pub fn insert(&mut self, name: impl Into<String>, strategy: impl MergeStrategy + 'static) {
+        self.strategies.insert(name.into(), Rc::new(strategy));
+    }
+
+    /// Returns the strategy for values tagged with `name`.
+    pub fn get(&self, name: &str) -> Option<&dyn MergeStrategy> {
+        self.strategies.get(name).map(Rc::as_ref)
+    }
+}
+
+/// Lists the names of the strategies, which can't be printed themselves.
+impl Debug for MergeStrategies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names = self.strategies.keys().collect::<Vec<_>>();
+        names.sort();
+        f.debug_set().entries(names).finish()
+    }
+}
+
+/// Appends the elements of the tagged array to the earlier array, like `+=`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Append;
+
+impl MergeStrategy for Append {
+    fn merge(&self, left: Option<Value>, right: Value) -> crate::Result<Value> {
+        concat_arrays(left, right, false)
+    }
+}
+
+/// Inserts the elements of the tagged array before the ones of the earlier array.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Prepend;
+
+impl MergeStrategy for Prepend {
+    fn merge(&self, left: Option<Value>, right: Value) -> crate::Result<Value> {
+        concat_arrays(left, right, true)
+    }
+}
+
+fn concat_arrays(left: Option<Value>, right: Value, prepend: bool) -> crate::Result<Value> {
+    let into_array = |value: Value| match value.into_unshared() {
+        Value::Array(array) => Ok(array),
+        other => Err(Error::InvalidConversion {
+            from: other.ty(),
+            to: "array",
+        }),
+    };
+    let mut right = into_array(right)?;
+    let Some(left) = left else {
+        return Ok(Value::Array(right));
+    };
+    let mut left = into_array(left)?;
+    if prepend {
+        right.append(&mut left);
+        Ok(Value::Array(right))
+    } else {
+        left.append(&mut right);
+        Ok(Value::Array(left))
+    }
+}
+
+/// Returns `true` if `value` is an object tagged with [`MERGE_TAG`] and merge strategies
+/// are enabled, see [`crate::ResolveOptions::merge_strategies`].
+pub(crate) fn is_tagged(value: &MValue, memo: &Memo) -> bool {
+    if memo.options.spec_strictness == SpecStrictness::Strict
+        || memo.options.merge_strategies.is_none()
+    {
+        return false;
+    }
+    match value {
+        MValue::Object(object) => object
+            .get(MERGE_TAG)
+            .is_some_and(|tag| matches!(&*tag.borrow(), MValue::String(_))),
+        _ => false,
+    }
+}
+
+/// Merges the tagged value `right` into `left` with the strategy named by its tag.
+///
+/// A tagged `left` that was never merged into anything is applied on its own first.
+pub(crate) fn apply(
+    path: &RefPath,
+    left: Option<MValue>,
+    right: MValue,
+    memo: &Memo,
+) -> crate::Result<MValue> {
+    let MValue::Object(mut fields) = right else {
+        unreachable!("only tagged objects are applied")
+    };
+    let name = match fields.remove(MERGE_TAG).map(|tag| tag.into_inner()) {
+        Some(MValue::String(name)) => name.into_string(),
+        _ => unreachable!("only tagged objects are applied"),
+    };
+    let error = |message: &str| Error::MergeStrategy {
+        path: path.to_string(),
+        strategy: name.clone(),
+        message: message.to_string(),
+    };
+    let right = fields
+        .remove(MERGE_VALUE)
+        .ok_or_else(|| error("the tagged value has no `value` field"))?
+        .into_inner();
+    let left = match left {
+        Some(left) if is_tagged(&left, memo) => Some(apply(path, None, left, memo)?),
+        Some(MValue::None) | None => None,
+        left => left,
+    };
+    let to_value = |value: MValue| {
+        Value::try_from(value)
+            .map_err(|_| error("the merged values must not contain substitutions"))
+    };
+    let left = left.map(to_value).transpose()?;
+    let right = to_value(right)?;
+    let strategy = memo
+        .options
+        .merge_strategies
+        .as_ref()
+        .and_then(|strategies| strategies.get(&name))
+        .ok_or_else(|| error("no such merge strategy is registered"))?;
+    let merged = strategy.merge(left, right).map_err(|e| Error::AtPath {
+        path: path.to_string(),
+        error: Box::new(e),
+        origin: None,
+    })?;
+    MValue::from_raw(Some(path), RawValue::from(merged), memo)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::Result;
+    use crate::config::Config;
+    use crate::config_options::ConfigOptions;
+    use crate::error::Error;
+    use crate::resolve_options::ResolveOptions;
+    use crate::value::Value;
+
+    use super::MergeStrategies;
+
+    #[test]
+    fn test_merge_strategy() -> Result<()> {
+        let strategies =
+            MergeStrategies::builtin().with("sum", |left: Option<Value>, right: Value| {
+                let left = left.and_then(|v| v.as_i64()).unwrap_or_default();
+                Ok(Value::from(left + right.as_i64().unwrap_or_default()))
+            });
+        let options = ConfigOptions {
+            resolve: ResolveOptions {
+                merge_strategies: Some(Rc::new(strategies)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let value: Value = Config::parse_str(
+            r#"
+            plugins = [core]
+            plugins = { "$merge" = append, value = [metrics] }
+            plugins = { "$merge" = prepend, value = [first] }
+            server { hosts = [a] }
+            server { hosts = { "$merge" = append, value = [b] } }
+            new = { "$merge" = append, value = [x] }
+            new = { "$merge" = append, value = [y] }
+            retries = 1
+            retries = { "$merge" = sum, value = 2 }
+            "#,
+            Some(options.clone()),
+        )?;
+        let expected: Value = Config::parse_str(
+            r#"
+            plugins = [first, core, metrics]
+            server.hosts = [a, b]
+            new = [x, y]
+            retries = 3
+            "#,
+            None,
+        )?;
+        assert_eq!(value, expected);
+
+        let input = r#"a = { "$merge" = unknown, value = 1 }"#;
+        let error = Config::parse_str::<Value>(input, Some(options.clone()));
+        assert!(matches!(error, Err(Error::MergeStrategy { .. })));
+        let error = Config::parse_str::<Value>(
+            r#"b = [1], a = [0], a = { "$merge" = append, value = ${b} }"#,
+            Some(options),
+        );
+        assert!(matches!(error, Err(Error::MergeStrategy { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_strategies_opt_in() -> Result<()> {
+        let input = r#"a = [1], a = { "$merge" = append, value = [2] }"#;
+        let value: Value = Config::parse_str(input, None)?;
+        let expected: Value = Config::parse_str(r#"a { "$merge" = append, value = [2] }"#, None)?;
+        assert_eq!(value, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+//! Comparisons and conversions for the [`Number`] stored in [`crate::Value::Number`].
+//!
+//! [`Number`] keeps integers and floats apart, so `1 == 1.0` is `false` and there is no
+//! ordering at all. [`NumberExt`] compares numbers by their mathematical value and
+//! converts them into the primitive types with range checks. [`NonFinite`] holds the
+//! `Infinity` and `NaN` values that a [`Number`] can't.
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use serde_json::Number;
+
+/// The exact value of a [`Number`], integers are never rounded through `f64`.
+#[derive(Debug, Clone, Copy)]
+enum Exact {
+    Int(i128),
+    Float(f64),
+}
+
+fn exact(number: &Number) -> Exact {
+    if let Some(n) = number.as_i64() {
+        Exact::Int(n as i128)
+    } else if let Some(n) = number.as_u64() {
+        Exact::Int(n as i128)
+    } else if let Some(n) = number.as_i128() {
+        Exact::Int(n)
+    } else {
+        Exact::Float(number.as_f64().unwrap_or(f64::NAN))
+    }
+}
+
+/// Compares an integer with a float without losing precision on either side.
+fn cmp_int_float(int: i128, float: f64) -> Ordering {
+    if float.is_nan() {
+        return Ordering::Less;
+    }
+    if float < i128::MIN as f64 {
+        return Ordering::Greater;
+    }
+    if float >= i128::MAX as f64 {
+        return Ordering::Less;
+    }
+    let whole = float.trunc();
+    int.cmp(&(whole as i128))
+        .then_with(|| 0.0.partial_cmp(&(float - whole)).unwrap_or(Ordering::Equal))
+}
+
+macro_rules! checked_conversion {
+    ($($(#[$doc:meta])* $name:ident -> $ty:ty;)*) => {
+        $(
+            $(#[$doc])*
+            fn $name(&self) -> Option<$ty> {
+                <$ty>::try_from(self.to_i128()?).ok()
+            }
+        )*
+    };
+}
+
+/// Numeric comparisons and checked conversions for [`Number`].
+///
+/// # Example
+/// ```rust
+/// use std::cmp::Ordering;
+/// use hocon_rs::number::NumberExt;
+/// use serde_json::Number;
+///
+/// let one = Number::from(1);
+/// let one_float = Number::from_f64(1.0).unwrap();
+/// assert!(one.numeric_eq(&one_float));
+/// assert_eq!(one.numeric_cmp(&Number::from_f64(1.5).unwrap()), Ordering::Less);
+/// assert_eq!(Number::from(300).to_u8(), None);
+/// assert_eq!(one_float.to_u32(), Some(1));
+/// ```
+pub trait NumberExt: private::Sealed {
+    /// Compares two numbers by their value, regardless of how they are stored.
+    ///
+    /// The ordering is total: numbers stored as floats that are NaN sort before
+    /// everything else, which only happens with `arbitrary_precision` numbers that
+    /// don't fit into an `f64`.
+    fn numeric_cmp(&self, other: &Number) -> Ordering;
+
+    /// Returns `true` if both numbers have the same value, e.g. `1` and `1.0`.
+    fn numeric_eq(&self, other: &Number) -> bool {
+        self.numeric_cmp(other) == Ordering::Equal
+    }
+
+    /// Returns the number as `i128` if it is an integer, or a float without a fractional
+    /// part that fits.
+    fn to_i128(&self) -> Option<i128>;
+
+    /// Returns the number as `f64`, which may round large integers.
+    fn to_f64(&self) -> f64;
+
+    checked_conversion! {
+        /// Returns the number as `i8` if it is a whole number in range.
+        to_i8 -> i8;
+        /// Returns the number as `i16` if it is a whole number in range.
+        to_i16 -> i16;
+        /// Returns the number as `i32` if it is a whole number in range.
+        to_i32 -> i32;
+        /// Returns the number as `i64` if it is a whole number in range.
+        to_i64 -> i64;
+        /// Returns the number as `isize` if it is a whole number in range.
+        to_isize -> isize;
+        /// Returns the number as `u8` if it is a whole number in range.
+        to_u8 -> u8;
+        /// Returns the number as `u16` if it is a whole number in range.
+        to_u16 -> u16;
+        /// Returns the number as `u32` if it is a whole number in range.
+        to_u32 -> u32;
+        /// Returns the number as `u64` if it is a whole number in range.
+        to_u64 -> u64;
+        /// Returns the number as `usize` if it is a whole number in range.
+        to_usize -> usize;
+    }
+
+    /// Returns the number as `f32` if it can be represented without overflowing.
+    fn to_f32(&self) -> Option<f32> {
+        let value = self.to_f64();
+        let narrowed = value as f32;
+        (narrowed.is_finite() || !value.is_finite()).then_some(narrowed)
+    }
+}
+
+impl NumberExt for Number {
+    fn numeric_cmp(&self, other: &Number) -> Ordering {
+        match (exact(self), exact(other)) {
+            (Exact::Int(a), Exact::Int(b)) => a.cmp(&b),
+            (Exact::Int(a), Exact::Float(b)) => cmp_int_float(a, b),
+            (Exact::Float(a), Exact::Int(b)) => cmp_int_float(b, a).reverse(),
+            (Exact::Float(a), Exact::Float(b)) => a.total_cmp(&b),
+        }
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        match exact(self) {
+            Exact::Int(n) => Some(n),
+            Exact::Float(n)
+                if n.fract() == 0.0 && n >= i128::MIN as f64 && n < i128::MAX as f64 =>
+            {
+                Some(n as i128)
+            }
+            Exact::Float(_) => None,
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        match exact(self) {
+            Exact::Int(n) => n as f64,
+            Exact::Float(n) => n,
+        }
+    }
+}
+
+/// Writes `number` so it reads back as the same number, both as HOCON and as JSON.
+///
+/// The digits are kept as [`Number`] stores them, which are the digits of the input with
+/// `arbitrary_precision`. Only the `+` of a positive exponent, e.g. in `1e+10`, is left
+/// out, HOCON doesn't allow it in unquoted values.
+pub(crate) fn write_number<W: std::fmt::Write>(out: &mut W, number: &Number) -> std::fmt::Result {
+    let rendered = number.to_string();
+    match rendered.find(['e', 'E']) {
+        Some(index) if rendered[index + 1..].starts_with('+') => {
+            out.write_str(&rendered[..=index])?;
+            out.write_str(&rendered[index + 2..])
+        }
+        _ => out.write_str(&rendered),
+    }
+}
+
+/// An infinite or NaN float, read from the unquoted `Infinity`, `-Infinity` and `NaN` with
+/// [`crate::ConfigOptions::non_finite_numbers`]. [`Number`] only holds finite values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NonFinite {
+    Infinity,
+    NegativeInfinity,
+    NaN,
+}
+
+impl NonFinite {
+    /// Returns the value for `Infinity`, `-Infinity` or `NaN`. The spelling is
+    /// case-sensitive.
+    pub fn parse(s: &str) -> Option<NonFinite> {
+        match s {
+            "Infinity" => Some(NonFinite::Infinity),
+            "-Infinity" => Some(NonFinite::NegativeInfinity),
+            "NaN" => Some(NonFinite::NaN),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            NonFinite::Infinity => f64::INFINITY,
+            NonFinite::NegativeInfinity => f64::NEG_INFINITY,
+            NonFinite::NaN => f64::NAN,
+        }
+    }
+
+    /// Returns the value of `float` if it's infinite or NaN.
+    pub fn from_f64(float: f64) -> Option<NonFinite> {
+        if float.is_nan() {
+            Some(NonFinite::NaN)
+        } else if float == f64::INFINITY {
+            Some(NonFinite::Infinity)
+        } else if float == f64::NEG_INFINITY {
+            Some(NonFinite::NegativeInfinity)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for NonFinite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            NonFinite::Infinity => "Infinity",
+            NonFinite::NegativeInfinity => "-Infinity",
+            NonFinite::NaN => "NaN",
+        };
+        f.write_str(s)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for serde_json::Number {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use rstest::rstest;
+    use serde_json::Number;
+
+    use super::NumberExt;
+
+    fn float(n: f64) -> Number {
+        Number::from_f64(n).unwrap()
+    }
+
+    #[rstest]
+    #[case(Number::from(1), float(1.0), Ordering::Equal)]
+    #[case(Number::from(1), float(1.5), Ordering::Less)]
+    #[case(Number::from(-1), float(-1.5), Ordering::Greater)]
+    #[case(Number::from(u64::MAX), Number::from(i64::MIN), Ordering::Greater)]
+    #[case(Number::from(i64::MAX), float(i64::MAX as f64), Ordering::Less)]
+    #[case(float(0.1), float(0.2), Ordering::Less)]
+    #[case(float(1e300), Number::from(u64::MAX), Ordering::Greater)]
+    fn test_numeric_cmp(#[case] a: Number, #[case] b: Number, #[case] expected: Ordering) {
+        assert_eq!(a.numeric_cmp(&b), expected);
+        assert_eq!(b.numeric_cmp(&a), expected.reverse());
+        assert_eq!(a.numeric_eq(&b), expected == Ordering::Equal);
+    }
+
+    #[rstest]
+    #[case(Number::from(42), Some(42), Some(42), Some(42))]
+    #[case(Number::from(300), None, Some(300), Some(300))]
+    #[case(Number::from(-1), None, None, Some(-1))]
+    #[case(float(7.0), Some(7), Some(7), Some(7))]
+    #[case(float(7.5), None, None, None)]
+    #[case(Number::from(u64::MAX), None, None, None)]
+    fn test_checked_conversions(
+        #[case] number: Number,
+        #[case] as_u8: Option<u8>,
+        #[case] as_u32: Option<u32>,
+        #[case] as_i32: Option<i32>,
+    ) {
+        assert_eq!(number.to_u8(), as_u8);
+        assert_eq!(number.to_u32(), as_u32);
+        assert_eq!(
+            number.to_usize(),
+            number.to_u64().and_then(|n| usize::try_from(n).ok())
+        );
+        assert_eq!(number.to_i32(), as_i32);
+    }
+
+    #[test]
+    fn test_to_f32() {
+        assert_eq!(float(0.5).to_f32(), Some(0.5));
+        assert_eq!(float(1e300).to_f32(), None);
+        assert_eq!(Number::from(3).to_f32(), Some(3.0));
+    }
+
+    #[rstest]
+    #[case("1E10")]
+    #[case("-2.5e+300")]
+    #[case("1.5E-3")]
+    fn test_write_number(#[case] input: &str) -> crate::Result<()> {
+        use std::str::FromStr;
+
+        use crate::{Config, Value};
+
+        let number = Number::from_str(input)?;
+        let mut rendered = String::new();
+        super::write_number(&mut rendered, &number).unwrap();
+        assert!(!rendered.contains('+'), "{rendered}");
+        assert_eq!(Value::Number(number.clone()).to_hocon_string(), rendered);
+        let value: Value = Config::parse_str(&format!("a = {rendered}"), None)?;
+        assert_eq!(
+            value.get_by_path(["a"]),
+            Some(&Value::Number(number.clone()))
+        );
+        assert_eq!(serde_json::from_str::<Number>(&rendered)?, number);
+        Ok(())
+    }
+}
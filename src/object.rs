@@ -1,11 +1,10 @@
 use crate::join_format;
-use crate::value::Value;
-use std::collections::HashMap;
+use crate::value::{Map, Value};
 use std::fmt::{Display, Formatter};
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, Clone, PartialEq, Default)]
-pub struct Object(HashMap<String, Value>);
+pub struct Object(Map);
 
 impl Object {
     pub fn new() -> Self {
@@ -14,7 +13,7 @@ impl Object {
 }
 
 impl Deref for Object {
-    type Target = HashMap<String, Value>;
+    type Target = Map;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -38,7 +37,7 @@ impl Display for Object {
     }
 }
 
-impl From<Object> for HashMap<String, Value> {
+impl From<Object> for Map {
     fn from(val: Object) -> Self {
         val.0
     }
@@ -46,6 +45,6 @@ impl From<Object> for HashMap<String, Value> {
 
 impl FromIterator<(String, Value)> for Object {
     fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
-        Self(HashMap::from_iter(iter))
+        Self(Map::from_iter(iter))
     }
 }
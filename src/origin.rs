@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use derive_more::Constructor;
+
+use crate::parser::read::{Position, Span};
+
+/// Describes where a configuration entry was defined.
+///
+/// Origins are recorded by the parser for every key-value field and survive merging,
+/// so the definition that finally won can be reported back to the user, e.g. in
+/// validation messages.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Constructor)]
+pub struct Origin {
+    /// The file path or URL the entry was read from, `None` when the configuration
+    /// was parsed from an in-memory string or built programmatically.
+    pub source: Option<SourceId>,
+    /// The position of the key inside the source. JSON and properties files
+    /// do not provide positions.
+    pub position: Option<Position>,
+    /// The position right after the value of the entry, when known.
+    pub end: Option<Position>,
+    /// The include statements that led to this source, outermost first.
+    pub include_chain: Vec<SourceId>,
+    /// The kind of source the entry comes from.
+    pub layer: Layer,
+    /// The sources read before `source` while loading the same configuration that had
+    /// exactly the same contents, so they were parsed only once, see
+    /// [`crate::ConfigOptions::cache_includes`].
+    pub same_content: Vec<SourceId>,
+}
+
+impl Origin {
+    /// Creates the origin of an entry read by a parser, deriving the [`Layer`] from
+    /// `source` and `include_chain`.
+    pub(crate) fn parsed(
+        source: Option<SourceId>,
+        position: Option<Position>,
+        end: Option<Position>,
+        include_chain: Vec<SourceId>,
+    ) -> Origin {
+        let layer = if !include_chain.is_empty() {
+            Layer::Include
+        } else if source.is_some() {
+            Layer::File
+        } else {
+            Layer::String
+        };
+        Origin::new(source, position, end, include_chain, layer, vec![])
+    }
+
+    /// Creates the origin of an entry that was not parsed from a document, e.g. an
+    /// environment variable. `name` is recorded as the source, if given.
+    pub(crate) fn synthetic(layer: Layer, name: Option<&str>) -> Origin {
+        Origin {
+            source: name.map(SourceId::new),
+            layer,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the source range covering the whole entry, from its key to the end of its value.
+    pub fn span(&self) -> Option<Span> {
+        match (self.position, self.end) {
+            (Some(start), Some(end)) => Some(Span { start, end }),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.layer, &self.source) {
+            (Layer::Environment, Some(name)) => write!(f, "environment variable {}", name)?,
+            (Layer::Environment, None) => write!(f, "<environment>")?,
+            (Layer::Override, Some(name)) => write!(f, "override {}", name)?,
+            (Layer::Override, None) => write!(f, "<override>")?,
+            (_, Some(source)) => write!(f, "{}", source)?,
+            (_, None) => write!(f, "<string>")?,
+        }
+        if let Some(position) = &self.position {
+            write!(f, ":{}", position)?;
+        }
+        if !self.include_chain.is_empty() {
+            write!(f, " (included from ")?;
+            crate::join(self.include_chain.iter(), " -> ", f)?;
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of source a configuration entry comes from, see [`crate::Config::provenance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Layer {
+    /// A configuration file, URL or classpath resource loaded directly.
+    File,
+    /// A file included by another one, see [`Origin::include_chain`].
+    Include,
+    /// A string parsed in memory, or a source whose name is unknown.
+    #[default]
+    String,
+    /// The environment variables, merged by [`crate::ConfigOptions::use_system_environment`]
+    /// or [`crate::ConfigBuilder::add_env_prefix`].
+    Environment,
+    /// An override added with [`crate::ConfigBuilder::add_override`].
+    Override,
+    /// A value added programmatically, e.g. with [`crate::Config::add_kv`], which has no origin.
+    Programmatic,
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let layer = match self {
+            Layer::File => "file",
+            Layer::Include => "include",
+            Layer::String => "string",
+            Layer::Environment => "environment",
+            Layer::Override => "override",
+            Layer::Programmatic => "programmatic",
+        };
+        f.write_str(layer)
+    }
+}
+
+/// Tells which layer contributed the value that won at a path, see
+/// [`crate::Config::provenance`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    /// The kind of source of the winning definition.
+    pub layer: Layer,
+    /// Where the winning definition was read from, `None` for [`Layer::Programmatic`].
+    pub origin: Option<Origin>,
+    /// The other sources read while loading the configuration that had the same contents
+    /// as the source of the winning definition, see [`Origin::same_content`].
+    pub same_content: Vec<SourceId>,
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.origin {
+            Some(origin) => write!(f, "{} ({}", origin, self.layer)?,
+            None => return write!(f, "{}", self.layer),
+        }
+        if !self.same_content.is_empty() {
+            write!(f, ", same content as ")?;
+            crate::join(self.same_content.iter(), ", ", f)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Identifies a file path or URL a configuration was read from.
+///
+/// The name is shared by every [`Origin`] and error that refers to the source, and freed
+/// with the last of them. Use [`SourceId::name`] to get the source back. Ids are compared
+/// by name only.
+#[derive(Debug, Clone)]
+pub struct SourceId(Arc<Source>);
+
+#[derive(Debug)]
+struct Source {
+    name: Arc<str>,
+    digest: Option<[u8; 32]>,
+}
+
+impl SourceId {
+    /// Returns the id of `name`.
+    pub fn new(name: &str) -> SourceId {
+        SourceId(Arc::new(Source {
+            name: Arc::from(name),
+            digest: None,
+        }))
+    }
+
+    /// Returns the id of the same source, which was read with the SHA-256 `digest`.
+    pub(crate) fn with_digest(&self, digest: [u8; 32]) -> SourceId {
+        SourceId(Arc::new(Source {
+            name: self.0.name.clone(),
+            digest: Some(digest),
+        }))
+    }
+
+    /// Returns the file path or URL this id was created for.
+    pub fn name(&self) -> Arc<str> {
+        self.0.name.clone()
+    }
+
+    /// Returns the SHA-256 of the contents read from the source while loading, `None`
+    /// if the source wasn't read, e.g. the file of an include chain.
+    pub fn digest(&self) -> Option<&[u8; 32]> {
+        self.0.digest.as_ref()
+    }
+}
+
+impl PartialEq for SourceId {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name == other.0.name
+    }
+}
+
+impl Eq for SourceId {}
+
+impl Hash for SourceId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.name.hash(state);
+    }
+}
+
+impl PartialOrd for SourceId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SourceId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.name.cmp(&other.0.name)
+    }
+}
+
+impl Display for SourceId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.name)
+    }
+}
@@ -0,0 +1,374 @@
+use std::ops::Range;
+
+use crate::Result;
+use crate::parser::HoconParser;
+use crate::parser::events::Event;
+use crate::parser::read::Read;
+use crate::raw::field::ObjectField;
+use crate::raw::include::Inclusion;
+use crate::raw::raw_array::RawArray;
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_string::RawString;
+use crate::raw::raw_value::RawValue;
+use crate::raw::space::Space;
+
+/// The index of a node in a [`RawArena`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A field of an object in a [`RawArena`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaField<'de> {
+    /// `key = value`, or `key += value` when `add_assign` is set.
+    KeyValue {
+        key: RawString<'de>,
+        add_assign: bool,
+        value: NodeId,
+    },
+    /// An `include` directive. The included file is not read.
+    Inclusion(Inclusion<'de>),
+}
+
+/// A node of a [`RawArena`], see [`RawArena::get`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaNode<'a, 'de> {
+    Object(&'a [ArenaField<'de>]),
+    Array(&'a [NodeId]),
+    /// The parts of a concatenation and the whitespace between them.
+    Concat {
+        parts: &'a [NodeId],
+        spaces: &'a [Option<Space>],
+    },
+    /// A string, number, boolean, null or substitution.
+    Value(&'a RawValue<'de>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node<'de> {
+    Object(Range<usize>),
+    Array(Range<usize>),
+    Concat {
+        parts: Range<usize>,
+        spaces: Range<usize>,
+    },
+    Value(RawValue<'de>),
+}
+
+/// A document parsed into a few contiguous buffers instead of a tree of
+/// [`RawObject`]s, see [`HoconParser::parse_arena`].
+///
+/// Objects, arrays and concatenations refer to their children by [`NodeId`], and the
+/// children of a node are stored next to each other, so parsing a document with many
+/// keys allocates a handful of growing buffers instead of a vector per object and array.
+/// Everything is freed at once when the arena is dropped.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RawArena<'de> {
+    nodes: Vec<Node<'de>>,
+    fields: Vec<ArenaField<'de>>,
+    children: Vec<NodeId>,
+    spaces: Vec<Option<Space>>,
+}
+
+impl<'de> RawArena<'de> {
+    /// Returns the object of the document.
+    pub fn root(&self) -> NodeId {
+        // Containers are added when they end, so the document comes last.
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Returns the node with the given id.
+    ///
+    /// # Panics
+    /// Panics if `id` is from another arena with fewer nodes.
+    pub fn get(&self, id: NodeId) -> ArenaNode<'_, 'de> {
+        match &self.nodes[id.0] {
+            Node::Object(fields) => ArenaNode::Object(&self.fields[fields.clone()]),
+            Node::Array(elements) => ArenaNode::Array(&self.children[elements.clone()]),
+            Node::Concat { parts, spaces } => ArenaNode::Concat {
+                parts: &self.children[parts.clone()],
+                spaces: &self.spaces[spaces.clone()],
+            },
+            Node::Value(value) => ArenaNode::Value(value),
+        }
+    }
+
+    /// Returns the number of nodes, the document and every value in it.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Builds the [`RawObject`] of the document, as [`HoconParser::parse`] would return
+    /// it without comments, spans and the contents of included files.
+    pub fn to_raw(&self) -> Result<RawObject<'de>> {
+        match self.value(self.root())? {
+            RawValue::Object(object) => Ok(object),
+            _ => unreachable!("the root of an arena is an object"),
+        }
+    }
+
+    fn value(&self, id: NodeId) -> Result<RawValue<'de>> {
+        let value = match self.get(id) {
+            ArenaNode::Object(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| match field {
+                        ArenaField::KeyValue {
+                            key,
+                            add_assign,
+                            value,
+                        } => {
+                            let mut value = self.value(*value)?;
+                            if *add_assign {
+                                value = RawValue::add_assign(value);
+                            }
+                            Ok(ObjectField::key_value(key.clone(), value))
+                        }
+                        ArenaField::Inclusion(inclusion) => {
+                            Ok(ObjectField::inclusion(inclusion.clone()))
+                        }
+                    })
+                    .collect::<Result<_>>()?;
+                RawValue::Object(RawObject::new(fields))
+            }
+            ArenaNode::Array(elements) => {
+                let values = elements
+                    .iter()
+                    .map(|element| self.value(*element))
+                    .collect::<Result<_>>()?;
+                RawValue::Array(RawArray::new(values))
+            }
+            ArenaNode::Concat { parts, spaces } => {
+                let values = parts
+                    .iter()
+                    .map(|part| self.value(*part))
+                    .collect::<Result<_>>()?;
+                RawValue::concat(values, spaces.to_vec())?
+            }
+            ArenaNode::Value(value) => value.clone(),
+        };
+        Ok(value)
+    }
+
+    fn push(&mut self, node: Node<'de>) -> NodeId {
+        self.nodes.push(node);
+        NodeId(self.nodes.len() - 1)
+    }
+}
+
+/// An object or array whose end was not reached yet. The indices tell where its items
+/// start in the scratch buffers of the [`Builder`].
+enum Frame<'de> {
+    Object {
+        fields: usize,
+        key: Option<(RawString<'de>, bool)>,
+        parts: usize,
+        spaces: usize,
+    },
+    Array {
+        elements: usize,
+        parts: usize,
+        spaces: usize,
+    },
+}
+
+impl Frame<'_> {
+    fn slot(&self) -> (usize, usize) {
+        match self {
+            Frame::Object { parts, spaces, .. } | Frame::Array { parts, spaces, .. } => {
+                (*parts, *spaces)
+            }
+        }
+    }
+}
+
+/// Builds a [`RawArena`] from the [`Event`]s of a document.
+///
+/// The items of the open containers are kept on scratch stacks and moved into the arena
+/// in one piece when their container ends, which keeps the children of a node together.
+#[derive(Default)]
+struct Builder<'de> {
+    arena: RawArena<'de>,
+    frames: Vec<Frame<'de>>,
+    fields: Vec<ArenaField<'de>>,
+    elements: Vec<NodeId>,
+    /// The parts of the values being parsed, more than one for a concatenation.
+    parts: Vec<NodeId>,
+    spaces: Vec<Option<Space>>,
+}
+
+impl<'de> Builder<'de> {
+    fn event(&mut self, event: Event<'de>) {
+        match event {
+            Event::ObjectStart => {
+                self.begin_value();
+                self.frames.push(Frame::Object {
+                    fields: self.fields.len(),
+                    key: None,
+                    parts: self.parts.len(),
+                    spaces: self.spaces.len(),
+                });
+            }
+            Event::ArrayStart => {
+                self.begin_value();
+                self.frames.push(Frame::Array {
+                    elements: self.elements.len(),
+                    parts: self.parts.len(),
+                    spaces: self.spaces.len(),
+                });
+            }
+            Event::ObjectEnd => {
+                self.end_slot();
+                let Some(Frame::Object { fields, .. }) = self.frames.pop() else {
+                    unreachable!("objects end in an object");
+                };
+                let start = self.arena.fields.len();
+                self.arena.fields.extend(self.fields.drain(fields..));
+                let id = self
+                    .arena
+                    .push(Node::Object(start..self.arena.fields.len()));
+                self.parts.push(id);
+            }
+            Event::ArrayEnd => {
+                self.end_slot();
+                let Some(Frame::Array { elements, .. }) = self.frames.pop() else {
+                    unreachable!("arrays end in an array");
+                };
+                let start = self.arena.children.len();
+                self.arena.children.extend(self.elements.drain(elements..));
+                let id = self
+                    .arena
+                    .push(Node::Array(start..self.arena.children.len()));
+                self.parts.push(id);
+            }
+            Event::Key { key, add_assign } => {
+                self.end_slot();
+                if let Some(Frame::Object { key: pending, .. }) = self.frames.last_mut() {
+                    *pending = Some((key, add_assign));
+                }
+            }
+            Event::Include(inclusion) => {
+                self.end_slot();
+                self.fields.push(ArenaField::Inclusion(inclusion));
+            }
+            Event::Scalar(value) => {
+                self.begin_value();
+                let id = self.arena.push(Node::Value(value));
+                self.parts.push(id);
+            }
+            Event::Concat(space) => self.spaces.push(space),
+        }
+    }
+
+    /// Ends the previous element of an array before a new one starts, unless the new
+    /// value is concatenated to it.
+    fn begin_value(&mut self) {
+        if let Some(frame @ Frame::Array { .. }) = self.frames.last() {
+            let (parts, spaces) = frame.slot();
+            if self.parts.len() - parts > self.spaces.len() - spaces {
+                self.end_slot();
+            }
+        }
+    }
+
+    /// Moves the value being parsed into the innermost container, as a field of an
+    /// object or an element of an array.
+    fn end_slot(&mut self) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let (parts, spaces) = frame.slot();
+        let value = match self.parts.len() - parts {
+            0 => return,
+            1 => self.parts.pop().unwrap(),
+            _ => {
+                let arena = &mut self.arena;
+                let parts_start = arena.children.len();
+                arena.children.extend(self.parts.drain(parts..));
+                let spaces_start = arena.spaces.len();
+                arena.spaces.extend(self.spaces.drain(spaces..));
+                arena.push(Node::Concat {
+                    parts: parts_start..arena.children.len(),
+                    spaces: spaces_start..arena.spaces.len(),
+                })
+            }
+        };
+        match frame {
+            Frame::Object { key, .. } => {
+                if let Some((key, add_assign)) = key.take() {
+                    self.fields.push(ArenaField::KeyValue {
+                        key,
+                        add_assign,
+                        value,
+                    });
+                }
+            }
+            Frame::Array { .. } => self.elements.push(value),
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> HoconParser<R> {
+    /// Parses the document into a [`RawArena`], which stores all of its values in a few
+    /// contiguous buffers and frees them at once, for documents with tens of thousands
+    /// of keys where allocating every object and array separately adds up.
+    ///
+    /// Like [`HoconParser::events`], which the arena is built from, comments are
+    /// skipped and included files are not read. [`RawArena::to_raw`] converts the
+    /// arena to the tree [`HoconParser::parse`] returns.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::parser::read::StrRead;
+    /// use hocon_rs::parser::{ArenaField, ArenaNode, HoconParser};
+    /// use hocon_rs::raw::raw_value::RawValue;
+    ///
+    /// let arena = HoconParser::new(StrRead::new("a = [1, 2]")).parse_arena().unwrap();
+    /// let ArenaNode::Object([ArenaField::KeyValue { key, value, .. }]) = arena.get(arena.root())
+    /// else {
+    ///     panic!("expected one field");
+    /// };
+    /// assert_eq!(key.to_string(), "a");
+    /// let ArenaNode::Array(elements) = arena.get(*value) else {
+    ///     panic!("expected an array");
+    /// };
+    /// assert_eq!(arena.get(elements[1]), ArenaNode::Value(&RawValue::number(2)));
+    /// ```
+    pub fn parse_arena(&mut self) -> Result<RawArena<'de>> {
+        let mut builder = Builder::default();
+        let mut events = self.events();
+        while let Some(event) = events.next_event()? {
+            builder.event(event);
+        }
+        Ok(builder.arena)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::ArenaNode;
+    use crate::Result;
+    use crate::parser::HoconParser;
+    use crate::parser::read::StrRead;
+
+    #[rstest]
+    #[case("a = 1, b = [true, null, \"x\"]")]
+    #[case("{ a { b += ${c} } }")]
+    #[case("a = ${x} ms, b = [1 2, [3] [4], { c = 5 }]")]
+    #[case("a = ${base} { b = 1 } { c = 2 }, d = [] []")]
+    #[case("a.b.\"c\" = x y  z\ninclude \"f.conf\"\ne = [{}, {f: [[]]}]")]
+    fn test_parse_arena(#[case] input: &str) -> Result<()> {
+        let arena = HoconParser::new(StrRead::new(input)).parse_arena()?;
+        let mut parser = HoconParser::new(StrRead::new(input));
+        parser.ctx.skip_includes = true;
+        let expected = parser.parse()?;
+        assert_eq!(arena.to_raw()?.to_string(), expected.to_string());
+        assert!(matches!(arena.get(arena.root()), ArenaNode::Object(_)));
+        Ok(())
+    }
+}
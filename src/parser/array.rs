@@ -1,11 +1,11 @@
 use crate::Result;
 use crate::error::Error;
 use crate::parser::HoconParser;
-use crate::parser::read::Read;
+use crate::parser::read::{Read, Span};
 use crate::raw::raw_array::RawArray;
 
 impl<'de, R: Read<'de>> HoconParser<R> {
-    pub(crate) fn parse_array(&mut self, verify_delimiter: bool) -> Result<RawArray> {
+    pub(crate) fn parse_array(&mut self, verify_delimiter: bool) -> Result<RawArray<'de>> {
         if verify_delimiter {
             let ch = self.reader.peek()?;
             if ch != b'[' {
@@ -15,8 +15,10 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 });
             }
         }
+        let start = self.reader.position();
         self.reader.discard(1)?;
         let mut values = vec![];
+        let mut spans = vec![];
         loop {
             self.drop_whitespace_and_comments()?;
             let ch = self.reader.peek()?;
@@ -24,14 +26,23 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 self.reader.discard(1)?;
                 break;
             }
-            let v = self.parse_value()?;
+            let start = self.reader.position();
+            let (v, end) = self.parse_value()?;
+            let max_len = self.options.max_array_len;
+            if values.len() == max_len {
+                return Err(Error::ArrayTooLong { max_len });
+            }
             values.push(v);
+            spans.push(Span { start, end });
             self.drop_whitespace_and_comments()?;
             if self.drop_comma_separator()? {
                 break;
             }
         }
-        Ok(RawArray::new(values))
+        let mut array = RawArray::new(values);
+        array.set_span(start, self.reader.position());
+        array.set_element_spans(spans);
+        Ok(array)
     }
 }
 
@@ -0,0 +1,559 @@
+//! Loading configurations inside async runtimes, behind the `tokio` feature.
+//!
+//! Files, URLs and readers are read with tokio, then parsed from memory with their include
+//! statements kept. The included sources are loaded the same way afterwards, so no step
+//! blocks on I/O. The include statements of a source are loaded concurrently, so slow URLs
+//! are fetched at the same time. Apart from that, loading works like in
+//! [`crate::parser::loader`].
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use futures_util::future::join_all;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::Result;
+use crate::classpath::ClasspathProvider;
+use crate::config_options::ConfigOptions;
+#[cfg(feature = "urls_includes")]
+use crate::config_options::UrlOptions;
+use crate::error::{Error, NotFound};
+use crate::origin::SourceId;
+use crate::parser::Context;
+use crate::parser::loader::{
+    self, candidate_files, classpath_providers, config_not_found, file_location, file_url_path,
+    include_paths, load_from_provider, parse_contents, parse_environments, parse_handler_contents,
+    parse_hocon, provider_candidates, searched_first,
+};
+use crate::parser::read::StreamRead;
+#[cfg(feature = "urls_includes")]
+use crate::parser::{
+    loader::{
+        check_response_len, parse_response, redirect_target, request_headers, too_many_redirects,
+    },
+    url_cache::{CachedRequest, CachedResponse},
+};
+use crate::raw::field::ObjectField;
+use crate::raw::include::{Inclusion, Location};
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_value::RawValue;
+use crate::url_handler::UrlHandler;
+
+fn context() -> Context {
+    Context {
+        skip_includes: true,
+        ..Default::default()
+    }
+}
+
+/// Reads `reader` to the end and parses it as HOCON, like
+/// [`crate::Config::parse_reader`].
+pub(crate) async fn parse_reader<R>(
+    mut reader: R,
+    options: &ConfigOptions,
+) -> Result<RawObject<'static>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut contents = vec![];
+    let limit = options.max_input_bytes.saturating_add(1) as u64;
+    (&mut reader).take(limit).read_to_end(&mut contents).await?;
+    let ctx = context();
+    let read = StreamRead::new(contents.as_slice());
+    let mut raw = parse_hocon(read, options.clone(), Some(ctx.clone()))?;
+    load_includes(&mut raw, options, &ctx).await?;
+    Ok(raw)
+}
+
+/// Fetches `url` and the sources it includes, like [`crate::Config::parse_url`].
+pub(crate) async fn parse_url(
+    url: url::Url,
+    options: &ConfigOptions,
+) -> Result<RawObject<'static>> {
+    load_from_url(url, options, &context()).await
+}
+
+/// Loads `path` from the file system or the classpath, see [`crate::parser::loader::load`].
+pub(crate) async fn load(path: &Path, options: &ConfigOptions) -> Result<RawObject<'static>> {
+    load_with_ctx(path, options, &context()).await
+}
+
+async fn load_with_ctx(
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let raw = if options.use_system_environment {
+        parse_environments(options)
+    } else {
+        RawObject::default()
+    };
+    let loaded = match load_from_path(path, options, ctx).await {
+        Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
+            let skipped = Error::Io(io)
+                .searched_locations()
+                .unwrap_or_default()
+                .to_vec();
+            searched_first(load_from_classpath(path, options, ctx).await, skipped)
+        }
+        result => result,
+    }?;
+    Ok(RawObject::merge(raw, loaded))
+}
+
+async fn load_from_path(
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let mut files = vec![];
+    for (candidate, syntax) in candidate_files(path, &options.extensions) {
+        let metadata = tokio::fs::metadata(&candidate).await;
+        if metadata.is_ok_and(|metadata| metadata.is_file()) {
+            files.push((candidate, syntax));
+        }
+    }
+    if files.is_empty() {
+        return Err(config_not_found(path, &options.extensions));
+    }
+    let cmp = &options.compare;
+    files.sort_by(|a, b| cmp(&a.1, &b.1));
+    let mut raw = RawObject::default();
+    for (file, syntax) in files {
+        let source = SourceId::new(&file.display().to_string());
+        let canonical = tokio::fs::canonicalize(&file).await.unwrap_or(file.clone());
+        let ctx = &Context {
+            source: Some(source.clone()),
+            ..ctx
+                .with_dir(file.parent())
+                .enter_source(&canonical.display().to_string())?
+        };
+        let location = file_location(&file, options);
+        let cached = ctx
+            .contents
+            .get_location(location.as_deref(), &source, &ctx.include_chain);
+        let mut raw_obj = match cached {
+            Some(raw_obj) => raw_obj,
+            None => {
+                let contents = tokio::fs::read(&file).await?;
+                parse_contents(&contents, location, source, syntax, options, ctx)?
+            }
+        };
+        load_includes(&mut raw_obj, options, ctx).await?;
+        raw = RawObject::merge(raw, raw_obj);
+    }
+    Ok(raw)
+}
+
+async fn load_from_classpath(
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    if !options.classpath.is_empty() && path.is_absolute() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Absolute path in classpath",
+        )));
+    }
+    let mut searched = vec![];
+    for provider in classpath_providers(options, ctx) {
+        let provider = provider?;
+        if let Some(dir) = provider.directory() {
+            match load_from_path(&dir.join(path), options, ctx).await {
+                Ok(raw) => return Ok(raw),
+                result @ Err(Error::Io(_)) => searched.extend_from_slice(loader::searched(&result)),
+                error => return error,
+            }
+            continue;
+        }
+        // Providers aren't `Send`, so they are read here rather than on a blocking thread;
+        // `ClasspathProvider` documents that they must not block.
+        let provider = provider.as_ref();
+        if let Some(mut raw) = load_from_provider(provider, path, options, Some(ctx.clone()))? {
+            let ctx = enter_provider(provider, path, options, ctx)?;
+            load_includes(&mut raw, options, &ctx).await?;
+            return Ok(raw);
+        }
+        searched.extend(provider_candidates(provider, path, options));
+    }
+    Err(NotFound { searched }.into())
+}
+
+/// Loads `url` like [`crate::parser::loader::load_from_url`], then loads the sources it
+/// includes.
+async fn load_from_url(
+    url: url::Url,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    if url.scheme() == "file" {
+        return load_from_path(&file_url_path(&url)?, options, ctx).await;
+    }
+    if let Some(handler) = options.url_handlers.get(url.scheme()) {
+        return load_from_handler(handler.as_ref(), &url, options, ctx).await;
+    }
+    #[cfg(feature = "urls_includes")]
+    return fetch_url(url, options, ctx).await;
+    #[cfg(not(feature = "urls_includes"))]
+    Err(Error::UrlsIncludesDisabled)
+}
+
+/// Fetches `url` without blocking like [`crate::parser::loader::load_from_url`], then
+/// loads the sources it includes.
+#[cfg(feature = "urls_includes")]
+async fn fetch_url(
+    url: url::Url,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let source = SourceId::new(url.as_str());
+    let ctx = &Context {
+        source: Some(source.clone()),
+        ..ctx.with_dir(None).enter_source(url.as_str())?
+    };
+    let location = options.cache_includes.then(|| url.to_string());
+    let cached = ctx
+        .contents
+        .get_location(location.as_deref(), &source, &ctx.include_chain);
+    if let Some(mut raw) = cached {
+        load_includes(&mut raw, options, ctx).await?;
+        return Ok(raw);
+    }
+    let url_options = &options.url_options;
+    let headers = request_headers(url_options, &url)?;
+    let client = ctx.url_clients.nonblocking(url_options)?;
+    let mut request = CachedRequest::new(url_options, &url, &headers);
+    let response = match send(client, &url, url_options, &request.validators()).await {
+        Ok(mut response) => match request.fallback(Some(response.status())) {
+            Some(cached) => cached,
+            None => {
+                let response_url = response.url().clone();
+                check_response_len(&response_url, response.content_length(), options, ctx)?;
+                let mut contents = vec![];
+                while let Some(chunk) = response.chunk().await.map_err(std::io::Error::other)? {
+                    contents.extend_from_slice(&chunk);
+                    let len = Some(contents.len() as u64);
+                    check_response_len(&response_url, len, options, ctx)?;
+                }
+                let fetched = CachedResponse::new(response_url, response.headers(), contents);
+                request.insert(response.status(), &fetched);
+                fetched
+            }
+        },
+        Err(error) => request
+            .fallback(None)
+            .ok_or_else(|| Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, error)))?,
+    };
+    let ctx = &Context {
+        source: Some(SourceId::new(response.url.as_str())),
+        ..ctx.clone()
+    };
+    let mut raw = parse_response(response, location, options, ctx)?;
+    load_includes(&mut raw, options, ctx).await?;
+    Ok(raw)
+}
+
+/// Requests `url` like [`crate::parser::loader::load_from_url`] does.
+#[cfg(feature = "urls_includes")]
+async fn send(
+    client: &reqwest::Client,
+    url: &url::Url,
+    options: &UrlOptions,
+    validators: &reqwest::header::HeaderMap,
+) -> std::io::Result<reqwest::Response> {
+    let mut url = url.clone();
+    for _ in 0..=options.max_redirects {
+        let response = client
+            .get(url.clone())
+            .headers(request_headers(options, &url)?)
+            .headers(validators.clone())
+            .send()
+            .await
+            .map_err(std::io::Error::other)?;
+        match redirect_target(&url, response.status(), response.headers()) {
+            Some(target) => url = target,
+            None => return Ok(response),
+        }
+    }
+    Err(too_many_redirects(&url, options))
+}
+
+/// Loads every include statement of `object` that isn't loaded yet, including the ones in
+/// nested objects. The includes are loaded concurrently, the first error in the order of
+/// the statements is returned.
+async fn load_includes(
+    object: &mut RawObject<'_>,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<()> {
+    let mut pending = vec![];
+    pending_inclusions(object, &mut pending);
+    let loads = pending
+        .into_iter()
+        .map(|inclusion| load_inclusion(inclusion, options, ctx));
+    join_all(loads).await.into_iter().collect()
+}
+
+/// Collects the include statements of `object` that aren't loaded yet, in order.
+fn pending_inclusions<'a, 'de>(
+    object: &'a mut RawObject<'de>,
+    pending: &mut Vec<&'a mut Inclusion<'de>>,
+) {
+    for field in object.iter_mut() {
+        match field {
+            ObjectField::Inclusion { inclusion, .. } if inclusion.val.is_none() => {
+                pending.push(inclusion);
+            }
+            ObjectField::KeyValue { value, .. } => pending_value_inclusions(value, pending),
+            _ => {}
+        }
+    }
+}
+
+fn pending_value_inclusions<'a, 'de>(
+    value: &'a mut RawValue<'de>,
+    pending: &mut Vec<&'a mut Inclusion<'de>>,
+) {
+    match value {
+        RawValue::Object(object) => pending_inclusions(object, pending),
+        RawValue::Array(array) => {
+            for value in array.iter_mut() {
+                pending_value_inclusions(value, pending);
+            }
+        }
+        RawValue::Concat(concat) => {
+            for value in concat.values_mut() {
+                pending_value_inclusions(value, pending);
+            }
+        }
+        RawValue::AddAssign(add_assign) => pending_value_inclusions(add_assign, pending),
+        _ => {}
+    }
+}
+
+/// The context for loading the includes of the classpath resources `provider` has for
+/// `path`, after [`load_from_provider`] parsed them.
+fn enter_provider(
+    provider: &dyn ClasspathProvider,
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<Context> {
+    let mut ctx = ctx.with_dir(None);
+    for name in provider_candidates(provider, path, options) {
+        ctx = ctx.enter_source(&name)?;
+    }
+    Ok(ctx)
+}
+
+/// Reads `url` with [`UrlHandler::read_async`] like
+/// [`crate::parser::loader::load_from_handler`], then loads the sources it includes.
+async fn load_from_handler(
+    handler: &dyn UrlHandler,
+    url: &url::Url,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let ctx = &Context {
+        source: Some(SourceId::new(url.as_str())),
+        ..ctx.with_dir(None).enter_source(url.as_str())?
+    };
+    let location = options.cache_includes.then(|| url.to_string());
+    let cached = ctx.contents.get_location(
+        location.as_deref(),
+        &SourceId::new(url.as_str()),
+        &ctx.include_chain,
+    );
+    let mut raw = match cached {
+        Some(raw) => raw,
+        None => {
+            let contents = handler.read_async(url).await?;
+            parse_handler_contents(contents, url, location, options, ctx)?
+        }
+    };
+    load_includes(&mut raw, options, ctx).await?;
+    Ok(raw)
+}
+
+/// Resolves the path of a file include like [`crate::parser::loader::include_path`].
+async fn include_path(
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> (PathBuf, Vec<String>) {
+    let mut paths = include_paths(path, options, ctx);
+    let last = paths.pop().expect("there is at least one path");
+    let mut skipped = vec![];
+    for path in paths {
+        let candidates = candidate_files(&path, &options.extensions);
+        for (candidate, _) in &candidates {
+            let metadata = tokio::fs::metadata(candidate).await;
+            if metadata.is_ok_and(|metadata| metadata.is_file()) {
+                return (path, skipped);
+            }
+        }
+        skipped.extend(
+            candidates
+                .iter()
+                .map(|(file, _)| file.display().to_string()),
+        );
+    }
+    (last, skipped)
+}
+
+/// Loads the source of `inclusion` like `HoconParser::parse_inclusion`.
+async fn load_inclusion(
+    inclusion: &mut Inclusion<'_>,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<()> {
+    let ctx = ctx.enter_include(inclusion, options)?;
+    let path = Path::new(inclusion.path.as_str());
+    let (file_path, skipped) = match inclusion.location {
+        None | Some(Location::File) => include_path(path, options, &ctx).await,
+        _ => (path.to_path_buf(), vec![]),
+    };
+    let result = match inclusion.location {
+        None | Some(Location::Url) => match url::Url::from_str(&inclusion.path) {
+            Ok(url) if url.scheme() == "file" => match file_url_path(&url) {
+                Ok(path) => Box::pin(load_from_path(&path, options, &ctx)).await,
+                Err(error) => Err(error),
+            },
+            Ok(url) => match options.url_handlers.get(url.scheme()) {
+                Some(handler) => {
+                    Box::pin(load_from_handler(handler.as_ref(), &url, options, &ctx)).await
+                }
+                #[cfg(feature = "urls_includes")]
+                None => Box::pin(fetch_url(url, options, &ctx)).await,
+                #[cfg(not(feature = "urls_includes"))]
+                None => return Err(Error::UrlsIncludesDisabled),
+            },
+            Err(_) => Box::pin(load_with_ctx(&file_path, options, &ctx)).await,
+        },
+        Some(Location::Classpath) => Box::pin(load_from_classpath(path, options, &ctx)).await,
+        Some(Location::File) => Box::pin(load_from_path(&file_path, options, &ctx)).await,
+    };
+    match searched_first(result, skipped) {
+        Ok(object) => inclusion.val = Some(object.into()),
+        Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound && !inclusion.required => {
+            let error = Error::Io(io);
+            inclusion.searched = error.searched_locations().unwrap_or_default().to_vec();
+        }
+        Err(error) => {
+            return Err(Error::Include {
+                inclusion: inclusion.to_string(),
+                error: Box::new(error),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::Result;
+    use crate::config::Config;
+    use crate::config_options::ConfigOptions;
+    use crate::error::Error;
+    use crate::value::Value;
+
+    #[rstest]
+    #[case("resources/include.conf")]
+    #[case("resources/add_assign_include.conf")]
+    #[case("resources/include_json.conf")]
+    #[tokio::test]
+    async fn test_load_async(#[case] path: &str) -> Result<()> {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let expected: Value = Config::load(path, Some(options.clone()))?;
+        let value: Value = Config::load_async(path, Some(options.clone())).await?;
+        assert_eq!(value, expected);
+        let reader = tokio::fs::File::open(path).await?;
+        let value: Value = Config::parse_async_reader(reader, Some(options)).await?;
+        assert_eq!(value, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_async_relative_includes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        std::fs::write(dir.path().join("main.conf"), "include \"sub/a\"\nb = 1")?;
+        std::fs::write(
+            dir.path().join("sub/a.conf"),
+            "include file(\"c.conf\")\na = 1",
+        )?;
+        std::fs::write(dir.path().join("sub/c.conf"), "c = 1")?;
+        let value: Value = Config::load_async(dir.path().join("main.conf"), None).await?;
+        assert_eq!(value, Config::parse_str("a = 1, b = 1, c = 1", None)?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_async_errors() {
+        let options = ConfigOptions::new(false, vec!["resources".to_string()]);
+        let result = Config::load_async::<Value>("resources/include_cycle.conf", Some(options));
+        let mut error = result.await.err().unwrap();
+        assert!(matches!(error, Error::Include { .. }));
+        while let Error::Include { error: inner, .. } = error {
+            error = *inner;
+        }
+        let Error::IncludeCycle { chain } = error else {
+            panic!("expected an include cycle, got {error}");
+        };
+        let root = std::path::Path::new("resources").join("include_cycle.conf");
+        let root = root.display().to_string();
+        assert_eq!(chain[0].source.as_ref().unwrap().to_string(), root);
+        assert_eq!(chain[2].path, "include_cycle.conf");
+        let reader = "include required(\"missing.conf\")".as_bytes();
+        let result = Config::parse_async_reader::<_, Value>(reader, None).await;
+        assert!(matches!(result, Err(Error::Include { .. })));
+    }
+
+    /// Serves `body` as `app.conf` to one request, after waiting for `delay`.
+    #[cfg(feature = "urls_includes")]
+    fn serve_once(body: String, delay: std::time::Duration) -> std::io::Result<url::Url> {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/app.conf", listener.local_addr()?);
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            while reader.read_line(&mut request).unwrap() > 2 {}
+            std::thread::sleep(delay);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        Ok(url::Url::parse(&url).unwrap())
+    }
+
+    #[cfg(feature = "urls_includes")]
+    #[tokio::test]
+    async fn test_parse_url_async() -> Result<()> {
+        use std::time::{Duration, Instant};
+
+        let delay = Duration::from_millis(500);
+        let a = serve_once("a = 1".to_string(), delay)?;
+        let b = serve_once("b = 3".to_string(), delay)?;
+        let body = format!("include \"{a}\"\nnested {{ include url(\"{b}\") }}\na = 2");
+        let url = serve_once(body, Duration::ZERO)?;
+        let start = Instant::now();
+        let value: Value = Config::parse_url_async(url.as_str(), None).await?;
+        // Both includes are fetched at the same time.
+        assert!(start.elapsed() < delay * 2);
+        let expected: Value = Config::parse_str("a = 2, nested.b = 3", None)?;
+        assert_eq!(value, expected);
+
+        let missing = serve_once("include required(\"missing.conf\")".to_string(), delay)?;
+        let result = Config::parse_url_async::<Value>(missing.as_str(), None).await;
+        assert!(matches!(result, Err(Error::Include { .. })));
+        Ok(())
+    }
+}
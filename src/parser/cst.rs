@@ -0,0 +1,901 @@
+//! A lossless concrete syntax tree, for tools like formatters and language servers.
+//!
+//! [`parse`] splits the input into tokens that cover every byte of it, whitespace and
+//! comments included, and groups them into nodes. Printing the tokens in order gives back
+//! the input, even if it is not valid HOCON: characters that can't start a token and
+//! unterminated strings become [`SyntaxKind::Error`] tokens, and tokens in places where they
+//! don't belong are wrapped in [`SyntaxKind::Error`] nodes.
+//!
+//! The tree of `a = [1, 2] // two` is:
+//!
+//! ```text
+//! Document
+//!   Object
+//!     Field
+//!       Key
+//!         Unquoted "a"
+//!       Whitespace " "
+//!       Equals "="
+//!       Whitespace " "
+//!       Value
+//!         Array
+//!           LeftBracket "["
+//!           Value
+//!             Unquoted "1"
+//!           Comma ","
+//!           Whitespace " "
+//!           Value
+//!             Unquoted "2"
+//!           RightBracket "]"
+//!     Whitespace " "
+//!     Comment "// two"
+//! ```
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::Result;
+use crate::config_options::ConfigOptions;
+use crate::parser::Context;
+use crate::parser::loader::parse_hocon;
+use crate::parser::read::{CopyRead, Position, Span, leading_whitespace_bytes};
+use crate::raw::raw_object::RawObject;
+
+/// The kind of a [`SyntaxToken`] or a [`SyntaxNode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SyntaxKind {
+    /// Horizontal whitespace.
+    Whitespace,
+    /// `\n` or `\r\n`.
+    Newline,
+    /// A `#` or `//` comment, without the line break.
+    Comment,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Colon,
+    Equals,
+    PlusEquals,
+    /// A `"..."` string, quotes included.
+    QuotedString,
+    /// A `"""..."""` string, quotes included.
+    MultilineString,
+    /// A run of characters that aren't whitespace or special, e.g. `a.b`, `10` or `include`.
+    Unquoted,
+    /// A `${...}` or `${?...}` substitution.
+    Substitution,
+    /// Text that isn't a valid token, or tokens in a place where they don't belong.
+    Error,
+    /// The root node.
+    Document,
+    /// An object, with or without braces.
+    Object,
+    /// A key-value field of an object.
+    Field,
+    /// An `include` statement.
+    Include,
+    /// The path of a field, e.g. `a.b` or `"a" . b`.
+    Key,
+    /// A value, or the parts of a concatenation like `${a} ms`.
+    Value,
+    Array,
+}
+
+impl SyntaxKind {
+    /// Returns `true` for whitespace, line breaks and comments.
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace | SyntaxKind::Newline | SyntaxKind::Comment
+        )
+    }
+}
+
+/// A token of the input, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxToken {
+    kind: SyntaxKind,
+    text: String,
+    range: Range<usize>,
+    span: Span,
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// Returns the text of the token as written.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the byte range of the token in the input.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the lines and columns of the token, columns count bytes like the positions
+    /// of the parser.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A child of a [`SyntaxNode`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind,
+            SyntaxElement::Token(token) => token.kind,
+        }
+    }
+
+    /// Returns a copy moved by `bytes` and `lines`, for elements that start at column 0.
+    fn moved(&self, bytes: isize, lines: isize) -> SyntaxElement {
+        match self {
+            SyntaxElement::Node(node) => SyntaxElement::Node(SyntaxNode::new(
+                node.kind,
+                node.children
+                    .iter()
+                    .map(|c| c.moved(bytes, lines))
+                    .collect(),
+            )),
+            SyntaxElement::Token(token) => {
+                let mut token = token.clone();
+                token.range = token.range.start.saturating_add_signed(bytes)
+                    ..token.range.end.saturating_add_signed(bytes);
+                token.span.start.line = token.span.start.line.saturating_add_signed(lines);
+                token.span.end.line = token.span.end.line.saturating_add_signed(lines);
+                SyntaxElement::Token(token)
+            }
+        }
+    }
+}
+
+/// A node of the tree, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> SyntaxNode {
+        SyntaxNode { kind, children }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// Returns the nodes and tokens directly below this node, in input order.
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    /// Returns the nodes directly below this node.
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Returns all tokens below this node, in input order.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = vec![];
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// Returns the text covered by this node. For the [`SyntaxKind::Document`] this is the
+    /// whole input.
+    pub fn text(&self) -> String {
+        self.tokens().into_iter().map(SyntaxToken::text).collect()
+    }
+
+    /// Returns the byte range covered by this node, `None` if it has no tokens.
+    pub fn range(&self) -> Option<Range<usize>> {
+        let tokens = self.tokens();
+        Some(tokens.first()?.range.start..tokens.last()?.range.end)
+    }
+
+    /// Returns the lines and columns covered by this node, `None` if it has no tokens.
+    pub fn span(&self) -> Option<Span> {
+        let tokens = self.tokens();
+        Some(Span {
+            start: tokens.first()?.span.start,
+            end: tokens.last()?.span.end,
+        })
+    }
+
+    /// Returns the token at the byte `offset`, and the nodes leading to it from this node,
+    /// outermost first. An offset between two tokens selects the one after it.
+    pub fn token_at(&self, offset: usize) -> Option<(Vec<&SyntaxNode>, &SyntaxToken)> {
+        let mut ancestors = vec![self];
+        let mut node = self;
+        loop {
+            let child = node.children.iter().find(|child| match child {
+                SyntaxElement::Node(node) => node.range().is_some_and(|r| r.contains(&offset)),
+                SyntaxElement::Token(token) => token.range.contains(&offset),
+            })?;
+            match child {
+                SyntaxElement::Node(child) => {
+                    ancestors.push(child);
+                    node = child;
+                }
+                SyntaxElement::Token(token) => return Some((ancestors, token)),
+            }
+        }
+    }
+
+    /// Parses the text of this [`SyntaxKind::Document`] into a [`RawObject`], like
+    /// [`crate::Config::parse_str_raw`] but without loading included files.
+    ///
+    /// # Errors
+    /// Fails if the text is not valid HOCON.
+    pub fn to_raw(&self) -> Result<RawObject<'static>> {
+        let ctx = Context {
+            skip_includes: true,
+            ..Default::default()
+        };
+        let text = self.text();
+        parse_hocon(
+            CopyRead::new(text.as_bytes()),
+            ConfigOptions::default(),
+            Some(ctx),
+        )
+    }
+
+    /// Returns the tree of the text after replacing its bytes in `range` with `replacement`,
+    /// the same as [`parse`] would, for editors that update the tree on every keystroke.
+    ///
+    /// Only the lines of the root object around the edit are parsed again: the nodes before
+    /// them are reused and the nodes after them are only moved. The whole text is parsed
+    /// again if this isn't a [`SyntaxKind::Document`] with a root object without braces, if
+    /// the edit touches its first line, or if it changes where the fields of the root object
+    /// end, e.g. by opening a string or an object.
+    ///
+    /// # Panics
+    /// If `range` is out of bounds or not on char boundaries, like [`String::replace_range`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::parser::cst::parse;
+    ///
+    /// let document = parse("a = 1\nb = 2\nc = 3\n");
+    /// let edited = document.reparse(10..11, "20");
+    /// assert_eq!(edited, parse("a = 1\nb = 20\nc = 3\n"));
+    /// ```
+    pub fn reparse(&self, range: Range<usize>, replacement: &str) -> SyntaxNode {
+        let mut text = self.text();
+        text.replace_range(range.clone(), replacement);
+        self.reparse_lines(&text, range, replacement.len())
+            .unwrap_or_else(|| parse(&text))
+    }
+
+    /// Parses the top level lines of `text` around the edit of `range` again, starting
+    /// after the last line break of the root object before the edit and ending after the
+    /// first one after it, if any. These line breaks always leave the lexer and the parser
+    /// in the same state, so the rest of the tree doesn't change.
+    fn reparse_lines(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        inserted: usize,
+    ) -> Option<SyntaxNode> {
+        fn newline(element: &SyntaxElement) -> Option<&SyntaxToken> {
+            match element {
+                SyntaxElement::Token(token) if token.kind == SyntaxKind::Newline => Some(token),
+                _ => None,
+            }
+        }
+        if self.kind != SyntaxKind::Document {
+            return None;
+        }
+        let index = self
+            .children
+            .iter()
+            .position(|c| c.kind() == SyntaxKind::Object)?;
+        let SyntaxElement::Node(object) = &self.children[index] else {
+            return None;
+        };
+        let object_range = object.range()?;
+        let braced = object.children.first()?.kind() == SyntaxKind::LeftBrace;
+        if braced || range.start < object_range.start || range.end > object_range.end {
+            return None;
+        }
+        let start = object
+            .children
+            .iter()
+            .rposition(|c| newline(c).is_some_and(|t| t.range.end <= range.start))?;
+        let before = newline(&object.children[start])?;
+        let lexer = Lexer::at(text, before.range.end, before.span.end);
+        let mut children = self.children[..index].to_vec();
+        let mut object_children = object.children[..=start].to_vec();
+        let end = object
+            .children
+            .iter()
+            .position(|c| newline(c).is_some_and(|t| t.range.start >= range.end));
+        let Some(end) = end else {
+            let builder = TreeBuilder {
+                tokens: lexer.tokenize(),
+                next: 0,
+            };
+            return Some(builder.finish_document(children, object_children, false));
+        };
+        let after = newline(&object.children[end])?;
+        let delta = inserted as isize - range.len() as isize;
+        let lexer = Lexer {
+            input: &text[..after.range.end.checked_add_signed(delta)?],
+            ..lexer
+        };
+        let mut builder = TreeBuilder {
+            tokens: lexer.tokenize(),
+            next: 0,
+        };
+        builder.object_body(&mut object_children, false);
+        let last = object_children.last().and_then(newline)?;
+        if builder.next < builder.tokens.len() || last.range.end != builder.tokens.last()?.range.end
+        {
+            return None;
+        }
+        // The following lines start at column 0, so only their lines change.
+        let lines = last.span.end.line as isize - after.span.end.line as isize;
+        let moved = object.children[end + 1..].iter();
+        object_children.extend(moved.map(|child| child.moved(delta, lines)));
+        children.push(SyntaxElement::Node(SyntaxNode::new(
+            SyntaxKind::Object,
+            object_children,
+        )));
+        let moved = self.children[index + 1..].iter();
+        children.extend(moved.map(|child| child.moved(delta, lines)));
+        Some(SyntaxNode::new(SyntaxKind::Document, children))
+    }
+
+    fn fmt_tree(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        writeln!(f, "{:indent$}{:?}", "", self.kind, indent = depth * 2)?;
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.fmt_tree(f, depth + 1)?,
+                SyntaxElement::Token(token) => writeln!(
+                    f,
+                    "{:indent$}{:?} {:?}",
+                    "",
+                    token.kind,
+                    token.text,
+                    indent = (depth + 1) * 2
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for SyntaxNode {
+    /// Writes the tree with one node or token per line, as in the module documentation.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+/// Parses `input` into a lossless [`SyntaxKind::Document`] node. Never fails, invalid input
+/// results in [`SyntaxKind::Error`] tokens and nodes.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::parser::cst::{SyntaxKind, parse};
+///
+/// let input = "# server\nserver { port = 80 }, bad = ^\n";
+/// let document = parse(input);
+/// assert_eq!(document.text(), input);
+/// let (ancestors, token) = document.token_at(input.find("80").unwrap()).unwrap();
+/// assert_eq!(token.text(), "80");
+/// let kinds = ancestors.iter().map(|node| node.kind()).collect::<Vec<_>>();
+/// assert_eq!(kinds.last(), Some(&SyntaxKind::Value));
+/// assert!(document.tokens().iter().any(|token| token.kind() == SyntaxKind::Error));
+/// assert!(document.to_raw().is_err());
+/// ```
+pub fn parse(input: &str) -> SyntaxNode {
+    let tokens = Lexer::new(input).tokenize();
+    TreeBuilder { tokens, next: 0 }.document()
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    offset: usize,
+    position: Position,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Lexer::at(input, 0, Position::new(1, 0))
+    }
+
+    /// Creates a lexer that starts at the byte `offset` of `input`, which is at `position`.
+    fn at(input: &'a str, offset: usize, position: Position) -> Self {
+        Lexer {
+            input,
+            offset,
+            position,
+        }
+    }
+
+    fn tokenize(mut self) -> Vec<SyntaxToken> {
+        let mut tokens = vec![];
+        while self.offset < self.input.len() {
+            let (kind, len) = self.next_token(&self.input[self.offset..]);
+            let range = self.offset..self.offset + len;
+            let start = self.position;
+            for byte in self.input[range.clone()].bytes() {
+                if byte == b'\n' {
+                    self.position = Position::new(self.position.line + 1, 0);
+                } else {
+                    self.position.column += 1;
+                }
+            }
+            self.offset = range.end;
+            tokens.push(SyntaxToken {
+                kind,
+                text: self.input[range.clone()].to_string(),
+                range,
+                span: Span {
+                    start,
+                    end: self.position,
+                },
+            });
+        }
+        tokens
+    }
+
+    fn next_token(&self, rest: &str) -> (SyntaxKind, usize) {
+        let punctuation = match rest.as_bytes()[0] {
+            b'{' => Some(SyntaxKind::LeftBrace),
+            b'}' => Some(SyntaxKind::RightBrace),
+            b'[' => Some(SyntaxKind::LeftBracket),
+            b']' => Some(SyntaxKind::RightBracket),
+            b',' => Some(SyntaxKind::Comma),
+            b':' => Some(SyntaxKind::Colon),
+            b'=' => Some(SyntaxKind::Equals),
+            b'\n' => Some(SyntaxKind::Newline),
+            _ => None,
+        };
+        if let Some(kind) = punctuation {
+            return (kind, 1);
+        }
+        if rest.starts_with("\r\n") {
+            (SyntaxKind::Newline, 2)
+        } else if rest.starts_with("+=") {
+            (SyntaxKind::PlusEquals, 2)
+        } else if rest.starts_with('#') || rest.starts_with("//") {
+            (SyntaxKind::Comment, line_len(rest))
+        } else if rest.starts_with("\"\"\"") {
+            multiline_string(rest)
+        } else if rest.starts_with('"') {
+            quoted_string(rest)
+        } else if rest.starts_with("${") {
+            substitution(rest)
+        } else if let len @ 1.. = whitespace_len(rest) {
+            (SyntaxKind::Whitespace, len)
+        } else if let len @ 1.. = unquoted_len(rest) {
+            (SyntaxKind::Unquoted, len)
+        } else {
+            let len = rest.chars().next().map_or(1, char::len_utf8);
+            (SyntaxKind::Error, len)
+        }
+    }
+}
+
+/// Returns the length of `rest` up to the line break.
+fn line_len(rest: &str) -> usize {
+    let end = rest.find('\n').unwrap_or(rest.len());
+    rest[..end].strip_suffix('\r').map_or(end, str::len)
+}
+
+fn whitespace_len(rest: &str) -> usize {
+    let mut len = 0;
+    while !rest[len..].starts_with('\n') && !rest[len..].starts_with("\r\n") {
+        match leading_whitespace_bytes(&rest.as_bytes()[len..]) {
+            0 => break,
+            n => len += n,
+        }
+    }
+    len
+}
+
+fn unquoted_len(rest: &str) -> usize {
+    let mut len = 0;
+    for ch in rest.chars() {
+        let forbidden = "$\"{}[]:=,+#`^?!@*&\\".contains(ch)
+            || rest[len..].starts_with("//")
+            || leading_whitespace_bytes(&rest.as_bytes()[len..]) > 0;
+        if forbidden {
+            break;
+        }
+        len += ch.len_utf8();
+    }
+    len
+}
+
+fn quoted_string(rest: &str) -> (SyntaxKind, usize) {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '"' => return (SyntaxKind::QuotedString, index + 1),
+            '\\' => {
+                chars.next();
+            }
+            '\n' => break,
+            _ => {}
+        }
+    }
+    (SyntaxKind::Error, line_len(rest))
+}
+
+fn multiline_string(rest: &str) -> (SyntaxKind, usize) {
+    match rest[3..].find("\"\"\"") {
+        Some(end) => {
+            // The string ends with the last quote of a run, e.g. `""""` ends with `"`.
+            let end = 3 + end + 3;
+            let extra = rest[end..].bytes().take_while(|&byte| byte == b'"').count();
+            (SyntaxKind::MultilineString, end + extra)
+        }
+        None => (SyntaxKind::Error, rest.len()),
+    }
+}
+
+fn substitution(rest: &str) -> (SyntaxKind, usize) {
+    let mut index = 2;
+    while index < rest.len() {
+        match rest.as_bytes()[index] {
+            b'}' => return (SyntaxKind::Substitution, index + 1),
+            b'"' => match quoted_string(&rest[index..]) {
+                (SyntaxKind::QuotedString, len) => index += len,
+                _ => break,
+            },
+            b'\n' => break,
+            _ => index += 1,
+        }
+    }
+    (SyntaxKind::Error, line_len(rest))
+}
+
+struct TreeBuilder {
+    tokens: Vec<SyntaxToken>,
+    next: usize,
+}
+
+impl TreeBuilder {
+    fn peek(&self) -> Option<SyntaxKind> {
+        self.tokens.get(self.next).map(|token| token.kind)
+    }
+
+    fn bump(&mut self, children: &mut Vec<SyntaxElement>) {
+        children.push(SyntaxElement::Token(self.tokens[self.next].clone()));
+        self.next += 1;
+    }
+
+    fn trivia(&mut self, children: &mut Vec<SyntaxElement>, newlines: bool) {
+        while let Some(kind) = self.peek() {
+            match kind {
+                SyntaxKind::Whitespace | SyntaxKind::Comment => {}
+                SyntaxKind::Newline if newlines => {}
+                _ => break,
+            }
+            self.bump(children);
+        }
+    }
+
+    /// Moves the whitespace at the end of `children` to `parent`, so nodes end with their
+    /// last significant token.
+    fn finish(kind: SyntaxKind, mut children: Vec<SyntaxElement>, parent: &mut Vec<SyntaxElement>) {
+        let significant = children
+            .iter()
+            .rposition(|child| child.kind() != SyntaxKind::Whitespace)
+            .map_or(0, |index| index + 1);
+        let trailing = children.split_off(significant);
+        parent.push(SyntaxElement::Node(SyntaxNode::new(kind, children)));
+        parent.extend(trailing);
+    }
+
+    fn document(mut self) -> SyntaxNode {
+        let mut children = vec![];
+        self.trivia(&mut children, true);
+        let mut object = vec![];
+        let braced = self.peek() == Some(SyntaxKind::LeftBrace);
+        if braced {
+            self.bump(&mut object);
+        }
+        self.finish_document(children, object, braced)
+    }
+
+    /// Parses the rest of the root object, whose `children` and first `object` children
+    /// are already built, and whatever follows it.
+    fn finish_document(
+        mut self,
+        mut children: Vec<SyntaxElement>,
+        mut object: Vec<SyntaxElement>,
+        braced: bool,
+    ) -> SyntaxNode {
+        self.object_body(&mut object, braced);
+        Self::finish(SyntaxKind::Object, object, &mut children);
+        self.trivia(&mut children, true);
+        if self.next < self.tokens.len() {
+            let mut rest = vec![];
+            while self.next < self.tokens.len() {
+                self.bump(&mut rest);
+            }
+            children.push(SyntaxElement::Node(SyntaxNode::new(
+                SyntaxKind::Error,
+                rest,
+            )));
+        }
+        SyntaxNode::new(SyntaxKind::Document, children)
+    }
+
+    fn object_body(&mut self, children: &mut Vec<SyntaxElement>, braced: bool) {
+        loop {
+            self.trivia(children, true);
+            match self.peek() {
+                None => break,
+                Some(SyntaxKind::Comma) => self.bump(children),
+                Some(SyntaxKind::RightBrace) => {
+                    if braced {
+                        self.bump(children);
+                    }
+                    break;
+                }
+                Some(SyntaxKind::Unquoted) if self.at_include() => self.include(children),
+                Some(SyntaxKind::Unquoted | SyntaxKind::QuotedString) => self.field(children),
+                Some(_) => {
+                    let mut error = vec![];
+                    self.bump(&mut error);
+                    children.push(SyntaxElement::Node(SyntaxNode::new(
+                        SyntaxKind::Error,
+                        error,
+                    )));
+                }
+            }
+        }
+    }
+
+    fn at_include(&self) -> bool {
+        let token = &self.tokens[self.next];
+        let next = self.tokens.get(self.next + 1).map(|token| token.kind);
+        token.text == "include"
+            && matches!(
+                next,
+                Some(SyntaxKind::Whitespace | SyntaxKind::QuotedString)
+            )
+            && !matches!(
+                self.tokens[self.next + 1..]
+                    .iter()
+                    .map(|token| token.kind)
+                    .find(|&kind| kind != SyntaxKind::Whitespace),
+                Some(
+                    SyntaxKind::Colon
+                        | SyntaxKind::Equals
+                        | SyntaxKind::PlusEquals
+                        | SyntaxKind::LeftBrace
+                )
+            )
+    }
+
+    fn include(&mut self, parent: &mut Vec<SyntaxElement>) {
+        let mut children = vec![];
+        while let Some(kind) = self.peek() {
+            if matches!(
+                kind,
+                SyntaxKind::Newline
+                    | SyntaxKind::Comma
+                    | SyntaxKind::Comment
+                    | SyntaxKind::RightBrace
+            ) {
+                break;
+            }
+            self.bump(&mut children);
+        }
+        Self::finish(SyntaxKind::Include, children, parent);
+    }
+
+    fn field(&mut self, parent: &mut Vec<SyntaxElement>) {
+        let mut children = vec![];
+        let mut key = vec![];
+        while let Some(SyntaxKind::Unquoted | SyntaxKind::QuotedString | SyntaxKind::Whitespace) =
+            self.peek()
+        {
+            self.bump(&mut key);
+        }
+        Self::finish(SyntaxKind::Key, key, &mut children);
+        match self.peek() {
+            Some(SyntaxKind::Colon | SyntaxKind::Equals | SyntaxKind::PlusEquals) => {
+                self.bump(&mut children);
+                self.trivia(&mut children, true);
+                self.value(&mut children);
+            }
+            Some(SyntaxKind::LeftBrace) => self.value(&mut children),
+            _ => {}
+        }
+        Self::finish(SyntaxKind::Field, children, parent);
+    }
+
+    fn value(&mut self, parent: &mut Vec<SyntaxElement>) {
+        let mut children = vec![];
+        while let Some(kind) = self.peek() {
+            match kind {
+                SyntaxKind::LeftBrace => {
+                    let mut object = vec![];
+                    self.bump(&mut object);
+                    self.object_body(&mut object, true);
+                    children.push(SyntaxElement::Node(SyntaxNode::new(
+                        SyntaxKind::Object,
+                        object,
+                    )));
+                }
+                SyntaxKind::LeftBracket => self.array(&mut children),
+                SyntaxKind::Newline
+                | SyntaxKind::Comma
+                | SyntaxKind::Comment
+                | SyntaxKind::RightBrace
+                | SyntaxKind::RightBracket => break,
+                _ => self.bump(&mut children),
+            }
+        }
+        if !children.is_empty() {
+            Self::finish(SyntaxKind::Value, children, parent);
+        }
+    }
+
+    fn array(&mut self, parent: &mut Vec<SyntaxElement>) {
+        let mut children = vec![];
+        self.bump(&mut children);
+        loop {
+            self.trivia(&mut children, true);
+            match self.peek() {
+                None => break,
+                Some(SyntaxKind::Comma) => self.bump(&mut children),
+                Some(SyntaxKind::RightBracket) => {
+                    self.bump(&mut children);
+                    break;
+                }
+                Some(SyntaxKind::RightBrace) => {
+                    let mut error = vec![];
+                    self.bump(&mut error);
+                    children.push(SyntaxElement::Node(SyntaxNode::new(
+                        SyntaxKind::Error,
+                        error,
+                    )));
+                }
+                Some(_) => self.value(&mut children),
+            }
+        }
+        parent.push(SyntaxElement::Node(SyntaxNode::new(
+            SyntaxKind::Array,
+            children,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{SyntaxKind, parse};
+    use crate::Result;
+    use crate::config::Config;
+
+    #[test]
+    fn test_cst_tree() {
+        let expected = include_str!("cst.rs")
+            .split("//! ```text\n")
+            .nth(1)
+            .unwrap()
+            .split("//! ```")
+            .next()
+            .unwrap()
+            .lines()
+            .map(|line| format!("{}\n", line.trim_start_matches("//! ")))
+            .collect::<String>();
+        assert_eq!(parse("a = [1, 2] // two").to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("resources/base.conf")]
+    #[case("resources/concat.conf")]
+    #[case("resources/deserialize.conf")]
+    #[case("resources/include.conf")]
+    #[case("resources/comment.conf")]
+    fn test_cst_lossless(#[case] path: &str) -> Result<()> {
+        let input = std::fs::read_to_string(path)?;
+        let document = parse(&input);
+        assert_eq!(document.text(), input);
+        assert!(
+            document
+                .tokens()
+                .iter()
+                .all(|t| t.kind() != SyntaxKind::Error),
+            "{document}"
+        );
+        let ends = document.tokens().windows(2).all(|pair| {
+            pair[0].range().end == pair[1].range().start
+                && pair[0].span().end == pair[1].span().start
+        });
+        assert!(ends);
+        let expected = Config::parse_str_raw(&input, None);
+        if let Ok(expected) = expected {
+            let raw = document.to_raw()?;
+            assert_eq!(raw.len(), expected.len());
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("a = \"open\nb = 1")]
+    #[case("a = ${x\n}")]
+    #[case("} a = [1, }, 2")]
+    #[case("a = \"\"\"never closed")]
+    #[case("{ a = 1 } b")]
+    #[case("a @ b = ^\u{a0}\r\n")]
+    fn test_cst_invalid(#[case] input: &str) {
+        let document = parse(input);
+        assert_eq!(document.text(), input);
+        assert!(document.to_string().contains("Error"), "{document}");
+        assert!(document.to_raw().is_err());
+    }
+
+    #[test]
+    fn test_cst_include() {
+        let document = parse("include required(file(\"a.conf\")) # x\ninclude = 1");
+        let object = document.child_nodes().next().unwrap();
+        let kinds = object.child_nodes().map(|n| n.kind()).collect::<Vec<_>>();
+        assert_eq!(kinds, [SyntaxKind::Include, SyntaxKind::Field]);
+        assert_eq!(
+            object.child_nodes().next().unwrap().text(),
+            "include required(file(\"a.conf\"))"
+        );
+    }
+
+    #[rstest]
+    #[case("resources/base.conf")]
+    #[case("resources/concat.conf")]
+    #[case("resources/comment.conf")]
+    fn test_cst_reparse(#[case] path: &str) -> Result<()> {
+        let input = std::fs::read_to_string(path)?;
+        let document = parse(&input);
+        let edits = ["", "x", "\n", "\"", "\"\"\"", "{", "}", "[", "#"];
+        for start in (0..=input.len()).filter(|&i| input.is_char_boundary(i)) {
+            let end = input[start..]
+                .chars()
+                .next()
+                .map_or(start, |c| start + c.len_utf8());
+            for replacement in edits {
+                for range in [start..start, start..end] {
+                    let mut text = input.clone();
+                    text.replace_range(range.clone(), replacement);
+                    let reparsed = document.reparse(range.clone(), replacement);
+                    assert_eq!(reparsed, parse(&text), "{range:?} {replacement:?}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+use crate::Result;
+use crate::error::Error;
+use crate::parser::HoconParser;
+use crate::parser::include::INCLUDE;
+use crate::parser::read::{Position, Read};
+use crate::parser::string::TRIPLE_DOUBLE_QUOTE;
+use crate::raw::include::Inclusion;
+use crate::raw::raw_string::RawString;
+use crate::raw::raw_value::RawValue;
+use crate::raw::space::Space;
+
+/// A syntactic event of the pull parser, see [`HoconParser::events`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Event<'de> {
+    /// The start of an object. The document itself is an object, with or without braces.
+    ObjectStart,
+    /// The end of the innermost object.
+    ObjectEnd,
+    /// The start of an array.
+    ArrayStart,
+    /// The end of the innermost array.
+    ArrayEnd,
+    /// The key of a field of the innermost object, followed by the events of its value.
+    /// `add_assign` is set for `key += value`.
+    Key {
+        key: RawString<'de>,
+        add_assign: bool,
+    },
+    /// An `include` directive in the innermost object. The included file is not read.
+    Include(Inclusion<'de>),
+    /// A string, number, boolean, null or substitution.
+    Scalar(RawValue<'de>),
+    /// Concatenates the values before and after it, as in `a = ${x} ms` or
+    /// `a = ${base} { b = 1 }`. Holds the whitespace between them, which is part of the
+    /// value when strings are concatenated.
+    Concat(Option<Space>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the document.
+    Start,
+    /// In an object, before a field or the end of the object.
+    Field,
+    /// In an array, before an element or the end of the array.
+    Element,
+    /// Before a part of a value, `first` is set for the first part.
+    Value {
+        first: bool,
+    },
+    /// After an object or array that is part of a value.
+    AfterContainer,
+    /// After the document.
+    End,
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object { braces: bool },
+    Array,
+}
+
+/// The events of a document, parsed one at a time, see [`HoconParser::events`].
+pub struct Events<'a, 'de, R> {
+    parser: &'a mut HoconParser<R>,
+    stack: Vec<Container>,
+    state: State,
+    pending: Option<Event<'de>>,
+}
+
+impl<'de, R: Read<'de>> HoconParser<R> {
+    /// Returns a pull parser that reports the document as a stream of [`Event`]s instead
+    /// of building a [`crate::raw::raw_object::RawObject`], so huge inputs can be processed
+    /// or transcoded in constant memory.
+    ///
+    /// The events follow the syntax as written: path expressions are not expanded,
+    /// substitutions are not resolved, included files are not read and comments are
+    /// skipped. A value that consists of a single unquoted token is reported as the
+    /// number, boolean or null it spells, like [`HoconParser::parse`] does.
+    ///
+    /// Errors are reported like in [`HoconParser::parse`], and end the stream.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::parser::read::StrRead;
+    /// use hocon_rs::parser::{Event, HoconParser};
+    /// use hocon_rs::raw::raw_value::RawValue;
+    ///
+    /// let mut parser = HoconParser::new(StrRead::new("a = [1, true]"));
+    /// let events = parser.events().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(events.len(), 7);
+    /// assert!(matches!(&events[1], Event::Key { key, .. } if key.to_string() == "a"));
+    /// assert_eq!(events[3], Event::Scalar(RawValue::number(1)));
+    /// assert_eq!(events[4], Event::Scalar(RawValue::Boolean(true)));
+    /// assert_eq!(events[6], Event::ObjectEnd);
+    /// ```
+    pub fn events(&mut self) -> Events<'_, 'de, R> {
+        Events {
+            parser: self,
+            stack: vec![],
+            state: State::Start,
+            pending: None,
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> Events<'_, 'de, R> {
+    /// Returns the next event, or `None` at the end of the document.
+    pub fn next_event(&mut self) -> Result<Option<Event<'de>>> {
+        if let Some(event) = self.pending.take() {
+            return Ok(Some(event));
+        }
+        self.step().map_err(|error| {
+            self.state = State::Done;
+            self.parser.syntax_error(error)
+        })
+    }
+
+    /// Returns the position of the reader, right after the last event.
+    pub fn position(&self) -> Position {
+        self.parser.reader.position()
+    }
+
+    fn step(&mut self) -> Result<Option<Event<'de>>> {
+        loop {
+            match self.state {
+                State::Start => {
+                    self.parser.drop_whitespace_and_comments()?;
+                    let braces = self.peek()? == Some(b'{');
+                    if braces {
+                        self.parser.reader.discard(1)?;
+                    }
+                    self.stack.push(Container::Object { braces });
+                    self.state = State::Field;
+                    return Ok(Some(Event::ObjectStart));
+                }
+                State::Field => {
+                    self.parser.drop_whitespace_and_comments()?;
+                    match self.peek()? {
+                        ch @ (None | Some(b'}')) => return self.close_object(ch),
+                        Some(b'i')
+                            if self
+                                .parser
+                                .reader
+                                .peek_n(INCLUDE.len())
+                                .is_ok_and(|bytes| bytes == INCLUDE) =>
+                        {
+                            let inclusion = self.parser.parse_include()?;
+                            self.value_done()?;
+                            return Ok(Some(Event::Include(inclusion)));
+                        }
+                        Some(_) => {
+                            let key = self.parser.parse_key()?;
+                            self.parser.drop_whitespace()?;
+                            let add_assign = self.parser.drop_kv_separator()?;
+                            self.state = State::Value { first: true };
+                            return Ok(Some(Event::Key { key, add_assign }));
+                        }
+                    }
+                }
+                State::Element => {
+                    self.parser.drop_whitespace_and_comments()?;
+                    match self.peek()? {
+                        Some(b']') => {
+                            self.parser.reader.discard(1)?;
+                            self.stack.pop();
+                            self.state = State::AfterContainer;
+                            return Ok(Some(Event::ArrayEnd));
+                        }
+                        Some(_) => self.state = State::Value { first: true },
+                        None => return Err(Error::Eof),
+                    }
+                }
+                State::Value { first } => {
+                    if first {
+                        self.parser.drop_whitespace()?;
+                    }
+                    return match self.peek()? {
+                        Some(b'{') => {
+                            self.push(Container::Object { braces: true })?;
+                            self.state = State::Field;
+                            Ok(Some(Event::ObjectStart))
+                        }
+                        Some(b'[') => {
+                            self.push(Container::Array)?;
+                            self.state = State::Element;
+                            Ok(Some(Event::ArrayStart))
+                        }
+                        ch if self.at_value_end()? => Err(Error::UnexpectedToken {
+                            expected: "a valid value",
+                            found_beginning: ch.unwrap_or(b'\0'),
+                        }),
+                        _ => self.parse_scalar(first).map(Some),
+                    };
+                }
+                State::AfterContainer => {
+                    if self.stack.is_empty() {
+                        self.state = State::End;
+                        continue;
+                    }
+                    if let Some(space) = self.concat_space()? {
+                        self.state = State::Value { first: false };
+                        return Ok(Some(Event::Concat(space)));
+                    }
+                    self.value_done()?;
+                }
+                State::End => {
+                    self.parser.drop_whitespace_and_comments()?;
+                    if let Some(ch) = self.peek()? {
+                        return Err(Error::UnexpectedToken {
+                            expected: "end of file",
+                            found_beginning: ch,
+                        });
+                    }
+                    self.state = State::Done;
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        match self.parser.reader.peek() {
+            Ok(ch) => Ok(Some(ch)),
+            Err(Error::Eof) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns whether the reader is at a character that ends a value, the same ones
+    /// [`HoconParser::parse_value`] stops at.
+    fn at_value_end(&mut self) -> Result<bool> {
+        let reader = &mut self.parser.reader;
+        match reader.peek() {
+            Ok(b']' | b'}' | b',' | b'#' | b'\n') | Err(Error::Eof) => Ok(true),
+            Ok(b'/') => Ok(reader.peek2().is_ok_and(|(_, ch)| ch == b'/')),
+            Ok(b'\r') => Ok(reader.peek2().is_ok_and(|(_, ch)| ch == b'\n')),
+            Ok(_) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Consumes the whitespace after a part of a value. Returns it if another part of the
+    /// value follows.
+    fn concat_space(&mut self) -> Result<Option<Option<Space>>> {
+        let mut space = vec![];
+        self.parser.parse_horizontal_whitespace(&mut space)?;
+        if self.at_value_end()? {
+            return Ok(None);
+        }
+        Ok(Some(Space::new(&String::from_utf8_lossy(&space))))
+    }
+
+    fn parse_scalar(&mut self, first: bool) -> Result<Event<'de>> {
+        let reader = &mut self.parser.reader;
+        let value = match reader.peek()? {
+            b'"' if reader
+                .peek_n(3)
+                .is_ok_and(|chars| chars == TRIPLE_DOUBLE_QUOTE) =>
+            {
+                let multiline = self.parser.parse_multiline_string(false)?;
+                RawValue::String(RawString::multiline(multiline))
+            }
+            b'"' => {
+                let quoted = self.parser.parse_quoted_string(false)?;
+                RawValue::String(RawString::quoted(quoted))
+            }
+            b'$' => RawValue::Substitution(self.parser.parse_substitution()?),
+            _ => {
+                let unquoted = self.parser.parse_unquoted_string()?;
+                RawValue::String(RawString::unquoted(unquoted))
+            }
+        };
+        if let Some(space) = self.concat_space()? {
+            self.pending = Some(Event::Concat(space));
+            self.state = State::Value { first: false };
+            return Ok(Event::Scalar(value));
+        }
+        self.value_done()?;
+        let value = match value {
+            RawValue::String(string) if first => self.parser.resolve_unquoted_string(string),
+            value => value,
+        };
+        Ok(Event::Scalar(value))
+    }
+
+    fn push(&mut self, container: Container) -> Result<()> {
+        let max_depth = self.parser.options.max_nesting_depth;
+        if self.stack.len() > max_depth {
+            return Err(Error::NestingDepthExceeded { max_depth });
+        }
+        self.parser.reader.discard(1)?;
+        self.stack.push(container);
+        Ok(())
+    }
+
+    fn close_object(&mut self, ch: Option<u8>) -> Result<Option<Event<'de>>> {
+        match (self.stack.last(), ch) {
+            (Some(Container::Object { braces: true }), Some(_)) => {
+                self.parser.reader.discard(1)?;
+                self.state = State::AfterContainer;
+            }
+            (Some(Container::Object { braces: false }), None) => self.state = State::End,
+            (_, Some(ch)) => {
+                return Err(Error::UnexpectedToken {
+                    expected: "end of file",
+                    found_beginning: ch,
+                });
+            }
+            (_, None) => return Err(Error::Eof),
+        }
+        self.stack.pop();
+        Ok(Some(Event::ObjectEnd))
+    }
+
+    /// Moves on after a complete value or include, consuming the comma after it.
+    fn value_done(&mut self) -> Result<()> {
+        self.state = match self.stack.last() {
+            Some(Container::Object { .. }) => {
+                self.parser.drop_horizontal_whitespace()?;
+                State::Field
+            }
+            Some(Container::Array) => {
+                self.parser.drop_whitespace_and_comments()?;
+                State::Element
+            }
+            None => State::End,
+        };
+        if self.peek()? == Some(b',') {
+            self.parser.reader.discard(1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de, R: Read<'de>> Iterator for Events<'_, 'de, R> {
+    type Item = Result<Event<'de>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter::Peekable;
+
+    use rstest::rstest;
+
+    use super::Event;
+    use crate::Result;
+    use crate::error::Error;
+    use crate::parser::HoconParser;
+    use crate::parser::read::StrRead;
+    use crate::raw::raw_value::RawValue;
+    use crate::value::Value;
+
+    fn events(input: &str) -> Result<Vec<Event<'_>>> {
+        HoconParser::new(StrRead::new(input)).events().collect()
+    }
+
+    /// Rebuilds the value of a document without substitutions, includes and
+    /// concatenations from its events.
+    fn build<'de>(events: &mut Peekable<impl Iterator<Item = Event<'de>>>) -> Value {
+        match events.next().unwrap() {
+            Event::ObjectStart => {
+                let mut fields = vec![];
+                while let Some(Event::Key { key, .. }) = events.next() {
+                    fields.push((key.to_string(), build(events)));
+                }
+                Value::object_from_iter(fields)
+            }
+            Event::ArrayStart => {
+                let mut values = vec![];
+                while events.next_if_eq(&Event::ArrayEnd).is_none() {
+                    values.push(build(events));
+                }
+                Value::Array(values)
+            }
+            Event::Scalar(RawValue::String(string)) => Value::new_string(string.to_string()),
+            Event::Scalar(RawValue::Number(number)) => Value::Number(number),
+            Event::Scalar(RawValue::Boolean(boolean)) => Value::Boolean(boolean),
+            Event::Scalar(RawValue::Null) => Value::Null,
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn test_events() -> Result<()> {
+        let events = events("{\n  a += ${b} \"x\" # c\n  include \"f.conf\", d: [{}], e { }\n}")?;
+        let expected = concat!(
+            "ObjectStart|Key a +=|Scalar ${b}|Concat Some(\" \")|Scalar x|",
+            "Include f.conf|Key d|ArrayStart|ObjectStart|ObjectEnd|ArrayEnd|",
+            "Key e|ObjectStart|ObjectEnd|ObjectEnd"
+        );
+        let rendered = events
+            .iter()
+            .map(|event| match event {
+                Event::Key { key, add_assign } => {
+                    format!("Key {key}{}", if *add_assign { " +=" } else { "" })
+                }
+                Event::Include(inclusion) => format!("Include {}", inclusion.path()),
+                Event::Scalar(value) => format!("Scalar {value}"),
+                Event::Concat(space) => format!("Concat {space:?}"),
+                event => format!("{event:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rendered.join("|"), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_events_transcode() -> Result<()> {
+        let input = r#"
+            // comment
+            a = 1, c: "x y"
+            d = [1.5, [], [true, null], { e: """multi
+            line""" }]
+            f { g { h = unquoted } }
+        "#;
+        let value = build(&mut events(input)?.into_iter().peekable());
+        let expected: Value = crate::Config::parse_str(input, None)?;
+        value.assert_deep_eq(&expected, "$");
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("a = ")]
+    #[case("a = [1, 2")]
+    #[case("a { b = 1")]
+    #[case("{ a = 1 } b")]
+    #[case("a = 1 }")]
+    fn test_events_error(#[case] input: &str) {
+        let mut parser = HoconParser::new(StrRead::new(input));
+        let mut events = parser.events();
+        let error = events.by_ref().find_map(Result::err).unwrap();
+        assert!(matches!(error, Error::Parse { .. }), "{error:?}");
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn test_events_max_depth() {
+        let input = format!("a = {}{}", "[".repeat(100), "]".repeat(100));
+        let error = events(&input).unwrap_err();
+        assert!(matches!(error, Error::NestingDepthExceeded { .. }));
+    }
+}
@@ -1,17 +1,18 @@
 use crate::Result;
-use crate::config_options::ConfigOptions;
 use crate::error::Error;
 use crate::parser::loader::{self, load_from_classpath, load_from_path};
-use crate::parser::read::Read;
+use crate::parser::read::{Read, Span};
 use crate::parser::{Context, HoconParser};
 use crate::raw::include::{Inclusion, Location};
 use crate::raw::raw_object::RawObject;
+use std::path::Path;
 use std::str::FromStr;
 
 pub(crate) const INCLUDE: &[u8] = b"include";
 
 impl<'de, R: Read<'de>> HoconParser<R> {
-    pub(crate) fn parse_include(&mut self) -> Result<Inclusion> {
+    pub(crate) fn parse_include(&mut self) -> Result<Inclusion<'de>> {
+        let start = self.reader.position();
         self.parse_include_token()?;
         self.drop_horizontal_whitespace()?;
         let required = self.parse_required_token()?;
@@ -29,34 +30,32 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 self.reader.discard(1)?;
             }
         }
-        let inclusion = Inclusion::new(include_path.into(), required, location, None);
+        let mut inclusion =
+            Inclusion::new(include_path.into_owned().into(), required, location, None);
+        inclusion.span = Some(Span {
+            start,
+            end: self.reader.position(),
+        });
         Ok(inclusion)
     }
 
     fn parse_include_token(&mut self) -> Result<()> {
-        let ch = self.reader.peek()?;
-        if ch != b'i' {
-            return Err(Error::UnexpectedToken {
-                expected: "include",
-                found_beginning: ch,
-            });
-        }
-        // At this point, we still don't know if it's an include or something else,
-        // so we need to use peek instead of consuming it
-        const N: usize = 7;
-        let bytes = self.reader.peek_n(N)?;
-        if bytes != INCLUDE {
-            let (_, ch) = bytes
-                .iter()
-                .enumerate()
-                .find(|(index, ch)| &&INCLUDE[*index] != ch)
-                .unwrap();
-            return Err(Error::UnexpectedToken {
-                expected: "include",
-                found_beginning: *ch,
-            });
+        self.expect_token(INCLUDE, "include")
+    }
+
+    /// Consumes `token` byte by byte. On a mismatch, the offending byte is not consumed so
+    /// the reader position of the error points at it.
+    fn expect_token(&mut self, token: &[u8], expected: &'static str) -> Result<()> {
+        for ele in token {
+            let ch = self.reader.peek()?;
+            if *ele != ch {
+                return Err(Error::UnexpectedToken {
+                    expected,
+                    found_beginning: ch,
+                });
+            }
+            self.reader.discard(1)?;
         }
-        self.reader.discard(N)?;
         Ok(())
     }
 
@@ -65,15 +64,7 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         let ch = self.reader.peek()?;
         const REQUIRED: &[u8] = b"required(";
         if ch == b'r' {
-            for ele in REQUIRED {
-                let next = self.reader.next()?;
-                if *ele != next {
-                    return Err(Error::UnexpectedToken {
-                        expected: "required(",
-                        found_beginning: next,
-                    });
-                }
-            }
+            self.expect_token(REQUIRED, "required(")?;
             required = true
         }
         if required {
@@ -87,46 +78,17 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         const FILE: &[u8] = b"file(";
         let location = match ch {
             b'f' => {
-                for ele in FILE {
-                    let next = self.reader.next()?;
-                    if *ele != next {
-                        return Err(Error::UnexpectedToken {
-                            expected: "file(",
-                            found_beginning: next,
-                        });
-                    }
-                }
+                self.expect_token(FILE, "file(")?;
                 Some(Location::File)
             }
-            #[cfg(feature = "urls_includes")]
             b'u' => {
                 const URL: &[u8] = b"url(";
-                for ele in URL {
-                    let next = self.reader.next()?;
-                    if *ele != next {
-                        return Err(Error::UnexpectedToken {
-                            expected: "url(",
-                            found_beginning: next,
-                        });
-                    }
-                }
+                self.expect_token(URL, "url(")?;
                 Some(Location::Url)
             }
-            #[cfg(not(feature = "urls_includes"))]
-            b'u' => {
-                return Err(Error::UrlsIncludesDisabled);
-            }
             b'c' => {
                 const CLASSPATH: &[u8] = b"classpath(";
-                for ele in CLASSPATH {
-                    let next = self.reader.next()?;
-                    if *ele != next {
-                        return Err(Error::UnexpectedToken {
-                            expected: "classpath(",
-                            found_beginning: next,
-                        });
-                    }
-                }
+                self.expect_token(CLASSPATH, "classpath(")?;
                 Some(Location::Classpath)
             }
             b'"' => None,
@@ -143,26 +105,23 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         Ok(location)
     }
 
-    fn handle_include_error<'a, F>(
-        load: F,
-        options: ConfigOptions,
-        inclusion: &'a mut Inclusion,
-        ctx: Option<Context>,
-    ) -> Result<()>
-    where
-        F: FnOnce(&'a std::path::Path, ConfigOptions, Option<Context>) -> Result<RawObject>,
-    {
-        match load((**inclusion.path).as_ref(), options, ctx) {
+    fn handle_include_error(
+        inclusion: &mut Inclusion<'de>,
+        result: Result<RawObject<'static>>,
+    ) -> Result<()> {
+        match result {
             Ok(object) => {
                 inclusion.val = Some(object.into());
             }
             Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
+                let error = Error::Io(io);
                 if inclusion.required {
                     return Err(Error::Include {
                         inclusion: inclusion.to_string(),
-                        error: Box::new(Error::Io(io)),
+                        error: Box::new(error),
                     });
                 }
+                inclusion.searched = error.searched_locations().unwrap_or_default().to_vec();
             }
             Err(e) => {
                 return Err(Error::Include {
@@ -174,81 +133,86 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         Ok(())
     }
 
-    fn inclusion_from_file(&self, inclusion: &mut Inclusion, ctx: Option<Context>) -> Result<()> {
-        Self::handle_include_error(load_from_path, self.options.clone(), inclusion, ctx)
+    fn inclusion_from_file(
+        &self,
+        inclusion: &mut Inclusion<'de>,
+        ctx: Option<Context>,
+    ) -> Result<()> {
+        let (path, skipped) =
+            loader::include_path(Path::new(inclusion.path.as_str()), &self.options, &self.ctx);
+        let result = load_from_path(path, self.options.clone(), ctx);
+        let result = loader::searched_first(result, skipped);
+        Self::handle_include_error(inclusion, result)
     }
 
     fn inclusion_from_classpath(
         &self,
-        inclusion: &mut Inclusion,
+        inclusion: &mut Inclusion<'de>,
         ctx: Option<Context>,
     ) -> Result<()> {
-        Self::handle_include_error(load_from_classpath, self.options.clone(), inclusion, ctx)
+        let result = load_from_classpath(inclusion.path.as_str(), self.options.clone(), ctx);
+        Self::handle_include_error(inclusion, result)
     }
 
     fn inclusion_from_file_and_classpath(
         &self,
-        inclusion: &mut Inclusion,
+        inclusion: &mut Inclusion<'de>,
         ctx: Option<Context>,
     ) -> Result<()> {
-        Self::handle_include_error(loader::load, self.options.clone(), inclusion, ctx)
+        let (path, skipped) =
+            loader::include_path(Path::new(inclusion.path.as_str()), &self.options, &self.ctx);
+        let result = loader::load(path, self.options.clone(), ctx);
+        let result = loader::searched_first(result, skipped);
+        Self::handle_include_error(inclusion, result)
+    }
+
+    /// Loads a `file:` URL from the local file system, like a `file()` include, without
+    /// the HTTP client.
+    fn inclusion_from_file_url(
+        &self,
+        inclusion: &mut Inclusion<'de>,
+        url: &url::Url,
+        ctx: Option<Context>,
+    ) -> Result<()> {
+        let result = loader::file_url_path(url)
+            .and_then(|path| load_from_path(path, self.options.clone(), ctx));
+        Self::handle_include_error(inclusion, result)
     }
 
     #[cfg(feature = "urls_includes")]
-    fn inclusion_from_url(&self, inclusion: &mut Inclusion, ctx: Option<Context>) -> Result<()> {
-        let url = url::Url::from_str(&inclusion.path)?;
-        match loader::load_from_url(url, self.options.clone(), ctx) {
-            Ok(object) => {
-                inclusion.val = Some(object.into());
-            }
-            Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
-                if inclusion.required {
-                    return Err(Error::Include {
-                        inclusion: inclusion.to_string(),
-                        error: Box::new(Error::Io(io)),
-                    });
-                }
-            }
-            Err(e) => {
-                return Err(Error::Include {
-                    inclusion: inclusion.to_string(),
-                    error: Box::new(e),
-                });
-            }
-        }
-        Ok(())
+    fn inclusion_from_url(
+        &self,
+        inclusion: &mut Inclusion<'de>,
+        url: url::Url,
+        ctx: Option<Context>,
+    ) -> Result<()> {
+        let result = loader::load_from_url(url, self.options.clone(), ctx);
+        Self::handle_include_error(inclusion, result)
     }
 
-    pub(crate) fn parse_inclusion(&self, inclusion: &mut Inclusion) -> Result<()> {
-        let has_cycle = self
-            .ctx
-            .include_chain
-            .iter()
-            .rfind(|p| **p == inclusion.path)
-            .is_some();
-        if has_cycle {
-            return Err(Error::InclusionCycle);
-        }
-        let mut ctx = self.ctx.clone();
-        ctx.include_chain.push(inclusion.path.clone());
+    pub(crate) fn parse_inclusion(&self, inclusion: &mut Inclusion<'de>) -> Result<()> {
+        let ctx = self.ctx.enter_include(inclusion, &self.options)?;
         match inclusion.location {
-            #[cfg(feature = "urls_includes")]
             None | Some(Location::Url) => match url::Url::from_str(&inclusion.path) {
-                Ok(url) => {
-                    if url.scheme() != "file" {
-                        self.inclusion_from_url(inclusion, Some(ctx))?;
-                    }
-                }
-                _ => {
-                    self.inclusion_from_file_and_classpath(inclusion, Some(ctx))?;
+                Ok(url) if url.scheme() == "file" => {
+                    self.inclusion_from_file_url(inclusion, &url, Some(ctx))?;
                 }
-            },
-            #[cfg(not(feature = "urls_includes"))]
-            None => match url::Url::from_str(&inclusion.path) {
-                Ok(url) if url.scheme() != "file" => {
-                    return Err(Error::UrlsIncludesDisabled);
-                }
-                _ => self.inclusion_from_file_and_classpath(inclusion, Some(ctx))?,
+                Ok(url) => match self.options.url_handlers.get(url.scheme()) {
+                    Some(handler) => {
+                        let result = loader::load_from_handler(
+                            handler.as_ref(),
+                            &url,
+                            &self.options,
+                            Some(ctx),
+                        );
+                        Self::handle_include_error(inclusion, result)?;
+                    }
+                    #[cfg(feature = "urls_includes")]
+                    None => self.inclusion_from_url(inclusion, url, Some(ctx))?,
+                    #[cfg(not(feature = "urls_includes"))]
+                    None => return Err(Error::UrlsIncludesDisabled),
+                },
+                Err(_) => self.inclusion_from_file_and_classpath(inclusion, Some(ctx))?,
             },
             Some(Location::Classpath) => self.inclusion_from_classpath(inclusion, Some(ctx))?,
             Some(Location::File) => self.inclusion_from_file(inclusion, Some(ctx))?,
@@ -306,4 +270,24 @@ mod tests {
         assert!(result.is_err());
         Ok(())
     }
+    #[test]
+    fn test_file_url_include() -> Result<()> {
+        use crate::{Config, Error, Value};
+
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("base.conf"), "a = 1")?;
+        std::fs::write(dir.path().join("other.json"), r#"{"b": 2}"#)?;
+        let base = url::Url::from_file_path(dir.path().join("base.conf")).unwrap();
+        let other = url::Url::from_file_path(dir.path().join("other")).unwrap();
+        let missing = url::Url::from_file_path(dir.path().join("missing.conf")).unwrap();
+        let input =
+            format!("include url(\"{base}\")\ninclude \"{other}\"\ninclude url(\"{missing}\")");
+        let value: Value = Config::parse_str(&input, None)?;
+        assert_eq!(value, Config::parse_str::<Value>("a = 1, b = 2", None)?);
+
+        let input = format!("include required(url(\"{missing}\"))");
+        let result = Config::parse_str::<Value>(&input, None);
+        assert!(matches!(result, Err(Error::Include { .. })));
+        Ok(())
+    }
 }
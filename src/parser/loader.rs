@@ -1,10 +1,24 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use sha2::{Digest, Sha256};
 
 use crate::Result;
-use crate::config_options::ConfigOptions;
-use crate::error::Error;
+use crate::classpath::ClasspathProvider;
+#[cfg(feature = "urls_includes")]
+use crate::config_options::UrlOptions;
+use crate::config_options::{ConfigOptions, DirectoryOptions, IncludeResolution};
+use crate::error::{Error, NotFound};
+use crate::origin::{Layer, Origin, SourceId};
 use crate::parser::read::StreamRead;
+#[cfg(feature = "urls_includes")]
+use crate::parser::url_cache::{CachedRequest, CachedResponse};
 use crate::parser::{Context, HoconParser};
+use crate::url_handler::UrlHandler;
 use crate::{
     raw::{field::ObjectField, raw_object::RawObject, raw_value::RawValue},
     syntax::Syntax,
@@ -33,52 +47,12 @@ impl ConfigPath {
     }
 }
 
-fn find_config_path(path: impl AsRef<Path>) -> Result<ConfigPath> {
+fn find_config_path(path: impl AsRef<Path>, extensions: &[Syntax]) -> Result<ConfigPath> {
     let path = path.as_ref();
-    let extension_syntax = if let Some(extension) = path.extension()
-        && let Some(extension) = extension.to_str()
-    {
-        if extension == "json" {
-            Some(Syntax::Json)
-        } else if extension == "conf" {
-            Some(Syntax::Hocon)
-        } else if extension == "properties" {
-            Some(Syntax::Properties)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
     let mut config_path = ConfigPath::default();
-    match extension_syntax {
-        Some(syntax) => {
-            let path = path.to_path_buf();
-            if path.is_file() {
-                config_path.set_path(path, syntax);
-            } else {
-                return Err(Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    path.display().to_string(),
-                )));
-            }
-        }
-        None => {
-            let mut json_path = path.to_path_buf();
-            json_path.set_extension("json");
-            let mut hocon_path = path.to_path_buf();
-            hocon_path.set_extension("conf");
-            let mut properties_path = path.to_path_buf();
-            properties_path.set_extension("properties");
-            if json_path.is_file() {
-                config_path.set_path(json_path, Syntax::Json);
-            }
-            if hocon_path.is_file() {
-                config_path.set_path(hocon_path, Syntax::Hocon);
-            }
-            if properties_path.is_file() {
-                config_path.set_path(properties_path, Syntax::Properties);
-            }
+    for (candidate, syntax) in candidate_files(path, extensions) {
+        if candidate.is_file() {
+            config_path.set_path(candidate, syntax);
         }
     }
     if [
@@ -89,43 +63,98 @@ fn find_config_path(path: impl AsRef<Path>) -> Result<ConfigPath> {
     .iter()
     .all(|p| p.is_none())
     {
-        let message = format!(
-            "No configuration file (.conf, .json, .properties) was found at the given path: {}",
-            path.display(),
-        );
-        return Err(Error::Io(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            message,
-        )));
+        return Err(config_not_found(path, extensions));
     }
     Ok(config_path)
 }
 
+fn extension_syntax(path: &Path) -> Option<Syntax> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(Syntax::from_extension)
+}
+
+/// Returns the files that may hold the configuration at `path`: `path` itself if its
+/// extension is known, otherwise `path` with each of `extensions` appended.
+pub(crate) fn candidate_files(path: &Path, extensions: &[Syntax]) -> Vec<(PathBuf, Syntax)> {
+    match extension_syntax(path) {
+        Some(syntax) => vec![(path.to_path_buf(), syntax)],
+        None => extensions
+            .iter()
+            .map(|syntax| {
+                let mut candidate = path.as_os_str().to_owned();
+                candidate.push(format!(".{syntax}"));
+                (PathBuf::from(candidate), *syntax)
+            })
+            .collect(),
+    }
+}
+
+/// The error for a `path` none of whose [`candidate_files`] exist.
+pub(crate) fn config_not_found(path: &Path, extensions: &[Syntax]) -> Error {
+    let searched = candidate_files(path, extensions)
+        .into_iter()
+        .map(|(file, _)| file.display().to_string())
+        .collect();
+    NotFound { searched }.into()
+}
+
+/// The locations searched by `result` if it failed with a [`NotFound`] error.
+pub(crate) fn searched<T>(result: &Result<T>) -> &[String] {
+    match result {
+        Err(error) => error.searched_locations().unwrap_or_default(),
+        Ok(_) => &[],
+    }
+}
+
+/// Adds `skipped` in front of the locations searched by a [`NotFound`] error.
+pub(crate) fn searched_first<T>(result: Result<T>, skipped: Vec<String>) -> Result<T> {
+    match &result {
+        Err(error)
+            if !skipped.is_empty()
+                && let Some(searched) = error.searched_locations() =>
+        {
+            let searched = skipped
+                .into_iter()
+                .chain(searched.iter().cloned())
+                .collect();
+            Err(NotFound { searched }.into())
+        }
+        _ => result,
+    }
+}
+
+/// Returns the files [`load_from_path`] would read for `path`.
+pub(crate) fn config_files(path: impl AsRef<Path>, extensions: &[Syntax]) -> Result<Vec<PathBuf>> {
+    let config_path = find_config_path(path, extensions)?;
+    let files = [config_path.hocon, config_path.json, config_path.properties]
+        .into_iter()
+        .flatten()
+        .collect();
+    Ok(files)
+}
+
+/// Loads every file found for `path`, see [`ConfigOptions::extensions`], merged in the
+/// order given by [`ConfigOptions::compare`].
 pub(crate) fn load_from_path(
     path: impl AsRef<Path>,
     options: ConfigOptions,
     ctx: Option<Context>,
-) -> Result<RawObject> {
-    let config_path = find_config_path(&path)?;
+) -> Result<RawObject<'static>> {
+    let config_path = find_config_path(&path, &options.extensions)?;
+    let ctx = ctx.unwrap_or_default();
     let mut result = vec![];
     if let Some(hocon) = config_path.hocon {
-        let file = std::fs::File::open(hocon)?;
-        let reader = std::io::BufReader::new(file);
-        let read = StreamRead::new(reader);
-        let raw_obj = parse_hocon(read, options.clone(), ctx)?;
+        let raw_obj = parse_file(&hocon, Syntax::Hocon, &options, &ctx)?;
         result.push((raw_obj, Syntax::Hocon));
     }
     if let Some(json) = config_path.json {
-        let file = std::fs::File::open(json)?;
-        let reader = std::io::BufReader::new(file);
-        let raw_obj = parse_json(reader)?;
+        let raw_obj = parse_file(&json, Syntax::Json, &options, &ctx)?;
         result.push((raw_obj, Syntax::Json));
     }
     if let Some(properties) = config_path.properties {
-        let file = std::fs::File::open(properties)?;
-        let reader = std::io::BufReader::new(file);
-        let raw_obj = parse_properties(reader)?;
-        result.push((raw_obj, Syntax::Json));
+        let raw_obj = parse_file(&properties, Syntax::Properties, &options, &ctx)?;
+        result.push((raw_obj, Syntax::Properties));
     }
     let cmp = &options.compare;
     result.sort_by(|a, b| cmp(&a.1, &b.1));
@@ -138,67 +167,619 @@ pub(crate) fn load_from_path(
     Ok(raw)
 }
 
-#[cfg(feature = "urls_includes")]
-pub(crate) fn load_from_url(
-    url: url::Url,
-    options: ConfigOptions,
-    ctx: Option<Context>,
-) -> Result<RawObject> {
-    let client = reqwest::blocking::Client::new();
-    match client.get(url).send() {
-        Ok(response) => {
-            let extension_syntax = if let Some(filename) = response
-                .url()
-                .path_segments()
-                .and_then(|segments| segments.last())
-            {
-                if let Some(dot_index) = filename.rfind('.') {
-                    let extension = &filename[dot_index + 1..];
-                    match extension {
-                        "json" => Some(Syntax::Json),
-                        "properties" => Some(Syntax::Properties),
-                        "conf" => Some(Syntax::Hocon),
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
+/// Reads and parses one of the files found by [`load_from_path`].
+fn parse_file(
+    path: &Path,
+    syntax: Syntax,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let source = SourceId::new(&path.display().to_string());
+    let ctx = ctx.enter_source(&canonical_path(path))?;
+    let location = file_location(path, options);
+    if let Some(raw_obj) =
+        ctx.contents
+            .get_location(location.as_deref(), &source, &ctx.include_chain)
+    {
+        return Ok(raw_obj);
+    }
+    // One byte more than allowed tells the file is too large without reading all of it.
+    let max_bytes = options.max_input_bytes;
+    let remaining = max_bytes.saturating_sub(ctx.usage.bytes.get());
+    let mut contents = vec![];
+    std::fs::File::open(path)?
+        .take((remaining as u64).saturating_add(1))
+        .read_to_end(&mut contents)?;
+    ctx.usage.check_bytes(contents.len(), max_bytes)?;
+    let ctx = ctx.with_dir(path.parent());
+    parse_contents(&contents, location, source, syntax, options, &ctx)
+}
+
+/// The paths a file include of `path` may refer to, in the order they are looked up, see
+/// [`ConfigOptions::include_resolution`] and [`ConfigOptions::expand_home`]. The last one
+/// is used if none of them exists.
+pub(crate) fn include_paths(path: &Path, options: &ConfigOptions, ctx: &Context) -> Vec<PathBuf> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) if options.expand_home && options.extended() => match std::env::home_dir() {
+            Some(home) => home.join(rest),
+            None => path.to_path_buf(),
+        },
+        _ => path.to_path_buf(),
+    };
+    match (&ctx.dir, options.include_resolution) {
+        (Some(dir), IncludeResolution::RelativeToFile) if path.is_relative() => {
+            vec![dir.join(&path), path]
+        }
+        _ => vec![path],
+    }
+}
+
+/// Resolves the path of a file include, see [`include_paths`]. Also returns the files
+/// looked up in the paths skipped before it, for [`searched_first`].
+pub(crate) fn include_path(
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> (PathBuf, Vec<String>) {
+    let mut paths = include_paths(path, options, ctx);
+    let last = paths.pop().expect("there is at least one path");
+    let mut skipped = vec![];
+    for path in paths {
+        let candidates = candidate_files(&path, &options.extensions);
+        if candidates.iter().any(|(file, _)| file.is_file()) {
+            return (path, skipped);
+        }
+        skipped.extend(
+            candidates
+                .iter()
+                .map(|(file, _)| file.display().to_string()),
+        );
+    }
+    (last, skipped)
+}
+
+/// The key of the file at `path` in [`ContentCache`], `None` if
+/// [`ConfigOptions::cache_includes`] is disabled.
+pub(crate) fn file_location(path: &Path, options: &ConfigOptions) -> Option<String> {
+    options.cache_includes.then(|| canonical_path(path))
+}
+
+/// The absolute path of the file at `path` with all symlinks resolved, or `path` itself if
+/// that fails.
+pub(crate) fn canonical_path(path: &Path) -> String {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    path.display().to_string()
+}
+
+/// Parses the contents of a file or URL. Contents already parsed while loading the same
+/// configuration, e.g. a file included both by a relative path and from the classpath, are
+/// taken from [`Context::contents`] instead of being parsed again, unless
+/// [`ConfigOptions::cache_includes`] is disabled.
+///
+/// `location` is the canonical path or URL the contents were read from, so later includes
+/// of it are taken from the cache without reading it again.
+pub(crate) fn parse_contents(
+    contents: &[u8],
+    location: Option<String>,
+    source: SourceId,
+    syntax: Syntax,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    // The digest is recorded in the origins, for `Config::fingerprint`.
+    let digest: [u8; 32] = Sha256::digest(contents).into();
+    let source = source.with_digest(digest);
+    // Relative includes of the same contents in another directory may refer to other files.
+    let key = options
+        .cache_includes
+        .then(|| (digest, syntax, ctx.dir.clone()));
+    if let Some(key) = &key
+        && let Some(raw_obj) = ctx.contents.get(key, &source, &ctx.include_chain)
+    {
+        return Ok(raw_obj);
+    }
+    // The HOCON parser counts the bytes it reads itself.
+    let max_bytes = options.max_input_bytes;
+    ctx.usage.check_bytes(contents.len(), max_bytes)?;
+    let raw_obj = match syntax {
+        Syntax::Hocon => {
+            let read = StreamRead::new(contents);
+            let mut ctx = ctx.clone();
+            ctx.source = Some(source.clone());
+            parse_hocon(read, options.clone(), Some(ctx))?
+        }
+        Syntax::Json | Syntax::Properties => {
+            ctx.usage.add_bytes(contents.len(), max_bytes)?;
+            let mut raw_obj = if syntax == Syntax::Json {
+                parse_json(contents)?
             } else {
-                None
+                parse_properties(contents)?
             };
-            let header_syntax =
-                if let Some(content_type) = response.headers().get(reqwest::header::CONTENT_TYPE) {
-                    match content_type.as_bytes() {
-                        b"application/json" => Some(Syntax::Json),
-                        b"text/x-java-properties" => Some(Syntax::Properties),
-                        b"application/hocon" => Some(Syntax::Hocon),
-                        _ => None,
+            let origin =
+                Origin::parsed(Some(source.clone()), None, None, ctx.include_chain.clone());
+            attach_origin(&mut raw_obj, &origin);
+            raw_obj
+        }
+    };
+    if let Some(key) = key {
+        if let Some(location) = location {
+            ctx.contents.insert_location(location, key.clone());
+        }
+        ctx.contents
+            .insert(key, source, &ctx.include_chain, &raw_obj);
+    }
+    Ok(raw_obj)
+}
+
+/// The files and URLs parsed while loading one configuration, keyed by the SHA-256 of
+/// their contents. Clones share the same entries.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ContentCache(Rc<RefCell<CachedEntries>>);
+
+#[derive(Debug, Default)]
+struct CachedEntries {
+    contents: HashMap<ContentKey, CachedContents>,
+    /// The contents read from each canonical file path or URL, see
+    /// [`ConfigOptions::cache_includes`].
+    locations: HashMap<String, ContentKey>,
+}
+
+/// The SHA-256 of the contents, how they were parsed and the directory their relative
+/// includes were resolved against.
+type ContentKey = ([u8; 32], Syntax, Option<Rc<Path>>);
+
+#[derive(Debug)]
+struct CachedContents {
+    source: SourceId,
+    include_chain: Vec<SourceId>,
+    object: RawObject<'static>,
+    /// The sources the contents were read from, in the order they were read.
+    sources: Vec<SourceId>,
+}
+
+impl ContentCache {
+    /// Returns the object parsed from the same contents, with the origins of its fields
+    /// moved to `source`. If `source` is another source, the sources read before it are
+    /// recorded in [`Origin::same_content`].
+    fn get(
+        &self,
+        key: &ContentKey,
+        source: &SourceId,
+        include_chain: &[SourceId],
+    ) -> Option<RawObject<'static>> {
+        let mut cache = self.0.borrow_mut();
+        let cached = cache.contents.get_mut(key)?;
+        let source = &source.with_digest(key.0);
+        let mut object = cached.object.clone();
+        if cached.source != *source || cached.include_chain != include_chain {
+            if !cached.sources.contains(source) {
+                cached.sources.push(source.clone());
+            }
+            let same_content = cached
+                .sources
+                .iter()
+                .take_while(|id| *id != source)
+                .cloned()
+                .collect::<Vec<_>>();
+            let from = (&cached.source, cached.include_chain.as_slice());
+            relocate_origins(&mut object, from, (source, include_chain), &same_content);
+        }
+        Some(object)
+    }
+
+    fn insert(
+        &self,
+        key: ContentKey,
+        source: SourceId,
+        include_chain: &[SourceId],
+        object: &RawObject<'static>,
+    ) {
+        let contents = CachedContents {
+            source: source.clone(),
+            include_chain: include_chain.to_vec(),
+            object: object.clone(),
+            sources: vec![source],
+        };
+        self.0.borrow_mut().contents.insert(key, contents);
+    }
+
+    /// Returns the object parsed from the file or URL at `location` like [`Self::get`],
+    /// without reading it again.
+    pub(crate) fn get_location(
+        &self,
+        location: Option<&str>,
+        source: &SourceId,
+        include_chain: &[SourceId],
+    ) -> Option<RawObject<'static>> {
+        let key = self.0.borrow().locations.get(location?)?.clone();
+        self.get(&key, source, include_chain)
+    }
+
+    fn insert_location(&self, location: String, key: ContentKey) {
+        self.0.borrow_mut().locations.insert(location, key);
+    }
+}
+
+/// Rewrites the origins of the fields parsed from the source `from`, and of the files it
+/// included, as if they were parsed from `to`. Sources are given with the include chain
+/// they were parsed with, which already ends with the source when it was included. The
+/// fields moved to `to` get `same_content` as their [`Origin::same_content`].
+fn relocate_origins(
+    object: &mut RawObject<'_>,
+    from: (&SourceId, &[SourceId]),
+    to: (&SourceId, &[SourceId]),
+    same_content: &[SourceId],
+) {
+    fn relocate_value(
+        value: &mut RawValue<'_>,
+        from: (&SourceId, &[SourceId]),
+        to: (&SourceId, &[SourceId]),
+        same_content: &[SourceId],
+    ) {
+        let relocate = |v: &mut RawValue<'_>| relocate_value(v, from, to, same_content);
+        match value {
+            RawValue::Object(object) => relocate_origins(object, from, to, same_content),
+            RawValue::Array(array) => array.iter_mut().for_each(relocate),
+            RawValue::Concat(concat) => concat.values_mut().iter_mut().for_each(relocate),
+            RawValue::AddAssign(add_assign) => relocate(add_assign),
+            _ => {}
+        }
+    }
+    for field in object.iter_mut() {
+        match field {
+            ObjectField::KeyValue {
+                origin: Some(origin),
+                value,
+                ..
+            } => {
+                if let Some(rest) = origin.include_chain.strip_prefix(from.1) {
+                    if rest.is_empty() && origin.source.as_ref() == Some(from.0) {
+                        origin.source = Some(to.0.clone());
+                        origin.same_content = same_content.to_vec();
                     }
-                } else {
-                    None
-                };
-            let syntax = extension_syntax.or(header_syntax).unwrap_or(Syntax::Hocon);
-            match syntax {
-                Syntax::Hocon => {
-                    let read = StreamRead::new(std::io::BufReader::new(response));
-                    parse_hocon(read, options, ctx)
+                    origin.include_chain = [to.1, rest].concat();
+                }
+                relocate_value(value, from, to, same_content);
+            }
+            ObjectField::KeyValue { value, .. } => relocate_value(value, from, to, same_content),
+            ObjectField::Inclusion { inclusion, .. } => {
+                if let Some(object) = &mut inclusion.val {
+                    relocate_origins(object, from, to, same_content);
                 }
-                Syntax::Json => parse_json(response),
-                Syntax::Properties => parse_properties(response),
             }
+            ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
         }
-        Err(error) => Err(Error::Io(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            error,
-        ))),
     }
 }
 
+/// Tells the syntax of a response by the extension of `url`, then by its content type,
+/// and falls back to HOCON.
+pub(crate) fn url_syntax(url: &url::Url, content_type: Option<&[u8]>) -> Syntax {
+    let extension_syntax = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|filename| filename.rsplit_once('.'))
+        .and_then(|(_, extension)| match extension {
+            "json" => Some(Syntax::Json),
+            "properties" => Some(Syntax::Properties),
+            "conf" => Some(Syntax::Hocon),
+            _ => None,
+        });
+    let header_syntax = content_type.and_then(|content_type| match content_type {
+        b"application/json" => Some(Syntax::Json),
+        b"text/x-java-properties" => Some(Syntax::Properties),
+        b"application/hocon" => Some(Syntax::Hocon),
+        _ => None,
+    });
+    extension_syntax.or(header_syntax).unwrap_or(Syntax::Hocon)
+}
+
+/// Reads `url` with the handler registered for its scheme, see
+/// [`ConfigOptions::url_handlers`].
+pub(crate) fn load_from_handler(
+    handler: &dyn UrlHandler,
+    url: &url::Url,
+    options: &ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>> {
+    let ctx = ctx
+        .unwrap_or_default()
+        .with_dir(None)
+        .enter_source(url.as_str())?;
+    let location = options.cache_includes.then(|| url.to_string());
+    let source = SourceId::new(url.as_str());
+    if let Some(raw_obj) =
+        ctx.contents
+            .get_location(location.as_deref(), &source, &ctx.include_chain)
+    {
+        return Ok(raw_obj);
+    }
+    let contents = handler.read(url)?;
+    parse_handler_contents(contents, url, location, options, &ctx)
+}
+
+/// Parses what a [`UrlHandler`] read from `url`, failing if it had nothing there.
+pub(crate) fn parse_handler_contents(
+    contents: Option<Cow<'static, [u8]>>,
+    url: &url::Url,
+    location: Option<String>,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let Some(contents) = contents else {
+        let searched = vec![url.to_string()];
+        return Err(NotFound { searched }.into());
+    };
+    let source = SourceId::new(url.as_str());
+    let syntax = url_syntax(url, None);
+    parse_contents(&contents, location, source, syntax, options, ctx)
+}
+
+/// The local path a `file:` URL refers to, so it is read like a `file()` include.
+pub(crate) fn file_url_path(url: &url::Url) -> Result<PathBuf> {
+    url.to_file_path().map_err(|_| {
+        let message = format!("Not a local file URL: {url}");
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            message,
+        ))
+    })
+}
+
+/// Loads `url`: `file:` URLs from the file system, URLs with a scheme of
+/// [`ConfigOptions::url_handlers`] with their handler, and other URLs over HTTP with the
+/// `urls_includes` feature.
+pub(crate) fn load_from_url(
+    url: url::Url,
+    options: ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>> {
+    if url.scheme() == "file" {
+        return load_from_path(file_url_path(&url)?, options, ctx);
+    }
+    if let Some(handler) = options.url_handlers.get(url.scheme()) {
+        return load_from_handler(handler.as_ref(), &url, &options, ctx);
+    }
+    #[cfg(feature = "urls_includes")]
+    return fetch_url(url, options, ctx);
+    #[cfg(not(feature = "urls_includes"))]
+    Err(Error::UrlsIncludesDisabled)
+}
+
+/// Fetches `url` over HTTP, answered from [`UrlOptions::cache`] when possible.
+#[cfg(feature = "urls_includes")]
+fn fetch_url(
+    url: url::Url,
+    options: ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>> {
+    use std::io::Read;
+
+    let ctx = ctx.unwrap_or_default().enter_source(url.as_str())?;
+    let location = options.cache_includes.then(|| url.to_string());
+    let source = SourceId::new(url.as_str());
+    if let Some(raw_obj) =
+        ctx.contents
+            .get_location(location.as_deref(), &source, &ctx.include_chain)
+    {
+        return Ok(raw_obj);
+    }
+    let url_options = &options.url_options;
+    let headers = request_headers(url_options, &url)?;
+    let client = ctx.url_clients.blocking(url_options)?;
+    let mut request = CachedRequest::new(url_options, &url, &headers);
+    let response = match send(client, &url, url_options, &request.validators()) {
+        Ok(mut response) => match request.fallback(Some(response.status())) {
+            Some(cached) => cached,
+            None => {
+                check_response_len(response.url(), response.content_length(), &options, &ctx)?;
+                let mut contents = vec![];
+                let limit = response_limit(&options, &ctx);
+                (&mut response).take(limit).read_to_end(&mut contents)?;
+                let len = Some(contents.len() as u64);
+                check_response_len(response.url(), len, &options, &ctx)?;
+                let fetched =
+                    CachedResponse::new(response.url().clone(), response.headers(), contents);
+                request.insert(response.status(), &fetched);
+                fetched
+            }
+        },
+        Err(error) => request
+            .fallback(None)
+            .ok_or_else(|| Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, error)))?,
+    };
+    parse_response(response, location, &options, &ctx.with_dir(None))
+}
+
+/// Parses a response from a URL, fetched or cached.
+#[cfg(feature = "urls_includes")]
+pub(crate) fn parse_response(
+    response: CachedResponse,
+    location: Option<String>,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<RawObject<'static>> {
+    let content_type = response.content_type.as_deref().map(str::as_bytes);
+    let syntax = url_syntax(&response.url, content_type);
+    let source = SourceId::new(response.url.as_str());
+    parse_contents(&response.body, location, source, syntax, options, ctx)
+}
+
+/// The HTTP clients of one load, built on first use, so the URLs fetched while loading a
+/// configuration share their connections.
+#[cfg(feature = "urls_includes")]
+#[derive(Debug, Default)]
+pub(crate) struct UrlClients {
+    blocking: std::cell::OnceCell<reqwest::blocking::Client>,
+    #[cfg(feature = "tokio")]
+    nonblocking: std::cell::OnceCell<reqwest::Client>,
+}
+
+/// The archives of [`ConfigOptions::classpath`] opened while loading a configuration, so
+/// every include of a resource in the same archive reads it through one handle.
+#[cfg(feature = "zip")]
+#[derive(Debug, Default)]
+pub(crate) struct Archives(RefCell<HashMap<String, Rc<crate::classpath::ArchiveClasspath>>>);
+
+#[cfg(feature = "zip")]
+impl Archives {
+    /// Returns the archive at `path`, opening it on first use.
+    pub(crate) fn open(&self, path: &str) -> Result<Rc<crate::classpath::ArchiveClasspath>> {
+        if let Some(archive) = self.0.borrow().get(path) {
+            return Ok(archive.clone());
+        }
+        let archive = Rc::new(crate::classpath::ArchiveClasspath::open(path)?);
+        self.0
+            .borrow_mut()
+            .insert(path.to_string(), archive.clone());
+        Ok(archive)
+    }
+}
+
+#[cfg(feature = "urls_includes")]
+impl UrlClients {
+    /// Redirects are followed by [`send`], which decides which headers each one gets.
+    pub(crate) fn blocking(&self, options: &UrlOptions) -> Result<&reqwest::blocking::Client> {
+        if let Some(client) = self.blocking.get() {
+            return Ok(client);
+        }
+        let client = reqwest::blocking::Client::builder()
+            .timeout(options.timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(std::io::Error::other)?;
+        Ok(self.blocking.get_or_init(|| client))
+    }
+
+    /// Like [`UrlClients::blocking`], for the async loaders.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn nonblocking(&self, options: &UrlOptions) -> Result<&reqwest::Client> {
+        if let Some(client) = self.nonblocking.get() {
+            return Ok(client);
+        }
+        let mut builder = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let client = builder.build().map_err(std::io::Error::other)?;
+        Ok(self.nonblocking.get_or_init(|| client))
+    }
+}
+
+/// The headers sent with a request to `url`: [`UrlOptions::headers`] if the origin of
+/// `url` is one of [`UrlOptions::header_origins`], none otherwise.
+#[cfg(feature = "urls_includes")]
+pub(crate) fn request_headers(
+    options: &UrlOptions,
+    url: &url::Url,
+) -> std::io::Result<reqwest::header::HeaderMap> {
+    fn invalid(error: impl std::error::Error + Send + Sync + 'static) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, error)
+    }
+    let mut headers = reqwest::header::HeaderMap::new();
+    let mut allowed = false;
+    for origin in &options.header_origins {
+        allowed |= url::Url::parse(origin).map_err(invalid)?.origin() == url.origin();
+    }
+    if !allowed {
+        return Ok(headers);
+    }
+    for (name, value) in &options.headers {
+        let name = reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(invalid)?;
+        let mut value = reqwest::header::HeaderValue::from_str(value).map_err(invalid)?;
+        value.set_sensitive(true);
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// The URL a response from `url` redirects to, `None` if it isn't a redirect.
+#[cfg(feature = "urls_includes")]
+pub(crate) fn redirect_target(
+    url: &url::Url,
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+) -> Option<url::Url> {
+    if !status.is_redirection() || status == reqwest::StatusCode::NOT_MODIFIED {
+        return None;
+    }
+    let location = headers.get(reqwest::header::LOCATION)?.to_str().ok()?;
+    url.join(location).ok()
+}
+
+/// The error for a URL that redirects more than [`UrlOptions::max_redirects`] times.
+#[cfg(feature = "urls_includes")]
+pub(crate) fn too_many_redirects(url: &url::Url, options: &UrlOptions) -> std::io::Error {
+    let max = options.max_redirects;
+    std::io::Error::other(format!("{url} redirected more than {max} times"))
+}
+
+/// Requests `url` with `validators`, following redirects with the headers allowed for
+/// each of them.
+#[cfg(feature = "urls_includes")]
+fn send(
+    client: &reqwest::blocking::Client,
+    url: &url::Url,
+    options: &UrlOptions,
+    validators: &reqwest::header::HeaderMap,
+) -> std::io::Result<reqwest::blocking::Response> {
+    let mut url = url.clone();
+    for _ in 0..=options.max_redirects {
+        let response = client
+            .get(url.clone())
+            .headers(request_headers(options, &url)?)
+            .headers(validators.clone())
+            .send()
+            .map_err(std::io::Error::other)?;
+        match redirect_target(&url, response.status(), response.headers()) {
+            Some(target) => url = target,
+            None => return Ok(response),
+        }
+    }
+    Err(too_many_redirects(&url, options))
+}
+
+/// The most bytes read from a response: one more than [`UrlOptions::max_response_bytes`]
+/// and the input [`ConfigOptions::max_input_bytes`] still allows, so
+/// [`check_response_len`] can tell a response that is too long.
+#[cfg(feature = "urls_includes")]
+pub(crate) fn response_limit(options: &ConfigOptions, ctx: &Context) -> u64 {
+    let remaining = options
+        .max_input_bytes
+        .saturating_sub(ctx.usage.bytes.get());
+    let max_bytes = options.url_options.max_response_bytes.min(remaining);
+    (max_bytes as u64).saturating_add(1)
+}
+
+/// Fails with [`Error::ResponseTooLarge`] if the response from `url` is longer than
+/// [`UrlOptions::max_response_bytes`], or with [`Error::InputTooLarge`] if it doesn't fit
+/// into [`ConfigOptions::max_input_bytes`].
+#[cfg(feature = "urls_includes")]
+pub(crate) fn check_response_len(
+    url: &url::Url,
+    len: Option<u64>,
+    options: &ConfigOptions,
+    ctx: &Context,
+) -> Result<()> {
+    let Some(len) = len else {
+        return Ok(());
+    };
+    let max_bytes = options.url_options.max_response_bytes;
+    if len > max_bytes as u64 {
+        return Err(Error::ResponseTooLarge {
+            url: url.to_string(),
+            max_bytes,
+        });
+    }
+    let len = usize::try_from(len).unwrap_or(usize::MAX);
+    ctx.usage.check_bytes(len, options.max_input_bytes)
+}
+
 pub(crate) fn load_from_classpath(
     path: impl AsRef<Path>,
     options: ConfigOptions,
     ctx: Option<Context>,
-) -> Result<RawObject> {
+) -> Result<RawObject<'static>> {
     let path = path.as_ref();
     if !options.classpath.is_empty() && path.is_absolute() {
         return Err(Error::Io(std::io::Error::new(
@@ -206,35 +787,127 @@ pub(crate) fn load_from_classpath(
             "Absolute path in classpath",
         )));
     }
-    for classpath in &*options.classpath {
-        let candidate = Path::new(classpath).join(path);
-        match load_from_path(&candidate, options.clone(), ctx.clone()) {
-            Ok(raw) => {
-                return Ok(raw);
-            }
-            Err(Error::Io(_)) => {}
-            error => {
-                return error;
+    let ctx = ctx.unwrap_or_default();
+    let mut searched = vec![];
+    for provider in classpath_providers(&options, &ctx) {
+        let provider = provider?;
+        if let Some(dir) = provider.directory() {
+            match load_from_path(dir.join(path), options.clone(), Some(ctx.clone())) {
+                Ok(raw) => return Ok(raw),
+                result @ Err(Error::Io(_)) => searched.extend_from_slice(self::searched(&result)),
+                error => return error,
             }
+            continue;
+        }
+        match load_from_provider(provider.as_ref(), path, &options, Some(ctx.clone()))? {
+            Some(raw) => return Ok(raw),
+            None => searched.extend(provider_candidates(provider.as_ref(), path, &options)),
         }
     }
-    let message = format!(
-        "No configuration file (.conf, .json, .properties) was found at the given path: {} in classpath: [{}]",
-        path.display(),
-        options.classpath.join(", ")
-    );
-    Err(Error::Io(std::io::Error::new(
-        std::io::ErrorKind::NotFound,
-        message,
-    )))
+    Err(NotFound { searched }.into())
+}
+
+/// The providers classpath resources are looked up in, in order: the directories and
+/// archives of [`ConfigOptions::classpath`], then [`ConfigOptions::classpath_providers`].
+/// Archives are opened when reached.
+pub(crate) fn classpath_providers<'a>(
+    options: &'a ConfigOptions,
+    ctx: &'a Context,
+) -> impl Iterator<Item = Result<Rc<dyn ClasspathProvider>>> + 'a {
+    let classpath = options.classpath.iter().map(|classpath| {
+        #[cfg(feature = "zip")]
+        if crate::classpath::ArchiveClasspath::is_archive(Path::new(classpath)) {
+            let archive: Rc<dyn ClasspathProvider> = ctx.archives.open(classpath)?;
+            return Ok(archive);
+        }
+        #[cfg(not(feature = "zip"))]
+        let _ = ctx;
+        let dir = crate::classpath::DirectoryClasspath(PathBuf::from(classpath));
+        Ok(Rc::new(dir) as Rc<dyn ClasspathProvider>)
+    });
+    classpath.chain(options.classpath_providers.iter().cloned().map(Ok))
 }
 
-fn parse_json<R>(reader: R) -> Result<RawObject>
+/// The names of the resources [`load_from_provider`] looks up for `path`.
+pub(crate) fn provider_candidates(
+    provider: &dyn ClasspathProvider,
+    path: &Path,
+    options: &ConfigOptions,
+) -> Vec<String> {
+    candidate_files(path, &options.extensions)
+        .into_iter()
+        .map(|(candidate, _)| provider.name(&candidate))
+        .collect()
+}
+
+/// Loads every resource `provider` has for `path` like [`load_from_path`], or returns
+/// `None` if it has none of them.
+pub(crate) fn load_from_provider(
+    provider: &dyn ClasspathProvider,
+    path: &Path,
+    options: &ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<Option<RawObject<'static>>> {
+    let ctx = ctx.unwrap_or_default().with_dir(None);
+    let mut files = candidate_files(path, &options.extensions);
+    let cmp = &options.compare;
+    files.sort_by(|a, b| cmp(&a.1, &b.1));
+    let mut raw = None;
+    for (candidate, syntax) in files {
+        // One byte more than allowed, so parsing the contents tells they are too large.
+        let remaining = options
+            .max_input_bytes
+            .saturating_sub(ctx.usage.bytes.get());
+        let limit = (remaining as u64).saturating_add(1);
+        let name = provider.name(&candidate);
+        let source = SourceId::new(&name);
+        let ctx = ctx.enter_source(&name)?;
+        let location = options.cache_includes.then_some(name);
+        let cached = ctx
+            .contents
+            .get_location(location.as_deref(), &source, &ctx.include_chain);
+        let raw_obj = match cached {
+            Some(raw_obj) => raw_obj,
+            None => match provider.read_limited(&candidate, limit)? {
+                Some(contents) => {
+                    parse_contents(&contents, location, source, syntax, options, &ctx)?
+                }
+                None => continue,
+            },
+        };
+        raw = Some(RawObject::merge(raw.unwrap_or_default(), raw_obj));
+    }
+    Ok(raw)
+}
+
+/// Records `origin` on every key-value field that doesn't have one yet. Used for
+/// formats whose parsers don't report positions.
+fn attach_origin(object: &mut RawObject<'_>, origin: &Origin) {
+    fn attach_value(value: &mut RawValue<'_>, origin: &Origin) {
+        match value {
+            RawValue::Object(object) => attach_origin(object, origin),
+            RawValue::Array(array) => array.iter_mut().for_each(|v| attach_value(v, origin)),
+            _ => {}
+        }
+    }
+    for field in object.iter_mut() {
+        if field.origin().is_none() {
+            field.set_origin(origin.clone());
+        }
+        if let ObjectField::KeyValue { value, .. } = field {
+            attach_value(value, origin);
+        }
+    }
+}
+
+/// Parses a `.json` file as strict JSON. Strings are always quoted strings, so `${...}`
+/// inside them is never a substitution, and keys are never split at dots.
+fn parse_json<R>(reader: R) -> Result<RawObject<'static>>
 where
     R: std::io::Read,
 {
     let value: serde_json::Value = serde_json::from_reader(reader)?;
-    let value: RawValue = value.into();
+    let value: RawValue<'static> = value.into();
     if let RawValue::Object(raw_object) = value {
         Ok(raw_object)
     } else {
@@ -245,11 +918,20 @@ where
     }
 }
 
+/// Stops reading `reader` right after [`ConfigOptions::max_input_bytes`], so a single
+/// oversized token can't exhaust memory. The parser reports the limit.
+pub(crate) fn limit_reader<R: std::io::Read>(
+    reader: R,
+    options: &ConfigOptions,
+) -> std::io::Take<R> {
+    reader.take(options.max_input_bytes.saturating_add(1) as u64)
+}
+
 pub(crate) fn parse_hocon<'de, R>(
     read: R,
     options: ConfigOptions,
     ctx: Option<Context>,
-) -> Result<RawObject>
+) -> Result<RawObject<'de>>
 where
     R: crate::parser::read::Read<'de>,
 {
@@ -259,7 +941,7 @@ where
     }
 }
 
-fn parse_properties<R>(reader: R) -> Result<RawObject>
+fn parse_properties<R>(reader: R) -> Result<RawObject<'static>>
 where
     R: std::io::Read,
 {
@@ -272,52 +954,436 @@ where
     Ok(raw_object)
 }
 
-fn parse_environments() -> RawObject {
+/// Returns the environment layer, made of the variables of [`environment_vars`].
+pub(crate) fn parse_environments(options: &ConfigOptions) -> RawObject<'static> {
     let mut raw = RawObject::default();
-    for (key, value) in std::env::vars() {
-        raw.push(ObjectField::key_value(key, RawValue::quoted_string(value)));
+    for (key, value) in environment_vars(options) {
+        let origin = Origin::synthetic(Layer::Environment, Some(&key));
+        let mut field = ObjectField::key_value(key, RawValue::quoted_string(value));
+        field.set_origin(origin);
+        raw.push(field);
     }
     raw
 }
 
+/// Returns the environment variables, from [`crate::ResolveOptions::environment`] of
+/// `options` if set, otherwise from the process.
+pub(crate) fn environment_vars(options: &ConfigOptions) -> Vec<(String, String)> {
+    match &options.resolve.environment {
+        Some(environment) => environment
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+        None => std::env::vars().collect(),
+    }
+}
+
 pub(crate) fn load(
     path: impl AsRef<Path>,
     options: ConfigOptions,
     ctx: Option<Context>,
-) -> Result<RawObject> {
-    let env_raw = if options.use_system_environment {
-        Some(parse_environments())
+) -> Result<RawObject<'static>> {
+    load_all([path], options, ctx)
+}
+
+/// Loads `paths` in order into a single object, so later files override earlier ones.
+/// The environment variables, when enabled, are merged once below all of the files.
+pub(crate) fn load_all<I, P>(
+    paths: I,
+    options: ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut raw_obj = if options.use_system_environment {
+        parse_environments(&options)
     } else {
-        None
+        RawObject::default()
     };
-    let path = path.as_ref();
-    let raw = match load_from_path(path, options.clone(), ctx.clone()) {
-        Ok(raw) => raw,
-        Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
-            match load_from_classpath(path, options, ctx) {
-                Ok(raw) => raw,
-                Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
-                    let message = format!(
-                        "No configuration file (.conf, .json, .properties) was found at the given path: {} and classpath",
-                        path.display(),
-                    );
-                    return Err(Error::Io(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        message,
-                    )));
-                }
-                error => {
-                    return error;
+    // Share the parsed contents between the files.
+    let ctx = Some(ctx.unwrap_or_default());
+    for path in paths {
+        let raw = load_file(path.as_ref(), options.clone(), ctx.clone())?;
+        raw_obj = RawObject::merge(raw_obj, raw);
+    }
+    Ok(raw_obj)
+}
+
+/// Loads every file in `dir` matching `dir_options` and merges them in lexicographic
+/// order of their paths, like a `conf.d` directory.
+pub(crate) fn load_dir(
+    dir: impl AsRef<Path>,
+    dir_options: &DirectoryOptions,
+    options: ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>> {
+    fn collect(dir: &Path, dir_options: &DirectoryOptions, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if dir_options.recursive {
+                    collect(&path, dir_options, files)?;
                 }
+            } else if let Some(syntax) = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(Syntax::from_extension)
+                && dir_options.extensions.contains(&syntax)
+            {
+                files.push(path);
             }
         }
-        error => {
-            return error;
+        Ok(())
+    }
+    let mut files = vec![];
+    collect(dir.as_ref(), dir_options, &mut files)?;
+    files.sort();
+    load_all(files, options, ctx)
+}
+
+/// Loads `path` like [`load`], then merges the overlays `<name>.<overlay>.<extension>`
+/// that exist next to it on top, in the given order.
+pub(crate) fn load_with_overlays(
+    path: impl AsRef<Path>,
+    overlays: &[&str],
+    options: ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>> {
+    let path = path.as_ref();
+    let mut raw_obj = load(path, options.clone(), ctx.clone())?;
+    for overlay in overlays {
+        match load_file(&overlay_path(path, overlay), options.clone(), ctx.clone()) {
+            Ok(raw) => raw_obj = RawObject::merge(raw_obj, raw),
+            Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
         }
-    };
-    let raw_obj = match env_raw {
-        Some(env_raw) => RawObject::merge(env_raw, raw),
-        None => raw,
-    };
+    }
     Ok(raw_obj)
 }
+
+/// Inserts `overlay` before the extension of `path`, e.g. `application.conf` becomes
+/// `application.dev.conf`. Extension-less paths get the overlay appended.
+fn overlay_path(path: &Path, overlay: &str) -> PathBuf {
+    match path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(Syntax::from_extension)
+    {
+        Some(syntax) => path.with_extension(format!("{overlay}.{syntax}")),
+        None => {
+            let mut overlay_path = path.as_os_str().to_owned();
+            overlay_path.push(format!(".{overlay}"));
+            overlay_path.into()
+        }
+    }
+}
+
+fn load_file(
+    path: &Path,
+    options: ConfigOptions,
+    ctx: Option<Context>,
+) -> Result<RawObject<'static>> {
+    match load_from_path(path, options.clone(), ctx.clone()) {
+        Err(Error::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
+            let skipped = Error::Io(io)
+                .searched_locations()
+                .unwrap_or_default()
+                .to_vec();
+            searched_first(load_from_classpath(path, options, ctx), skipped)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use crate::config_options::ConfigOptions;
+    use crate::parser::Context;
+    use crate::syntax::Syntax;
+
+    use super::{load, parse_file};
+
+    #[test]
+    fn test_include_resolution() -> Result<()> {
+        use std::path::Path;
+
+        use crate::config_options::IncludeResolution;
+        use crate::{Config, Value};
+
+        let dir = tempfile::tempdir()?;
+        let conf = dir.path().join("conf");
+        std::fs::create_dir_all(conf.join("sub"))?;
+        std::fs::write(conf.join("main.conf"), "include \"sub/a\"\nb = 1")?;
+        std::fs::write(conf.join("sub/a.conf"), "include file(\"c.conf\")\na = 1")?;
+        std::fs::write(conf.join("sub/c.conf"), "c = 1")?;
+        let main = conf.join("main.conf");
+        let value: Value = Config::load(&main, None)?;
+        assert_eq!(value, Config::parse_str("a = 1, b = 1, c = 1", None)?);
+
+        let options = ConfigOptions {
+            include_resolution: IncludeResolution::WorkingDirectory,
+            ..Default::default()
+        };
+        let value: Value = Config::load(&main, Some(options))?;
+        assert_eq!(value, Config::parse_str("b = 1", None)?);
+
+        let ctx = Context::default().with_dir(Some(&conf));
+        let options = ConfigOptions {
+            expand_home: true,
+            ..Default::default()
+        };
+        let paths = super::include_paths(Path::new("~/app.conf"), &options, &ctx);
+        if let Some(home) = std::env::home_dir() {
+            assert_eq!(paths, vec![home.join("app.conf")]);
+        }
+        let paths = super::include_paths(Path::new("app.conf"), &options, &ctx);
+        assert_eq!(paths, vec![conf.join("app.conf"), "app.conf".into()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_includes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let lib = dir.path().join("lib.conf");
+        std::fs::write(&lib, "k = 1")?;
+        let main = dir.path().join("main.conf");
+        let include = format!("include file({:?})", lib.display().to_string());
+        std::fs::write(&main, format!("a {{ {include} }}\nb {{ {include} }}"))?;
+
+        let ctx = Context::default();
+        load(&main, ConfigOptions::default(), Some(ctx.clone()))?;
+        assert_eq!(ctx.contents.0.borrow().locations.len(), 2);
+        // The contents changed on disk aren't read again during the same load.
+        std::fs::write(&lib, "k = 2")?;
+        let cached = parse_file(&lib, Syntax::Hocon, &ConfigOptions::default(), &ctx)?;
+        assert_eq!(cached.to_string(), "{k: 1}");
+
+        let options = ConfigOptions {
+            cache_includes: false,
+            ..Default::default()
+        };
+        let ctx = Context::default();
+        load(&main, options.clone(), Some(ctx.clone()))?;
+        assert!(ctx.contents.0.borrow().locations.is_empty());
+        assert!(ctx.contents.0.borrow().contents.is_empty());
+        let changed = parse_file(&lib, Syntax::Hocon, &options, &ctx)?;
+        assert_eq!(changed.to_string(), "{k: 2}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_input_bytes_of_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("app.conf");
+        std::fs::write(&file, "a = 12345")?;
+        let options = ConfigOptions {
+            max_input_bytes: 8,
+            ..Default::default()
+        };
+        let error = parse_file(&file, Syntax::Hocon, &options, &Context::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            crate::error::Error::InputTooLarge { max_bytes: 8 }
+        ));
+        let options = ConfigOptions {
+            max_input_bytes: 9,
+            ..Default::default()
+        };
+        let raw = parse_file(&file, Syntax::Hocon, &options, &Context::default())?;
+        assert_eq!(raw.to_string(), "{a: 12345}");
+        Ok(())
+    }
+
+    /// Serves one connection on a local port with `response`, or never answers if it's
+    /// `None`. Returns the URL and the request that was received.
+    #[cfg(feature = "urls_includes")]
+    fn serve_once(
+        response: Option<String>,
+    ) -> std::io::Result<(url::Url, std::thread::JoinHandle<String>)> {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/app.conf", listener.local_addr()?);
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            while reader.read_line(&mut request).unwrap() > 2 {}
+            match response {
+                Some(response) => stream.write_all(response.as_bytes()).unwrap(),
+                None => std::thread::sleep(std::time::Duration::from_secs(2)),
+            }
+            request
+        });
+        Ok((url::Url::parse(&url).unwrap(), handle))
+    }
+
+    /// Answers one request per response, in order, then stops listening.
+    #[cfg(feature = "urls_includes")]
+    fn serve_all(
+        responses: Vec<String>,
+    ) -> std::io::Result<(url::Url, std::thread::JoinHandle<Vec<String>>)> {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/app.conf", listener.local_addr()?);
+        let handle = std::thread::spawn(move || {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                while reader.read_line(&mut request).unwrap() > 2 {}
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        Ok((url::Url::parse(&url).unwrap(), handle))
+    }
+
+    #[cfg(feature = "urls_includes")]
+    #[rstest::rstest]
+    #[case(None)]
+    #[case(Some(tempfile::tempdir().unwrap()))]
+    fn test_url_cache(#[case] dir: Option<tempfile::TempDir>) -> Result<()> {
+        use crate::config_options::{UrlCache, UrlOptions};
+
+        use super::load_from_url;
+
+        let cache = match &dir {
+            Some(dir) => UrlCache::Directory(dir.path().join("urls")),
+            None => UrlCache::Memory,
+        };
+        let options = ConfigOptions {
+            url_options: UrlOptions {
+                cache: Some(cache),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let close = "Connection: close\r\nContent-Length";
+        let (url, handle) = serve_all(vec![
+            format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\n{close}: 5\r\n\r\na = 1"),
+            format!("HTTP/1.1 304 Not Modified\r\n{close}: 0\r\n\r\n"),
+            format!("HTTP/1.1 503 Service Unavailable\r\n{close}: 0\r\n\r\n"),
+        ])?;
+        for _ in 0..3 {
+            let raw = load_from_url(url.clone(), options.clone(), None)?;
+            assert_eq!(raw.to_string(), "{a: 1}");
+        }
+        let requests = handle.join().unwrap();
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+        // The server is gone, the stale copy is used.
+        let raw = load_from_url(url.clone(), options.clone(), None)?;
+        assert_eq!(raw.to_string(), "{a: 1}");
+        // Unless it is too old.
+        let mut stale = options.clone();
+        stale.url_options.max_stale = Some(std::time::Duration::ZERO);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert!(load_from_url(url.clone(), stale, None).is_err());
+        // Requests with other headers don't share the cached response.
+        let mut authorized = options.clone();
+        authorized.url_options.headers = vec![("Authorization".into(), "Bearer secret".into())];
+        authorized.url_options.header_origins = vec![url.origin().ascii_serialization()];
+        assert!(load_from_url(url.clone(), authorized, None).is_err());
+        if let Some(dir) = &dir {
+            let files = std::fs::read_dir(dir.path().join("urls"))?.collect::<Vec<_>>();
+            assert_eq!(files.len(), 1);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let metadata = files[0].as_ref().unwrap().metadata()?;
+                assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+            }
+        }
+        // Without a cache, the load fails.
+        let options = ConfigOptions::default();
+        assert!(load_from_url(url, options, None).is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "urls_includes")]
+    #[test]
+    fn test_url_options() -> Result<()> {
+        use std::time::{Duration, Instant};
+
+        use crate::Error;
+        use crate::config_options::UrlOptions;
+
+        use super::load_from_url;
+
+        let body = "a = 1";
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let (url, handle) = serve_once(Some(ok.clone()))?;
+        let url_options = UrlOptions {
+            headers: vec![("Authorization".to_string(), "Bearer secret".to_string())],
+            header_origins: vec![url.origin().ascii_serialization()],
+            ..Default::default()
+        };
+        assert!(!format!("{url_options:?}").contains("secret"));
+        let options = ConfigOptions {
+            url_options,
+            ..Default::default()
+        };
+        let raw = load_from_url(url.clone(), options.clone(), None)?;
+        assert_eq!(raw.to_string(), "{a: 1}");
+        let secret = "authorization: Bearer secret";
+        assert!(handle.join().unwrap().contains(secret));
+
+        // A redirect to another origin is followed without the headers.
+        let (other, other_handle) = serve_once(Some(ok.clone()))?;
+        let redirect =
+            format!("HTTP/1.1 302 Found\r\nLocation: {other}\r\nContent-Length: 0\r\n\r\n");
+        let (url, handle) = serve_once(Some(redirect))?;
+        let mut options = options;
+        options.url_options.header_origins = vec![url.origin().ascii_serialization()];
+        let raw = load_from_url(url, options.clone(), None)?;
+        assert_eq!(raw.to_string(), "{a: 1}");
+        assert!(handle.join().unwrap().contains(secret));
+        assert!(!other_handle.join().unwrap().contains(secret));
+
+        // Included URLs of other origins don't get them either.
+        let (other, other_handle) = serve_once(Some(ok.clone()))?;
+        let body = format!("include url(\"{other}\")");
+        let include = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let (url, handle) = serve_once(Some(include))?;
+        options.url_options.header_origins = vec![url.origin().ascii_serialization()];
+        load_from_url(url, options.clone(), None)?;
+        assert!(handle.join().unwrap().contains(secret));
+        assert!(!other_handle.join().unwrap().contains(secret));
+
+        options.url_options.max_response_bytes = 4;
+        let (url, _) = serve_once(Some(ok.clone()))?;
+        let error = load_from_url(url, options.clone(), None).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ResponseTooLarge { max_bytes: 4, .. }
+        ));
+        // The input limit applies while the body is read, also without a length.
+        options.url_options.max_response_bytes = usize::MAX;
+        options.max_input_bytes = 4;
+        let unsized_ok = format!("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{body}");
+        let (url, _) = serve_once(Some(unsized_ok))?;
+        let error = load_from_url(url, options.clone(), None).unwrap_err();
+        assert!(matches!(error, Error::InputTooLarge { max_bytes: 4 }));
+        options.max_input_bytes = usize::MAX;
+
+        options.url_options.timeout = Some(Duration::from_millis(100));
+        let (url, _) = serve_once(None)?;
+        let start = Instant::now();
+        assert!(load_from_url(url, options, None).is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        Ok(())
+    }
+}
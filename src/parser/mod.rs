@@ -1,29 +1,108 @@
+mod arena;
 mod array;
+#[cfg(feature = "tokio")]
+pub(crate) mod async_loader;
 mod comment;
+pub mod cst;
+mod events;
 mod include;
 pub(crate) mod loader;
+mod multi;
 mod object;
 pub mod read;
 mod string;
 mod substitution;
+#[cfg(feature = "urls_includes")]
+pub(crate) mod url_cache;
 
-use std::rc::Rc;
+pub use arena::{ArenaField, ArenaNode, NodeId, RawArena};
+pub use events::{Event, Events};
+pub use multi::Documents;
+pub(crate) use string::needs_quotes;
 
-use derive_more::Constructor;
+use std::cell::Cell;
+use std::path::Path;
+use std::rc::Rc;
 
 use crate::Result;
 use crate::config_options::ConfigOptions;
-use crate::error::Error;
-use crate::parser::read::Read;
+#[cfg(doc)]
+use crate::config_options::IncludeResolution;
+use crate::error::{Error, IncludeSite};
+use crate::merge::intern::Interner;
+use crate::origin::{Origin, SourceId};
+use crate::parser::read::{Position, Read};
+use crate::raw::include::Inclusion;
+use crate::raw::layout::SourceText;
 use crate::raw::raw_object::RawObject;
 
-#[derive(Constructor, Default, Debug, Clone)]
+#[derive(Default, Debug, Clone)]
 pub(crate) struct Context {
-    pub(crate) include_chain: Vec<Rc<String>>,
+    pub(crate) include_chain: Vec<SourceId>,
+    /// The include statements that led to the source being parsed, one per entry of
+    /// `include_chain`, reported by [`Error::IncludeCycle`].
+    pub(crate) include_sites: Vec<IncludeSite>,
+    /// The canonical paths and URLs of the sources being parsed, outermost first.
+    pub(crate) loading: Vec<Rc<str>>,
     pub(crate) depth: usize,
+    /// The file path or URL currently being parsed, used to record field origins.
+    pub(crate) source: Option<SourceId>,
+    /// The directory of the file being parsed, see [`IncludeResolution::RelativeToFile`].
+    pub(crate) dir: Option<Rc<Path>>,
+    /// Keep include statements without loading the files they refer to.
+    pub(crate) skip_includes: bool,
+    /// The contents parsed so far, shared by all clones of the context.
+    pub(crate) contents: loader::ContentCache,
+    /// What was parsed so far, shared by all clones of the context.
+    pub(crate) usage: Rc<Usage>,
+    /// The keys parsed so far with [`crate::ResolveOptions::intern_keys`], shared by all
+    /// clones of the context.
+    pub(crate) keys: Rc<Interner>,
+    /// The clients fetching URLs, shared by all clones of the context.
+    #[cfg(feature = "urls_includes")]
+    pub(crate) url_clients: Rc<loader::UrlClients>,
+    /// The archives on the classpath, opened once per load and shared by all clones of the
+    /// context.
+    #[cfg(feature = "zip")]
+    pub(crate) archives: Rc<loader::Archives>,
+}
+
+/// The bytes and values parsed while loading one configuration, checked against the limits
+/// of [`ConfigOptions`].
+#[derive(Debug, Default)]
+pub(crate) struct Usage {
+    /// The bytes of the sources parsed completely.
+    pub(crate) bytes: Cell<usize>,
+    pub(crate) nodes: Cell<usize>,
+}
+
+impl Usage {
+    /// Counts `bytes` more input, failing if that exceeds `max_bytes`.
+    pub(crate) fn add_bytes(&self, bytes: usize, max_bytes: usize) -> Result<()> {
+        self.check_bytes(bytes, max_bytes)?;
+        self.bytes.set(self.bytes.get() + bytes);
+        Ok(())
+    }
+
+    /// Fails if `bytes` more input would exceed `max_bytes`.
+    pub(crate) fn check_bytes(&self, bytes: usize, max_bytes: usize) -> Result<()> {
+        if self.bytes.get().saturating_add(bytes) > max_bytes {
+            return Err(Error::InputTooLarge { max_bytes });
+        }
+        Ok(())
+    }
 }
 
 impl Context {
+    pub(crate) fn origin(&self, start: Position, end: Position) -> Origin {
+        Origin::parsed(
+            self.source.clone(),
+            Some(start),
+            Some(end),
+            self.include_chain.clone(),
+        )
+    }
+
     pub(crate) fn increase_depth(&mut self) -> usize {
         self.depth += 1;
         self.depth
@@ -33,6 +112,56 @@ impl Context {
         self.depth -= 1;
         self.depth
     }
+
+    /// A clone of this context for loading the source of `inclusion`, a statement of the
+    /// source being parsed.
+    ///
+    /// Fails with [`Error::IncludeDepthExceeded`] past [`ConfigOptions::max_include_depth`].
+    pub(crate) fn enter_include(
+        &self,
+        inclusion: &Inclusion<'_>,
+        options: &ConfigOptions,
+    ) -> Result<Context> {
+        let max_depth = options.max_include_depth;
+        if self.include_chain.len() >= max_depth {
+            return Err(Error::IncludeDepthExceeded { max_depth });
+        }
+        let site = IncludeSite {
+            // Includes loaded after parsing may not know the name of their source.
+            source: self.source.clone().or(self.include_chain.last().cloned()),
+            position: inclusion.span.map(|span| span.start),
+            path: inclusion.path.to_string(),
+        };
+        let mut ctx = self.clone();
+        ctx.include_chain.push(SourceId::new(&inclusion.path));
+        ctx.include_sites.push(site);
+        Ok(ctx)
+    }
+
+    /// A clone of this context for parsing the source at `location`, its canonical path or
+    /// URL. The same literal path may refer to different files from different directories,
+    /// and different paths to the same file, so cycles are told by the location.
+    ///
+    /// Fails with [`Error::IncludeCycle`] if the source is already being parsed, i.e. it
+    /// includes itself through the statements of `include_sites`.
+    pub(crate) fn enter_source(&self, location: &str) -> Result<Context> {
+        if self.loading.iter().any(|loading| **loading == *location) {
+            let chain = self.include_sites.clone();
+            return Err(Error::IncludeCycle { chain });
+        }
+        let mut ctx = self.clone();
+        ctx.loading.push(location.into());
+        Ok(ctx)
+    }
+
+    /// A clone of this context for parsing a source in `dir`, `None` for sources that
+    /// aren't files.
+    pub(crate) fn with_dir(&self, dir: Option<&Path>) -> Context {
+        Context {
+            dir: dir.map(Rc::from),
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -128,19 +257,109 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         Ok(false)
     }
 
-    pub fn parse(&mut self) -> Result<RawObject> {
-        self.drop_whitespace_and_comments()?;
-        let raw_obj = match self.reader.peek() {
+    /// Parses the whole input into a [`RawObject`].
+    ///
+    /// Syntax errors are reported as [`Error::Parse`] with the position of the reader
+    /// when the error occurred. I/O errors, depth limits and include errors are returned
+    /// as is, the latter already carry the positions of the included file.
+    ///
+    /// The parsed objects and arrays retain their source text if the reader keeps the
+    /// consumed input, which all readers of this crate do. Rendering writes unchanged
+    /// parts as they were, see [`crate::RenderOptions::preserve_layout`].
+    pub fn parse(&mut self) -> Result<RawObject<'de>> {
+        let mut object = self.parse_tree()?;
+        self.attach_source(&mut object);
+        Ok(object)
+    }
+
+    /// Parses the whole input, without attaching the source text.
+    pub(crate) fn parse_tree(&mut self) -> Result<RawObject<'de>> {
+        let object = self
+            .parse_document()
+            .map_err(|error| self.syntax_error(error))?;
+        let max_bytes = self.options.max_input_bytes;
+        self.ctx.usage.add_bytes(self.consumed_len(), max_bytes)?;
+        Ok(object)
+    }
+
+    /// Wraps a syntax error into an [`Error::Parse`] with the current position.
+    ///
+    /// Errors after more input than [`ConfigOptions::max_input_bytes`] was read become
+    /// [`Error::InputTooLarge`], since readers may stop right after the limit.
+    pub(crate) fn syntax_error(&self, error: Error) -> Error {
+        if let Err(error) = self.check_input_size() {
+            return error;
+        }
+        match error {
+            error @ (Error::Io(_)
+            | Error::Include { .. }
+            | Error::IncludeCycle { .. }
+            | Error::NestingDepthExceeded { .. }
+            | Error::IncludeDepthExceeded { .. }
+            | Error::InputTooLarge { .. }
+            | Error::TooManyObjectEntries { .. }
+            | Error::ArrayTooLong { .. }
+            | Error::TooManyNodes { .. }
+            | Error::Parse { .. }) => error,
+            error => Error::Parse {
+                origin: self.ctx.source.clone(),
+                position: self.reader.position(),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    fn consumed_len(&self) -> usize {
+        self.reader.consumed().map_or(0, <[u8]>::len)
+    }
+
+    /// Fails if the input read so far, together with the sources parsed before, exceeds
+    /// [`ConfigOptions::max_input_bytes`].
+    pub(crate) fn check_input_size(&self) -> Result<()> {
+        let max_bytes = self.options.max_input_bytes;
+        self.ctx.usage.check_bytes(self.consumed_len(), max_bytes)
+    }
+
+    /// Counts `n` parsed values against [`ConfigOptions::max_nodes`] and checks the size
+    /// of the input read so far.
+    pub(crate) fn count_nodes(&self, n: usize) -> Result<()> {
+        let max_nodes = self.options.max_nodes;
+        let nodes = self.ctx.usage.nodes.get().saturating_add(n);
+        if nodes > max_nodes {
+            return Err(Error::TooManyNodes { max_nodes });
+        }
+        self.ctx.usage.nodes.set(nodes);
+        self.check_input_size()
+    }
+
+    /// Attaches the text consumed by the reader to `object`, whose positions must be the
+    /// ones of this reader.
+    pub(crate) fn attach_source(&self, object: &mut RawObject) {
+        if let Some(text) = self.reader.consumed()
+            && let Ok(text) = std::str::from_utf8(text)
+        {
+            object.attach_source(&SourceText::new(text));
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<RawObject<'de>> {
+        let start = self.reader.position();
+        let mut fields = vec![];
+        let line_has_content = self.parse_trivia(&mut fields, false)?;
+        let mut raw_obj = match self.reader.peek() {
             Ok(ch) => {
                 if ch == b'{' {
-                    self.parse_object(false)?
+                    // The comments around the root braces belong to the root object.
+                    let mut object = self.parse_object(false)?;
+                    fields.append(&mut object.0);
+                    self.parse_trivia(&mut fields, true)?;
+                    object.0 = fields;
+                    object
                 } else {
-                    self.parse_braces_omitted_object()?
+                    self.parse_braces_omitted_object(fields, line_has_content)?
                 }
             }
-            Err(Error::Eof) => {
-                return Ok(RawObject::default());
-            }
+            Err(Error::Eof) => RawObject::new(fields),
             Err(err) => {
                 return Err(err);
             }
@@ -158,6 +377,7 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 return Err(err);
             }
         }
+        raw_obj.set_span(start, self.reader.position());
         Ok(raw_obj)
     }
 }
@@ -168,8 +388,10 @@ mod tests {
 
     use crate::Result;
     use crate::config_options::ConfigOptions;
+    use crate::error::Error;
     use crate::parser::HoconParser;
-    use crate::parser::read::StreamRead;
+    use crate::parser::read::{CopyRead, Position, SliceRead, StrRead, StreamRead};
+    use crate::raw::raw_object::RawObject;
     use rstest::rstest;
 
     #[rstest]
@@ -190,4 +412,150 @@ mod tests {
         parser.parse()?;
         Ok(())
     }
+
+    #[rstest]
+    #[case("a = 1\nb = {\n  c = ]\n}", Position::new(3, 6))]
+    #[case("a = \"x\\q\"", Position::new(1, 8))]
+    #[case("a = ${b\nc = 1", Position::new(1, 7))]
+    #[case("a = 1\nb = \"open", Position::new(2, 9))]
+    #[case("include foo(\"x\")", Position::new(1, 9))]
+    fn test_parse_error_position(#[case] input: &str, #[case] expected: Position) {
+        fn position(result: Result<RawObject>) -> Position {
+            match result {
+                Err(Error::Parse { position, .. }) => position,
+                other => panic!("expected a parse error, got {other:?}"),
+            }
+        }
+        let stream = StreamRead::new(BufReader::new(input.as_bytes()));
+        assert_eq!(position(HoconParser::new(stream).parse()), expected);
+        let slice = SliceRead::new(input.as_bytes());
+        assert_eq!(position(HoconParser::new(slice).parse()), expected);
+        let str = StrRead::new(input);
+        assert_eq!(position(HoconParser::new(str).parse()), expected);
+    }
+
+    #[test]
+    fn test_parse_borrows_strings() -> Result<()> {
+        use crate::raw::field::ObjectField;
+        use crate::raw::raw_string::{CowStr, RawString};
+        use crate::raw::raw_value::RawValue;
+
+        fn borrowed(string: &RawString) -> Vec<bool> {
+            match string {
+                RawString::QuotedString(s)
+                | RawString::UnquotedString(s)
+                | RawString::MultilineString(s) => vec![matches!(s, CowStr::Borrowed(_))],
+                RawString::PathExpression(paths) => paths.iter().flat_map(borrowed).collect(),
+            }
+        }
+        // Escapes and whitespace inside a path expression need a copy.
+        let input = "a.\"b\" = x\nc = \"y\\n\"\n\"d\" .e = \"\"\"z\"\"\"";
+        let expected = [
+            (vec![true, true], vec![true]),
+            (vec![true], vec![false]),
+            (vec![false, true], vec![true]),
+        ];
+        fn fields(object: &RawObject) -> Vec<(Vec<bool>, Vec<bool>)> {
+            let mut fields = vec![];
+            for field in object.fields() {
+                if let ObjectField::KeyValue {
+                    key,
+                    value: RawValue::String(value),
+                    ..
+                } = field
+                {
+                    fields.push((borrowed(key), borrowed(value)));
+                }
+            }
+            fields
+        }
+        let object = HoconParser::new(StrRead::new(input)).parse()?;
+        assert_eq!(fields(&object), expected);
+        let stream = StreamRead::new(BufReader::new(input.as_bytes()));
+        assert_eq!(HoconParser::new(stream).parse()?, object);
+        // The copied tree outlives its input.
+        let copied: RawObject<'static> =
+            HoconParser::new(CopyRead::new(input.to_string().as_bytes())).parse()?;
+        assert!(
+            fields(&copied)
+                .into_iter()
+                .all(|(key, value)| !key.contains(&true) && !value.contains(&true))
+        );
+        assert_eq!(copied, object);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("a = [1, 2, 3]", "max_array_len", 2, true)]
+    #[case("a = [1, 2, 3]", "max_array_len", 3, false)]
+    #[case("a = 1, b { c = 2, d = 3 }", "max_object_entries", 1, true)]
+    #[case("a = 1, b { c = 2, d = 3 }", "max_object_entries", 2, false)]
+    #[case("a = [1, 2], b = x y", "max_nodes", 4, true)]
+    #[case("a = [1, 2], b = x y", "max_nodes", 5, false)]
+    #[case("a = \"long string\"", "max_input_bytes", 10, true)]
+    #[case("a = \"long string\"", "max_input_bytes", 17, false)]
+    fn test_limits(
+        #[case] input: &str,
+        #[case] limit: &str,
+        #[case] max: usize,
+        #[case] exceeded: bool,
+    ) {
+        let mut options = ConfigOptions::default();
+        match limit {
+            "max_array_len" => options.max_array_len = max,
+            "max_object_entries" => options.max_object_entries = max,
+            "max_nodes" => options.max_nodes = max,
+            _ => options.max_input_bytes = max,
+        }
+        let str = HoconParser::with_options(StrRead::new(input), options.clone()).parse();
+        let reader =
+            crate::Config::parse_reader::<_, crate::Value>(input.as_bytes(), Some(options));
+        if !exceeded {
+            assert!(str.is_ok() && reader.is_ok(), "{str:?} {reader:?}");
+            return;
+        }
+        for error in [str.unwrap_err(), reader.unwrap_err()] {
+            assert!(
+                matches!(
+                    error,
+                    Error::ArrayTooLong { .. }
+                        | Error::TooManyObjectEntries { .. }
+                        | Error::TooManyNodes { .. }
+                        | Error::InputTooLarge { .. }
+                ),
+                "{error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_limits_include_files() -> Result<()> {
+        let options = ConfigOptions {
+            classpath: std::rc::Rc::new(vec!["resources".to_string()]),
+            ..Default::default()
+        };
+        let result: crate::Value = crate::Config::load("include.conf", Some(options.clone()))?;
+        let nodes = result.entry_set().len();
+        let options = ConfigOptions {
+            max_nodes: nodes,
+            ..options
+        };
+        let result = crate::Config::load::<crate::Value>("include.conf", Some(options.clone()));
+        assert!(
+            matches!(result, Err(Error::TooManyNodes { .. })),
+            "{result:?}"
+        );
+        let main = std::fs::metadata("resources/include.conf")?.len() as usize;
+        let options = ConfigOptions {
+            max_nodes: usize::MAX,
+            max_input_bytes: main,
+            ..options
+        };
+        let result = crate::Config::load::<crate::Value>("include.conf", Some(options));
+        assert!(
+            matches!(result, Err(Error::InputTooLarge { .. })),
+            "{result:?}"
+        );
+        Ok(())
+    }
 }
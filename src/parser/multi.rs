@@ -0,0 +1,206 @@
+use crate::Result;
+use crate::error::Error;
+use crate::parser::HoconParser;
+use crate::parser::read::{CopyRead, Read};
+use crate::raw::field::ObjectField;
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_value::RawValue;
+
+/// The documents of a stream, parsed one at a time, see [`HoconParser::parse_multi`].
+pub struct Documents<'a, R> {
+    parser: &'a mut HoconParser<R>,
+    delimiter: Vec<u8>,
+    /// The number of lines consumed so far.
+    lines: usize,
+    done: bool,
+}
+
+impl<'de, R: Read<'de>> HoconParser<R> {
+    /// Parses an input that contains several documents, separated by lines that consist
+    /// of `delimiter`, e.g. `---`.
+    ///
+    /// Documents are read lazily, so snippets arriving over a pipe are parsed as soon as
+    /// their delimiter line is read. Whitespace around the delimiter is ignored, and the
+    /// delimiter is recognized on any line, even inside multiline strings. Documents that
+    /// are empty or contain only whitespace are skipped. Positions in errors and origins
+    /// are relative to the whole input.
+    ///
+    /// A document that fails to parse yields an error and the next document is parsed as
+    /// usual. I/O errors end the iteration.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::parser::HoconParser;
+    /// use hocon_rs::parser::read::StrRead;
+    ///
+    /// let input = "a = 1\n---\nb = 2\n---\n";
+    /// let mut parser = HoconParser::new(StrRead::new(input));
+    /// let documents = parser.parse_multi("---").collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(documents.len(), 2);
+    /// ```
+    pub fn parse_multi(&mut self, delimiter: &str) -> Documents<'_, R> {
+        Documents {
+            parser: self,
+            delimiter: delimiter.trim().as_bytes().to_vec(),
+            lines: 0,
+            done: false,
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> Documents<'_, R> {
+    /// Reads the lines up to the next delimiter line or the end of the input. Returns the
+    /// document without the delimiter line and the number of lines consumed.
+    fn read_document(&mut self) -> Result<(Vec<u8>, usize)> {
+        let mut document = vec![];
+        let mut lines = 0;
+        loop {
+            let start = document.len();
+            let eof = loop {
+                match self.parser.reader.peek() {
+                    Ok(byte) => {
+                        self.parser.reader.next()?;
+                        document.push(byte);
+                        if byte == b'\n' {
+                            break false;
+                        }
+                    }
+                    Err(Error::Eof) => break true,
+                    Err(error) => return Err(error),
+                }
+            };
+            lines += 1;
+            if document[start..].trim_ascii() == self.delimiter {
+                document.truncate(start);
+                return Ok((document, lines));
+            }
+            if eof {
+                self.done = true;
+                return Ok((document, lines));
+            }
+        }
+    }
+}
+
+impl<'de, R: Read<'de>> Iterator for Documents<'_, R> {
+    type Item = Result<RawObject<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let (document, lines) = match self.read_document() {
+                Ok(document) => document,
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            };
+            let offset = self.lines;
+            self.lines += lines;
+            if document.trim_ascii().is_empty() {
+                continue;
+            }
+            let mut parser = HoconParser::with_options_and_ctx(
+                CopyRead::new(&document),
+                self.parser.options.clone(),
+                self.parser.ctx.clone(),
+            );
+            let result = match parser.parse_tree() {
+                Ok(mut object) => {
+                    shift_object(&mut object, offset);
+                    parser.attach_source(&mut object);
+                    Ok(object)
+                }
+                Err(Error::Parse {
+                    origin,
+                    mut position,
+                    error,
+                }) => {
+                    position.line += offset;
+                    Err(Error::Parse {
+                        origin,
+                        position,
+                        error,
+                    })
+                }
+                Err(error) => Err(error),
+            };
+            return Some(result);
+        }
+        None
+    }
+}
+
+/// Moves the origins of the fields parsed from a document down by `lines`. Fields of
+/// included files keep their positions.
+fn shift_object(object: &mut RawObject, lines: usize) {
+    for field in object.iter_mut() {
+        if let ObjectField::KeyValue { value, origin, .. } = field {
+            if let Some(origin) = origin {
+                for position in [&mut origin.position, &mut origin.end]
+                    .into_iter()
+                    .flatten()
+                {
+                    position.line += lines;
+                }
+            }
+            shift_value(value, lines);
+        }
+    }
+}
+
+fn shift_value(value: &mut RawValue, lines: usize) {
+    match value {
+        RawValue::Object(object) => shift_object(object, lines),
+        RawValue::Array(array) => array.iter_mut().for_each(|v| shift_value(v, lines)),
+        RawValue::Concat(concat) => concat
+            .values_mut()
+            .iter_mut()
+            .for_each(|v| shift_value(v, lines)),
+        RawValue::AddAssign(add_assign) => shift_value(add_assign, lines),
+        RawValue::Boolean(_)
+        | RawValue::Null
+        | RawValue::String(_)
+        | RawValue::Number(_)
+        | RawValue::NonFinite(_)
+        | RawValue::Substitution(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::Result;
+    use crate::error::Error;
+    use crate::parser::HoconParser;
+    use crate::parser::read::{Position, StreamRead};
+    use crate::path::Path;
+
+    #[test]
+    fn test_parse_multi() -> Result<()> {
+        let input =
+            "---\na = 1\n  ---  \n\n--- \nb {\n  c = \"\"\"x\"\"\"\n}\n---\nd = }\n---\ne = 2";
+        let stream = StreamRead::new(BufReader::new(input.as_bytes()));
+        let mut parser = HoconParser::new(stream);
+        let documents = parser.parse_multi("---").collect::<Vec<_>>();
+        assert_eq!(documents.len(), 4);
+        let mut documents = documents.into_iter();
+
+        let first = documents.next().unwrap()?;
+        assert_eq!(first.to_string(), "{a: 1}");
+        let origin = first.get_origin(&Path::from_str("a")?).unwrap();
+        assert_eq!(origin.position, Some(Position::new(2, 0)));
+
+        let second = documents.next().unwrap()?;
+        let origin = second.get_origin(&Path::from_str("b.c")?).unwrap();
+        assert_eq!(origin.position, Some(Position::new(7, 2)));
+
+        match documents.next().unwrap() {
+            Err(Error::Parse { position, .. }) => assert_eq!(position.line, 10),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+        let last = documents.next().unwrap()?;
+        assert_eq!(last.to_string(), "{e: 2}");
+        Ok(())
+    }
+}
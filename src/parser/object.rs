@@ -1,13 +1,19 @@
 use crate::Result;
 use crate::error::Error;
+use crate::number::NonFinite;
 use crate::parser::HoconParser;
 use crate::parser::include::INCLUDE;
-use crate::parser::read::Read;
+use crate::parser::read::{Position, Read, Span};
 use crate::parser::string::TRIPLE_DOUBLE_QUOTE;
+use crate::raw::space::Space;
 use crate::raw::{
-    comment::Comment, field::ObjectField, raw_object::RawObject, raw_string::RawString,
+    comment::Comment,
+    field::{FieldSpans, ObjectField},
+    raw_object::RawObject,
+    raw_string::RawString,
     raw_value::RawValue,
 };
+use serde_json::Number;
 use std::str::FromStr;
 
 #[macro_export]
@@ -22,24 +28,27 @@ macro_rules! try_peek {
 }
 
 impl<'de, R: Read<'de>> HoconParser<R> {
-    pub(crate) fn parse_key(&mut self) -> Result<RawString> {
+    pub(crate) fn parse_key(&mut self) -> Result<RawString<'de>> {
         self.drop_horizontal_whitespace()?;
         self.parse_path_expression()
     }
 
-    pub(crate) fn parse_value(&mut self) -> Result<RawValue> {
+    /// Parses a value, returning it with the position right after it, before the whitespace
+    /// that follows it.
+    pub(crate) fn parse_value(&mut self) -> Result<(RawValue<'de>, Position)> {
         self.drop_whitespace()?;
+        let mut end = self.reader.position();
         let mut values = vec![];
         let mut scratch = vec![];
         let mut spaces = vec![];
         let mut prev_space = None;
         #[inline]
-        fn push_value_and_space(
-            values: &mut Vec<RawValue>,
-            spaces: &mut Vec<Option<String>>,
-            mut space_after_value: Option<String>,
-            v: RawValue,
-        ) -> Option<String> {
+        fn push_value_and_space<'de>(
+            values: &mut Vec<RawValue<'de>>,
+            spaces: &mut Vec<Option<Space>>,
+            mut space_after_value: Option<Space>,
+            v: RawValue<'de>,
+        ) -> Option<Space> {
             if !values.is_empty() {
                 spaces.push(space_after_value);
                 space_after_value = None;
@@ -52,10 +61,10 @@ impl<'de, R: Read<'de>> HoconParser<R> {
             match ch {
                 b'[' => {
                     // Parse array
-                    let max_depth = self.options.max_depth;
+                    let max_depth = self.options.max_nesting_depth;
                     let current_depth = self.ctx.increase_depth();
                     if current_depth > max_depth {
-                        return Err(Error::RecursionDepthExceeded { max_depth });
+                        return Err(Error::NestingDepthExceeded { max_depth });
                     }
                     let array = self.parse_array(false)?;
                     self.ctx.decrease_depth();
@@ -64,10 +73,10 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 }
                 b'{' => {
                     // Parse object
-                    let max_depth = self.options.max_depth;
+                    let max_depth = self.options.max_nesting_depth;
                     let current_depth = self.ctx.increase_depth();
                     if current_depth > max_depth {
-                        return Err(Error::RecursionDepthExceeded { max_depth });
+                        return Err(Error::NestingDepthExceeded { max_depth });
                     }
                     let object = self.parse_object(false)?;
                     self.ctx.decrease_depth();
@@ -80,10 +89,10 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                         && chars == TRIPLE_DOUBLE_QUOTE
                     {
                         let multiline = self.parse_multiline_string(false)?;
-                        RawValue::String(RawString::MultilineString(multiline))
+                        RawValue::String(RawString::multiline(multiline))
                     } else {
                         let quoted = self.parse_quoted_string(false)?;
-                        RawValue::String(RawString::QuotedString(quoted))
+                        RawValue::String(RawString::quoted(quoted))
                     };
                     prev_space = push_value_and_space(&mut values, &mut spaces, prev_space, v);
                 }
@@ -123,20 +132,19 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                         scratch.clear();
                         self.parse_horizontal_whitespace(&mut scratch)?;
                         let space = unsafe { str::from_utf8_unchecked(&scratch) };
-                        if space.is_empty() {
-                            prev_space = None
-                        } else {
-                            prev_space = Some(space.to_string());
-                        }
+                        prev_space = Space::new(space);
+                        continue;
                     } else {
                         let unquoted = self.parse_unquoted_string()?;
-                        let v = RawValue::String(RawString::UnquotedString(unquoted));
+                        let v = RawValue::String(RawString::unquoted(unquoted));
                         prev_space = push_value_and_space(&mut values, &mut spaces, prev_space, v);
                     }
                 }
             };
+            end = self.reader.position();
         }
-        match values.len() {
+        self.count_nodes(values.len())?;
+        let value = match values.len() {
             0 => Err(Error::UnexpectedToken {
                 expected: "value",
                 found_beginning: 0,
@@ -144,7 +152,7 @@ impl<'de, R: Read<'de>> HoconParser<R> {
             1 => {
                 let v = values.remove(0);
                 let v = if let RawValue::String(s) = v {
-                    Self::resolve_unquoted_string(s)
+                    self.resolve_unquoted_string(s)
                 } else {
                     v
                 };
@@ -154,21 +162,39 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 debug_assert_eq!(values.len(), spaces.len() + 1);
                 RawValue::concat(values, spaces)
             }
-        }
+        }?;
+        Ok((value, end))
     }
 
     // TODO if key parse success and value parse error, should report an error.
-    pub(crate) fn parse_key_value(&mut self) -> Result<(RawString, RawValue)> {
+    pub(crate) fn parse_key_value(
+        &mut self,
+    ) -> Result<(RawString<'de>, RawValue<'de>, FieldSpans)> {
         self.drop_whitespace()?;
-        let key = self.parse_key()?;
+        let key_start = self.reader.position();
+        let (mut key, key_end) = self.parse_path_expression_spanned()?;
+        if self.options.resolve.intern_keys {
+            key = key.intern(&self.ctx.keys);
+        }
         self.drop_whitespace()?;
         let is_add_assign = self.drop_kv_separator()?;
         self.drop_whitespace()?;
-        let mut value = self.parse_value()?;
+        let value_start = self.reader.position();
+        let (mut value, value_end) = self.parse_value()?;
         if is_add_assign {
             value = RawValue::add_assign(value)
         }
-        Ok((key, value))
+        let spans = FieldSpans {
+            key: Span {
+                start: key_start,
+                end: key_end,
+            },
+            value: Span {
+                start: value_start,
+                end: value_end,
+            },
+        };
+        Ok((key, value, spans))
     }
 
     pub fn drop_kv_separator(&mut self) -> Result<bool> {
@@ -200,30 +226,49 @@ impl<'de, R: Read<'de>> HoconParser<R> {
     }
 
     #[inline]
-    pub(crate) fn parse_object_field(&mut self) -> Result<ObjectField> {
+    pub(crate) fn parse_object_field(&mut self) -> Result<ObjectField<'de>> {
         let ch = self.reader.peek()?;
         // It maybe an include syntax, we need to peek more chars to determine.
         let field = if ch == b'i' && self.reader.peek_n(7)? == INCLUDE {
             let mut inclusion = self.parse_include()?;
-            self.parse_inclusion(&mut inclusion)?;
+            if !self.ctx.skip_includes {
+                self.parse_inclusion(&mut inclusion)?;
+            }
             ObjectField::inclusion(inclusion)
         } else {
-            let (key, value) = self.parse_key_value()?;
-            ObjectField::key_value(key, value)
+            let position = self.reader.position();
+            let (key, value, spans) = self.parse_key_value()?;
+            let mut field = ObjectField::key_value(key, value);
+            field.set_origin(self.ctx.origin(position, self.reader.position()));
+            field.set_spans(spans);
+            field
         };
         Ok(field)
     }
 
-    pub(crate) fn parse_braces_omitted_object(&mut self) -> Result<RawObject> {
-        let mut fields = vec![];
+    /// Parses the fields of an object up to the closing brace or the end of the input.
+    ///
+    /// `fields` holds the comments and blank lines already read before the first field, and
+    /// `line_has_content` tells whether the current line holds an opening brace.
+    pub(crate) fn parse_braces_omitted_object(
+        &mut self,
+        mut fields: Vec<ObjectField<'de>>,
+        mut line_has_content: bool,
+    ) -> Result<RawObject<'de>> {
+        let mut entries = 0;
         loop {
-            self.drop_whitespace_and_comments()?;
+            self.parse_trivia(&mut fields, line_has_content)?;
             let ch = self.reader.peek()?;
             if ch == b'}' {
                 break;
             }
             match self.parse_object_field() {
                 Ok(field) => {
+                    entries += 1;
+                    let max_entries = self.options.max_object_entries;
+                    if entries > max_entries {
+                        return Err(Error::TooManyObjectEntries { max_entries });
+                    }
                     fields.push(field);
                 }
                 Err(Error::Eof) => {
@@ -233,16 +278,35 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                     return Err(err);
                 }
             }
-            self.drop_whitespace_and_comments()?;
-            if self.drop_comma_separator()? {
-                break;
+            self.drop_horizontal_whitespace()?;
+            let comma = self.reader.peek().is_ok_and(|ch| ch == b',');
+            if comma {
+                self.reader.discard(1)?;
+                self.drop_horizontal_whitespace()?;
+            }
+            // A comment on the same line as the field belongs to the field.
+            match self.parse_comment() {
+                Ok((ty, content)) => {
+                    if let Some(field) = fields.last_mut() {
+                        field.set_comment(Comment::new(content, ty));
+                    }
+                }
+                Err(Error::Eof | Error::UnexpectedToken { .. }) => {}
+                Err(err) => return Err(err),
+            }
+            line_has_content = true;
+            if !comma {
+                line_has_content = self.parse_trivia(&mut fields, line_has_content)?;
+                if self.drop_comma_separator()? {
+                    break;
+                }
             }
         }
         let raw_obj = RawObject::new(fields);
         Ok(raw_obj)
     }
 
-    pub(crate) fn parse_object(&mut self, verify_delimiter: bool) -> Result<RawObject> {
+    pub(crate) fn parse_object(&mut self, verify_delimiter: bool) -> Result<RawObject<'de>> {
         if verify_delimiter {
             let ch = self.reader.peek()?;
             if ch != b'{' {
@@ -252,8 +316,9 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 });
             }
         }
+        let start = self.reader.position();
         self.reader.discard(1)?;
-        let raw_obj = self.parse_braces_omitted_object()?;
+        let mut raw_obj = self.parse_braces_omitted_object(vec![], true)?;
         let ch = self.reader.peek()?;
         if ch != b'}' {
             return Err(Error::UnexpectedToken {
@@ -262,37 +327,74 @@ impl<'de, R: Read<'de>> HoconParser<R> {
             });
         }
         self.reader.discard(1)?;
+        raw_obj.set_span(start, self.reader.position());
         Ok(raw_obj)
     }
 
-    pub(crate) fn resolve_unquoted_string(string: RawString) -> RawValue {
+    pub(crate) fn resolve_unquoted_string(&self, string: RawString<'de>) -> RawValue<'de> {
         if let RawString::UnquotedString(unquoted) = string {
             match &*unquoted {
                 "true" => RawValue::Boolean(true),
                 "false" => RawValue::Boolean(false),
                 "null" => RawValue::Null,
-                other => match serde_json::Number::from_str(other) {
-                    Ok(number) => RawValue::Number(number),
-                    Err(_) => RawValue::unquoted_string(unquoted),
-                },
+                other
+                    if self.options.non_finite_numbers
+                        && self.options.extended()
+                        && let Some(non_finite) = NonFinite::parse(other) =>
+                {
+                    RawValue::NonFinite(non_finite)
+                }
+                other => {
+                    let number = Number::from_str(other).ok().or_else(|| {
+                        let extended = self.options.extended_numbers && self.options.extended();
+                        extended.then(|| parse_extended_number(other)).flatten()
+                    });
+                    match number {
+                        Some(number) => RawValue::Number(number),
+                        None => RawValue::unquoted_string(unquoted),
+                    }
+                }
             }
         } else {
             RawValue::String(string)
         }
     }
 
-    #[allow(unused)]
-    pub(crate) fn parse_newline_comments(&mut self) -> Result<Vec<ObjectField>> {
-        let mut fields = vec![];
+    /// Parses the whitespace and comments between fields. Comments become
+    /// [`ObjectField::NewlineComment`]s and empty lines [`ObjectField::BlankLine`]s.
+    ///
+    /// `line_has_content` tells whether the current line already holds a field, comment or
+    /// brace, whose line break doesn't end an empty line. Returns the same for the line
+    /// the reader stops on.
+    pub(crate) fn parse_trivia(
+        &mut self,
+        fields: &mut Vec<ObjectField>,
+        mut line_has_content: bool,
+    ) -> Result<bool> {
         loop {
+            loop {
+                match self.reader.peek_whitespace() {
+                    Ok(Some(n)) => {
+                        for _ in 0..n {
+                            if self.reader.next()? == b'\n' {
+                                if !line_has_content {
+                                    fields.push(ObjectField::BlankLine);
+                                }
+                                line_has_content = false;
+                            }
+                        }
+                    }
+                    Ok(None) | Err(Error::Eof) => break,
+                    Err(err) => return Err(err),
+                }
+            }
             match self.parse_comment() {
                 Ok((ty, content)) => {
                     let comment = Comment::new(content, ty);
                     fields.push(ObjectField::newline_comment(comment));
+                    line_has_content = true;
                 }
-                Err(Error::Eof | Error::UnexpectedToken { .. }) => {
-                    break Ok(fields);
-                }
+                Err(Error::Eof | Error::UnexpectedToken { .. }) => break Ok(line_has_content),
                 Err(err) => {
                     return Err(err);
                 }
@@ -300,3 +402,40 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         }
     }
 }
+
+/// Parses a hexadecimal number like `0xFF` or a number with `_` between its digits like
+/// `1_000_000`, see [`crate::ConfigOptions::extended_numbers`].
+fn parse_extended_number(s: &str) -> Option<Number> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit() || b == b'_') {
+            return None;
+        }
+        let value =
+            u64::from_str_radix(&remove_separators(hex, u8::is_ascii_hexdigit)?, 16).ok()?;
+        return if negative {
+            0i64.checked_sub_unsigned(value).map(Number::from)
+        } else {
+            Some(Number::from(value))
+        };
+    }
+    if !digits.contains('_') {
+        return None;
+    }
+    Number::from_str(&remove_separators(s, u8::is_ascii_digit)?).ok()
+}
+
+/// Removes the `_` from `digits`, if every one of them is between two digits.
+fn remove_separators(digits: &str, is_digit: fn(&u8) -> bool) -> Option<String> {
+    let bytes = digits.as_bytes();
+    let separated = bytes.iter().enumerate().all(|(i, b)| {
+        *b != b'_' || (i > 0 && is_digit(&bytes[i - 1]) && bytes.get(i + 1).is_some_and(is_digit))
+    });
+    separated.then(|| digits.replace('_', ""))
+}
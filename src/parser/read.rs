@@ -1,6 +1,6 @@
 use std::str;
 
-use derive_more::{Deref, DerefMut};
+use derive_more::{Constructor, Deref, DerefMut};
 
 use crate::Result;
 use crate::error::Error;
@@ -156,12 +156,32 @@ fn parse_escaped_unicode<'de, R: Read<'de>>(reader: &mut R, scratch: &mut Vec<u8
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// A location inside the parsed input. Lines start at 1, columns start at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Constructor)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
 }
 
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A range inside the parsed input, `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
 pub enum Reference<'b, 'c, T>
 where
     T: ?Sized + 'static,
@@ -184,6 +204,19 @@ where
     }
 }
 
+impl<'b, T> Reference<'b, '_, T>
+where
+    T: ?Sized + ToOwned + 'static,
+{
+    /// Keeps a borrow of the input, copies what was copied into the scratch buffer.
+    pub fn into_cow(self) -> std::borrow::Cow<'b, T> {
+        match self {
+            Reference::Borrowed(b) => std::borrow::Cow::Borrowed(b),
+            Reference::Copied(c) => std::borrow::Cow::Owned(c.to_owned()),
+        }
+    }
+}
+
 pub trait Read<'de> {
     fn position(&self) -> Position;
 
@@ -203,6 +236,12 @@ pub trait Read<'de> {
 
     fn next(&mut self) -> Result<u8>;
 
+    /// Returns all bytes consumed so far, if the reader keeps them. The parser uses them to
+    /// retain the source text of the objects and arrays it parsed.
+    fn consumed(&self) -> Option<&[u8]> {
+        None
+    }
+
     #[inline]
     fn discard(&mut self, n: usize) -> Result<()> {
         for _ in 0..n {
@@ -259,26 +298,33 @@ pub trait Read<'de> {
     }
 }
 
+/// Reads HOCON from a byte stream.
+///
+/// The encoding is detected from a byte order mark: UTF-16LE and UTF-16BE streams are
+/// decoded to UTF-8, and a UTF-8 byte order mark is skipped. Streams without one are read
+/// as UTF-8.
 pub struct StreamRead<R: std::io::Read> {
-    inner: R,
+    inner: Decode<R>,
     buffer: [u8; DEFAULT_BUFFER_SIZE],
     head: usize,
     tail: usize,
     eof: bool,
     line: usize,
     col: usize,
+    consumed: Vec<u8>,
 }
 
 impl<R: std::io::Read> StreamRead<R> {
     pub fn new(reader: R) -> Self {
         StreamRead {
-            inner: reader,
+            inner: Decode::new(reader),
             buffer: [0u8; _],
             head: 0,
             tail: 0,
             eof: false,
-            line: 0,
+            line: 1,
             col: 0,
+            consumed: vec![],
         }
     }
 
@@ -293,7 +339,7 @@ impl<R: std::io::Read> StreamRead<R> {
         }
 
         let empty_buf = &mut self.buffer[self.tail..];
-        let n = self.inner.read(empty_buf)?;
+        let n = std::io::Read::read(&mut self.inner, empty_buf)?;
         if n == 0 {
             self.eof = true;
         }
@@ -307,6 +353,106 @@ impl<R: std::io::Read> StreamRead<R> {
     }
 }
 
+/// Turns the bytes of a stream into UTF-8, see [StreamRead].
+struct Decode<R> {
+    inner: R,
+    detected: bool,
+    decoder: Option<encoding_rs::Decoder>,
+    /// Decoded bytes that weren't returned yet.
+    pending: Vec<u8>,
+    head: usize,
+    eof: bool,
+}
+
+impl<R: std::io::Read> Decode<R> {
+    fn new(inner: R) -> Self {
+        Decode {
+            inner,
+            detected: false,
+            decoder: None,
+            pending: vec![],
+            head: 0,
+            eof: false,
+        }
+    }
+
+    /// Reads the first bytes, enough to tell a byte order mark, and sets up decoding.
+    fn detect(&mut self) -> std::io::Result<()> {
+        self.detected = true;
+        let mut prefix = [0u8; 3];
+        let mut len = 0;
+        while len < prefix.len() {
+            let n = self.inner.read(&mut prefix[len..])?;
+            if n == 0 {
+                self.eof = true;
+                break;
+            }
+            len += n;
+        }
+        let prefix = &prefix[..len];
+        match encoding_rs::Encoding::for_bom(prefix) {
+            Some((encoding, bom)) if encoding != encoding_rs::UTF_8 => {
+                self.decoder = Some(encoding.new_decoder_without_bom_handling());
+                self.decode(&prefix[bom..])
+            }
+            Some((_, bom)) => {
+                self.pending.extend_from_slice(&prefix[bom..]);
+                Ok(())
+            }
+            None => {
+                self.pending.extend_from_slice(prefix);
+                Ok(())
+            }
+        }
+    }
+
+    /// Decodes `input` into the pending bytes, the end of the stream if `self.eof` is set.
+    fn decode(&mut self, input: &[u8]) -> std::io::Result<()> {
+        let decoder = self.decoder.as_mut().expect("only UTF-16 is decoded");
+        let max = decoder
+            .max_utf8_buffer_length_without_replacement(input.len())
+            .ok_or_else(|| std::io::Error::other("input too large to decode"))?;
+        self.pending.resize(max, 0);
+        let (result, _, written) =
+            decoder.decode_to_utf8_without_replacement(input, &mut self.pending, self.eof);
+        self.pending.truncate(written);
+        match result {
+            encoding_rs::DecoderResult::Malformed(..) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("stream did not contain valid {}", decoder.encoding().name()),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for Decode<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.detected {
+            self.detect()?;
+        }
+        loop {
+            if self.head < self.pending.len() {
+                let n = buf.len().min(self.pending.len() - self.head);
+                buf[..n].copy_from_slice(&self.pending[self.head..self.head + n]);
+                self.head += n;
+                return Ok(n);
+            }
+            if self.eof {
+                return Ok(0);
+            }
+            if self.decoder.is_none() {
+                return self.inner.read(buf);
+            }
+            let mut input = [0u8; DEFAULT_BUFFER_SIZE];
+            let n = self.inner.read(&mut input)?;
+            self.eof = n == 0;
+            self.head = 0;
+            self.decode(&input[..n])?;
+        }
+    }
+}
+
 impl<'de, R: std::io::Read> Read<'de> for StreamRead<R> {
     fn position(&self) -> Position {
         Position {
@@ -344,10 +490,12 @@ impl<'de, R: std::io::Read> Read<'de> for StreamRead<R> {
         let byte = self.buffer[self.head];
         if byte == b'\n' {
             self.line += 1;
+            self.col = 0;
         } else {
             self.col += 1;
         }
         self.head += 1;
+        self.consumed.push(byte);
         if self.head == self.tail {
             self.head = 0;
             self.tail = 0;
@@ -355,6 +503,10 @@ impl<'de, R: std::io::Read> Read<'de> for StreamRead<R> {
         Ok(byte)
     }
 
+    fn consumed(&self) -> Option<&[u8]> {
+        Some(&self.consumed)
+    }
+
     #[inline]
     fn parse_str<'s, F>(
         &'s mut self,
@@ -385,8 +537,10 @@ impl<'de, R: std::io::Read> Read<'de> for StreamRead<R> {
     }
 }
 
-macro_rules! parse_str_bytes_impl {
-    ($self:expr, $escape:expr, $scratch:expr, $delimiter:expr, $result:expr) => {{
+/// Advances `$self.index` to the delimiter, unescaping into `$scratch` as needed, and
+/// evaluates to the start of the bytes after the last escape.
+macro_rules! scan_str_bytes_impl {
+    ($self:expr, $escape:expr, $scratch:expr, $delimiter:expr) => {{
         let mut start = $self.index;
         loop {
             if !$delimiter($self)? {
@@ -408,6 +562,13 @@ macro_rules! parse_str_bytes_impl {
                 break;
             }
         }
+        start
+    }};
+}
+
+macro_rules! parse_str_bytes_impl {
+    ($self:expr, $escape:expr, $scratch:expr, $delimiter:expr, $result:expr) => {{
+        let start = scan_str_bytes_impl!($self, $escape, $scratch, $delimiter);
         if $scratch.is_empty() {
             let borrowed = &$self.slice[start..$self.index];
             $result(borrowed).map(Reference::Borrowed)
@@ -418,24 +579,42 @@ macro_rules! parse_str_bytes_impl {
     }};
 }
 
+/// The position of byte `i` of `slice`, computed from `last_position`, the last computed
+/// (index, line, start of line). Readers only move forward, so positions can be computed
+/// incrementally instead of rescanning the whole slice.
+fn position_of_index(
+    slice: &[u8],
+    last_position: &std::cell::Cell<(usize, usize, usize)>,
+    i: usize,
+) -> Position {
+    let (mut from, mut line, mut start_of_line) = last_position.get();
+    if i < from {
+        (from, line, start_of_line) = (0, 1, 0);
+    }
+    for newline in memchr::memchr_iter(b'\n', &slice[from..i]) {
+        line += 1;
+        start_of_line = from + newline + 1;
+    }
+    last_position.set((i, line, start_of_line));
+    Position {
+        line,
+        column: i - start_of_line,
+    }
+}
+
 pub struct SliceRead<'de> {
     slice: &'de [u8],
     index: usize,
+    // See `position_of_index`.
+    last_position: std::cell::Cell<(usize, usize, usize)>,
 }
 
 impl<'de> SliceRead<'de> {
     pub fn new(slice: &'de [u8]) -> Self {
-        SliceRead { slice, index: 0 }
-    }
-
-    fn position_of_index(&self, i: usize) -> Position {
-        let start_of_line = match memchr::memrchr(b'\n', &self.slice[..i]) {
-            Some(position) => position + 1,
-            None => 0,
-        };
-        Position {
-            line: 1 + memchr::memchr_iter(b'\n', &self.slice[..start_of_line]).count(),
-            column: i - start_of_line,
+        SliceRead {
+            slice,
+            index: 0,
+            last_position: std::cell::Cell::new((0, 1, 0)),
         }
     }
 
@@ -467,7 +646,7 @@ impl<'de> SliceRead<'de> {
 
 impl<'de> Read<'de> for SliceRead<'de> {
     fn position(&self) -> Position {
-        self.position_of_index(self.index)
+        position_of_index(self.slice, &self.last_position, self.index)
     }
 
     #[inline]
@@ -490,6 +669,10 @@ impl<'de> Read<'de> for SliceRead<'de> {
         Ok(byte)
     }
 
+    fn consumed(&self) -> Option<&[u8]> {
+        Some(&self.slice[..self.index])
+    }
+
     fn discard(&mut self, n: usize) -> Result<()> {
         if self.available_data_len() < n {
             Err(Error::Eof)
@@ -563,6 +746,10 @@ impl<'de> Read<'de> for StrRead<'de> {
         self.delegate.next()
     }
 
+    fn consumed(&self) -> Option<&[u8]> {
+        self.delegate.consumed()
+    }
+
     #[inline]
     fn parse_str<'s, F>(
         &'s mut self,
@@ -579,6 +766,152 @@ impl<'de> Read<'de> for StrRead<'de> {
     }
 }
 
+/// Reads HOCON from a slice like [SliceRead], but copies the strings out of it instead of
+/// borrowing them, so the parsed tree can outlive the slice without being copied again.
+#[derive(Deref, DerefMut)]
+pub(crate) struct CopyRead<'a> {
+    delegate: SliceRead<'a>,
+}
+
+impl<'a> CopyRead<'a> {
+    pub(crate) fn new(slice: &'a [u8]) -> Self {
+        Self {
+            delegate: SliceRead::new(slice),
+        }
+    }
+}
+
+impl<'de> Read<'de> for CopyRead<'_> {
+    fn position(&self) -> Position {
+        self.delegate.position()
+    }
+
+    #[inline]
+    fn peek_n(&mut self, n: usize) -> Result<&[u8]> {
+        self.delegate.peek_n(n)
+    }
+
+    #[inline]
+    fn next(&mut self) -> Result<u8> {
+        self.delegate.next()
+    }
+
+    fn consumed(&self) -> Option<&[u8]> {
+        self.delegate.consumed()
+    }
+
+    fn discard(&mut self, n: usize) -> Result<()> {
+        self.delegate.discard(n)
+    }
+
+    #[inline]
+    fn parse_str<'s, F>(
+        &'s mut self,
+        escape: bool,
+        scratch: &'s mut Vec<u8>,
+        end: F,
+    ) -> Result<Reference<'de, 's, str>>
+    where
+        F: Fn(&mut Self) -> Result<bool>,
+    {
+        let start = scan_str_bytes_impl!(self, escape, scratch, end);
+        scratch.extend_from_slice(&self.slice[start..self.index]);
+        str::from_utf8(scratch)
+            .map_err(|_| Error::InvalidUtf8)
+            .map(Reference::Copied)
+    }
+}
+
+/// Reads HOCON from [`bytes::Bytes`], e.g. a request body, without copying it first.
+///
+/// Like [SliceRead], the input must be UTF-8. Strings are copied out of the buffer since
+/// the reader owns it, which a parser can't borrow from.
+#[cfg(feature = "bytes")]
+pub struct BytesRead {
+    slice: bytes::Bytes,
+    index: usize,
+    // See `position_of_index`.
+    last_position: std::cell::Cell<(usize, usize, usize)>,
+}
+
+#[cfg(feature = "bytes")]
+impl BytesRead {
+    pub fn new(bytes: bytes::Bytes) -> Self {
+        BytesRead {
+            slice: bytes,
+            index: 0,
+            last_position: std::cell::Cell::new((0, 1, 0)),
+        }
+    }
+
+    /// Returns the bytes that weren't read yet, sharing the buffer.
+    pub fn rest(&self) -> bytes::Bytes {
+        self.slice.slice(self.index..)
+    }
+
+    #[inline]
+    fn available_data_len(&self) -> usize {
+        self.slice.len() - self.index
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'de> Read<'de> for BytesRead {
+    fn position(&self) -> Position {
+        position_of_index(&self.slice, &self.last_position, self.index)
+    }
+
+    #[inline]
+    fn peek_n(&mut self, n: usize) -> Result<&[u8]> {
+        debug_assert!(n > 0 && n <= MAX_PEEK_N);
+        if self.available_data_len() < n {
+            Err(Error::Eof)
+        } else {
+            Ok(&self.slice[self.index..self.index + n])
+        }
+    }
+
+    #[inline]
+    fn next(&mut self) -> Result<u8> {
+        if self.index == self.slice.len() {
+            return Err(Error::Eof);
+        }
+        let byte = self.slice[self.index];
+        self.index += 1;
+        Ok(byte)
+    }
+
+    fn consumed(&self) -> Option<&[u8]> {
+        Some(&self.slice[..self.index])
+    }
+
+    fn discard(&mut self, n: usize) -> Result<()> {
+        if self.available_data_len() < n {
+            Err(Error::Eof)
+        } else {
+            self.index += n;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn parse_str<'s, F>(
+        &'s mut self,
+        escape: bool,
+        scratch: &'s mut Vec<u8>,
+        end: F,
+    ) -> Result<Reference<'de, 's, str>>
+    where
+        F: Fn(&mut Self) -> Result<bool>,
+    {
+        let start = scan_str_bytes_impl!(self, escape, scratch, end);
+        scratch.extend_from_slice(&self.slice[start..self.index]);
+        str::from_utf8(scratch)
+            .map_err(|_| Error::InvalidUtf8)
+            .map(Reference::Copied)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Result;
@@ -608,6 +941,51 @@ mod tests {
         Ok(())
     }
 
+    #[rstest]
+    #[case(b"a = \"\xc3\xa9\"".to_vec())]
+    #[case(b"\xef\xbb\xbfa = \"\xc3\xa9\"".to_vec())]
+    #[case([0xFF, 0xFE].into_iter().chain("a = \"\u{e9}\"".encode_utf16().flat_map(u16::to_le_bytes)).collect())]
+    #[case([0xFE, 0xFF].into_iter().chain("a = \"\u{e9}\"".encode_utf16().flat_map(u16::to_be_bytes)).collect())]
+    fn test_stream_encoding(#[case] input: Vec<u8>) -> Result<()> {
+        let mut read = StreamRead::new(input.as_slice());
+        let mut bytes = vec![];
+        while read.peek().is_ok() {
+            bytes.push(read.next()?);
+        }
+        assert_eq!(bytes, "a = \"\u{e9}\"".as_bytes());
+        assert_eq!(read.position(), crate::parser::read::Position::new(1, 8));
+        Ok(())
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes_read() -> Result<()> {
+        use crate::parser::HoconParser;
+        use crate::parser::read::{BytesRead, SliceRead};
+
+        let input = "a = \"x\\ty\"\nb {\n  c = [1, 2]\n}\nd = ${a} z\n";
+        let bytes = bytes::Bytes::from(input);
+        let mut read = BytesRead::new(bytes.clone());
+        read.discard(4)?;
+        assert_eq!(read.rest(), bytes.slice(4..));
+        let expected = HoconParser::new(SliceRead::new(input.as_bytes())).parse()?;
+        let object = HoconParser::new(BytesRead::new(bytes)).parse()?;
+        assert_eq!(object, expected);
+        assert!(
+            HoconParser::new(BytesRead::new(bytes::Bytes::from_static(b"a = \"\xff\"")))
+                .parse()
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_invalid_utf16() {
+        let input = [0xFF, 0xFE, b'a', 0, 0x00, 0xDC];
+        let mut read = StreamRead::new(input.as_slice());
+        assert!(matches!(read.peek(), Err(crate::error::Error::Io(_))));
+    }
+
     #[rstest]
     #[case(&[] as &[u8], 0)]
     #[case(b"\txyz", 1)]
@@ -1,8 +1,11 @@
 use crate::Result;
+use crate::config_options::MultilineMargin;
 use crate::error::Error;
 use crate::parser::HoconParser;
-use crate::parser::read::Read;
+use crate::parser::include::INCLUDE;
+use crate::parser::read::{Position, Read};
 use crate::raw::raw_string::RawString;
+use std::borrow::Cow;
 
 // Precompute forbidden characters table
 const FORBIDDEN_TABLE: [bool; 256] = {
@@ -31,8 +34,23 @@ const FORBIDDEN_TABLE: [bool; 256] = {
 
 pub(crate) const TRIPLE_DOUBLE_QUOTE: &[u8] = b"\"\"\"";
 
+/// Returns `true` if `key` can't be written as a single unquoted key, i.e. it is empty,
+/// contains a dot, whitespace, a forbidden character or a comment start, or it would be
+/// mistaken for an include statement.
+pub(crate) fn needs_quotes(key: &str) -> bool {
+    key.is_empty()
+        || key.as_bytes().starts_with(INCLUDE)
+        || key.contains("//")
+        || key
+            .bytes()
+            .any(|b| b == b'.' || FORBIDDEN_TABLE[b as usize])
+        || key
+            .char_indices()
+            .any(|(i, _)| crate::parser::read::leading_whitespace_bytes(&key.as_bytes()[i..]) > 0)
+}
+
 impl<'de, R: Read<'de>> HoconParser<R> {
-    pub(crate) fn parse_quoted_string(&mut self, check: bool) -> Result<String> {
+    pub(crate) fn parse_quoted_string(&mut self, check: bool) -> Result<Cow<'de, str>> {
         if check {
             let ch = self.reader.peek()?;
             if ch != b'"' {
@@ -46,9 +64,10 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         self.scratch.clear();
         let content = self
             .reader
-            .parse_str(true, &mut self.scratch, |reader| Ok(reader.peek()? == b'"'))?
-            .to_string();
-        let ch = self.reader.peek()?;
+            .parse_str(true, &mut self.scratch, |reader| Ok(reader.peek()? == b'"'))
+            .map_err(Self::unterminated_string)?
+            .into_cow();
+        let ch = self.reader.peek().map_err(Self::unterminated_string)?;
         if ch != b'"' {
             return Err(Error::UnexpectedToken {
                 expected: "\"",
@@ -59,15 +78,27 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         Ok(content)
     }
 
-    pub(crate) fn parse_unquoted_string(&mut self) -> Result<String> {
+    /// Reaching the end of the input inside a quoted string is a syntax error, not the
+    /// end of the document.
+    fn unterminated_string(error: Error) -> Error {
+        match error {
+            Error::Eof => Error::UnexpectedToken {
+                expected: "\"",
+                found_beginning: b'\0',
+            },
+            error => error,
+        }
+    }
+
+    pub(crate) fn parse_unquoted_string(&mut self) -> Result<Cow<'de, str>> {
         self.parse_unquoted(true)
     }
 
-    pub(crate) fn parse_unquoted_path(&mut self) -> Result<String> {
+    pub(crate) fn parse_unquoted_path(&mut self) -> Result<Cow<'de, str>> {
         self.parse_unquoted(false)
     }
 
-    fn parse_unquoted(&mut self, allow_dot: bool) -> Result<String> {
+    fn parse_unquoted(&mut self, allow_dot: bool) -> Result<Cow<'de, str>> {
         self.scratch.clear();
         let content = self.reader.parse_str(true, &mut self.scratch, |reader| {
             let mut end = false;
@@ -106,11 +137,14 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 found_beginning: b'\0',
             })
         } else {
-            Ok(content.to_string())
+            Ok(content.into_cow())
         }
     }
 
-    pub(crate) fn parse_multiline_string(&mut self, verify_delimiter: bool) -> Result<String> {
+    pub(crate) fn parse_multiline_string(
+        &mut self,
+        verify_delimiter: bool,
+    ) -> Result<Cow<'de, str>> {
         if verify_delimiter {
             let bytes = self.reader.peek_n(3)?;
             if bytes != TRIPLE_DOUBLE_QUOTE {
@@ -132,13 +166,34 @@ impl<'de, R: Read<'de>> HoconParser<R> {
             .parse_str(false, &mut self.scratch, |reader| {
                 Ok(reader.peek_n(3)? == TRIPLE_DOUBLE_QUOTE)
             })?
-            .to_string();
+            .into_cow();
         self.reader.discard(3)?;
-        Ok(content)
+        let margin = if self.options.extended() {
+            self.options.multiline_margin
+        } else {
+            MultilineMargin::Keep
+        };
+        let stripped = match margin {
+            MultilineMargin::Keep => return Ok(content),
+            MultilineMargin::TrimIndent => trim_indent(&content),
+            MultilineMargin::StripMargin => strip_margin(&content),
+        };
+        if stripped == content {
+            Ok(content)
+        } else {
+            Ok(Cow::Owned(stripped))
+        }
+    }
+
+    pub(crate) fn parse_path_expression(&mut self) -> Result<RawString<'de>> {
+        self.parse_path_expression_spanned().map(|(path, _)| path)
     }
 
-    pub(crate) fn parse_path_expression(&mut self) -> Result<RawString> {
+    /// Like [HoconParser::parse_path_expression], also returning the position right after
+    /// the last key of the path, before the whitespace that follows it.
+    pub(crate) fn parse_path_expression_spanned(&mut self) -> Result<(RawString<'de>, Position)> {
         let mut paths = vec![];
+        let mut end = self.reader.position();
         let mut scratch = vec![];
         if self.reader.starts_with_horizontal_whitespace()? {
             return Err(Error::UnexpectedToken {
@@ -178,10 +233,16 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                 }
                 _ => self.parse_unquoted_path()?,
             };
-            scratch.extend_from_slice(path.as_bytes());
-            let mut path = unsafe { str::from_utf8_unchecked(&scratch) }.to_string();
+            // The key borrows from the input unless it starts with whitespace.
+            let mut path = if scratch.is_empty() {
+                path
+            } else {
+                scratch.extend_from_slice(path.as_bytes());
+                Cow::Owned(unsafe { str::from_utf8_unchecked(&scratch) }.to_string())
+            };
             // We always need to parse the ending whitespace after a path, because we don't
             // know if there are any valid path expressions after it.
+            end = self.reader.position();
             scratch.clear();
             self.parse_horizontal_whitespace(&mut scratch)?;
             let ending_space = unsafe { str::from_utf8_unchecked(&scratch) };
@@ -201,7 +262,9 @@ impl<'de, R: Read<'de>> HoconParser<R> {
                     break;
                 }
                 b'.' => {
-                    path.push_str(ending_space);
+                    if !ending_space.is_empty() {
+                        path.to_mut().push_str(ending_space);
+                    }
                     paths.push(path);
                     self.reader.discard(1)?;
                 }
@@ -220,13 +283,62 @@ impl<'de, R: Read<'de>> HoconParser<R> {
         } else {
             RawString::path_expression(paths.into_iter().map(RawString::quoted).collect())
         };
-        Ok(path)
+        Ok((path, end))
+    }
+}
+
+/// Returns the lines of `string`, without their `\r` if they end with `\r\n`.
+fn lines(string: &str) -> impl Iterator<Item = &str> {
+    string
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+/// Removes the indentation common to the lines of `string` that aren't blank, and its
+/// first and last line if they are blank, see [`MultilineMargin::TrimIndent`].
+fn trim_indent(string: &str) -> String {
+    let is_blank = |line: &&str| line.trim().is_empty();
+    let mut lines = lines(string).collect::<Vec<_>>();
+    if lines.last().is_some_and(is_blank) {
+        lines.pop();
     }
+    if lines.first().is_some_and(is_blank) {
+        lines.remove(0);
+    }
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or(0);
+    let lines = lines
+        .iter()
+        .map(|line| &line[indent.min(indentation(line))..])
+        .collect::<Vec<_>>();
+    lines.join("\n")
+}
+
+/// Removes the indentation and the `|` at the start of every line of `string` that has
+/// one, see [`MultilineMargin::StripMargin`].
+fn strip_margin(string: &str) -> String {
+    let lines = lines(string)
+        .map(|line| {
+            line.trim_start_matches([' ', '\t'])
+                .strip_prefix('|')
+                .unwrap_or(line)
+        })
+        .collect::<Vec<_>>();
+    lines.join("\n")
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Result;
+    use crate::config_options::{ConfigOptions, MultilineMargin};
     use crate::parser::HoconParser;
     use crate::parser::read::StrRead;
     use rstest::rstest;
@@ -310,6 +422,32 @@ mod tests {
         Ok(())
     }
 
+    #[rstest]
+    #[case(
+        MultilineMargin::Keep,
+        "\n    a\n      b\n    ",
+        "\n    a\n      b\n    "
+    )]
+    #[case(MultilineMargin::TrimIndent, "\n    a\n      b\n    ", "a\n  b")]
+    #[case(MultilineMargin::TrimIndent, "\r\n  a\r\n\r\n  b\r\n", "a\n\nb")]
+    #[case(MultilineMargin::TrimIndent, "a\n  b", "a\n  b")]
+    #[case(MultilineMargin::StripMargin, "a\n    |b\n  | c\n d", "a\nb\n c\n d")]
+    fn test_multiline_margin(
+        #[case] margin: MultilineMargin,
+        #[case] content: &str,
+        #[case] expected: &str,
+    ) -> Result<()> {
+        let input = format!("\"\"\"{content}\"\"\"");
+        let options = ConfigOptions {
+            multiline_margin: margin,
+            ..Default::default()
+        };
+        let mut parser = HoconParser::with_options(StrRead::new(&input), options);
+        let s = parser.parse_multiline_string(true)?;
+        assert_eq!(s, expected);
+        Ok(())
+    }
+
     #[rstest]
     #[case(r#""#)]
     #[case(r#""""Hello"""#)]
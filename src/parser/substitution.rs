@@ -5,7 +5,7 @@ use crate::parser::read::Read;
 use crate::raw::substitution::Substitution;
 
 impl<'de, R: Read<'de>> HoconParser<R> {
-    pub(crate) fn parse_substitution(&mut self) -> Result<Substitution> {
+    pub(crate) fn parse_substitution(&mut self) -> Result<Substitution<'de>> {
         let (ch1, ch2) = self.reader.peek2()?;
         if ch1 != b'$' {
             return Err(Error::UnexpectedToken {
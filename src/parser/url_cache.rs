@@ -0,0 +1,235 @@
+//! The responses of included URLs kept by [`crate::config_options::UrlOptions::cache`].
+//!
+//! A cached response is revalidated with the `ETag` and `Last-Modified` headers it came
+//! with, and used as is when the server answers `304 Not Modified`, or when it can't be
+//! reached and the response isn't older than [`UrlOptions::max_stale`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use reqwest::StatusCode;
+use reqwest::header::{
+    CONTENT_TYPE, ETAG, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use sha2::{Digest, Sha256};
+
+use crate::config_options::{UrlCache, UrlOptions};
+use crate::fingerprint::hex;
+
+/// A response read from a URL, or from the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CachedResponse {
+    /// The URL the response came from, after following redirects.
+    pub(crate) url: url::Url,
+    pub(crate) content_type: Option<String>,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+    /// When the response was fetched or last revalidated.
+    pub(crate) fetched: SystemTime,
+    pub(crate) body: Vec<u8>,
+}
+
+impl CachedResponse {
+    pub(crate) fn new(url: url::Url, headers: &HeaderMap, body: Vec<u8>) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        CachedResponse {
+            url,
+            content_type: header(CONTENT_TYPE),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fetched: SystemTime::now(),
+            body,
+        }
+    }
+
+    /// The headers asking the server to answer `304 Not Modified` if this response is
+    /// still current.
+    fn validators(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let validators = [
+            (IF_NONE_MATCH, &self.etag),
+            (IF_MODIFIED_SINCE, &self.last_modified),
+        ];
+        for (name, value) in validators {
+            if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    /// The contents of a cache file: a line with the headers as JSON, then the body.
+    fn to_bytes(&self) -> Vec<u8> {
+        let fetched = self
+            .fetched
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        let json = serde_json::json!({
+            "url": self.url.as_str(),
+            "content_type": self.content_type,
+            "etag": self.etag,
+            "last_modified": self.last_modified,
+            "fetched": fetched.as_secs(),
+        });
+        let mut bytes = json.to_string().into_bytes();
+        bytes.push(b'\n');
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let newline = memchr::memchr(b'\n', bytes)?;
+        let json: serde_json::Value = serde_json::from_slice(&bytes[..newline]).ok()?;
+        let field = |name| json.get(name).and_then(|v| v.as_str()).map(str::to_string);
+        let fetched = Duration::from_secs(json.get("fetched")?.as_u64()?);
+        Some(CachedResponse {
+            url: url::Url::parse(json.get("url")?.as_str()?).ok()?,
+            content_type: field("content_type"),
+            etag: field("etag"),
+            last_modified: field("last_modified"),
+            fetched: SystemTime::UNIX_EPOCH + fetched,
+            body: bytes[newline + 1..].to_vec(),
+        })
+    }
+
+    /// Returns `true` if the response was fetched or revalidated at most `max_stale` ago.
+    fn is_fresh(&self, max_stale: Option<Duration>) -> bool {
+        // A response from the future, after the clock was turned back, counts as new.
+        let age = self.fetched.elapsed().unwrap_or_default();
+        max_stale.is_none_or(|max_stale| age <= max_stale)
+    }
+}
+
+/// A request to a URL answered from [`UrlOptions::cache`] when possible.
+pub(crate) struct CachedRequest<'a> {
+    cache: Option<&'a UrlCache>,
+    max_stale: Option<Duration>,
+    /// The URL and the headers sent with the request, hashed, so requests with different
+    /// credentials never share a response.
+    key: String,
+    cached: Option<CachedResponse>,
+}
+
+impl<'a> CachedRequest<'a> {
+    /// Looks up the response cached for a request to `url` with `headers`.
+    pub(crate) fn new(options: &'a UrlOptions, url: &url::Url, headers: &HeaderMap) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_str());
+        for (name, value) in headers {
+            hasher.update([0]);
+            hasher.update(name.as_str());
+            hasher.update([0]);
+            hasher.update(value.as_bytes());
+        }
+        let key = hex(&hasher.finalize());
+        let cache = options.cache.as_ref();
+        let cached = cache.and_then(|cache| cache.get(&key));
+        CachedRequest {
+            cache,
+            max_stale: options.max_stale,
+            key,
+            cached,
+        }
+    }
+
+    /// The headers asking the server to answer `304 Not Modified` if the cached response
+    /// is still current.
+    pub(crate) fn validators(&self) -> HeaderMap {
+        self.cached
+            .as_ref()
+            .map(CachedResponse::validators)
+            .unwrap_or_default()
+    }
+
+    /// Returns the cached response to use instead of a response with `status`, or instead
+    /// of a request that failed with `None`: the cached response is still current, or the
+    /// server failed and the cached response isn't older than [`UrlOptions::max_stale`].
+    pub(crate) fn fallback(&mut self, status: Option<StatusCode>) -> Option<CachedResponse> {
+        match status {
+            Some(StatusCode::NOT_MODIFIED) => {
+                let mut cached = self.cached.take()?;
+                cached.fetched = SystemTime::now();
+                self.insert(StatusCode::OK, &cached);
+                Some(cached)
+            }
+            Some(status) if !status.is_server_error() => None,
+            _ => self.cached.take().filter(|c| c.is_fresh(self.max_stale)),
+        }
+    }
+
+    /// Caches `response` if its `status` is a success.
+    pub(crate) fn insert(&self, status: StatusCode, response: &CachedResponse) {
+        if let Some(cache) = self.cache
+            && status.is_success()
+        {
+            cache.insert(&self.key, response);
+        }
+    }
+}
+
+fn memory() -> &'static Mutex<HashMap<String, CachedResponse>> {
+    static MEMORY: OnceLock<Mutex<HashMap<String, CachedResponse>>> = OnceLock::new();
+    MEMORY.get_or_init(Default::default)
+}
+
+/// The file holding the response cached under `key` in `dir`.
+fn file(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{key}.cache"))
+}
+
+impl UrlCache {
+    /// Returns the response cached under `key`. Unreadable cache files count as missing.
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        match self {
+            UrlCache::Memory => {
+                let memory = memory().lock().unwrap_or_else(|e| e.into_inner());
+                memory.get(key).cloned()
+            }
+            UrlCache::Directory(dir) => {
+                CachedResponse::from_bytes(&std::fs::read(file(dir, key)).ok()?)
+            }
+        }
+    }
+
+    /// Caches `response` under `key`. A cache file is replaced atomically and readable by
+    /// its owner only, since the response may hold secrets. Failing to write it is not an
+    /// error, the response is fetched again next time.
+    fn insert(&self, key: &str, response: &CachedResponse) {
+        match self {
+            UrlCache::Memory => {
+                let mut memory = memory().lock().unwrap_or_else(|e| e.into_inner());
+                memory.insert(key.to_string(), response.clone());
+            }
+            UrlCache::Directory(dir) => {
+                let result = std::fs::create_dir_all(dir)
+                    .map_err(crate::Error::from)
+                    .and_then(|_| {
+                        crate::render::write_atomic(
+                            &file(dir, key),
+                            &response.to_bytes(),
+                            false,
+                            false,
+                        )
+                    });
+                if let Err(error) = result {
+                    tracing::warn!(
+                        "failed to cache the response from {}: {error}",
+                        response.url
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Removes every response cached in memory.
+pub(crate) fn invalidate() {
+    memory().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
@@ -1,7 +1,11 @@
 use derive_more::Constructor;
+use std::borrow::Cow;
 use std::fmt::Display;
 
+use crate::error::Error;
 use crate::join;
+use crate::parser::HoconParser;
+use crate::parser::read::StrRead;
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Constructor)]
 pub struct Path {
@@ -10,8 +14,33 @@ pub struct Path {
 }
 
 impl Path {
-    pub fn from_str(paths: impl AsRef<str>) -> crate::Result<Path> {
-        Self::from_iter(paths.as_ref().split('.'))
+    /// Parses a HOCON path expression like `a."b.c".d`, where quoted keys may contain dots.
+    ///
+    /// Keys are read as they are in the key of a field, so keys that contain dots, quotes
+    /// or other characters that aren't allowed unquoted must be quoted. Every function of
+    /// this crate that takes a path as a string reads it this way.
+    ///
+    /// Fails with [`Error::InvalidPathExpression`] if `path` is empty or isn't a path
+    /// expression, e.g. `a..b` or `a.`.
+    pub fn from_str(path: impl AsRef<str>) -> crate::Result<Path> {
+        let path = path.as_ref();
+        if path.is_empty() {
+            return Err(Error::InvalidPathExpression("path is empty"));
+        }
+        let mut parser = HoconParser::new(StrRead::new(path));
+        let expression = parser
+            .parse_path_expression()
+            .map_err(|_| Error::InvalidPathExpression("malformed path"))?;
+        // The parser ends a path at the end of the input, even right after a dot.
+        if path.trim_end().ends_with('.') || !matches!(parser.reader.rest(), Ok("")) {
+            return Err(Error::InvalidPathExpression("malformed path"));
+        }
+        Ok(expression.into_path())
+    }
+
+    /// The keys of this path, with indices as their decimal text.
+    pub(crate) fn keys(&self) -> Vec<String> {
+        self.iter().map(|path| path.first.to_string()).collect()
     }
 
     pub fn from_iter<I, V>(paths: I) -> crate::Result<Path>
@@ -172,3 +201,197 @@ impl Display for Key {
         }
     }
 }
+
+/// Renders a single key so that it parses back as exactly one path element,
+/// quoting it only when needed.
+pub(crate) fn render_key(key: &str) -> Cow<'_, str> {
+    if crate::parser::needs_quotes(key) {
+        Cow::Owned(serde_json::to_string(key).expect("string serialization never fails"))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+/// Renders keys as a HOCON path expression, e.g. `a."b.c".d`.
+pub(crate) fn render_path<'a, I>(keys: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut path = String::new();
+    for (index, key) in keys.into_iter().enumerate() {
+        if index > 0 {
+            path.push('.');
+        }
+        path.push_str(&render_key(key));
+    }
+    path
+}
+
+/// A key or an array index of a path reported by [`crate::Error::AtPath`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Splits a path like `servers[0]."a.b"` into its keys and indices, the inverse of how
+/// deserialization errors render them. Returns `None` for malformed paths.
+pub(crate) fn parse_error_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = vec![];
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(index) = rest.strip_prefix('[') {
+            let end = index.find(']')?;
+            segments.push(PathSegment::Index(index[..end].parse().ok()?));
+            rest = &index[end + 1..];
+        } else if rest.starts_with('"') {
+            let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<String>();
+            let key = stream.next()?.ok()?;
+            segments.push(PathSegment::Key(key));
+            rest = &rest[stream.byte_offset()..];
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            segments.push(PathSegment::Key(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+        if let Some(next) = rest.strip_prefix('.') {
+            rest = next;
+        } else if !rest.is_empty() && !rest.starts_with('[') {
+            return None;
+        }
+    }
+    Some(segments)
+}
+
+/// Removes the array indices, e.g. `[1]`, from a path like the ones of
+/// [`crate::Error::AtPath`], so the rest can be parsed with [`Path::from_str`]. Quoted keys
+/// are kept as they are.
+pub(crate) fn strip_indices(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut quoted = false;
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                stripped.push(c);
+                stripped.extend(chars.next());
+                continue;
+            }
+            '[' if !quoted => {
+                let rest = chars.as_str();
+                if let Some(end) = rest.find(']')
+                    && rest[..end].parse::<usize>().is_ok()
+                {
+                    chars = rest[end + 1..].chars();
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
+/// A path pattern where the unquoted keys `*` and `**` match any single key and any
+/// number of keys, including none. Other keys are read like [`Path::from_str`] reads them,
+/// so keys containing dots are quoted like `a."b.c"`.
+///
+/// Matching works on a set of positions in the pattern, so callers can advance it key by
+/// key while walking a tree and stop as soon as no position is left.
+pub(crate) struct Selector {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    /// `*`
+    Any,
+    /// `**`
+    AnyDepth,
+}
+
+impl Selector {
+    /// Reads `selector`. An invalid selector matches nothing.
+    pub(crate) fn new(selector: &str) -> Self {
+        Self {
+            segments: Self::parse(selector).unwrap_or_default(),
+        }
+    }
+
+    fn parse(selector: &str) -> Option<Vec<Segment>> {
+        let mut segments = vec![];
+        let mut start = 0;
+        let mut quoted = false;
+        let mut chars = selector.char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => quoted = !quoted,
+                '\\' if quoted => {
+                    chars.next();
+                }
+                '.' if !quoted => {
+                    segments.push(Self::segment(&selector[start..index])?);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        segments.push(Self::segment(&selector[start..])?);
+        Some(segments)
+    }
+
+    fn segment(segment: &str) -> Option<Segment> {
+        match segment {
+            "*" => Some(Segment::Any),
+            "**" => Some(Segment::AnyDepth),
+            _ => {
+                let path = Path::from_str(segment).ok()?;
+                (path.len() == 1).then(|| Segment::Key(path.first.to_string()))
+            }
+        }
+    }
+
+    /// Returns the positions before any key was matched.
+    pub(crate) fn start(&self) -> Vec<usize> {
+        self.closure(vec![0])
+    }
+
+    /// Advances the positions in `states` over `key`.
+    pub(crate) fn step(&self, states: &[usize], key: &str) -> Vec<usize> {
+        let mut next = vec![];
+        for &state in states {
+            match self.segments.get(state) {
+                Some(Segment::AnyDepth) => next.push(state),
+                Some(Segment::Any) => next.push(state + 1),
+                Some(Segment::Key(segment)) if segment == key => next.push(state + 1),
+                _ => {}
+            }
+        }
+        self.closure(next)
+    }
+
+    /// Returns `true` if the keys consumed so far match the whole pattern.
+    pub(crate) fn is_match(&self, states: &[usize]) -> bool {
+        states.contains(&self.segments.len())
+    }
+
+    /// Adds the positions reachable by letting `**` match no key.
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut index = 0;
+        while index < states.len() {
+            let state = states[index];
+            if self.segments.get(state) == Some(&Segment::AnyDepth)
+                && !states.contains(&(state + 1))
+            {
+                states.push(state + 1);
+            }
+            index += 1;
+        }
+        states.sort_unstable();
+        states.dedup();
+        states
+    }
+}
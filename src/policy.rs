@@ -0,0 +1,297 @@
+//! Rules that are checked against a resolved configuration.
+//!
+//! A [`Policy`] inspects the whole resolved [`Value`] and reports [`Violation`]s. Policies
+//! are run with [`Value::check_policies`], or with [`crate::Config::resolve_with_policies`],
+//! which fails if any violation has [`Severity::Error`].
+
+use std::fmt::{Display, Formatter};
+
+use crate::path::{Path, render_path};
+use crate::value::{Value, ordered_entries};
+
+/// How serious a [`Violation`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    /// Makes [`crate::Config::resolve_with_policies`] fail.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        f.write_str(severity)
+    }
+}
+
+/// A rule broken by a configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The rendered path of the offending entry, e.g. `db.password`.
+    pub path: String,
+    /// What is wrong with the entry.
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl Violation {
+    pub fn new(path: impl Into<String>, message: impl Into<String>, severity: Severity) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+            severity,
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+/// A rule checked against a resolved configuration.
+///
+/// Closures taking the resolved value and returning the violations implement this trait.
+pub trait Policy {
+    /// Returns every violation of this policy found in `value`.
+    fn check(&self, value: &Value) -> Vec<Violation>;
+}
+
+impl<F> Policy for F
+where
+    F: Fn(&Value) -> Vec<Violation>,
+{
+    fn check(&self, value: &Value) -> Vec<Violation> {
+        self(value)
+    }
+}
+
+/// Forbids secrets written as plain strings.
+///
+/// Every string whose key contains one of [`ForbidPlaintextPasswords::keys`], ignoring
+/// case, is reported unless it is empty or starts with one of the
+/// [`ForbidPlaintextPasswords::allowed_prefixes`], e.g. a reference to a secret store.
+/// Objects in arrays are checked too, their paths contain the index of the element, e.g.
+/// `datasources.0.password`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForbidPlaintextPasswords {
+    /// Parts of key names that mark a secret, lowercase.
+    pub keys: Vec<String>,
+    /// Prefixes of values that refer to a secret instead of containing it.
+    pub allowed_prefixes: Vec<String>,
+}
+
+impl ForbidPlaintextPasswords {
+    pub fn new(allowed_prefixes: Vec<String>) -> Self {
+        Self {
+            allowed_prefixes,
+            ..Default::default()
+        }
+    }
+
+    /// Reports the secrets in `value`, found at the keys `path`.
+    fn collect(&self, value: &Value, path: &mut Vec<String>, violations: &mut Vec<Violation>) {
+        match value.unshared() {
+            Value::Object(object) => {
+                for (key, value) in ordered_entries(object) {
+                    path.push(key.to_string());
+                    self.collect(value, path, violations);
+                    path.pop();
+                }
+            }
+            Value::Array(array) => {
+                for (index, value) in array.iter().enumerate() {
+                    path.push(index.to_string());
+                    self.collect(value, path, violations);
+                    path.pop();
+                }
+            }
+            Value::String(string) if self.is_secret(path) && self.is_plain_text(string) => {
+                violations.push(Violation::new(
+                    render_path(path.iter().map(String::as_str)),
+                    "secret is written in plain text",
+                    Severity::Error,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    fn is_secret(&self, path: &[String]) -> bool {
+        // Array elements are named by the key of their array.
+        let Some(key) = path.iter().rev().find(|key| key.parse::<usize>().is_err()) else {
+            return false;
+        };
+        let key = key.to_lowercase();
+        self.keys.iter().any(|k| key.contains(k.as_str()))
+    }
+
+    fn is_plain_text(&self, string: &str) -> bool {
+        !string.is_empty() && !self.allowed_prefixes.iter().any(|p| string.starts_with(p))
+    }
+}
+
+impl Default for ForbidPlaintextPasswords {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                "password".to_string(),
+                "passwd".to_string(),
+                "secret".to_string(),
+            ],
+            allowed_prefixes: vec![],
+        }
+    }
+}
+
+impl Policy for ForbidPlaintextPasswords {
+    fn check(&self, value: &Value) -> Vec<Violation> {
+        let mut violations = vec![];
+        self.collect(value, &mut vec![], &mut violations);
+        violations
+    }
+}
+
+/// Requires a group of settings to be configured together, e.g. the certificate and the
+/// private key of a TLS setup: if any of [`RequireTogether::paths`] is set, all of them
+/// must be set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequireTogether {
+    /// HOCON path expressions, keys containing dots are quoted like `a."b.c"`. A path that
+    /// isn't a path expression is reported as a violation.
+    pub paths: Vec<String>,
+}
+
+impl RequireTogether {
+    pub fn new<I, S>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Policy for RequireTogether {
+    fn check(&self, value: &Value) -> Vec<Violation> {
+        let (paths, invalid): (Vec<_>, Vec<_>) = self
+            .paths
+            .iter()
+            .partition(|path| Path::from_str(path).is_ok());
+        let mut violations = invalid
+            .into_iter()
+            .map(|path| {
+                Violation::new(
+                    path.as_str(),
+                    "is not a valid path expression",
+                    Severity::Error,
+                )
+            })
+            .collect::<Vec<_>>();
+        let Some(present) = paths.iter().find(|path| value.has_path(path)) else {
+            return violations;
+        };
+        let missing = paths
+            .iter()
+            .filter(|path| !value.has_path(path))
+            .map(|path| {
+                Violation::new(
+                    path.as_str(),
+                    format!("is required because {present} is set"),
+                    Severity::Error,
+                )
+            });
+        violations.extend(missing);
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use crate::config::Config;
+    use crate::error::Error;
+    use crate::value::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_policies() -> Result<()> {
+        let value: Value = Config::parse_str(
+            r#"
+            db { password = hunter2, user = admin }
+            cache.secretKey = "vault:cache/key"
+            api.client_secret = ""
+            server.tls.cert = "/etc/cert.pem"
+            "#,
+            None,
+        )?;
+        let plaintext = ForbidPlaintextPasswords::new(vec!["vault:".to_string()]);
+        let tls = RequireTogether::new(["server.tls.cert", "server.tls.key"]);
+        let port = |value: &Value| {
+            if value.has_path("server.port") {
+                vec![]
+            } else {
+                vec![Violation::new(
+                    "server.port",
+                    "uses the default port",
+                    Severity::Warning,
+                )]
+            }
+        };
+        let violations = value.check_policies(&[&plaintext, &tls, &port]);
+        let violations = violations.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            [
+                "[error] db.password: secret is written in plain text",
+                "[error] server.tls.key: is required because server.tls.cert is set",
+                "[warning] server.port: uses the default port",
+            ]
+        );
+
+        let config = Config::parse_str_raw("server.tls.key = key.pem", None)?;
+        let error = config.clone().resolve_with_policies::<Value>(&[&tls]);
+        assert!(
+            matches!(error, Err(Error::PolicyViolation { violations }) if violations.len() == 1)
+        );
+        let value: Value = config.resolve_with_policies(&[&plaintext, &port])?;
+        assert!(value.has_path("server.tls.key"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_policies_arrays_and_quoted_keys() -> Result<()> {
+        let value: Value = Config::parse_str(
+            r#"
+            datasources = [{ password = hunter2 }, { password = "vault:db" }]
+            "db.password" = hunter2
+            "tls.cert" = cert.pem
+            "#,
+            None,
+        )?;
+        let plaintext = ForbidPlaintextPasswords::new(vec!["vault:".to_string()]);
+        let tls = RequireTogether::new([r#""tls.cert""#, r#""tls.key""#]);
+        let invalid = RequireTogether::new(["tls..cert"]);
+        let violations = value.check_policies(&[&plaintext, &tls, &invalid]);
+        let violations = violations.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            violations,
+            [
+                "[error] datasources.0.password: secret is written in plain text",
+                r#"[error] "db.password": secret is written in plain text"#,
+                r#"[error] "tls.key": is required because "tls.cert" is set"#,
+                "[error] tls..cert: is not a valid path expression",
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Flattening of resolved values into the Java `.properties` format.
+//!
+//! Every leaf becomes a `path=value` line, where nested objects are joined with dots and
+//! array elements use their index as key, e.g. `servers.0.host`. This is the layout
+//! Lightbend Config reads back into objects and lists. The output is pure ASCII: the
+//! characters `java.util.Properties` treats specially are escaped with a backslash and
+//! everything outside of printable ASCII is written as `\uXXXX`, so the file reads the
+//! same in ISO-8859-1 and UTF-8.
+
+use std::fmt::Write;
+
+use crate::value::{Value, ordered_entries};
+
+/// Renders the leaves of `value` as `.properties` lines, see the module documentation.
+pub(crate) fn render_properties(value: &Value) -> String {
+    let mut rendered = String::new();
+    if let Value::Object(_) = value.unshared() {
+        render_entries(value, &mut String::new(), &mut rendered);
+    }
+    rendered
+}
+
+fn render_entries(value: &Value, path: &mut String, out: &mut String) {
+    let len = path.len();
+    match value.unshared() {
+        Value::Object(object) => {
+            for (key, value) in ordered_entries(object) {
+                push_segment(path, len, key);
+                render_entries(value, path, out);
+                path.truncate(len);
+            }
+        }
+        Value::Array(array) => {
+            for (index, element) in array.iter().enumerate() {
+                push_segment(path, len, &index.to_string());
+                render_entries(element, path, out);
+                path.truncate(len);
+            }
+        }
+        Value::Null => {}
+        Value::Boolean(boolean) => write_entry(path, &boolean.to_string(), out),
+        Value::Number(number) => write_entry(path, &number.to_string(), out),
+        Value::NonFinite(non_finite) => write_entry(path, &non_finite.to_string(), out),
+        Value::String(string) => write_entry(path, string, out),
+        Value::Shared(_) => unreachable!("unshared values are never shared"),
+    }
+}
+
+fn push_segment(path: &mut String, len: usize, segment: &str) {
+    if len > 0 {
+        path.push('.');
+    }
+    path.push_str(segment);
+}
+
+fn write_entry(key: &str, value: &str, out: &mut String) {
+    escape(key, true, out);
+    out.push('=');
+    escape(value, false, out);
+    out.push('\n');
+}
+
+/// Escapes `s` like `java.util.Properties::store`. Spaces are significant in keys, in
+/// values only a leading one needs escaping.
+fn escape(s: &str, is_key: bool, out: &mut String) {
+    for (index, ch) in s.chars().enumerate() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            ' ' if is_key || index == 0 => out.push_str("\\ "),
+            ' '..='~' => out.push(ch),
+            _ => {
+                let mut units = [0; 2];
+                for unit in ch.encode_utf16(&mut units) {
+                    write!(out, "\\u{unit:04X}").expect("writing to a String never fails");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Result;
+    use crate::config::Config;
+    use crate::value::Value;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("a { b = 1, c = true }", "a.b=1\na.c=true\n")]
+    #[case(
+        "servers = [{ host = x }, { host = y }]",
+        "servers.0.host=x\nservers.1.host=y\n"
+    )]
+    #[case("a = null, b = [], c = {}", "")]
+    #[case("\"a b\" = \" x = y \"", "a\\ b=\\ x \\= y \n")]
+    #[case("a = \"#!:\\\\\\t\\n\"", "a=\\#\\!\\:\\\\\\t\\n\n")]
+    #[case("a = \"é\u{1}😀\"", "a=\\u00E9\\u0001\\uD83D\\uDE00\n")]
+    fn test_render_properties(#[case] input: &str, #[case] expected: &str) -> Result<()> {
+        let value: Value = Config::parse_str(input, None)?;
+        assert_eq!(value.to_properties_string(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_properties_round_trip() -> Result<()> {
+        let value: Value = Config::parse_str("a { b = \"x = y\", c = \"ü \\n\" }", None)?;
+        let properties = java_properties::read(value.to_properties_string().as_bytes())?;
+        assert_eq!(properties["a.b"], "x = y");
+        assert_eq!(properties["a.c"], "ü \n");
+        Ok(())
+    }
+}
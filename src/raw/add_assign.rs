@@ -4,16 +4,23 @@ use crate::raw::raw_value::RawValue;
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deref, DerefMut, Constructor)]
-pub struct AddAssign(Box<RawValue>);
+pub struct AddAssign<'de>(Box<RawValue<'de>>);
 
-impl Display for AddAssign {
+impl AddAssign<'_> {
+    /// Copies the strings borrowed from the input, see [`RawValue::into_owned`].
+    pub fn into_owned(self) -> AddAssign<'static> {
+        AddAssign(Box::new(self.0.into_owned()))
+    }
+}
+
+impl Display for AddAssign<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-impl From<AddAssign> for RawValue {
-    fn from(val: AddAssign) -> Self {
+impl<'de> From<AddAssign<'de>> for RawValue<'de> {
+    fn from(val: AddAssign<'de>) -> Self {
         *val.0
     }
 }
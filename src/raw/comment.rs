@@ -1,7 +1,9 @@
 use derive_more::{Constructor, Deref, DerefMut};
 use std::fmt::{Display, Formatter};
 
+/// The marker a comment starts with.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
 pub enum CommentType {
     DoubleSlash,
     Hash,
@@ -16,15 +18,26 @@ impl Display for CommentType {
     }
 }
 
+/// A comment, derefs to its text after the marker.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Constructor, Deref, DerefMut)]
 pub struct Comment {
     #[deref]
     #[deref_mut]
-    pub content: String,
-    pub ty: CommentType,
+    pub(crate) content: String,
+    pub(crate) ty: CommentType,
 }
 
 impl Comment {
+    /// Returns the text after the comment marker, including leading spaces.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the marker the comment starts with.
+    pub fn ty(&self) -> CommentType {
+        self.ty
+    }
+
     pub fn double_slash(comment: impl Into<String>) -> Comment {
         Comment::new(comment.into(), CommentType::DoubleSlash)
     }
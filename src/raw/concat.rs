@@ -1,3 +1,4 @@
+use crate::raw::space::Space;
 use crate::{error::Error, join, raw::raw_value::RawValue};
 use std::fmt::Display;
 
@@ -9,26 +10,26 @@ use std::fmt::Display;
 ///
 /// # Fields
 /// - `values`: The list of HOCON values being concatenated.
-/// - `spaces`: Optional string fragments representing spaces between values.
+/// - `spaces`: The optional whitespace between values, see [`Space`].
 ///   `spaces.len() + 1` must equal `values.len()`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Concat {
-    values: Vec<RawValue>,
-    spaces: Vec<Option<String>>,
+pub struct Concat<'de> {
+    values: Vec<RawValue<'de>>,
+    spaces: Vec<Option<Space>>,
 }
 
-impl Concat {
+impl<'de> Concat<'de> {
     /// Creates a new `Concat` instance.
     ///
     /// # Arguments
     /// * `values` - A vector of `RawValue` elements to be concatenated.
-    /// * `spaces` - A vector of optional strings representing spaces between values.
+    /// * `spaces` - The optional whitespace between values.
     ///
     /// # Errors
     /// Returns `Error::InvalidConcat` if `values.len() != spaces.len() + 1`.
     /// Returns `Error::InvalidValue` if any value is a nested `Concat` or `AddAssign`,
     /// which are not allowed within a concatenation.
-    pub fn new(values: Vec<RawValue>, spaces: Vec<Option<String>>) -> crate::Result<Self> {
+    pub fn new(values: Vec<RawValue<'de>>, spaces: Vec<Option<Space>>) -> crate::Result<Self> {
         if values.len() != spaces.len() + 1 {
             return Err(Error::InvalidConcat(values.len(), spaces.len()));
         }
@@ -47,22 +48,35 @@ impl Concat {
     /// Consumes the `Concat` and returns its internal vectors.
     ///
     /// Returns a tuple `(values, spaces)`.
-    pub fn into_inner(self) -> (Vec<RawValue>, Vec<Option<String>>) {
+    pub fn into_inner(self) -> (Vec<RawValue<'de>>, Vec<Option<Space>>) {
         (self.values, self.spaces)
     }
 
     /// Returns a reference to the vector of concatenated values.
-    pub fn get_values(&self) -> &Vec<RawValue> {
+    pub fn get_values(&self) -> &Vec<RawValue<'de>> {
         &self.values
     }
 
+    /// Returns the concatenated values for in-place edits that keep their number.
+    pub(crate) fn values_mut(&mut self) -> &mut [RawValue<'de>] {
+        &mut self.values
+    }
+
     /// Returns a reference to the vector of optional spaces between values.
-    pub fn get_spaces(&self) -> &Vec<Option<String>> {
+    pub fn get_spaces(&self) -> &Vec<Option<Space>> {
         &self.spaces
     }
+
+    /// Copies the strings borrowed from the input, see [`RawValue::into_owned`].
+    pub fn into_owned(self) -> Concat<'static> {
+        Concat {
+            values: self.values.into_iter().map(RawValue::into_owned).collect(),
+            spaces: self.spaces,
+        }
+    }
 }
 
-impl Display for Concat {
+impl Display for Concat<'_> {
     /// Formats the concatenated values as a string.
     ///
     /// Uses the `join` function to output all `values` separated by a single space.
@@ -1,25 +1,67 @@
+use crate::origin::Origin;
+use crate::parser::read::Span;
 use crate::raw::comment::Comment;
 use crate::raw::include::Inclusion;
+use crate::raw::raw_object::RawObject;
 use crate::raw::raw_string::RawString;
 use crate::raw::raw_value::RawValue;
 use std::fmt::{Display, Formatter};
 
+/// An entry of a [RawObject]: a field, an include or a line of its own.
+///
+/// New variants and fields may be added, use the constructors like
+/// [ObjectField::key_value] to create fields.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum ObjectField {
+#[non_exhaustive]
+pub enum ObjectField<'de> {
+    #[non_exhaustive]
     Inclusion {
-        inclusion: Inclusion,
+        inclusion: Inclusion<'de>,
         comment: Option<Comment>,
     },
+    #[non_exhaustive]
     KeyValue {
-        key: RawString,
-        value: RawValue,
+        key: RawString<'de>,
+        value: RawValue<'de>,
         comment: Option<Comment>,
+        origin: Option<Origin>,
+        spans: Option<FieldSpans>,
     },
     NewlineComment(Comment),
+    /// An empty line between fields, kept so rendering reproduces the grouping of fields.
+    BlankLine,
 }
 
-impl ObjectField {
-    pub fn inclusion(inclusion: Inclusion) -> ObjectField {
+/// Where the key and the value of a parsed key-value field are, see
+/// [`ObjectField::key_span`] and [`ObjectField::value_span`].
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::Config;
+/// use hocon_rs::raw::field::ObjectField;
+/// use hocon_rs::raw::raw_value::RawValue;
+///
+/// let config =
+///     Config::parse_str_raw("a.b += [1, 2]\ninclude \"x.conf\"\nc { d = x }", None).unwrap();
+/// let fields = config.fields();
+/// assert_eq!(fields[0].key_span().unwrap().to_string(), "1:0-1:3");
+/// assert_eq!(fields[0].value_span().unwrap().to_string(), "1:7-1:13");
+/// assert_eq!(fields[1].span().unwrap().to_string(), "2:0-2:16");
+/// let ObjectField::KeyValue { value: RawValue::Object(c), .. } = &fields[2] else {
+///     unreachable!()
+/// };
+/// assert_eq!(c.span().unwrap().to_string(), "3:2-3:11");
+/// assert_eq!(c.fields()[0].value_span().unwrap().to_string(), "3:8-3:9");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSpans {
+    pub key: Span,
+    /// The value after the separator. For `key += value` this excludes the `+=`.
+    pub value: Span,
+}
+
+impl<'de> ObjectField<'de> {
+    pub fn inclusion(inclusion: Inclusion<'de>) -> ObjectField<'de> {
         ObjectField::Inclusion {
             inclusion,
             comment: None,
@@ -27,36 +69,43 @@ impl ObjectField {
     }
 
     pub fn inclusion_with_comment(
-        inclusion: Inclusion,
+        inclusion: Inclusion<'de>,
         comment: impl Into<Comment>,
-    ) -> ObjectField {
+    ) -> ObjectField<'de> {
         ObjectField::Inclusion {
             inclusion,
             comment: Some(comment.into()),
         }
     }
 
-    pub fn key_value(key: impl Into<RawString>, value: impl Into<RawValue>) -> ObjectField {
+    pub fn key_value(
+        key: impl Into<RawString<'de>>,
+        value: impl Into<RawValue<'de>>,
+    ) -> ObjectField<'de> {
         ObjectField::KeyValue {
             key: key.into(),
             value: value.into(),
             comment: None,
+            origin: None,
+            spans: None,
         }
     }
 
     pub fn key_value_with_comment(
-        key: impl Into<RawString>,
-        value: impl Into<RawValue>,
+        key: impl Into<RawString<'de>>,
+        value: impl Into<RawValue<'de>>,
         comment: impl Into<Comment>,
-    ) -> ObjectField {
+    ) -> ObjectField<'de> {
         ObjectField::KeyValue {
             key: key.into(),
             value: value.into(),
             comment: Some(comment.into()),
+            origin: None,
+            spans: None,
         }
     }
 
-    pub fn newline_comment(comment: impl Into<Comment>) -> ObjectField {
+    pub fn newline_comment(comment: impl Into<Comment>) -> ObjectField<'de> {
         ObjectField::NewlineComment(comment.into())
     }
 
@@ -65,11 +114,134 @@ impl ObjectField {
             ObjectField::Inclusion { comment: c, .. }
             | ObjectField::KeyValue { comment: c, .. } => *c = Some(comment),
             ObjectField::NewlineComment(c) => *c = comment,
+            ObjectField::BlankLine => *self = ObjectField::NewlineComment(comment),
+        }
+    }
+
+    /// Returns the comment on the same line as an inclusion or key-value field, or the
+    /// comment itself for a [`ObjectField::NewlineComment`].
+    pub fn comment(&self) -> Option<&Comment> {
+        match self {
+            ObjectField::Inclusion { comment, .. } | ObjectField::KeyValue { comment, .. } => {
+                comment.as_ref()
+            }
+            ObjectField::NewlineComment(comment) => Some(comment),
+            ObjectField::BlankLine => None,
+        }
+    }
+
+    /// Removes the comment on the same line as an inclusion or key-value field.
+    pub fn take_comment(&mut self) -> Option<Comment> {
+        match self {
+            ObjectField::Inclusion { comment, .. } | ObjectField::KeyValue { comment, .. } => {
+                comment.take()
+            }
+            ObjectField::NewlineComment(_) | ObjectField::BlankLine => None,
+        }
+    }
+
+    /// Returns the object an inclusion or key-value field holds, if any.
+    pub(crate) fn nested_object(&self) -> Option<&RawObject<'de>> {
+        match self {
+            ObjectField::Inclusion { inclusion, .. } => inclusion.val.as_deref(),
+            ObjectField::KeyValue {
+                value: RawValue::Object(obj),
+                ..
+            } => Some(obj),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn nested_object_mut(&mut self) -> Option<&mut RawObject<'de>> {
+        match self {
+            ObjectField::Inclusion { inclusion, .. } => inclusion.val.as_deref_mut(),
+            ObjectField::KeyValue {
+                value: RawValue::Object(obj),
+                ..
+            } => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Returns where this field was defined, if it is a key-value field parsed from a source.
+    pub fn origin(&self) -> Option<&Origin> {
+        match self {
+            ObjectField::KeyValue { origin, .. } => origin.as_ref(),
+            ObjectField::Inclusion { .. }
+            | ObjectField::NewlineComment(_)
+            | ObjectField::BlankLine => None,
+        }
+    }
+
+    /// Records where this field was defined. Has no effect on inclusions and comments.
+    pub fn set_origin(&mut self, origin: Origin) {
+        if let ObjectField::KeyValue { origin: o, .. } = self {
+            *o = Some(origin);
+        }
+    }
+
+    /// Returns where the whole field or include statement was parsed from, if it was.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ObjectField::KeyValue { spans, .. } => spans.map(|spans| Span {
+                start: spans.key.start,
+                end: spans.value.end,
+            }),
+            ObjectField::Inclusion { inclusion, .. } => inclusion.span(),
+            ObjectField::NewlineComment(_) | ObjectField::BlankLine => None,
+        }
+    }
+
+    /// Returns where the key of a key-value field was parsed from, if it was.
+    pub fn key_span(&self) -> Option<Span> {
+        match self {
+            ObjectField::KeyValue { spans, .. } => spans.map(|spans| spans.key),
+            _ => None,
+        }
+    }
+
+    /// Returns where the value of a key-value field was parsed from, if it was. Elements of
+    /// arrays have spans of their own, see [`crate::raw::raw_array::RawArray::element_span`].
+    pub fn value_span(&self) -> Option<Span> {
+        match self {
+            ObjectField::KeyValue { spans, .. } => spans.map(|spans| spans.value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_spans(&mut self, field_spans: FieldSpans) {
+        if let ObjectField::KeyValue { spans, .. } = self {
+            *spans = Some(field_spans);
+        }
+    }
+
+    /// Copies the strings borrowed from the input, see [`RawValue::into_owned`].
+    pub fn into_owned(self) -> ObjectField<'static> {
+        match self {
+            ObjectField::Inclusion { inclusion, comment } => ObjectField::Inclusion {
+                inclusion: inclusion.into_owned(),
+                comment,
+            },
+            ObjectField::KeyValue {
+                key,
+                value,
+                comment,
+                origin,
+                spans,
+            } => ObjectField::KeyValue {
+                key: key.into_owned(),
+                value: value.into_owned(),
+                comment,
+                origin,
+                spans,
+            },
+            ObjectField::NewlineComment(comment) => ObjectField::NewlineComment(comment),
+            ObjectField::BlankLine => ObjectField::BlankLine,
         }
     }
 }
 
-impl Display for ObjectField {
+impl Display for ObjectField<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ObjectField::Inclusion { inclusion, comment } => {
@@ -82,6 +254,7 @@ impl Display for ObjectField {
                 key,
                 value,
                 comment,
+                ..
             } => {
                 write!(f, "{}: {}", key, value)?;
                 if let Some(comment) = comment {
@@ -91,6 +264,7 @@ impl Display for ObjectField {
             ObjectField::NewlineComment(c) => {
                 write!(f, "{}", c)?;
             }
+            ObjectField::BlankLine => {}
         }
         Ok(())
     }
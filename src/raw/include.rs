@@ -1,20 +1,90 @@
-use derive_more::Constructor;
-
+use crate::parser::read::Span;
 use crate::raw::raw_object::RawObject;
 use std::{fmt::Display, rc::Rc};
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Constructor)]
-pub struct Inclusion {
-    pub path: Rc<String>,
-    pub required: bool,
-    pub location: Option<Location>,
-    pub val: Option<Box<RawObject>>,
+/// An `include` directive, together with the object it loaded.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Inclusion<'de> {
+    pub(crate) path: Rc<String>,
+    pub(crate) required: bool,
+    pub(crate) location: Option<Location>,
+    /// The loaded object. It is kept in the tree, and merged with the rest of the
+    /// configuration only when that is resolved.
+    pub(crate) val: Option<Box<RawObject<'de>>>,
+    pub(crate) span: Option<Span>,
+    /// The locations searched when nothing was found.
+    pub(crate) searched: Vec<String>,
+}
+
+impl<'de> Inclusion<'de> {
+    pub fn new(
+        path: Rc<String>,
+        required: bool,
+        location: Option<Location>,
+        val: Option<Box<RawObject<'de>>>,
+    ) -> Self {
+        Self {
+            path,
+            required,
+            location,
+            val,
+            span: None,
+            searched: vec![],
+        }
+    }
+
+    /// Returns the included path or URL as written.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns `true` for `include required(...)`, which fails if nothing is found.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns where the path is looked up, `None` if it wasn't specified.
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+
+    /// Returns the loaded object, `None` if the directive wasn't loaded or an optional
+    /// include wasn't found.
+    pub fn object(&self) -> Option<&RawObject<'de>> {
+        self.val.as_deref()
+    }
+
+    /// Returns the files, classpath resources and URLs looked up by an optional include
+    /// that found nothing, in order.
+    pub fn searched(&self) -> &[String] {
+        &self.searched
+    }
+
+    /// Returns where the statement was parsed from, from `include` to the closing quote
+    /// or parenthesis.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Copies the strings of the loaded object, see [`RawObject::into_owned`].
+    pub fn into_owned(self) -> Inclusion<'static> {
+        Inclusion {
+            path: self.path,
+            required: self.required,
+            location: self.location,
+            val: self.val.map(|val| Box::new(val.into_owned())),
+            span: self.span,
+            searched: self.searched,
+        }
+    }
 }
 
+/// Where an [Inclusion] looks up its path, the `file(...)`, `url(...)` and
+/// `classpath(...)` forms.
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+#[non_exhaustive]
 pub enum Location {
     File,
-    #[cfg(feature = "urls_includes")]
     Url,
     Classpath,
 }
@@ -23,14 +93,13 @@ impl Display for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Location::File => write!(f, "file"),
-            #[cfg(feature = "urls_includes")]
             Location::Url => write!(f, "url"),
             Location::Classpath => write!(f, "classpath"),
         }
     }
 }
 
-impl Display for Inclusion {
+impl Display for Inclusion<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "include ")?;
         if self.required {
@@ -0,0 +1,185 @@
+//! The source text of parsed objects and arrays.
+//!
+//! The parser records where every object and array starts and ends, and once the whole
+//! input is read, the text in between is attached together with a hash of the parsed
+//! node. The renderer writes nodes whose hash still matches as their original text, so
+//! unchanged parts of a configuration keep their exact formatting, including comments
+//! inside arrays and the separators that the raw tree doesn't model.
+//!
+//! Objects also keep the text and hash of each of their fields, from the key to the end
+//! of the value, and of their keys, so the fields that weren't edited keep their
+//! formatting after another field of the object changed, and edited values keep the key
+//! and indentation they were written with.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::parser::read::{Position, Span};
+use crate::raw::field::FieldSpans;
+
+/// The text a parser read, with the start of each line to map positions to offsets.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceText {
+    text: Arc<str>,
+    line_starts: Vec<usize>,
+}
+
+impl SourceText {
+    pub(crate) fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(memchr::memchr_iter(b'\n', text.as_bytes()).map(|i| i + 1))
+            .collect();
+        Self {
+            text: Arc::from(text),
+            line_starts,
+        }
+    }
+
+    /// Returns the byte range from `start` to `end` if both are on character boundaries.
+    fn range(&self, start: Position, end: Position) -> Option<Range<usize>> {
+        let (start, end) = self.offset(start).zip(self.offset(end))?;
+        (start <= end && self.text.is_char_boundary(start) && self.text.is_char_boundary(end))
+            .then_some(start..end)
+    }
+
+    /// Returns the whitespace before `position` if nothing else precedes it on its line.
+    fn indentation(&self, position: Position) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(position.line.checked_sub(1)?)?;
+        let end = self.offset(position)?;
+        let indent = self.text.as_bytes().get(start..end)?;
+        indent
+            .iter()
+            .all(|b| matches!(b, b' ' | b'\t'))
+            .then_some(start..end)
+    }
+
+    /// Returns the byte offset of `position`, columns count bytes.
+    fn offset(&self, position: Position) -> Option<usize> {
+        let offset = self.line_starts.get(position.line.checked_sub(1)?)? + position.column;
+        (offset <= self.text.len()).then_some(offset)
+    }
+}
+
+/// Where a node was parsed from, see the module documentation.
+#[derive(Debug, Clone)]
+pub(crate) struct Layout {
+    span: Span,
+    text: Option<(Arc<str>, Range<usize>)>,
+    hash: u64,
+    /// The text of the fields, by the start of their key.
+    fields: Vec<FieldText>,
+}
+
+impl Layout {
+    pub(crate) fn new(start: Position, end: Position) -> Box<Layout> {
+        Box::new(Layout {
+            span: Span { start, end },
+            text: None,
+            hash: 0,
+            fields: vec![],
+        })
+    }
+
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Attaches the text of the recorded span, `node` is the node as parsed.
+    pub(crate) fn attach<T: Hash + ?Sized>(&mut self, source: &SourceText, node: &T) {
+        let range = source
+            .offset(self.span.start)
+            .zip(source.offset(self.span.end))
+            .filter(|(start, end)| start <= end && source.text.is_char_boundary(*start))
+            .map(|(start, end)| start..end);
+        if let Some(range) = range
+            && source.text.is_char_boundary(range.end)
+        {
+            self.text = Some((source.text.clone(), range));
+            self.hash = content_hash(node);
+        }
+    }
+
+    /// Returns the original text if `node` is still the node it was parsed as.
+    pub(crate) fn text<T: Hash + ?Sized>(&self, node: &T) -> Option<&str> {
+        let (text, range) = self.text.as_ref()?;
+        (content_hash(node) == self.hash).then(|| &text[range.clone()])
+    }
+
+    /// Attaches the text of a field of the object, `field` and `key` are the field and its
+    /// key as parsed.
+    pub(crate) fn attach_field<T: Hash + ?Sized, K: Hash + ?Sized>(
+        &mut self,
+        source: &SourceText,
+        spans: FieldSpans,
+        field: &T,
+        key: &K,
+    ) {
+        let field_range = source.range(spans.key.start, spans.value.end);
+        let key_range = source.range(spans.key.start, spans.key.end);
+        if let Some((field_range, key_range)) = field_range.zip(key_range) {
+            self.fields.push(FieldText {
+                start: spans.key.start,
+                text: source.text.clone(),
+                indent: source.indentation(spans.key.start),
+                field: (field_range, content_hash(field)),
+                key: (key_range, content_hash(key)),
+            });
+        }
+    }
+
+    /// The fields are attached in document order, so they can be searched by position.
+    fn field(&self, spans: FieldSpans) -> Option<&FieldText> {
+        let start = spans.key.start;
+        let index = self
+            .fields
+            .binary_search_by_key(&(start.line, start.column), |f| {
+                (f.start.line, f.start.column)
+            })
+            .ok()?;
+        self.fields.get(index)
+    }
+
+    /// Returns the original text of the field parsed at `spans` if `field` is still the
+    /// field it was parsed as.
+    pub(crate) fn field_text<T: Hash + ?Sized>(
+        &self,
+        spans: FieldSpans,
+        field: &T,
+    ) -> Option<&str> {
+        let entry = self.field(spans)?;
+        let (range, hash) = &entry.field;
+        (content_hash(field) == *hash).then(|| &entry.text[range.clone()])
+    }
+
+    /// Returns the whitespace the field parsed at `spans` was indented with.
+    pub(crate) fn indent_text(&self, spans: FieldSpans) -> Option<&str> {
+        let entry = self.field(spans)?;
+        entry.indent.clone().map(|range| &entry.text[range])
+    }
+
+    /// Returns the original text of the key of the field parsed at `spans` if `key` is
+    /// still the key it was parsed as.
+    pub(crate) fn key_text<K: Hash + ?Sized>(&self, spans: FieldSpans, key: &K) -> Option<&str> {
+        let entry = self.field(spans)?;
+        let (range, hash) = &entry.key;
+        (content_hash(key) == *hash).then(|| &entry.text[range.clone()])
+    }
+}
+
+/// The text of a field of an object, with its indentation if it starts a line, and the
+/// range and hash of the whole field and of its key.
+#[derive(Debug, Clone)]
+struct FieldText {
+    start: Position,
+    text: Arc<str>,
+    indent: Option<Range<usize>>,
+    field: (Range<usize>, u64),
+    key: (Range<usize>, u64),
+}
+
+fn content_hash<T: Hash + ?Sized>(node: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    node.hash(&mut hasher);
+    hasher.finish()
+}
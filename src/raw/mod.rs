@@ -1,11 +1,51 @@
+//! The unresolved syntax tree of a configuration, as returned by
+//! [`crate::Config::load_raw`] and friends.
+//!
+//! This is the API for tools that work on configurations as written, such as formatters
+//! and linters. It stays compatible across minor releases:
+//!
+//! - Enums like [`raw_value::RawValue`] and [`field::ObjectField`] are `#[non_exhaustive]`,
+//!   so matches need a wildcard arm and variants with named fields need `..`. Nodes are
+//!   created with constructors such as [`field::ObjectField::key_value`].
+//! - Structs have private fields and are read through accessors.
+//!
+//! # Example
+//! ```rust
+//! use hocon_rs::Config;
+//! use hocon_rs::raw::field::ObjectField;
+//! use hocon_rs::raw::raw_value::RawValue;
+//!
+//! let config = Config::parse_str_raw("a = 1 // one\nb = ${a}\ninclude \"x.conf\"", None).unwrap();
+//! let mut summary = vec![];
+//! for field in config.fields() {
+//!     match field {
+//!         ObjectField::KeyValue { key, value, comment, .. } => {
+//!             let kind = match value {
+//!                 RawValue::Substitution(substitution) => format!("-> {}", substitution.path()),
+//!                 other => other.ty().to_string(),
+//!             };
+//!             let comment = comment.as_ref().map(|comment| comment.content().trim());
+//!             summary.push(format!("{key}: {kind} {comment:?}"));
+//!         }
+//!         ObjectField::Inclusion { inclusion, .. } => {
+//!             summary.push(format!("include {}", inclusion.path()));
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! assert_eq!(summary, ["a: number Some(\"one\")", "b: -> a None", "include x.conf"]);
+//! ```
+
 pub mod add_assign;
 pub mod comment;
 pub mod concat;
 pub mod field;
 pub mod include;
+pub(crate) mod layout;
 pub mod macros;
 pub mod raw_array;
 pub mod raw_object;
 pub mod raw_string;
 pub mod raw_value;
+pub mod space;
 pub mod substitution;
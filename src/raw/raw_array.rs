@@ -1,17 +1,109 @@
+use crate::parser::read::{Position, Span};
+use crate::raw::layout::{Layout, SourceText};
 use crate::{join, raw::raw_value::RawValue};
-use derive_more::{Constructor, Deref, DerefMut};
-use std::fmt::Display;
+use derive_more::{Deref, DerefMut};
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deref, DerefMut, Constructor)]
-pub struct RawArray(pub Vec<RawValue>);
+/// An array as written. Like [`crate::raw::raw_object::RawObject`], parsed arrays remember
+/// their source text, which is not part of equality.
+#[derive(Clone, Deref, DerefMut)]
+pub struct RawArray<'de>(
+    #[deref]
+    #[deref_mut]
+    pub(crate) Vec<RawValue<'de>>,
+    Option<Box<Layout>>,
+    /// Where each element was parsed from, empty if the array wasn't parsed.
+    Vec<Span>,
+);
 
-impl RawArray {
-    pub fn into_inner(self) -> Vec<RawValue> {
+impl PartialEq for RawArray<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for RawArray<'_> {}
+
+impl Hash for RawArray<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Debug for RawArray<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RawArray").field(&self.0).finish()
+    }
+}
+
+impl<'de> RawArray<'de> {
+    pub fn new(values: Vec<RawValue<'de>>) -> Self {
+        Self(values, None, vec![])
+    }
+
+    /// Returns the elements in document order.
+    pub fn values(&self) -> &[RawValue<'de>] {
+        &self.0
+    }
+
+    /// Returns the elements for editing. Changed arrays are rendered from their elements
+    /// instead of their source text.
+    pub fn values_mut(&mut self) -> &mut Vec<RawValue<'de>> {
+        &mut self.0
+    }
+
+    /// Records that the array spans `start..end` of the parsed input.
+    pub(crate) fn set_span(&mut self, start: Position, end: Position) {
+        self.1 = Some(Layout::new(start, end));
+    }
+
+    /// Returns where the array was parsed from, from `[` to `]`.
+    pub fn span(&self) -> Option<Span> {
+        self.1.as_ref().map(|layout| layout.span())
+    }
+
+    /// Records where each element was parsed from, in the order of the elements.
+    pub(crate) fn set_element_spans(&mut self, spans: Vec<Span>) {
+        self.2 = spans;
+    }
+
+    /// Returns where the element at `index` was parsed from. Not available for arrays that
+    /// weren't parsed or whose elements were added or removed since.
+    pub fn element_span(&self, index: usize) -> Option<Span> {
+        if self.2.len() != self.0.len() {
+            return None;
+        }
+        self.2.get(index).copied()
+    }
+
+    pub(crate) fn attach_source(&mut self, source: &SourceText) {
+        for value in self.0.iter_mut() {
+            value.attach_source(source);
+        }
+        if let Some(layout) = &mut self.1 {
+            layout.attach(source, &self.0);
+        }
+    }
+
+    /// Returns the source text of the array if it was parsed and hasn't changed since.
+    pub(crate) fn verbatim(&self) -> Option<&str> {
+        self.1.as_ref()?.text(&self.0)
+    }
+
+    pub fn into_inner(self) -> Vec<RawValue<'de>> {
         self.0
     }
+
+    /// Copies the strings borrowed from the input, see [`RawValue::into_owned`]. The
+    /// source text and spans are kept.
+    pub fn into_owned(self) -> RawArray<'static> {
+        let values = self.0.into_iter().map(RawValue::into_owned).collect();
+        RawArray(values, self.1, self.2)
+    }
 }
 
-impl Display for RawArray {
+impl Display for RawArray<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
         join(self.iter(), ", ", f)?;
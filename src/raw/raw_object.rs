@@ -1,22 +1,190 @@
 use crate::join;
+use crate::origin::{Origin, SourceId};
+use crate::parser::read::{Position, Span};
+use crate::path::{PathSegment, Selector, render_path};
+use crate::raw::comment::Comment;
 use crate::raw::field::ObjectField;
+use crate::raw::layout::{Layout, SourceText};
 use crate::raw::raw_string::RawString;
 use crate::raw::raw_value::RawValue;
+use crate::raw::substitution::Substitution;
+use crate::render::Renderer;
 use crate::{path::Path, value::Value};
-use derive_more::{Constructor, Deref, DerefMut};
-use std::fmt::{Display, Formatter};
+use derive_more::{Deref, DerefMut};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Deref, DerefMut, Constructor)]
-pub struct RawObject(pub Vec<ObjectField>);
+/// An object as written, before includes are merged and substitutions are resolved.
+///
+/// Parsed objects remember their source text, which is rendered again as long as the
+/// fields stay the same. The source text is not part of equality.
+#[derive(Clone, Default, Deref, DerefMut)]
+pub struct RawObject<'de>(
+    #[deref]
+    #[deref_mut]
+    pub(crate) Vec<ObjectField<'de>>,
+    Option<Box<Layout>>,
+);
 
-impl RawObject {
-    pub fn into_inner(self) -> Vec<ObjectField> {
+impl PartialEq for RawObject<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for RawObject<'_> {}
+
+impl Hash for RawObject<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Debug for RawObject<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RawObject").field(&self.0).finish()
+    }
+}
+
+impl<'de> RawObject<'de> {
+    pub fn new(fields: Vec<ObjectField<'de>>) -> Self {
+        Self(fields, None)
+    }
+
+    pub fn into_inner(self) -> Vec<ObjectField<'de>> {
         self.0
     }
 
-    pub fn from_entries<I>(entries: Vec<(RawString, RawValue)>) -> Self
+    /// Returns the fields, comments and blank lines in document order.
+    pub fn fields(&self) -> &[ObjectField<'de>] {
+        &self.0
+    }
+
+    /// Returns the fields for editing. Changed objects are rendered from their fields
+    /// instead of their source text.
+    pub fn fields_mut(&mut self) -> &mut Vec<ObjectField<'de>> {
+        &mut self.0
+    }
+
+    /// Copies the strings borrowed from the input, so the object outlives it, e.g. to keep
+    /// an object parsed with [`crate::Config::parse_str_raw`]. The source text and spans
+    /// are kept.
+    pub fn into_owned(self) -> RawObject<'static> {
+        let fields = self.0.into_iter().map(ObjectField::into_owned).collect();
+        RawObject(fields, self.1)
+    }
+
+    /// Records that the object spans `start..end` of the parsed input.
+    pub(crate) fn set_span(&mut self, start: Position, end: Position) {
+        self.1 = Some(Layout::new(start, end));
+    }
+
+    /// Returns where the object was parsed from, from `{` to `}`. Objects without braces,
+    /// like the root of most documents, have no span.
+    pub fn span(&self) -> Option<Span> {
+        self.1.as_ref().map(|layout| layout.span())
+    }
+
+    /// Attaches the source text to this object and the objects and arrays in it, see
+    /// [`crate::raw::layout`]. Included objects already carry the text of their own file.
+    pub(crate) fn attach_source(&mut self, source: &SourceText) {
+        for field in self.0.iter_mut() {
+            if let ObjectField::KeyValue { value, .. } = field {
+                value.attach_source(source);
+            }
+        }
+        if let Some(layout) = &mut self.1 {
+            layout.attach(source, &self.0);
+            for field in &self.0 {
+                if let ObjectField::KeyValue {
+                    key,
+                    value,
+                    spans: Some(spans),
+                    ..
+                } = field
+                {
+                    layout.attach_field(source, *spans, &(key, value), key);
+                }
+            }
+        }
+    }
+
+    /// Returns the source text of the object if it was parsed and hasn't changed since.
+    pub(crate) fn verbatim(&self) -> Option<&str> {
+        self.1.as_ref()?.text(&self.0)
+    }
+
+    /// Returns the source text of `field`, from its key to the end of its value, if it is
+    /// a field of this object that hasn't changed since it was parsed.
+    pub(crate) fn field_verbatim(&self, field: &ObjectField) -> Option<&str> {
+        let ObjectField::KeyValue {
+            key,
+            value,
+            spans: Some(spans),
+            ..
+        } = field
+        else {
+            return None;
+        };
+        self.1.as_ref()?.field_text(*spans, &(key, value))
+    }
+
+    /// Returns the indentation of `field` if it is a field of this object that was parsed at
+    /// the start of a line.
+    pub(crate) fn indent_verbatim(&self, field: &ObjectField) -> Option<&str> {
+        let ObjectField::KeyValue {
+            spans: Some(spans), ..
+        } = field
+        else {
+            return None;
+        };
+        self.1.as_ref()?.indent_text(*spans)
+    }
+
+    /// Returns the source text of the key of `field` if it is a field of this object whose
+    /// key hasn't changed since it was parsed.
+    pub(crate) fn key_verbatim(&self, field: &ObjectField) -> Option<&str> {
+        let ObjectField::KeyValue {
+            key,
+            spans: Some(spans),
+            ..
+        } = field
+        else {
+            return None;
+        };
+        self.1.as_ref()?.key_text(*spans, key)
+    }
+
+    /// Renders the object as HOCON or JSON without resolving it, see
+    /// [`crate::RenderOptions`].
+    ///
+    /// Field order, include statements, substitutions and `+=` fields are written as they
+    /// are, so the output parses back to the same raw tree. Useful to combine files without
+    /// resolving them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, RenderOptions};
+    ///
+    /// let base = Config::parse_str_raw("port = 80", None).unwrap();
+    /// let app = Config::parse_str_raw("url = \"http://\"${host}\nlist += 1", None).unwrap();
+    /// let options = RenderOptions { comments: false, ..Default::default() };
+    /// assert_eq!(
+    ///     app.with_fallback(base).render(&options),
+    ///     "\"port\" = 80\n\"url\" = \"http://\"${\"host\"}\n\"list\" += 1\n"
+    /// );
+    /// ```
+    pub fn render(&self, options: &crate::RenderOptions) -> String {
+        let mut rendered = String::new();
+        Renderer::with_options(&mut rendered, options.clone())
+            .render_root_raw(self)
+            .expect("writing to a String never fails");
+        rendered
+    }
+
+    pub fn from_entries<I>(entries: Vec<(RawString<'de>, RawValue<'de>)>) -> Self
     where
-        I: IntoIterator<Item = (RawString, RawValue)>,
+        I: IntoIterator<Item = (RawString<'de>, RawValue<'de>)>,
     {
         let fields = entries
             .into_iter()
@@ -25,7 +193,7 @@ impl RawObject {
         Self::new(fields)
     }
 
-    pub fn remove_by_path(&mut self, path: &Path) -> Option<ObjectField> {
+    pub fn remove_by_path(&mut self, path: &Path) -> Option<ObjectField<'de>> {
         let mut remove_index = None;
         for (index, field) in self.iter_mut().enumerate().rev() {
             match field {
@@ -50,7 +218,7 @@ impl RawObject {
                         }
                     }
                 }
-                ObjectField::NewlineComment(_) => {}
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
             }
         }
         remove_index.map(|index| self.remove(index))
@@ -58,7 +226,7 @@ impl RawObject {
 
     /// Removes all object fields from the given path, preserving their original
     /// order but reversed relative to the file.
-    pub fn remove_all_by_path(&mut self, path: &Path) -> Vec<ObjectField> {
+    pub fn remove_all_by_path(&mut self, path: &Path) -> Vec<ObjectField<'de>> {
         let mut results = vec![];
         let mut remove_indices = vec![]; // These indices are stored in reverse order
         for (index, field) in self.iter_mut().enumerate().rev() {
@@ -83,7 +251,7 @@ impl RawObject {
                         }
                     }
                 }
-                ObjectField::NewlineComment(_) => {}
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
             }
         }
         for idx in remove_indices {
@@ -92,7 +260,7 @@ impl RawObject {
         results
     }
 
-    pub fn get_by_path(&self, path: &Path) -> Option<&RawValue> {
+    pub fn get_by_path(&self, path: &Path) -> Option<&RawValue<'de>> {
         for field in self.iter().rev() {
             match field {
                 ObjectField::Inclusion { inclusion, .. } => {
@@ -113,13 +281,13 @@ impl RawObject {
                         }
                     }
                 }
-                ObjectField::NewlineComment(_) => {}
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
             }
         }
         None
     }
 
-    pub fn get_by_path_mut(&mut self, path: &Path) -> Option<&mut RawValue> {
+    pub fn get_by_path_mut(&mut self, path: &Path) -> Option<&mut RawValue<'de>> {
         for field in self.iter_mut().rev() {
             match field {
                 ObjectField::Inclusion { inclusion, .. } => {
@@ -140,12 +308,462 @@ impl RawObject {
                         }
                     }
                 }
-                ObjectField::NewlineComment(_) => {}
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
             }
         }
         None
     }
 
+    /// Returns the origin of the definition that wins for `path`.
+    ///
+    /// Fields are searched from the last to the first, following the HOCON rule that
+    /// later definitions override earlier ones. Nested objects are searched as well,
+    /// so `a { b = 1 }` and `a.b = 1` both report the origin of `b = 1`. If the path is
+    /// shadowed by a later non-object value, `None` is returned.
+    ///
+    /// Substitutions are not resolved, so a path that only exists through a substitution
+    /// has no origin.
+    pub fn get_origin(&self, path: &Path) -> Option<&Origin> {
+        self.find_definition(path).flatten()
+    }
+
+    /// Like [`RawObject::get_origin`], but tells a missing path (`None`) from a definition
+    /// without origin (`Some(None)`), e.g. a field added with [`crate::Config::add_kv`].
+    pub(crate) fn find_definition(&self, path: &Path) -> Option<Option<&Origin>> {
+        for field in self.iter().rev() {
+            match field {
+                ObjectField::Inclusion { inclusion, .. } => {
+                    if let Some(obj) = &inclusion.val
+                        && let Some(origin) = obj.find_definition(path)
+                    {
+                        return Some(origin);
+                    }
+                }
+                ObjectField::KeyValue { key, value, .. } => {
+                    let k = &key.as_path();
+                    if path.starts_with1(k) {
+                        match path.sub_path(k.len()) {
+                            None => return Some(field.origin()),
+                            Some(sub_path) => match value {
+                                RawValue::Object(obj) => {
+                                    if let Some(origin) = obj.find_definition(sub_path) {
+                                        return Some(origin);
+                                    }
+                                }
+                                RawValue::Concat(concat) => {
+                                    let origin =
+                                        concat.get_values().iter().rev().find_map(|v| match v {
+                                            RawValue::Object(obj) => obj.find_definition(sub_path),
+                                            _ => None,
+                                        });
+                                    if origin.is_some() {
+                                        return origin;
+                                    }
+                                }
+                                RawValue::Substitution(_) | RawValue::AddAssign(_) => {}
+                                _ => return None,
+                            },
+                        }
+                    }
+                }
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
+            }
+        }
+        None
+    }
+
+    /// Adds the sources read after `source` with the same contents to `found`, i.e. the
+    /// ones of the fields that list `source` in their [`Origin::same_content`].
+    pub(crate) fn same_content_after(&self, source: &SourceId, found: &mut Vec<SourceId>) {
+        fn visit(value: &RawValue<'_>, source: &SourceId, found: &mut Vec<SourceId>) {
+            match value {
+                RawValue::Object(obj) => obj.same_content_after(source, found),
+                RawValue::Array(array) => array.iter().for_each(|v| visit(v, source, found)),
+                RawValue::Concat(concat) => concat
+                    .get_values()
+                    .iter()
+                    .for_each(|v| visit(v, source, found)),
+                RawValue::AddAssign(add_assign) => visit(add_assign, source, found),
+                _ => {}
+            }
+        }
+        for field in self.iter() {
+            match field {
+                ObjectField::Inclusion { inclusion, .. } => {
+                    if let Some(obj) = &inclusion.val {
+                        obj.same_content_after(source, found);
+                    }
+                }
+                ObjectField::KeyValue { value, origin, .. } => {
+                    if let Some(Origin {
+                        source: Some(other),
+                        same_content,
+                        ..
+                    }) = origin
+                        && same_content.contains(source)
+                        && !found.contains(other)
+                    {
+                        found.push(other.clone());
+                    }
+                    visit(value, source, found);
+                }
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
+            }
+        }
+    }
+
+    /// Returns `true` if any definition, not only the one that wins, may provide `path`:
+    /// a field defines it, or a parent of it is a substitution or a concatenation with one,
+    /// whose fields are only known after resolving.
+    pub(crate) fn may_define(&self, path: &Path) -> bool {
+        self.iter().any(|field| match field {
+            ObjectField::Inclusion { inclusion, .. } => inclusion
+                .val
+                .as_ref()
+                .is_some_and(|obj| obj.may_define(path)),
+            ObjectField::KeyValue { key, value, .. } => {
+                let k = &key.as_path();
+                path.starts_with1(k)
+                    && match path.sub_path(k.len()) {
+                        None => true,
+                        Some(sub_path) => Self::value_may_define(value, sub_path),
+                    }
+            }
+            ObjectField::NewlineComment(_) | ObjectField::BlankLine => false,
+        })
+    }
+
+    fn value_may_define(value: &RawValue<'de>, path: &Path) -> bool {
+        match value {
+            RawValue::Object(obj) => obj.may_define(path),
+            RawValue::Concat(concat) => concat
+                .get_values()
+                .iter()
+                .any(|value| Self::value_may_define(value, path)),
+            RawValue::Substitution(_) => true,
+            RawValue::AddAssign(_)
+            | RawValue::Array(_)
+            | RawValue::Boolean(_)
+            | RawValue::Null
+            | RawValue::String(_)
+            | RawValue::Number(_)
+            | RawValue::NonFinite(_) => false,
+        }
+    }
+
+    /// Returns the comments on the lines directly above the field at `index`, in document
+    /// order. A blank line or another field ends the comments that belong to a field.
+    pub fn leading_comments(&self, index: usize) -> Vec<&Comment> {
+        let mut comments = self.0[..index.min(self.len())]
+            .iter()
+            .rev()
+            .map_while(|field| match field {
+                ObjectField::NewlineComment(comment) => Some(comment),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        comments.reverse();
+        comments
+    }
+
+    /// Replaces the comments directly above the field at `index` with `comments`, and
+    /// returns the index the field has afterwards.
+    ///
+    /// # Panics
+    /// If `index` is out of bounds.
+    pub fn set_leading_comments<I>(&mut self, index: usize, comments: I) -> usize
+    where
+        I: IntoIterator,
+        I::Item: Into<Comment>,
+    {
+        assert!(index < self.len(), "field index {index} out of bounds");
+        let start = index - self.leading_comments(index).len();
+        let comments = comments
+            .into_iter()
+            .map(|comment| ObjectField::NewlineComment(comment.into()));
+        self.0.splice(start..index, comments);
+        let mut index = start;
+        while matches!(self.0[index], ObjectField::NewlineComment(_)) {
+            index += 1;
+        }
+        index
+    }
+
+    /// Returns the object and index of the field that wins for `path`, following the same
+    /// rules as [`RawObject::get_origin`].
+    pub(crate) fn find_field(&self, path: &Path) -> Option<(&RawObject<'de>, usize)> {
+        let mut route = vec![];
+        self.field_route(path, &mut route)?;
+        let (index, parents) = route.split_last()?;
+        let mut object = self;
+        for &parent in parents {
+            object = object.0[parent].nested_object()?;
+        }
+        Some((object, *index))
+    }
+
+    /// Like [`RawObject::find_field`], but returns the object mutably.
+    pub(crate) fn find_field_mut(&mut self, path: &Path) -> Option<(&mut RawObject<'de>, usize)> {
+        let mut route = vec![];
+        self.field_route(path, &mut route)?;
+        let (index, parents) = route.split_last()?;
+        let mut object = self;
+        for &parent in parents {
+            object = object.0[parent].nested_object_mut()?;
+        }
+        Some((object, *index))
+    }
+
+    /// Pushes the indices of the fields leading to the one that wins for `path` to `route`.
+    fn field_route(&self, path: &Path, route: &mut Vec<usize>) -> Option<()> {
+        for (index, field) in self.iter().enumerate().rev() {
+            route.push(index);
+            match field {
+                ObjectField::Inclusion { inclusion, .. } => {
+                    if let Some(obj) = &inclusion.val
+                        && obj.field_route(path, route).is_some()
+                    {
+                        return Some(());
+                    }
+                }
+                ObjectField::KeyValue { key, value, .. } => {
+                    let k = &key.as_path();
+                    if path.starts_with1(k) {
+                        match path.sub_path(k.len()) {
+                            None => return Some(()),
+                            Some(sub_path) => match value {
+                                RawValue::Object(obj) => {
+                                    if obj.field_route(sub_path, route).is_some() {
+                                        return Some(());
+                                    }
+                                }
+                                RawValue::Concat(_)
+                                | RawValue::Substitution(_)
+                                | RawValue::AddAssign(_) => {}
+                                _ => {
+                                    route.pop();
+                                    return None;
+                                }
+                            },
+                        }
+                    }
+                }
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
+            }
+            route.pop();
+        }
+        None
+    }
+
+    /// Returns the origin that best matches `path`: the origin of the path itself if it
+    /// has one, otherwise the origin of its closest ancestor that does.
+    ///
+    /// Useful to point users at the source of an error that is only known by path, e.g.
+    /// a deserialization error for a value produced by a substitution.
+    pub fn closest_origin(&self, path: &Path) -> Option<&Origin> {
+        (1..=path.len()).rev().find_map(|len| {
+            let prefix =
+                Path::from_iter(path.iter().take(len).map(|p| p.first.to_string())).ok()?;
+            self.get_origin(&prefix)
+        })
+    }
+
+    /// Returns the origin of the value at `segments`, down to the array element if the array
+    /// was parsed, otherwise the closest origin found on the way.
+    pub(crate) fn origin_at(&self, segments: &[PathSegment]) -> Option<Origin> {
+        let keys = segments
+            .iter()
+            .map_while(|segment| match segment {
+                PathSegment::Key(key) => Some(key.as_str()),
+                PathSegment::Index(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let path = Path::from_iter(keys.iter()).ok()?;
+        let closest = || self.closest_origin(&path).cloned();
+        let rest = &segments[keys.len()..];
+        if rest.is_empty() {
+            return closest();
+        }
+        match self.find_field(&path) {
+            Some((object, index)) => match &object[index] {
+                field @ ObjectField::KeyValue { value, .. } => match field.origin() {
+                    Some(origin) => Some(Self::element_origin(value, origin, rest)),
+                    None => closest(),
+                },
+                _ => closest(),
+            },
+            None => closest(),
+        }
+    }
+
+    fn element_origin(value: &RawValue<'de>, origin: &Origin, segments: &[PathSegment]) -> Origin {
+        match (value, segments.split_first()) {
+            (RawValue::Array(array), Some((PathSegment::Index(index), rest)))
+                if *index < array.len() =>
+            {
+                let origin = match array.element_span(*index) {
+                    Some(span) => Origin {
+                        position: Some(span.start),
+                        end: Some(span.end),
+                        ..origin.clone()
+                    },
+                    None => origin.clone(),
+                };
+                Self::element_origin(&array[*index], &origin, rest)
+            }
+            (RawValue::Object(object), Some((PathSegment::Key(_), _))) => {
+                object.origin_at(segments).unwrap_or_else(|| origin.clone())
+            }
+            _ => origin.clone(),
+        }
+    }
+
+    /// Returns every field whose path matches `selector`, in document order, without
+    /// resolving substitutions.
+    ///
+    /// The selector is a path expression where the unquoted keys `*` and `**` match any
+    /// single key and any number of keys, including none, see [`crate::Value::select`].
+    /// Fields are matched however they are written, so `services.*.image` finds both
+    /// `services.web.image = x` and `services { web { image = x } }`, as well as fields of
+    /// included files. All definitions of a path are returned, not just the one that wins.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    ///
+    /// let config = Config::parse_str_raw(
+    ///     "services {\n  web { image = \"web:1.0\" }\n  db.image = \"db:2.0\"\n}",
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let images = config.select("services.*.image");
+    /// assert_eq!(images[0].path, "services.web.image");
+    /// assert_eq!(images[1].span().unwrap().start.line, 3);
+    /// ```
+    pub fn select(&self, selector: &str) -> Vec<Selection<'_>> {
+        let selector = Selector::new(selector);
+        let mut selections = vec![];
+        self.select_fields(&selector, &selector.start(), &mut vec![], &mut selections);
+        selections
+    }
+
+    fn select_fields<'a>(
+        &'a self,
+        selector: &Selector,
+        states: &[usize],
+        path: &mut Vec<&'a str>,
+        selections: &mut Vec<Selection<'a>>,
+    ) {
+        for field in self.iter() {
+            match field {
+                ObjectField::Inclusion { inclusion, .. } => {
+                    if let Some(obj) = &inclusion.val {
+                        obj.select_fields(selector, states, path, selections);
+                    }
+                }
+                ObjectField::KeyValue { key, value, .. } => {
+                    let keys = key.as_path();
+                    let mut field_states = states.to_vec();
+                    for key in &keys {
+                        field_states = selector.step(&field_states, key);
+                    }
+                    if field_states.is_empty() {
+                        continue;
+                    }
+                    let depth = path.len();
+                    path.extend(keys);
+                    if selector.is_match(&field_states) {
+                        selections.push(Selection {
+                            path: render_path(path.iter().copied()),
+                            field,
+                        });
+                    }
+                    let objects: Vec<&RawObject<'de>> = match value {
+                        RawValue::Object(obj) => vec![obj],
+                        RawValue::Concat(concat) => concat
+                            .get_values()
+                            .iter()
+                            .filter_map(|v| match v {
+                                RawValue::Object(obj) => Some(obj),
+                                _ => None,
+                            })
+                            .collect(),
+                        _ => vec![],
+                    };
+                    for obj in objects {
+                        obj.select_fields(selector, &field_states, path, selections);
+                    }
+                    path.truncate(depth);
+                }
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
+            }
+        }
+    }
+
+    /// Returns every substitution in this object, in document order, with the rendered
+    /// path of the field or array element it appears in.
+    pub(crate) fn substitutions(&self) -> Vec<(String, &Substitution<'de>)> {
+        let mut substitutions = vec![];
+        self.collect_substitutions(&mut vec![], &mut substitutions);
+        substitutions
+    }
+
+    fn collect_substitutions<'a>(
+        &'a self,
+        path: &mut Vec<String>,
+        substitutions: &mut Vec<(String, &'a Substitution<'de>)>,
+    ) {
+        for field in self.iter() {
+            match field {
+                ObjectField::Inclusion { inclusion, .. } => {
+                    if let Some(obj) = &inclusion.val {
+                        obj.collect_substitutions(path, substitutions);
+                    }
+                }
+                ObjectField::KeyValue { key, value, .. } => {
+                    let depth = path.len();
+                    path.extend(key.as_path().into_iter().map(str::to_string));
+                    Self::collect_value_substitutions(value, path, substitutions);
+                    path.truncate(depth);
+                }
+                ObjectField::NewlineComment(_) | ObjectField::BlankLine => {}
+            }
+        }
+    }
+
+    fn collect_value_substitutions<'a>(
+        value: &'a RawValue<'de>,
+        path: &mut Vec<String>,
+        substitutions: &mut Vec<(String, &'a Substitution<'de>)>,
+    ) {
+        match value {
+            RawValue::Object(obj) => obj.collect_substitutions(path, substitutions),
+            RawValue::Array(array) => {
+                for (index, value) in array.iter().enumerate() {
+                    path.push(index.to_string());
+                    Self::collect_value_substitutions(value, path, substitutions);
+                    path.pop();
+                }
+            }
+            RawValue::Substitution(substitution) => {
+                substitutions.push((render_path(path.iter().map(String::as_str)), substitution));
+            }
+            RawValue::Concat(concat) => {
+                for value in concat.get_values() {
+                    Self::collect_value_substitutions(value, path, substitutions);
+                }
+            }
+            RawValue::AddAssign(add_assign) => {
+                Self::collect_value_substitutions(add_assign, path, substitutions)
+            }
+            RawValue::Boolean(_)
+            | RawValue::Null
+            | RawValue::String(_)
+            | RawValue::Number(_)
+            | RawValue::NonFinite(_) => {}
+        }
+    }
+
     /// Merges two `RawObject`s into one.
     ///
     /// - If both objects contain the same key, the field from `right` takes precedence
@@ -154,21 +772,59 @@ impl RawObject {
     /// - This follows HOCON’s rule that later definitions of the same key override
     ///   earlier ones.
     pub(crate) fn merge(mut left: Self, right: Self) -> Self {
+        if left.0.is_empty() {
+            // Keeps the source text of `right`.
+            return right;
+        }
         left.0.extend(right.0);
         left
     }
 }
 
-impl Display for RawObject {
+/// A field matched by [`RawObject::select`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Selection<'a> {
+    /// The full path of the field, rendered as a HOCON path expression.
+    pub path: String,
+    /// The matched key-value field.
+    pub field: &'a ObjectField<'a>,
+}
+
+impl<'a> Selection<'a> {
+    /// Returns the unresolved value of the field.
+    pub fn value(&self) -> &'a RawValue<'a> {
+        match self.field {
+            ObjectField::KeyValue { value, .. } => value,
+            _ => unreachable!("only key-value fields are selected"),
+        }
+    }
+
+    /// Returns where the field was defined.
+    pub fn origin(&self) -> Option<&'a Origin> {
+        self.field.origin()
+    }
+
+    /// Returns the source range of the field, from its key to the end of its value.
+    /// Not available for JSON and properties files or fields added programmatically.
+    pub fn span(&self) -> Option<Span> {
+        self.origin()?.span()
+    }
+}
+
+impl Display for RawObject<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{")?;
-        join(self.iter(), ", ", f)?;
+        let fields = self
+            .iter()
+            .filter(|field| !matches!(field, ObjectField::BlankLine));
+        join(fields, ", ", f)?;
         write!(f, "}}")?;
         Ok(())
     }
 }
 
-impl From<Value> for RawValue {
+impl From<Value> for RawValue<'_> {
     fn from(val: Value) -> Self {
         match val {
             Value::Object(object) => {
@@ -188,6 +844,8 @@ impl From<Value> for RawValue {
             Value::Null => RawValue::Null,
             Value::String(string) => RawValue::String(string.into()),
             Value::Number(number) => RawValue::Number(number),
+            Value::NonFinite(non_finite) => RawValue::NonFinite(non_finite),
+            Value::Shared(shared) => std::sync::Arc::unwrap_or_clone(shared).into(),
         }
     }
 }
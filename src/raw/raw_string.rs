@@ -1,6 +1,10 @@
 use derive_more::{Constructor, Deref, DerefMut};
+use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
 
+use crate::merge::intern::Interner;
 use crate::{
     join, join_debug,
     path::{Key, Path},
@@ -8,47 +12,166 @@ use crate::{
         RAW_CONCAT_STRING_TYPE, RAW_MULTILINE_STRING_TYPE, RAW_QUOTED_STRING_TYPE,
         RAW_UNQUOTED_STRING_TYPE,
     },
+    value::ValueString,
 };
 
+/// The text of a [`RawString`], borrowed from the input or owned like a `Cow<str>`.
+/// Owned text is a [`ValueString`], which is stored inline when it's short with the
+/// `compact_str` feature.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum CowStr<'de> {
+    Borrowed(&'de str),
+    Owned(ValueString),
+}
+
+impl CowStr<'_> {
+    /// Copies the text if it's borrowed.
+    pub fn into_owned(self) -> CowStr<'static> {
+        match self {
+            CowStr::Borrowed(s) => CowStr::Owned(ValueString::from(s)),
+            CowStr::Owned(s) => CowStr::Owned(s),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self {
+            CowStr::Borrowed(s) => s.to_string(),
+            CowStr::Owned(s) => s.into_string(),
+        }
+    }
+
+    /// Returns owned text interned in `keys`, borrowed text doesn't allocate anyway.
+    pub(crate) fn intern(self, keys: &Interner) -> Self {
+        match self {
+            CowStr::Borrowed(s) => CowStr::Borrowed(s),
+            CowStr::Owned(s) => CowStr::Owned(keys.intern(s)),
+        }
+    }
+}
+
+impl From<CowStr<'_>> for ValueString {
+    fn from(s: CowStr<'_>) -> Self {
+        match s {
+            CowStr::Borrowed(s) => ValueString::from(s),
+            CowStr::Owned(s) => s,
+        }
+    }
+}
+
+impl Deref for CowStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            CowStr::Borrowed(s) => s,
+            CowStr::Owned(s) => s,
+        }
+    }
+}
+
+impl AsRef<str> for CowStr<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq for CowStr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for CowStr<'_> {}
+
+impl Hash for CowStr<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<'de> From<&'de str> for CowStr<'de> {
+    fn from(s: &'de str) -> Self {
+        CowStr::Borrowed(s)
+    }
+}
+
+impl From<ValueString> for CowStr<'_> {
+    fn from(s: ValueString) -> Self {
+        CowStr::Owned(s)
+    }
+}
+
+impl From<String> for CowStr<'_> {
+    fn from(s: String) -> Self {
+        CowStr::Owned(ValueString::from(s))
+    }
+}
+
+impl<'de> From<Cow<'de, str>> for CowStr<'de> {
+    fn from(s: Cow<'de, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => CowStr::Borrowed(s),
+            Cow::Owned(s) => CowStr::from(s),
+        }
+    }
+}
+
+impl Display for CowStr<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl Debug for CowStr<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
 /// Represents the different types of string values in a HOCON configuration.
 ///
 /// This enum covers the three standard HOCON string types, plus an additional variant
 /// to handle path expressions.
+///
+/// Strings parsed from a slice or a `str` borrow from the input where they contain no
+/// escapes, so parsing them doesn't allocate, see [`RawString::into_owned`].
 #[derive(Clone, Eq, PartialEq, Hash)]
-pub enum RawString {
+#[non_exhaustive]
+pub enum RawString<'de> {
     /// A string literal enclosed in double quotes.
-    QuotedString(String),
+    QuotedString(CowStr<'de>),
     /// A simple string without quotes.
-    UnquotedString(String),
+    UnquotedString(CowStr<'de>),
     /// A multiline string enclosed in three double quotes.
-    MultilineString(String),
+    MultilineString(CowStr<'de>),
     /// A path expression
-    PathExpression(PathExpression),
+    PathExpression(PathExpression<'de>),
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Constructor, Deref, DerefMut)]
-pub struct PathExpression(Vec<RawString>);
+pub struct PathExpression<'de>(Vec<RawString<'de>>);
 
-impl PathExpression {
-    pub fn into_inner(self) -> Vec<RawString> {
+impl<'de> PathExpression<'de> {
+    pub fn into_inner(self) -> Vec<RawString<'de>> {
         self.0
     }
 }
 
-impl Debug for PathExpression {
+impl Debug for PathExpression<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         join_debug(self.iter(), ".", f)
     }
 }
 
-impl Display for PathExpression {
+impl Display for PathExpression<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         join(self.iter(), ".", f)
     }
 }
 
-impl From<&str> for RawString {
-    fn from(val: &str) -> Self {
+impl<'de> From<&'de str> for RawString<'de> {
+    fn from(val: &'de str) -> Self {
         if val.chars().any(|c| c == '\n') {
             RawString::multiline(val)
         } else {
@@ -57,7 +180,7 @@ impl From<&str> for RawString {
     }
 }
 
-impl From<String> for RawString {
+impl From<String> for RawString<'_> {
     fn from(val: String) -> Self {
         if val.chars().any(|c| c == '\n') {
             RawString::multiline(val)
@@ -67,7 +190,17 @@ impl From<String> for RawString {
     }
 }
 
-impl RawString {
+impl From<ValueString> for RawString<'_> {
+    fn from(val: ValueString) -> Self {
+        if val.chars().any(|c| c == '\n') {
+            RawString::multiline(val)
+        } else {
+            RawString::quoted(val)
+        }
+    }
+}
+
+impl<'de> RawString<'de> {
     pub fn ty(&self) -> &'static str {
         match self {
             RawString::QuotedString(_) => RAW_QUOTED_STRING_TYPE,
@@ -81,16 +214,43 @@ impl RawString {
         match self {
             RawString::QuotedString(s)
             | RawString::UnquotedString(s)
-            | RawString::MultilineString(s) => vec![s],
+            | RawString::MultilineString(s) => vec![s.as_ref()],
             RawString::PathExpression(c) => c.iter().flat_map(|s| s.as_path()).collect(),
         }
     }
 
+    /// The keys of the path this string stands for, like [RawString::as_path].
+    pub(crate) fn into_keys(self) -> Vec<CowStr<'de>> {
+        match self {
+            RawString::QuotedString(s)
+            | RawString::UnquotedString(s)
+            | RawString::MultilineString(s) => vec![s],
+            RawString::PathExpression(c) => c
+                .into_inner()
+                .into_iter()
+                .flat_map(RawString::into_keys)
+                .collect(),
+        }
+    }
+
+    /// Shares the keys of this path that don't borrow from the input with the equal keys
+    /// interned before them.
+    pub(crate) fn intern(self, keys: &Interner) -> Self {
+        match self {
+            RawString::QuotedString(s) => RawString::QuotedString(s.intern(keys)),
+            RawString::UnquotedString(s) => RawString::UnquotedString(s.intern(keys)),
+            RawString::MultilineString(s) => RawString::MultilineString(s.intern(keys)),
+            RawString::PathExpression(c) => RawString::PathExpression(PathExpression::new(
+                c.into_inner().into_iter().map(|s| s.intern(keys)).collect(),
+            )),
+        }
+    }
+
     pub fn into_path(self) -> Path {
         match self {
             RawString::QuotedString(s)
             | RawString::UnquotedString(s)
-            | RawString::MultilineString(s) => Path::new(Key::String(s), None),
+            | RawString::MultilineString(s) => Path::new(Key::String(s.into_string()), None),
             RawString::PathExpression(c) => {
                 let mut dummy = Path::new(Key::String("".to_string()), None);
                 let mut curr = &mut dummy;
@@ -103,24 +263,36 @@ impl RawString {
         }
     }
 
-    pub fn quoted(string: impl Into<String>) -> Self {
+    pub fn quoted(string: impl Into<CowStr<'de>>) -> Self {
         Self::QuotedString(string.into())
     }
 
-    pub fn unquoted(string: impl Into<String>) -> Self {
+    pub fn unquoted(string: impl Into<CowStr<'de>>) -> Self {
         Self::UnquotedString(string.into())
     }
 
-    pub fn multiline(string: impl Into<String>) -> Self {
+    pub fn multiline(string: impl Into<CowStr<'de>>) -> Self {
         Self::MultilineString(string.into())
     }
 
-    pub fn path_expression(paths: Vec<RawString>) -> Self {
+    pub fn path_expression(paths: Vec<RawString<'de>>) -> Self {
         Self::PathExpression(PathExpression::new(paths))
     }
+
+    /// Copies the parts borrowed from the input, so the string outlives it.
+    pub fn into_owned(self) -> RawString<'static> {
+        match self {
+            RawString::QuotedString(s) => RawString::QuotedString(s.into_owned()),
+            RawString::UnquotedString(s) => RawString::UnquotedString(s.into_owned()),
+            RawString::MultilineString(s) => RawString::MultilineString(s.into_owned()),
+            RawString::PathExpression(paths) => RawString::PathExpression(PathExpression(
+                paths.0.into_iter().map(RawString::into_owned).collect(),
+            )),
+        }
+    }
 }
 
-impl Display for RawString {
+impl Display for RawString<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             RawString::QuotedString(s) => write!(f, "{}", s),
@@ -131,7 +303,7 @@ impl Display for RawString {
     }
 }
 
-impl Debug for RawString {
+impl Debug for RawString<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::QuotedString(s) => {
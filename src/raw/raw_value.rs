@@ -1,11 +1,14 @@
 use crate::Result;
+use crate::number::{NonFinite, write_number};
 use crate::raw::add_assign::AddAssign;
 use crate::raw::concat::Concat;
 use crate::raw::field::ObjectField;
 use crate::raw::include::Inclusion;
+use crate::raw::layout::SourceText;
 use crate::raw::raw_array::RawArray;
 use crate::raw::raw_object::RawObject;
-use crate::raw::raw_string::RawString;
+use crate::raw::raw_string::{CowStr, RawString};
+use crate::raw::space::Space;
 use crate::raw::substitution::Substitution;
 use serde_json::Number;
 use std::fmt::{Display, Formatter};
@@ -23,20 +26,26 @@ pub const RAW_SUBSTITUTION_TYPE: &str = "substitution";
 pub const RAW_CONCAT_TYPE: &str = "concat";
 pub const RAW_ADD_ASSIGN_TYPE: &str = "add_assign";
 
+/// A value as written, before includes are merged and substitutions are resolved.
+///
+/// `'de` is the lifetime of the input the strings in it may borrow from, see
+/// [`RawString`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum RawValue {
-    Object(RawObject),
-    Array(RawArray),
+#[non_exhaustive]
+pub enum RawValue<'de> {
+    Object(RawObject<'de>),
+    Array(RawArray<'de>),
     Boolean(bool),
     Null,
-    String(RawString),
+    String(RawString<'de>),
     Number(Number),
-    Substitution(Substitution),
-    Concat(Concat),
-    AddAssign(AddAssign),
+    NonFinite(NonFinite),
+    Substitution(Substitution<'de>),
+    Concat(Concat<'de>),
+    AddAssign(AddAssign<'de>),
 }
 
-impl RawValue {
+impl<'de> RawValue<'de> {
     pub fn ty(&self) -> &'static str {
         match self {
             RawValue::Object(_) => RAW_OBJECT_TYPE,
@@ -44,7 +53,7 @@ impl RawValue {
             RawValue::Boolean(_) => RAW_BOOLEAN_TYPE,
             RawValue::Null => RAW_NULL_TYPE,
             RawValue::String(s) => s.ty(),
-            RawValue::Number(_) => RAW_NUMBER_TYPE,
+            RawValue::Number(_) | RawValue::NonFinite(_) => RAW_NUMBER_TYPE,
             RawValue::Substitution(_) => RAW_SUBSTITUTION_TYPE,
             RawValue::Concat(_) => RAW_CONCAT_TYPE,
             RawValue::AddAssign(_) => RAW_ADD_ASSIGN_TYPE,
@@ -54,16 +63,39 @@ impl RawValue {
     pub fn is_simple_value(&self) -> bool {
         matches!(
             self,
-            RawValue::Boolean(_) | RawValue::Null | RawValue::String(_) | RawValue::Number(_)
+            RawValue::Boolean(_)
+                | RawValue::Null
+                | RawValue::String(_)
+                | RawValue::Number(_)
+                | RawValue::NonFinite(_)
         ) || matches!(self, RawValue::AddAssign(r) if r.is_simple_value())
     }
 
-    pub fn inclusion(inclusion: Inclusion) -> RawValue {
+    /// Attaches the source text to the objects and arrays in this value.
+    pub(crate) fn attach_source(&mut self, source: &SourceText) {
+        match self {
+            RawValue::Object(object) => object.attach_source(source),
+            RawValue::Array(array) => array.attach_source(source),
+            RawValue::Concat(concat) => concat
+                .values_mut()
+                .iter_mut()
+                .for_each(|value| value.attach_source(source)),
+            RawValue::AddAssign(add_assign) => add_assign.attach_source(source),
+            RawValue::Boolean(_)
+            | RawValue::Null
+            | RawValue::String(_)
+            | RawValue::Number(_)
+            | RawValue::NonFinite(_)
+            | RawValue::Substitution(_) => {}
+        }
+    }
+
+    pub fn inclusion(inclusion: Inclusion<'de>) -> RawValue<'de> {
         let field = ObjectField::inclusion(inclusion);
         RawValue::Object(RawObject::new(vec![field]))
     }
 
-    pub fn object(values: Vec<(RawString, RawValue)>) -> RawValue {
+    pub fn object(values: Vec<(RawString<'de>, RawValue<'de>)>) -> RawValue<'de> {
         let fields = values
             .into_iter()
             .map(|(k, v)| ObjectField::key_value(k, v))
@@ -71,52 +103,70 @@ impl RawValue {
         RawValue::Object(RawObject::new(fields))
     }
 
-    pub fn array(values: Vec<RawValue>) -> RawValue {
+    pub fn array(values: Vec<RawValue<'de>>) -> RawValue<'de> {
         RawValue::Array(RawArray::new(values))
     }
 
-    pub fn boolean(b: bool) -> RawValue {
+    pub fn boolean(b: bool) -> RawValue<'de> {
         RawValue::Boolean(b)
     }
 
-    pub fn null() -> RawValue {
+    pub fn null() -> RawValue<'de> {
         RawValue::Null
     }
 
-    pub fn quoted_string(s: impl Into<String>) -> RawValue {
+    pub fn quoted_string(s: impl Into<CowStr<'de>>) -> RawValue<'de> {
         RawValue::String(RawString::quoted(s))
     }
 
-    pub fn unquoted_string(s: impl Into<String>) -> RawValue {
+    pub fn unquoted_string(s: impl Into<CowStr<'de>>) -> RawValue<'de> {
         RawValue::String(RawString::unquoted(s))
     }
 
-    pub fn multiline_string(s: impl Into<String>) -> RawValue {
+    pub fn multiline_string(s: impl Into<CowStr<'de>>) -> RawValue<'de> {
         RawValue::String(RawString::multiline(s))
     }
 
-    pub fn path_expression(paths: Vec<RawString>) -> RawValue {
+    pub fn path_expression(paths: Vec<RawString<'de>>) -> RawValue<'de> {
         RawValue::String(RawString::path_expression(paths))
     }
 
-    pub fn number(n: impl Into<Number>) -> RawValue {
+    pub fn number(n: impl Into<Number>) -> RawValue<'de> {
         RawValue::Number(n.into())
     }
 
-    pub fn substitution(s: Substitution) -> RawValue {
+    pub fn substitution(s: Substitution<'de>) -> RawValue<'de> {
         RawValue::Substitution(s)
     }
 
-    pub fn concat(values: Vec<RawValue>, spaces: Vec<Option<String>>) -> Result<RawValue> {
+    pub fn concat(values: Vec<RawValue<'de>>, spaces: Vec<Option<Space>>) -> Result<RawValue<'de>> {
         Ok(RawValue::Concat(Concat::new(values, spaces)?))
     }
 
-    pub fn add_assign(v: RawValue) -> RawValue {
+    pub fn add_assign(v: RawValue<'de>) -> RawValue<'de> {
         RawValue::AddAssign(AddAssign::new(v.into()))
     }
+
+    /// Copies the strings borrowed from the input, so the value outlives it.
+    pub fn into_owned(self) -> RawValue<'static> {
+        match self {
+            RawValue::Object(object) => RawValue::Object(object.into_owned()),
+            RawValue::Array(array) => RawValue::Array(array.into_owned()),
+            RawValue::Boolean(boolean) => RawValue::Boolean(boolean),
+            RawValue::Null => RawValue::Null,
+            RawValue::String(string) => RawValue::String(string.into_owned()),
+            RawValue::Number(number) => RawValue::Number(number),
+            RawValue::NonFinite(non_finite) => RawValue::NonFinite(non_finite),
+            RawValue::Substitution(substitution) => {
+                RawValue::Substitution(substitution.into_owned())
+            }
+            RawValue::Concat(concat) => RawValue::Concat(concat.into_owned()),
+            RawValue::AddAssign(add_assign) => RawValue::AddAssign(add_assign.into_owned()),
+        }
+    }
 }
 
-impl Display for RawValue {
+impl Display for RawValue<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             RawValue::Object(object) => write!(f, "{}", object),
@@ -124,7 +174,8 @@ impl Display for RawValue {
             RawValue::Boolean(boolean) => write!(f, "{}", boolean),
             RawValue::Null => write!(f, "null"),
             RawValue::String(string) => write!(f, "{}", string),
-            RawValue::Number(number) => write!(f, "{}", number),
+            RawValue::Number(number) => write_number(f, number),
+            RawValue::NonFinite(non_finite) => write!(f, "{}", non_finite),
             RawValue::Substitution(substitution) => write!(f, "{}", substitution),
             RawValue::Concat(concat) => write!(f, "{}", concat),
             RawValue::AddAssign(add_assign) => write!(f, "{}", add_assign),
@@ -132,10 +183,10 @@ impl Display for RawValue {
     }
 }
 
-impl TryInto<RawArray> for RawValue {
+impl<'de> TryInto<RawArray<'de>> for RawValue<'de> {
     type Error = crate::error::Error;
 
-    fn try_into(self) -> Result<RawArray> {
+    fn try_into(self) -> Result<RawArray<'de>> {
         match self {
             RawValue::Array(a) => Ok(a),
             other => Err(crate::error::Error::InvalidConversion {
@@ -146,10 +197,10 @@ impl TryInto<RawArray> for RawValue {
     }
 }
 
-impl TryInto<RawObject> for RawValue {
+impl<'de> TryInto<RawObject<'de>> for RawValue<'de> {
     type Error = crate::error::Error;
 
-    fn try_into(self) -> Result<RawObject> {
+    fn try_into(self) -> Result<RawObject<'de>> {
         match self {
             RawValue::Object(o) => Ok(o),
             other => Err(crate::error::Error::InvalidConversion {
@@ -160,7 +211,7 @@ impl TryInto<RawObject> for RawValue {
     }
 }
 
-impl From<serde_json::Value> for RawValue {
+impl From<serde_json::Value> for RawValue<'_> {
     fn from(val: serde_json::Value) -> Self {
         match val {
             serde_json::Value::Null => RawValue::Null,
@@ -0,0 +1,91 @@
+use std::fmt::{Debug, Display, Formatter, Write};
+
+/// The horizontal whitespace between two concatenated values, e.g. the space in
+/// `${name} rocks`.
+///
+/// Runs of a single space or tab character, by far the most common separators, are stored
+/// as their length, so only mixed whitespace allocates.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Space {
+    /// A run of `' '`.
+    Spaces(usize),
+    /// A run of `'\t'`.
+    Tabs(usize),
+    /// Any other whitespace, e.g. spaces and tabs mixed.
+    Other(Box<str>),
+}
+
+impl Space {
+    /// Returns the separator for `whitespace`, or `None` if it's empty.
+    pub fn new(whitespace: &str) -> Option<Space> {
+        let len = whitespace.len();
+        let space = match whitespace.as_bytes().first()? {
+            b' ' if whitespace.bytes().all(|b| b == b' ') => Space::Spaces(len),
+            b'\t' if whitespace.bytes().all(|b| b == b'\t') => Space::Tabs(len),
+            _ => Space::Other(whitespace.into()),
+        };
+        Some(space)
+    }
+
+    /// Returns the length of the whitespace in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            Space::Spaces(n) | Space::Tabs(n) => *n,
+            Space::Other(other) => other.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<&str> for Space {
+    fn from(value: &str) -> Self {
+        Space::new(value).unwrap_or(Space::Spaces(0))
+    }
+}
+
+impl From<String> for Space {
+    fn from(value: String) -> Self {
+        Space::from(value.as_str())
+    }
+}
+
+impl Display for Space {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Space::Spaces(n) => (0..*n).try_for_each(|_| f.write_char(' ')),
+            Space::Tabs(n) => (0..*n).try_for_each(|_| f.write_char('\t')),
+            Space::Other(other) => f.write_str(other),
+        }
+    }
+}
+
+impl Debug for Space {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.to_string(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::Space;
+
+    #[rstest]
+    #[case(" ", Some(Space::Spaces(1)))]
+    #[case("\t\t", Some(Space::Tabs(2)))]
+    #[case(" \t", Some(Space::Other(" \t".into())))]
+    #[case("\u{00A0}", Some(Space::Other("\u{00A0}".into())))]
+    #[case("", None)]
+    fn test_space(#[case] whitespace: &str, #[case] expected: Option<Space>) {
+        let space = Space::new(whitespace);
+        assert_eq!(space, expected);
+        if let Some(space) = space {
+            assert_eq!(space.to_string(), whitespace);
+            assert_eq!(space.len(), whitespace.len());
+        }
+    }
+}
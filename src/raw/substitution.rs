@@ -15,8 +15,9 @@ use std::fmt::{Debug, Display, Formatter};
 /// This structure is used to represent such expressions in the AST (Abstract Syntax Tree).
 ///
 /// # Fields
-/// - [`path`]: the path being referenced (e.g. `"b.c"` or `"x.y"`).
-/// - [`optional`]: indicates whether this is an *optional substitution* (`${?...}`).
+/// - [`Substitution::path`]: the path being referenced (e.g. `"b.c"` or `"x.y"`).
+/// - [`Substitution::is_optional`]: indicates whether this is an *optional substitution*
+///   (`${?...}`).
 ///
 /// # Behavior
 /// - If `optional` is `true`, missing values during resolution will not produce an error.
@@ -35,18 +36,38 @@ use std::fmt::{Debug, Display, Formatter};
 /// assert_eq!(format!("{}", optional), "${?x.y}");
 /// ```
 #[derive(Eq, PartialEq, Hash, Clone, derive_more::Constructor)]
-pub struct Substitution {
+pub struct Substitution<'de> {
     /// The referenced path, e.g. `"foo.bar"` or `"config.value"`.
-    pub path: RawString,
+    pub(crate) path: RawString<'de>,
 
     /// Indicates whether this substitution is optional (`${?path}`).
     ///
     /// When `true`, unresolved substitutions will not cause an error.
     /// When `false`, missing references will trigger an evaluation failure.
-    pub optional: bool,
+    pub(crate) optional: bool,
 }
 
-impl Display for Substitution {
+impl<'de> Substitution<'de> {
+    /// Returns the referenced path.
+    pub fn path(&self) -> &RawString<'de> {
+        &self.path
+    }
+
+    /// Returns `true` for an optional substitution, `${?path}`.
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    /// Copies the path if it borrows from the input, see [`RawString::into_owned`].
+    pub fn into_owned(self) -> Substitution<'static> {
+        Substitution {
+            path: self.path.into_owned(),
+            optional: self.optional,
+        }
+    }
+}
+
+impl Display for Substitution<'_> {
     /// Formats the substitution into standard HOCON syntax.
     ///
     /// Examples:
@@ -63,7 +84,7 @@ impl Display for Substitution {
     }
 }
 
-impl Debug for Substitution {
+impl Debug for Substitution<'_> {
     /// Displays the substitution in a debug-friendly format.
     ///
     /// Unlike [`Display`], this version uses the `{:?}` formatter for `path`
@@ -0,0 +1,1220 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write;
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::number::write_number;
+use crate::path::{render_key, render_path};
+use crate::raw::comment::Comment;
+use crate::raw::field::ObjectField;
+use crate::raw::include::Inclusion;
+use crate::raw::raw_object::RawObject;
+use crate::raw::raw_string::RawString;
+use crate::raw::raw_value::RawValue;
+use crate::value::{Map, Value};
+
+/// Controls how configurations are rendered as text.
+///
+/// The default renders HOCON the way a person would write it: the root object without
+/// braces, nested objects in `key { ... }` form, two space indentation, sorted keys and
+/// the comments of the parsed files. Objects and arrays of a parsed file that weren't
+/// changed are written exactly as they were, so an unchanged file is reproduced byte for
+/// byte.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::{Config, RenderOptions, Value};
+///
+/// let value: Value = Config::parse_str("b = 1, a { c = [x, y] }", None).unwrap();
+/// assert_eq!(
+///     value.render(&RenderOptions::default()),
+///     "a {\n  c = [\"x\", \"y\"]\n}\nb = 1\n"
+/// );
+/// let json = RenderOptions { indent: 0, ..RenderOptions::json() };
+/// assert_eq!(
+///     value.render(&json),
+///     "{\n\"a\": {\n\"c\": [\"x\", \"y\"]\n},\n\"b\": 1\n}\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderOptions {
+    /// Render JSON instead of HOCON: keys are always quoted, fields are separated by
+    /// commas and comments are never written. Unresolved configurations can still contain
+    /// includes, substitutions and `+=` fields, which have no JSON equivalent.
+    /// `Infinity`, `-Infinity` and `NaN` are written as `null`, like
+    /// [`crate::Value::into_json`] converts them.
+    pub json: bool,
+    /// The number of spaces each nesting level is indented by.
+    pub indent: usize,
+    /// Keep the comments and blank lines of unresolved configurations. Resolved values
+    /// have no comments.
+    pub comments: bool,
+    /// Write a comment naming the origin above every field of an unresolved configuration,
+    /// e.g. `# app.conf:3:1`. Resolved values don't keep their origins.
+    pub origin_comments: bool,
+    /// Sort the keys of resolved objects, so the output doesn't depend on the hash order.
+    /// The fields of unresolved configurations always keep their order, since it decides
+    /// which definition wins.
+    pub sort_keys: bool,
+    /// Omit the braces around the root object, which HOCON allows. Ignored for JSON.
+    pub omit_root_braces: bool,
+    /// Write the objects and arrays of an unresolved configuration that are unchanged since
+    /// they were parsed as their original text, ignoring the other options. In edited
+    /// objects, the unchanged fields are written as they were, and edited fields keep their
+    /// key and indentation. Only applies to HOCON with comments and without origin comments.
+    pub preserve_layout: bool,
+}
+
+impl RenderOptions {
+    /// Options for rendering JSON, otherwise the same as the default.
+    pub fn json() -> Self {
+        Self {
+            json: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            json: false,
+            indent: 2,
+            comments: true,
+            origin_comments: false,
+            sort_keys: true,
+            omit_root_braces: true,
+            preserve_layout: true,
+        }
+    }
+}
+
+/// Controls how [`crate::Config::save_with_options`] writes a configuration to a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SaveOptions {
+    /// How the configuration is rendered.
+    pub render: RenderOptions,
+    /// Keep the previous file as `<path>.bak`. If `path` is a symbolic link, the backup is
+    /// made next to the file it points to.
+    pub backup: bool,
+    /// Give the new file the permissions of the file it replaces. Otherwise, and when there
+    /// is no file to replace, the new file is readable and writable by its owner only.
+    pub preserve_permissions: bool,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            render: RenderOptions::default(),
+            backup: false,
+            preserve_permissions: true,
+        }
+    }
+}
+
+/// Writes configurations back out as text, see [`RenderOptions`].
+///
+/// Every field goes on its own line, arrays only span several lines if they contain
+/// objects or arrays.
+pub(crate) struct Renderer<'a, W> {
+    out: &'a mut W,
+    depth: usize,
+    options: RenderOptions,
+    /// Normalizes the style of unresolved configurations, see [`crate::transform::format`].
+    canonical: bool,
+}
+
+impl<'a, W: Write> Renderer<'a, W> {
+    pub(crate) fn new(out: &'a mut W) -> Self {
+        Self::with_options(out, RenderOptions::default())
+    }
+
+    pub(crate) fn with_options(out: &'a mut W, options: RenderOptions) -> Self {
+        Self {
+            out,
+            depth: 0,
+            options,
+            canonical: false,
+        }
+    }
+
+    /// A renderer that writes unresolved configurations in the canonical style of
+    /// [`crate::transform::format`].
+    pub(crate) fn canonical(out: &'a mut W, indent: usize) -> Self {
+        let options = RenderOptions {
+            indent,
+            preserve_layout: false,
+            ..Default::default()
+        };
+        Self {
+            canonical: true,
+            ..Self::with_options(out, options)
+        }
+    }
+
+    fn indent(&mut self) -> std::fmt::Result {
+        for _ in 0..self.depth * self.options.indent {
+            self.out.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn omit_root_braces(&self) -> bool {
+        self.options.omit_root_braces && !self.options.json
+    }
+
+    fn comments(&self) -> bool {
+        self.options.comments && !self.options.json
+    }
+
+    fn preserve_layout(&self) -> bool {
+        self.options.preserve_layout && self.comments() && !self.options.origin_comments
+    }
+
+    /// Writes the separator between a key and its value.
+    fn render_separator(&mut self, object: bool) -> std::fmt::Result {
+        if self.options.json {
+            self.out.write_str(": ")
+        } else if object {
+            self.out.write_char(' ')
+        } else {
+            self.out.write_str(" = ")
+        }
+    }
+
+    /// Writes the end of a field, with a comma in JSON unless it is the last one.
+    fn render_field_end(&mut self, last: bool) -> std::fmt::Result {
+        if self.options.json && !last {
+            self.out.write_char(',')?;
+        }
+        self.out.write_char('\n')
+    }
+
+    /// Renders a resolved value.
+    pub(crate) fn render_root_value(&mut self, value: &Value) -> std::fmt::Result {
+        match value.unshared() {
+            Value::Object(object) if self.omit_root_braces() => self.render_value_fields(object),
+            other => {
+                self.render_value(other)?;
+                self.out.write_char('\n')
+            }
+        }
+    }
+
+    fn render_value_fields(&mut self, object: &Map) -> std::fmt::Result {
+        let mut entries = object.iter().collect::<Vec<_>>();
+        if self.options.sort_keys {
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        let len = entries.len();
+        for (index, (key, value)) in entries.into_iter().enumerate() {
+            self.indent()?;
+            if self.options.json {
+                self.render_quoted(key)?;
+            } else {
+                self.out.write_str(&render_key(key))?;
+            }
+            self.render_separator(matches!(value.unshared(), Value::Object(_)))?;
+            self.render_value(value)?;
+            self.render_field_end(index + 1 == len)?;
+        }
+        Ok(())
+    }
+
+    fn render_value(&mut self, value: &Value) -> std::fmt::Result {
+        match value {
+            Value::Object(object) if object.is_empty() => self.out.write_str("{}"),
+            Value::Object(object) => {
+                self.out.write_str("{\n")?;
+                self.depth += 1;
+                self.render_value_fields(object)?;
+                self.depth -= 1;
+                self.indent()?;
+                self.out.write_char('}')
+            }
+            Value::Array(array) if array.is_empty() => self.out.write_str("[]"),
+            Value::Array(array)
+                if array
+                    .iter()
+                    .all(|v| !matches!(v.unshared(), Value::Object(_) | Value::Array(_))) =>
+            {
+                self.out.write_char('[')?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        self.out.write_str(", ")?;
+                    }
+                    self.render_value(value)?;
+                }
+                self.out.write_char(']')
+            }
+            Value::Array(array) => {
+                self.out.write_str("[\n")?;
+                self.depth += 1;
+                for (index, value) in array.iter().enumerate() {
+                    self.indent()?;
+                    self.render_value(value)?;
+                    if index + 1 < array.len() {
+                        self.out.write_char(',')?;
+                    }
+                    self.out.write_char('\n')?;
+                }
+                self.depth -= 1;
+                self.indent()?;
+                self.out.write_char(']')
+            }
+            Value::Boolean(boolean) => write!(self.out, "{boolean}"),
+            Value::Null => self.out.write_str("null"),
+            Value::String(string) => self.render_quoted(string),
+            Value::Number(number) => write_number(self.out, number),
+            Value::NonFinite(_) if self.options.json => self.out.write_str("null"),
+            Value::NonFinite(non_finite) => write!(self.out, "{non_finite}"),
+            Value::Shared(shared) => self.render_value(shared),
+        }
+    }
+
+    /// Renders a resolved value on a single line, e.g. `{a = 1, b {c = ["x"]}}`. Strings
+    /// are always quoted and keys only when needed. Ignores all options but `sort_keys`.
+    pub(crate) fn render_compact_value(&mut self, value: &Value) -> std::fmt::Result {
+        match value {
+            Value::Object(object) => {
+                let mut entries = object.iter().collect::<Vec<_>>();
+                if self.options.sort_keys {
+                    entries.sort_by(|a, b| a.0.cmp(b.0));
+                }
+                self.out.write_char('{')?;
+                for (index, (key, value)) in entries.into_iter().enumerate() {
+                    if index > 0 {
+                        self.out.write_str(", ")?;
+                    }
+                    self.out.write_str(&render_key(key))?;
+                    if matches!(value.unshared(), Value::Object(_)) {
+                        self.out.write_char(' ')?;
+                    } else {
+                        self.out.write_str(" = ")?;
+                    }
+                    self.render_compact_value(value)?;
+                }
+                self.out.write_char('}')
+            }
+            Value::Array(array) => {
+                self.out.write_char('[')?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        self.out.write_str(", ")?;
+                    }
+                    self.render_compact_value(value)?;
+                }
+                self.out.write_char(']')
+            }
+            Value::Shared(shared) => self.render_compact_value(shared),
+            other => self.render_value(other),
+        }
+    }
+
+    /// Renders an unresolved object, keeping the field order, include statements,
+    /// substitutions and `+=` fields as they are.
+    pub(crate) fn render_root_raw(&mut self, object: &RawObject) -> std::fmt::Result {
+        if self.preserve_layout()
+            && let Some(text) = object.verbatim()
+        {
+            return self.out.write_str(text);
+        }
+        if self.omit_root_braces() {
+            return self.render_raw_fields(object);
+        }
+        self.render_raw_object(object)?;
+        self.out.write_char('\n')
+    }
+
+    fn render_raw_object(&mut self, object: &RawObject) -> std::fmt::Result {
+        if self.preserve_layout()
+            && let Some(text) = object.verbatim()
+        {
+            return self.out.write_str(text);
+        }
+        let blank = |field: &ObjectField| matches!(field, ObjectField::BlankLine);
+        if object.is_empty() || self.canonical && object.iter().all(blank) {
+            return self.out.write_str("{}");
+        }
+        self.out.write_str("{\n")?;
+        self.depth += 1;
+        self.render_raw_fields(object)?;
+        self.depth -= 1;
+        self.indent()?;
+        self.out.write_char('}')
+    }
+
+    fn render_raw_fields(&mut self, object: &RawObject) -> std::fmt::Result {
+        let comments = self.comments();
+        let mut fields = object
+            .iter()
+            .filter(|field| {
+                comments
+                    || !matches!(
+                        field,
+                        ObjectField::NewlineComment(_) | ObjectField::BlankLine
+                    )
+            })
+            .collect::<Vec<_>>();
+        if self.canonical {
+            // At most one blank line in a row, and none at the start or end of an object.
+            fields.dedup_by(|field, previous| {
+                matches!(field, ObjectField::BlankLine)
+                    && matches!(previous, ObjectField::BlankLine)
+            });
+            let is_blank = |field: &&ObjectField| matches!(field, ObjectField::BlankLine);
+            if fields.last().is_some_and(is_blank) {
+                fields.pop();
+            }
+            if fields.first().is_some_and(is_blank) {
+                fields.remove(0);
+            }
+        }
+        let len = fields.len();
+        for (index, field) in fields.into_iter().enumerate() {
+            self.render_raw_field(object, field, index + 1 == len)?;
+        }
+        Ok(())
+    }
+
+    fn render_raw_field(
+        &mut self,
+        object: &RawObject,
+        field: &ObjectField,
+        last: bool,
+    ) -> std::fmt::Result {
+        let comments = self.comments();
+        let comment = match field {
+            ObjectField::Inclusion { inclusion, comment } => {
+                self.indent()?;
+                self.render_inclusion(inclusion)?;
+                comment
+            }
+            ObjectField::KeyValue {
+                key,
+                value,
+                comment,
+                origin,
+                ..
+            } => {
+                if self.options.origin_comments
+                    && !self.options.json
+                    && let Some(origin) = origin
+                {
+                    self.indent()?;
+                    writeln!(self.out, "# {origin}")?;
+                }
+                // Unchanged fields of an edited object keep their original text, edited
+                // values the key and indentation they were written with.
+                let layout = self.preserve_layout();
+                match object.indent_verbatim(field) {
+                    Some(indent) if layout => self.out.write_str(indent)?,
+                    _ => self.indent()?,
+                }
+                match object.field_verbatim(field) {
+                    Some(text) if layout => self.out.write_str(text)?,
+                    _ => {
+                        let key_text = object.key_verbatim(field).filter(|_| layout);
+                        self.render_raw_key_value(key, key_text, value)?
+                    }
+                }
+                comment
+            }
+            ObjectField::NewlineComment(comment) => {
+                self.indent()?;
+                self.render_comment(comment)?;
+                return self.out.write_char('\n');
+            }
+            ObjectField::BlankLine => return self.out.write_char('\n'),
+        };
+        if comments && let Some(comment) = comment {
+            self.out.write_char(' ')?;
+            self.render_comment(comment)?;
+        }
+        self.render_field_end(last)
+    }
+
+    /// Writes a comment, without trailing whitespace in the canonical style.
+    fn render_comment(&mut self, comment: &Comment) -> std::fmt::Result {
+        if self.canonical {
+            write!(self.out, "{}{}", comment.ty(), comment.content().trim_end())
+        } else {
+            write!(self.out, "{comment}")
+        }
+    }
+
+    /// Writes a key or substitution path, quoting only the keys that need it in the
+    /// canonical style.
+    fn render_raw_path(&mut self, path: &RawString) -> std::fmt::Result {
+        if self.canonical {
+            self.out.write_str(&render_path(path.as_path()))
+        } else {
+            self.render_raw_string(path, true)
+        }
+    }
+
+    /// Writes a field, `key_text` is the key as it was written.
+    fn render_raw_key_value(
+        &mut self,
+        key: &RawString,
+        key_text: Option<&str>,
+        value: &RawValue,
+    ) -> std::fmt::Result {
+        let value = match value {
+            RawValue::AddAssign(add_assign) => {
+                self.render_raw_key(key, key_text)?;
+                self.out.write_str(" += ")?;
+                return self.render_raw_value(add_assign);
+            }
+            value => value,
+        };
+        if !self.options.json {
+            self.render_raw_key(key, key_text)?;
+            self.render_separator(matches!(value, RawValue::Object(_)))?;
+            return self.render_raw_value(value);
+        }
+        // JSON has no path expressions, `a.b: 1` is written as `"a": {"b": 1}`.
+        let path = key.as_path();
+        for (index, part) in path.iter().enumerate() {
+            if index > 0 {
+                self.out.write_char('{')?;
+            }
+            self.render_quoted(part)?;
+            self.render_separator(false)?;
+        }
+        self.render_raw_value(value)?;
+        for _ in 1..path.len() {
+            self.out.write_char('}')?;
+        }
+        Ok(())
+    }
+
+    fn render_raw_key(&mut self, key: &RawString, key_text: Option<&str>) -> std::fmt::Result {
+        match key_text {
+            Some(text) => self.out.write_str(text),
+            None => self.render_raw_path(key),
+        }
+    }
+
+    fn render_inclusion(&mut self, inclusion: &Inclusion) -> std::fmt::Result {
+        self.out.write_str("include ")?;
+        if inclusion.required {
+            self.out.write_str("required(")?;
+        }
+        if let Some(location) = inclusion.location {
+            write!(self.out, "{location}(")?;
+        }
+        self.render_quoted(&inclusion.path)?;
+        if inclusion.location.is_some() {
+            self.out.write_char(')')?;
+        }
+        if inclusion.required {
+            self.out.write_char(')')?;
+        }
+        Ok(())
+    }
+
+    fn render_raw_value(&mut self, value: &RawValue) -> std::fmt::Result {
+        match value {
+            RawValue::Object(object) => self.render_raw_object(object),
+            RawValue::Array(array)
+                if self.preserve_layout()
+                    && let Some(text) = array.verbatim() =>
+            {
+                self.out.write_str(text)
+            }
+            // Arrays don't keep their comments, so the canonical style keeps them as written.
+            RawValue::Array(array)
+                if self.canonical
+                    && let Some(text) = array.verbatim()
+                    && has_comment(text) =>
+            {
+                self.out.write_str(text)
+            }
+            RawValue::Array(array)
+                if self.canonical
+                    && array
+                        .iter()
+                        .any(|v| matches!(v, RawValue::Object(_) | RawValue::Array(_))) =>
+            {
+                self.out.write_str("[\n")?;
+                self.depth += 1;
+                for (index, value) in array.iter().enumerate() {
+                    self.indent()?;
+                    self.render_raw_value(value)?;
+                    if index + 1 < array.len() {
+                        self.out.write_char(',')?;
+                    }
+                    self.out.write_char('\n')?;
+                }
+                self.depth -= 1;
+                self.indent()?;
+                self.out.write_char(']')
+            }
+            RawValue::Array(array) => {
+                self.out.write_char('[')?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        self.out.write_str(", ")?;
+                    }
+                    self.render_raw_value(value)?;
+                }
+                self.out.write_char(']')
+            }
+            RawValue::Boolean(boolean) => write!(self.out, "{boolean}"),
+            RawValue::Null => self.out.write_str("null"),
+            RawValue::String(string) if self.options.json => {
+                self.render_quoted(&string.to_string())
+            }
+            RawValue::String(string) => self.render_raw_string(string, false),
+            RawValue::Number(number) => write_number(self.out, number),
+            RawValue::NonFinite(_) if self.options.json => self.out.write_str("null"),
+            RawValue::NonFinite(non_finite) => write!(self.out, "{non_finite}"),
+            RawValue::Substitution(substitution) => {
+                self.out.write_str("${")?;
+                if substitution.optional {
+                    self.out.write_char('?')?;
+                }
+                self.render_raw_path(&substitution.path)?;
+                self.out.write_char('}')
+            }
+            RawValue::Concat(concat) => {
+                let spaces = concat.get_spaces();
+                for (index, value) in concat.get_values().iter().enumerate() {
+                    if index > 0
+                        && let Some(space) = &spaces[index - 1]
+                    {
+                        write!(self.out, "{space}")?;
+                    }
+                    self.render_raw_value(value)?;
+                }
+                Ok(())
+            }
+            RawValue::AddAssign(add_assign) => self.render_raw_value(add_assign),
+        }
+    }
+
+    /// Renders a raw string. Keys and substitution paths are split on unquoted dots, so
+    /// their quoted parts are quoted again, while values keep the original string style.
+    fn render_raw_string(&mut self, string: &RawString, path: bool) -> std::fmt::Result {
+        match string {
+            RawString::QuotedString(s) => self.render_quoted(s),
+            RawString::UnquotedString(s) => self.out.write_str(s),
+            RawString::MultilineString(s) if path || s.contains("\"\"\"") => self.render_quoted(s),
+            RawString::MultilineString(s) => write!(self.out, "\"\"\"{s}\"\"\""),
+            RawString::PathExpression(expression) => {
+                for (index, part) in expression.iter().enumerate() {
+                    if index > 0 {
+                        self.out.write_char('.')?;
+                    }
+                    self.render_raw_string(part, path)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn render_quoted(&mut self, string: &str) -> std::fmt::Result {
+        let quoted = serde_json::to_string(string).expect("string serialization never fails");
+        self.out.write_str(&quoted)
+    }
+}
+
+/// Returns whether the HOCON `text` contains a `#` or `//` comment outside of strings.
+fn has_comment(text: &str) -> bool {
+    let mut rest = text;
+    while let Some(index) = rest.find(['"', '#', '/']) {
+        let (ch, after) = (rest.as_bytes()[index], &rest[index + 1..]);
+        rest = match ch {
+            b'#' => return true,
+            b'/' if after.starts_with('/') => return true,
+            b'/' => after,
+            _ if after.starts_with("\"\"") => match after[2..].find("\"\"\"") {
+                // A multiline string ends at the last of a run of quotes.
+                Some(end) => after[2 + end + 3..].trim_start_matches('"'),
+                None => return false,
+            },
+            _ => {
+                let mut chars = after.char_indices();
+                let mut end = after.len();
+                while let Some((position, ch)) = chars.next() {
+                    match ch {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            end = position + 1;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                &after[end..]
+            }
+        };
+    }
+    false
+}
+
+/// Passes rendered text on to an [`std::io::Write`], keeping the I/O error that
+/// [`std::fmt::Write`] can't carry.
+pub(crate) struct IoWriter<W> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> IoWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Turns the result of rendering into the I/O error that caused it, if any.
+    pub(crate) fn finish(mut self, result: std::fmt::Result) -> crate::Result<()> {
+        match (result, self.error) {
+            (_, Some(error)) => Err(error.into()),
+            (Err(_), None) => Err(std::io::Error::other("rendering failed").into()),
+            (Ok(()), None) => Ok(self.inner.flush()?),
+        }
+    }
+}
+
+impl<W: std::io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Writes `contents` to `path` through a temporary file in the same directory that is
+/// renamed over `path`, so readers never observe a partially written file.
+///
+/// The temporary file is created readable and writable by its owner only, so the contents
+/// are never exposed through a more permissive file. With `preserve_permissions` set, it
+/// gets the permissions of the existing file before anything is written. With `backup`
+/// set, an existing file is first copied to `<path>.bak`.
+///
+/// Symbolic links are followed, so the file a link points to is replaced and the link
+/// stays. On unix, the directory is synced after the rename, so the new file survives a
+/// crash.
+pub(crate) fn write_atomic(
+    path: &Path,
+    contents: &[u8],
+    backup: bool,
+    preserve_permissions: bool,
+) -> crate::Result<()> {
+    let path = &resolve_links(path)?;
+    let Some(name) = path.file_name() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        )
+        .into());
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let (temp_path, file) = create_temp_file(&dir, name)?;
+    let result = replace_with(
+        file,
+        &temp_path,
+        path,
+        contents,
+        backup,
+        preserve_permissions,
+    )
+    .and_then(|()| sync_dir(&dir));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+/// The most symbolic links followed for one path, like the limit of most systems.
+const MAX_LINKS: usize = 40;
+
+/// Returns the file `path` points to after following symbolic links. The target doesn't
+/// need to exist.
+fn resolve_links(path: &Path) -> std::io::Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_LINKS {
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = std::fs::read_link(&path)?;
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("too many levels of symbolic links at {}", path.display()),
+    ))
+}
+
+/// Makes a rename in `dir` durable. Directories can't be synced on other platforms.
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Creates a file in `dir` that no other writer uses, named after the file it replaces.
+fn create_temp_file(dir: &Path, name: &OsStr) -> std::io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    loop {
+        let mut temp_name = OsString::from(".");
+        temp_name.push(name);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        temp_name.push(format!(".{}.{count}.tmp", std::process::id()));
+        let temp_path = dir.join(temp_name);
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Writes `contents` to the temporary `file` at `temp_path` and renames it over `path`.
+fn replace_with(
+    mut file: File,
+    temp_path: &Path,
+    path: &Path,
+    contents: &[u8],
+    backup: bool,
+    preserve_permissions: bool,
+) -> std::io::Result<()> {
+    if preserve_permissions && let Ok(metadata) = std::fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    if backup && path.exists() {
+        let mut backup_path = path.as_os_str().to_owned();
+        backup_path.push(".bak");
+        std::fs::copy(path, backup_path)?;
+    }
+    std::fs::rename(temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::Result;
+    use crate::config::Config;
+    use crate::config_options::{ConfigOptions, IncludeResolution};
+    use crate::raw::field::ObjectField;
+    use crate::raw::raw_value::RawValue;
+    use crate::value::Value;
+
+    use super::RenderOptions;
+
+    #[rstest]
+    #[case("resources/base.conf")]
+    #[case("resources/add_assign.conf")]
+    #[case("resources/concat.conf")]
+    #[case("resources/concat2.conf")]
+    #[case("resources/concat3.conf")]
+    #[case("resources/concat4.conf")]
+    #[case("resources/concat5.conf")]
+    #[case("resources/include.conf")]
+    #[case("resources/comment.conf")]
+    #[case("resources/substitution.conf")]
+    #[case("resources/self_referential.conf")]
+    fn test_render_round_trip(#[case] path: &str) -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        // The rendered include statements are resolved from the working directory.
+        let options = ConfigOptions {
+            include_resolution: IncludeResolution::WorkingDirectory,
+            ..Default::default()
+        };
+        let raw = Config::load_raw(path, Some(options.clone()))?;
+        let expected: Value = Config::load(path, Some(options))?;
+
+        let raw_path = dir.path().join("raw.conf");
+        raw.render_to_file(&raw_path, false)?;
+        let rendered = std::fs::read_to_string(&raw_path)?;
+        let value: Value = Config::parse_str(&rendered, None)?;
+        value.assert_deep_eq(&expected, "$");
+
+        let resolved_path = dir.path().join("resolved.conf");
+        expected.render_to_file(&resolved_path, false)?;
+        let value: Value = Config::load(&resolved_path, None)?;
+        value.assert_deep_eq(&expected, "$");
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_raw_object() -> Result<()> {
+        let base = Config::parse_str_raw(
+            "include \"missing.conf\"\nhost = localhost\nlist = [1]",
+            None,
+        )?;
+        let app = Config::parse_str_raw("url = \"http://\"${host}\nlist += 2", None)?;
+        let merged = app.with_fallback(base);
+        let expected: Value = merged.clone().resolve()?;
+
+        let options = RenderOptions {
+            comments: false,
+            ..Default::default()
+        };
+        let rendered = merged.object.render(&options);
+        assert!(rendered.starts_with("include \"missing.conf\"\n"));
+        assert!(rendered.contains("${\"host\"}"));
+        assert!(rendered.contains("\"list\" += 2"));
+        let reparsed = Config::parse_str_raw(&rendered, None)?;
+        let value: Value = reparsed.resolve()?;
+        value.assert_deep_eq(&expected, "$");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_to() -> Result<()> {
+        struct Failing;
+
+        impl std::io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let config = Config::load_raw("resources/comment.conf", None)?;
+        let options = RenderOptions {
+            preserve_layout: false,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        config.write_to(&mut out, &options)?;
+        assert_eq!(String::from_utf8(out).unwrap(), config.render(&options));
+
+        let error = config.write_to(Failing, &options).unwrap_err();
+        assert!(matches!(error, crate::Error::Io(io) if io.to_string() == "disk full"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_options() -> Result<()> {
+        let config = Config::parse_str_raw("a.b = 1\nc { d = x }\ne = [1, \"\"\"y\"\"\"]\n", None)?;
+        let expected: Value = config.clone().resolve()?;
+
+        let hocon = config.render(&RenderOptions {
+            comments: false,
+            omit_root_braces: false,
+            indent: 4,
+            ..Default::default()
+        });
+        assert_eq!(
+            hocon,
+            concat!(
+                "{\n",
+                "    \"a\".\"b\" = 1\n",
+                "    \"c\" {\n",
+                "        \"d\" = x\n",
+                "    }\n",
+                "    \"e\" = [1, \"\"\"y\"\"\"]\n",
+                "}\n"
+            )
+        );
+
+        let json = config.render(&RenderOptions::json());
+        let parsed: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(Value::from(parsed), expected);
+        let json = expected.render(&RenderOptions::json());
+        let parsed: serde_json::Value = serde_json::from_str(&json)?;
+        assert_eq!(Value::from(parsed), expected);
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.conf");
+        std::fs::write(&path, "a = 1\n")?;
+        let config = Config::load_raw(&path, None)?;
+        let rendered = config.render(&RenderOptions {
+            origin_comments: true,
+            ..Default::default()
+        });
+        assert_eq!(rendered, format!("# {}:1:0\n\"a\" = 1\n", path.display()));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("resources/base.conf")]
+    #[case("resources/comment.conf")]
+    #[case("resources/concat.conf")]
+    #[case("resources/demo.conf")]
+    #[case("resources/include.conf")]
+    #[case("resources/substitution.conf")]
+    fn test_render_lossless(#[case] path: &str) -> Result<()> {
+        let raw = Config::load_raw(path, None)?;
+        assert_eq!(
+            raw.render(&RenderOptions::default()),
+            std::fs::read_to_string(path)?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("resources/base.conf")]
+    #[case("resources/add_assign.conf")]
+    #[case("resources/comment.conf")]
+    #[case("resources/concat.conf")]
+    #[case("resources/concat2.conf")]
+    #[case("resources/concat3.conf")]
+    #[case("resources/concat4.conf")]
+    #[case("resources/concat5.conf")]
+    #[case("resources/demo.conf")]
+    #[case("resources/deserialize.conf")]
+    #[case("resources/self_referential.conf")]
+    #[case("resources/substitution.conf")]
+    fn test_format(#[case] path: &str) -> Result<()> {
+        let input = std::fs::read_to_string(path)?;
+        let formatted = crate::transform::format(&input, 2)?;
+        assert_eq!(crate::transform::format(&formatted, 2)?, formatted);
+        let expected: Value = Config::parse_str(&input, None)?;
+        let value: Value = Config::parse_str(&formatted, None)?;
+        value.assert_deep_eq(&expected, "$");
+        let comments = |text: &str| {
+            let config = Config::parse_str_raw(text, None).unwrap();
+            config
+                .render(&RenderOptions::default())
+                .matches(['#', '/'])
+                .count()
+        };
+        assert_eq!(comments(&formatted), comments(&input));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_style() -> Result<()> {
+        let input = concat!(
+            "\n\n# header   \n{\n",
+            "include \"missing.conf\"\n",
+            "\"a\" : { \"b.c\" = 1, d : \"x\"  y  // trailing\n",
+            "list += [ {e = ${\"a\".d}}, [1] ]\n",
+            "plain = [1, # one\n  2]\n",
+            "tags = [ \"#x\" ,\"\"\"a//b\"\"\"  ]\n",
+            "}}\n\n\n",
+        );
+        let expected = concat!(
+            "# header\n",
+            "include \"missing.conf\"\n",
+            "a {\n",
+            "  \"b.c\" = 1\n",
+            "  d = \"x\"  y // trailing\n",
+            "  list += [\n",
+            "    {\n",
+            "      e = ${a.d}\n",
+            "    },\n",
+            "    [1]\n",
+            "  ]\n",
+            "  plain = [1, # one\n  2]\n",
+            "  tags = [\"#x\", \"\"\"a//b\"\"\"]\n",
+            "}\n",
+        );
+        assert_eq!(crate::transform::format(input, 2)?, expected);
+        assert!(crate::transform::format("a = ", 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_changed_layout() -> Result<()> {
+        let mut config = Config::parse_str_raw(
+            "# header\n\na {\n  x   :  1 // odd spacing\n}\nb = [1, # one\n  2]\n\n\nc = 3 # three\n",
+            None,
+        )?;
+        config.add_kv("d", Value::from(4));
+        let expected = concat!(
+            "# header\n",
+            "\n",
+            "a {\n  x   :  1 // odd spacing\n}\n",
+            "b = [1, # one\n  2]\n",
+            "\n",
+            "\n",
+            "c = 3 # three\n",
+            "\"d\" = 4\n",
+        );
+        assert_eq!(config.render(&RenderOptions::default()), expected);
+
+        let without_layout = config.render(&RenderOptions {
+            preserve_layout: false,
+            ..Default::default()
+        });
+        assert!(without_layout.contains("\"a\" {\n  \"x\" = 1 // odd spacing\n}\n"));
+        assert!(without_layout.contains("\"b\" = [1, 2]\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_edit_keeps_other_fields() -> Result<()> {
+        let input = concat!(
+            "# header\n",
+            "a.b   :  1 // odd spacing\n",
+            "\"quoted\" = yes\n",
+            "list += [1,\n  2]\n",
+            "server {\n      host: localhost\n    port=80\n}\n",
+            "name = ${?NAME}\n",
+        );
+        let mut config = Config::parse_str_raw(input, None)?;
+        let ObjectField::KeyValue { value, .. } = &mut config.object.fields_mut()[4] else {
+            unreachable!()
+        };
+        let RawValue::Object(server) = value else {
+            unreachable!()
+        };
+        let ObjectField::KeyValue { value, .. } = &mut server.fields_mut()[1] else {
+            unreachable!()
+        };
+        *value = RawValue::from(Value::from(8080));
+        let rendered = config.render(&RenderOptions::default());
+        let expected = input.replace("port=80", "port=8080");
+        assert_eq!(rendered.lines().count(), expected.lines().count());
+        for (rendered, expected) in rendered.lines().zip(expected.lines()) {
+            if expected.contains("port") {
+                assert_eq!(rendered, "    port = 8080");
+            } else {
+                assert_eq!(rendered, expected);
+            }
+        }
+
+        config.set_doc_comment("name", "The name")?;
+        let rendered = config.render(&RenderOptions::default());
+        assert!(rendered.ends_with("# The name\nname = ${?NAME}\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_backup() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.conf");
+        let first: Value = Config::parse_str("version = 1", None)?;
+        first.render_to_file(&path, true)?;
+        assert!(!dir.path().join("app.conf.bak").exists());
+
+        let second: Value = Config::parse_str("version = 2", None)?;
+        second.render_to_file(&path, true)?;
+        let backup = std::fs::read_to_string(dir.path().join("app.conf.bak"))?;
+        let backup: Value = Config::parse_str(&backup, None)?;
+        assert_eq!(backup, first);
+        let current: Value = Config::load(&path, None)?;
+        assert_eq!(current, second);
+
+        let third = Config::parse_str_raw("version = 3", None)?;
+        third.render_to_file(&path, true)?;
+        let backup = std::fs::read_to_string(dir.path().join("app.conf.bak"))?;
+        assert_eq!(Config::parse_str::<Value>(&backup, None)?, second);
+        let current: Value = Config::load(&path, None)?;
+        assert_eq!(current, third.resolve()?);
+
+        let entries = std::fs::read_dir(dir.path())?.count();
+        assert_eq!(entries, 2, "the temporary file must not be left behind");
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_concurrently() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.conf");
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    let config = Config::parse_str_raw(&format!("writer = {i}"), None).unwrap();
+                    config.save(path).unwrap();
+                });
+            }
+        });
+        let saved: Value = Config::load(&path, None)?;
+        assert!(saved.get_by_path(["writer"]).is_some());
+        let entries = std::fs::read_dir(dir.path())?.count();
+        assert_eq!(entries, 1, "the temporary files must not be left behind");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_preserves_permissions() -> Result<()> {
+        use crate::SaveOptions;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("secrets.conf");
+        std::fs::write(&path, "password = old")?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+        let config = Config::parse_str_raw("password = new", None)?;
+        config.save(&path)?;
+        let mode = std::fs::metadata(&path)?.permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        let saved: Value = Config::load(&path, None)?;
+        assert_eq!(saved, Config::parse_str("password = new", None)?);
+
+        let options = SaveOptions {
+            preserve_permissions: false,
+            ..Default::default()
+        };
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644))?;
+        config.save_with_options(&path, &options)?;
+        let mode = std::fs::metadata(&path)?.permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+
+        let new_path = dir.path().join("new.conf");
+        config.save(&new_path)?;
+        let mode = std::fs::metadata(&new_path)?.permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        std::fs::remove_file(&new_path)?;
+
+        let entries = std::fs::read_dir(dir.path())?.count();
+        assert_eq!(entries, 1, "the temporary file must not be left behind");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_through_symlink() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let target_dir = dir.path().join("shared");
+        std::fs::create_dir(&target_dir)?;
+        let target = target_dir.join("app.conf");
+        std::fs::write(&target, "port = 80\n")?;
+        let link = dir.path().join("app.conf");
+        std::os::unix::fs::symlink("shared/app.conf", &link)?;
+        let chain = dir.path().join("current.conf");
+        std::os::unix::fs::symlink(&link, &chain)?;
+
+        let mut config = Config::load_raw(&chain, None)?;
+        config.add_kv("host", Value::from("localhost"));
+        config.save(&chain)?;
+        assert!(std::fs::symlink_metadata(&link)?.file_type().is_symlink());
+        assert!(std::fs::symlink_metadata(&chain)?.file_type().is_symlink());
+        let saved: Value = Config::load(&target, None)?;
+        assert_eq!(
+            saved,
+            Config::parse_str("port = 80, host = localhost", None)?
+        );
+        assert_eq!(std::fs::read_dir(&target_dir)?.count(), 1);
+
+        // A dangling link gets its target created.
+        std::os::unix::fs::symlink("shared/new.conf", dir.path().join("new.conf"))?;
+        config.save(dir.path().join("new.conf"))?;
+        assert!(target_dir.join("new.conf").is_file());
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! A record of how substitutions were resolved, see [`crate::Config::resolve_traced`].
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// The longest value preview, in characters, before it is cut off.
+const MAX_PREVIEW_CHARS: usize = 80;
+
+/// Where the value of a substitution was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubstitutionSource {
+    /// A value defined in the configuration itself, including included files and the
+    /// environment layer.
+    Configuration,
+    /// A value of [`crate::ResolveOptions::overrides`].
+    Override,
+    /// [`crate::ResolveOptions::resolver`] or [`crate::ResolveOptions::external_root`].
+    Resolver,
+    /// An environment variable named like the substitution path.
+    Environment,
+    /// A value of [`crate::ResolveOptions::defaults_for_missing`] for an optional
+    /// substitution that was found nowhere else.
+    Default,
+    /// Nothing was found, the optional substitution was dropped.
+    NotFound,
+}
+
+impl Display for SubstitutionSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let source = match self {
+            SubstitutionSource::Configuration => "configuration",
+            SubstitutionSource::Override => "override",
+            SubstitutionSource::Resolver => "resolver",
+            SubstitutionSource::Environment => "environment",
+            SubstitutionSource::Default => "default",
+            SubstitutionSource::NotFound => "not found",
+        };
+        f.write_str(source)
+    }
+}
+
+/// One resolved substitution.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubstitutionStep {
+    /// The path of the field the substitution appears in, e.g. `server.url`.
+    pub path: String,
+    /// The substitution as written, e.g. `${host}`.
+    pub substitution: String,
+    /// Where the value was found.
+    pub source: SubstitutionSource,
+    /// The value the substitution resolved to, cut off after 80 characters. `None` if
+    /// nothing was found.
+    pub value: Option<String>,
+}
+
+impl SubstitutionStep {
+    pub(crate) fn new(
+        path: String,
+        substitution: String,
+        source: SubstitutionSource,
+        value: Option<&dyn Display>,
+    ) -> Self {
+        Self {
+            path,
+            substitution,
+            source,
+            value: value.map(preview),
+        }
+    }
+}
+
+impl Display for SubstitutionStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} from {}",
+            self.path, self.substitution, self.source
+        )?;
+        if let Some(value) = &self.value {
+            write!(f, " = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The substitutions resolved by [`crate::Config::resolve_traced`], in the order they were
+/// completed. Substitutions a value depends on come before the substitution that needed it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolutionTrace {
+    pub steps: Vec<SubstitutionStep>,
+    /// The environment variables substitutions were resolved from, by name.
+    ///
+    /// Passing them as [`crate::ResolveOptions::environment`] resolves the same
+    /// configuration to the same value on another machine. Variables that were looked up
+    /// but not set are left out, so they are missing from the snapshot as well.
+    pub environment: BTreeMap<String, String>,
+}
+
+impl ResolutionTrace {
+    /// Returns the steps that resolved substitutions in the field at `path`.
+    pub fn steps_for<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a SubstitutionStep> {
+        self.steps.iter().filter(move |step| step.path == path)
+    }
+}
+
+impl Display for ResolutionTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{step}")?;
+        }
+        Ok(())
+    }
+}
+
+fn preview(value: &dyn Display) -> String {
+    let value = value.to_string();
+    match value.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}...", &value[..end]),
+        None => value,
+    }
+}
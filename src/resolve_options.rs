@@ -0,0 +1,170 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    rc::Rc,
+};
+
+use crate::config::Config;
+use crate::config_options::SpecStrictness;
+use crate::merge_strategy::MergeStrategies;
+use crate::value::Value;
+
+pub(crate) const MAX_SUBSTITUTION_DEPTH: usize = 32;
+
+/// A hook consulted for substitutions that cannot be found in the configuration.
+///
+/// It receives the full substitution path (e.g. `"db.host"`) and returns the value to use,
+/// or `None` to fall through to the environment variables.
+pub type ResolverFn = Rc<dyn Fn(&str) -> Option<Value>>;
+
+/// How a substitution that refers to its own field (or a parent of it) is resolved,
+/// e.g. `path = ${path} [1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SelfReference {
+    /// Look up the previous value of the field: earlier definitions of the same path,
+    /// including included files and the environment layer, then the resolver and the
+    /// environment variables. Fails with [`crate::Error::SelfReferenceNotFound`] if none
+    /// of them has a value and the substitution is not optional.
+    #[default]
+    Fallback,
+    /// Reject self-referential substitutions with [`crate::Error::SelfReferenceNotAllowed`].
+    Error,
+}
+
+/// Options that control how substitutions are resolved.
+///
+/// Unlike [`crate::ConfigOptions`], which controls how configuration files are found and
+/// parsed, these options are only applied when a parsed configuration is resolved. This
+/// allows the same parsed [`crate::Config`] to be resolved differently per environment.
+/// Loading resolves with [`crate::ConfigOptions::resolve`].
+#[derive(Clone)]
+pub struct ResolveOptions {
+    /// Fall back to environment variables for substitutions that are not defined
+    /// in the configuration.
+    pub use_system_environment: bool,
+    /// Leave substitutions that cannot be resolved in place instead of failing with
+    /// [`crate::Error::SubstitutionNotFound`]. Only useful with
+    /// [`crate::Config::resolve_partial`], a fully resolved value can't contain substitutions.
+    pub allow_unresolved: bool,
+    /// The maximum nesting depth of substitutions before
+    /// [`crate::Error::SubstitutionDepthExceeded`] is returned.
+    pub max_substitution_depth: usize,
+    /// Values that override the configuration, keyed by HOCON path expressions like
+    /// `a."b.c"`. Resolving fails with [`crate::Error::InvalidPathExpression`] if one of
+    /// them is invalid.
+    /// They behave as if they were appended to the end of the configuration.
+    pub overrides: Rc<HashMap<String, Value>>,
+    /// Consulted for substitutions that are not defined in the configuration, before
+    /// environment variables.
+    pub resolver: Option<ResolverFn>,
+    /// How substitutions that refer to their own field are resolved.
+    pub self_reference: SelfReference,
+    /// Another configuration that substitutions not defined in the configuration are looked
+    /// up in, before [ResolveOptions::resolver] and the environment variables. It is resolved
+    /// on its own and is not merged into the result.
+    pub external_root: Option<Rc<Config>>,
+    /// Environment variables substitutions are looked up in instead of the ones of the
+    /// process, e.g. [`crate::resolution_trace::ResolutionTrace::environment`] recorded by
+    /// an earlier resolution. Only consulted if
+    /// [ResolveOptions::use_system_environment] is set. In [`crate::ConfigOptions::resolve`],
+    /// the environment layer that [`crate::ConfigOptions::use_system_environment`] adds and
+    /// [`crate::ConfigBuilder::add_env_prefix`] read these variables too.
+    pub environment: Option<Rc<BTreeMap<String, String>>>,
+    /// Values for optional substitutions like `${?PORT}` that are found nowhere, keyed by
+    /// the substitution path, e.g. `"PORT"`. Without a default, such a substitution is
+    /// dropped, which leaves out a field that consists of only the substitution.
+    pub defaults_for_missing: Rc<HashMap<String, Value>>,
+    /// Whether the extensions of this crate are enabled.
+    pub spec_strictness: SpecStrictness,
+    /// Share one allocation between equal keys of the merged objects and of the resolved
+    /// [`crate::Value`], for configurations that repeat the same keys many times, e.g.
+    /// thousands of entries that each have a `host` and a `port`. Costs a lookup per key.
+    /// Disabled by default.
+    pub intern_keys: bool,
+    /// The strategies values tagged with [`crate::merge_strategy::MERGE_TAG`] are merged
+    /// with, see [`crate::merge_strategy`]. Without them, which is the default, tagged
+    /// values are ordinary objects.
+    pub merge_strategies: Option<Rc<MergeStrategies>>,
+}
+
+impl ResolveOptions {
+    pub fn new(use_system_environment: bool, allow_unresolved: bool) -> Self {
+        Self {
+            use_system_environment,
+            allow_unresolved,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_resolver<F>(resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<Value> + 'static,
+    {
+        Self {
+            resolver: Some(Rc::new(resolver)),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            use_system_environment: true,
+            allow_unresolved: false,
+            max_substitution_depth: MAX_SUBSTITUTION_DEPTH,
+            overrides: Default::default(),
+            resolver: None,
+            self_reference: SelfReference::default(),
+            external_root: None,
+            environment: None,
+            defaults_for_missing: Default::default(),
+            spec_strictness: SpecStrictness::default(),
+            intern_keys: false,
+            merge_strategies: None,
+        }
+    }
+}
+
+impl Debug for ResolveOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolveOptions")
+            .field("use_system_environment", &self.use_system_environment)
+            .field("allow_unresolved", &self.allow_unresolved)
+            .field("max_substitution_depth", &self.max_substitution_depth)
+            .field("overrides", &self.overrides)
+            .field("self_reference", &self.self_reference)
+            .field("external_root", &self.external_root)
+            .field("environment", &self.environment)
+            .field("defaults_for_missing", &self.defaults_for_missing)
+            .field("spec_strictness", &self.spec_strictness)
+            .field("intern_keys", &self.intern_keys)
+            .field("merge_strategies", &self.merge_strategies)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ResolveOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.use_system_environment == other.use_system_environment
+            && self.allow_unresolved == other.allow_unresolved
+            && self.max_substitution_depth == other.max_substitution_depth
+            && self.overrides == other.overrides
+            && self.self_reference == other.self_reference
+            && self.external_root == other.external_root
+            && self.environment == other.environment
+            && self.defaults_for_missing == other.defaults_for_missing
+            && self.spec_strictness == other.spec_strictness
+            && self.intern_keys == other.intern_keys
+            && match (&self.resolver, &other.resolver) {
+                (Some(left), Some(right)) => Rc::ptr_eq(left, right),
+                (None, None) => true,
+                _ => false,
+            }
+            && match (&self.merge_strategies, &other.merge_strategies) {
+                (Some(left), Some(right)) => Rc::ptr_eq(left, right),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
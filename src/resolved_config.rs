@@ -0,0 +1,275 @@
+//! The resolved stage of a configuration.
+
+use std::time::Duration;
+
+use num_bigint::BigUint;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+use crate::error::Error;
+use crate::value::Value;
+
+/// A configuration whose substitutions are all resolved, with typed getters.
+///
+/// Produced by resolving an [UnresolvedConfig](crate::UnresolvedConfig), or by loading or
+/// parsing straight into it. Paths passed to the getters are HOCON path expressions, like
+/// `"a.b"`, so keys containing dots are quoted like `a."b.c"`. The getters fail with
+/// [crate::Error::InvalidPathExpression] if a path is invalid, while [ResolvedConfig::get_value],
+/// [ResolvedConfig::has_path] and [ResolvedConfig::has_path_or_null] find nothing.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use hocon_rs::{Config, ResolvedConfig};
+///
+/// let config: ResolvedConfig =
+///     Config::parse_str("port = 80, timeout = 5s, db { hosts = [a, b] }", None).unwrap();
+/// assert_eq!(config.get_i64("port").unwrap(), 80);
+/// assert_eq!(config.get_duration("timeout").unwrap(), Duration::from_secs(5));
+/// let hosts: Vec<String> = config.get("db.hosts").unwrap();
+/// assert_eq!(hosts, ["a", "b"]);
+/// assert!(config.get_string("missing").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedConfig {
+    root: Value,
+}
+
+impl ResolvedConfig {
+    /// Returns the resolved root value.
+    pub fn root(&self) -> &Value {
+        &self.root
+    }
+
+    /// Consumes the config and returns the resolved root value.
+    pub fn into_value(self) -> Value {
+        self.root
+    }
+
+    /// Returns the value at `path`, if any.
+    pub fn get_value(&self, path: &str) -> Option<&Value> {
+        self.find(&keys(path).ok()?)
+    }
+
+    /// Returns `true` if a value exists at `path` and it is not null.
+    pub fn has_path(&self, path: &str) -> bool {
+        self.root.has_path(path)
+    }
+
+    /// Returns `true` if a value exists at `path`, including null values.
+    pub fn has_path_or_null(&self, path: &str) -> bool {
+        self.root.has_path_or_null(path)
+    }
+
+    /// Returns whether the value at `path` is null, see [Value::get_is_null].
+    pub fn get_is_null(&self, path: &str) -> crate::Result<bool> {
+        self.root.get_is_null(path)
+    }
+
+    /// Returns a copy of the config with `value` set at `path`, see [Value::with_value].
+    pub fn with_value(self, path: &str, value: impl Into<Value>) -> crate::Result<ResolvedConfig> {
+        self.root.with_value(path, value).map(ResolvedConfig::from)
+    }
+
+    /// Returns a copy of the config with the entry at `path` removed, see
+    /// [Value::without_path].
+    pub fn without_path(self, path: &str) -> crate::Result<ResolvedConfig> {
+        self.root.without_path(path).map(ResolvedConfig::from)
+    }
+
+    /// Deserializes the value at `path` into `T`.
+    ///
+    /// # Errors
+    /// - [crate::Error::PathNotFound] if nothing is defined at `path`.
+    /// - Any deserialization error, reported at `path`.
+    pub fn get<T>(&self, path: &str) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let keys = keys(path)?;
+        T::deserialize(self.lookup(path, &keys)?).map_err(|error| {
+            keys.iter()
+                .rev()
+                .fold(error, |error, key| error.at_key(key))
+        })
+    }
+
+    pub fn get_string(&self, path: &str) -> crate::Result<String> {
+        self.get(path)
+    }
+
+    pub fn get_bool(&self, path: &str) -> crate::Result<bool> {
+        self.get(path)
+    }
+
+    pub fn get_i64(&self, path: &str) -> crate::Result<i64> {
+        self.get(path)
+    }
+
+    pub fn get_f64(&self, path: &str) -> crate::Result<f64> {
+        self.get(path)
+    }
+
+    /// Returns the duration at `path`, see [Value::as_duration].
+    pub fn get_duration(&self, path: &str) -> crate::Result<Duration> {
+        self.convert(path, "duration", Value::as_duration)
+    }
+
+    /// Returns the size in bytes at `path`, see [Value::as_bytes].
+    pub fn get_bytes(&self, path: &str) -> crate::Result<BigUint> {
+        self.convert(path, "bytes", Value::as_bytes)
+    }
+
+    /// Returns the object at `path` as a config of its own.
+    ///
+    /// # Errors
+    /// - [crate::Error::PathNotFound] if nothing is defined at `path`.
+    /// - [crate::Error::InvalidConversion] if the value at `path` is not an object.
+    pub fn get_config(&self, path: &str) -> crate::Result<ResolvedConfig> {
+        self.convert(path, "object", |value| match value.unshared() {
+            Value::Object(_) => Some(ResolvedConfig::from(value.clone())),
+            _ => None,
+        })
+    }
+
+    /// Converts the config into a [serde_json::Value].
+    pub fn to_json(&self) -> serde_json::Value {
+        self.root.clone().into_json()
+    }
+
+    fn find(&self, keys: &[String]) -> Option<&Value> {
+        self.root
+            .get_by_path(keys.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+
+    fn lookup(&self, path: &str, keys: &[String]) -> crate::Result<&Value> {
+        self.find(keys)
+            .ok_or_else(|| Error::PathNotFound(path.to_string()))
+    }
+
+    fn convert<T>(
+        &self,
+        path: &str,
+        to: &'static str,
+        f: impl FnOnce(&Value) -> Option<T>,
+    ) -> crate::Result<T> {
+        let keys = keys(path)?;
+        let value = self.lookup(path, &keys)?;
+        f(value).ok_or_else(|| {
+            let error = Error::InvalidConversion {
+                from: value.ty(),
+                to,
+            };
+            keys.iter()
+                .rev()
+                .fold(error, |error, key| error.at_key(key))
+        })
+    }
+}
+
+fn keys(path: &str) -> crate::Result<Vec<String>> {
+    Ok(crate::path::Path::from_str(path)?.keys())
+}
+
+impl From<Value> for ResolvedConfig {
+    fn from(root: Value) -> Self {
+        ResolvedConfig { root }
+    }
+}
+
+impl From<ResolvedConfig> for Value {
+    fn from(config: ResolvedConfig) -> Self {
+        config.root
+    }
+}
+
+impl<'de> Deserialize<'de> for ResolvedConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(ResolvedConfig::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResolvedConfig;
+    use crate::Result;
+    use crate::config::UnresolvedConfig;
+    use crate::error::Error;
+    use crate::value::Value;
+
+    #[test]
+    fn test_resolved_config_getters() -> Result<()> {
+        let unresolved = UnresolvedConfig::parse_str_raw(
+            "a { b = 1, c = ${a.b}, d = 1.5, e = true, f = 1KiB, g = null }",
+            None,
+        )?;
+        let config: ResolvedConfig = unresolved.resolve()?;
+        assert_eq!(config.get_i64("a.c")?, 1);
+        assert_eq!(config.get_f64("a.d")?, 1.5);
+        assert!(config.get_bool("a.e")?);
+        assert_eq!(config.get_bytes("a.f")?, 1024u32.into());
+        assert!(config.has_path("a.b"));
+        assert!(!config.has_path("a.g"));
+        assert!(matches!(
+            config.get_string("a.x"),
+            Err(Error::PathNotFound(path)) if path == "a.x"
+        ));
+        assert_eq!(
+            config.get_duration("a.e").unwrap_err().to_string(),
+            Error::InvalidConversion {
+                from: "Boolean",
+                to: "duration"
+            }
+            .at_key("e")
+            .at_key("a")
+            .to_string()
+        );
+        let section = config.get_config("a")?;
+        assert_eq!(section.get_value("b"), Some(&Value::from(1)));
+        assert!(config.get_config("a.b").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolved_config_quoted_keys() -> Result<()> {
+        let text = "a { \"b.c\" = 1, b { c = 2 } }";
+        let config: ResolvedConfig = UnresolvedConfig::parse_str(text, None)?;
+        let unresolved = UnresolvedConfig::parse_str_raw(text, None)?;
+        assert_eq!(config.get_i64("a.\"b.c\"")?, 1);
+        assert_eq!(unresolved.get::<i64>("a.\"b.c\"")?, 1);
+        assert_eq!(config.get_i64("a.b.c")?, 2);
+        assert_eq!(config.get_value("a.\"b.c\""), Some(&Value::from(1)));
+        assert!(config.has_path("a.\"b.c\""));
+        assert!(matches!(
+            config.get_i64("a..b"),
+            Err(Error::InvalidPathExpression(_))
+        ));
+        assert_eq!(config.get_value("a..b"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolved_config_edits() -> Result<()> {
+        let config: ResolvedConfig = UnresolvedConfig::parse_str("a { b = 1, c = null }", None)?;
+        assert!(config.has_path_or_null("a.c"));
+        assert!(config.get_is_null("a.c")?);
+        assert!(matches!(
+            config.get_is_null("a.x"),
+            Err(Error::PathNotFound(path)) if path == "a.x"
+        ));
+        let config = config
+            .with_value(r#"a."d.e""#, 2)?
+            .without_path("a.b")?
+            .without_path("a.c")?;
+        assert_eq!(config.get_i64(r#"a."d.e""#)?, 2);
+        assert!(!config.has_path_or_null("a.b"));
+        assert!(matches!(
+            config.with_value("a..b", 1),
+            Err(Error::InvalidPathExpression(_))
+        ));
+        Ok(())
+    }
+}
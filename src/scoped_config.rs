@@ -0,0 +1,164 @@
+//! A view of a configuration rooted at a path, see [`UnresolvedConfig::scoped`].
+
+use serde::de::DeserializeOwned;
+
+use crate::config::UnresolvedConfig;
+use crate::origin::{Origin, Provenance};
+use crate::resolve_options::ResolveOptions;
+
+/// A view of an [UnresolvedConfig] rooted at a path, returned by
+/// [UnresolvedConfig::scoped].
+///
+/// Paths passed to the getters are relative to the root of the view, while paths in
+/// errors are absolute, so users can find the offending value in their files. Values are
+/// resolved against the whole configuration, so substitutions may point outside the view.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::Config;
+///
+/// let config = Config::parse_str_raw(
+///     "timeout = 5\nmylib { retries = 3, timeout = ${timeout}, port = x }",
+///     None,
+/// )
+/// .unwrap();
+/// let mylib = config.scoped("mylib");
+/// assert_eq!(mylib.get::<u32>("timeout").unwrap(), 5);
+/// assert!(mylib.get::<u32>("port").unwrap_err().to_string().contains("mylib.port"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ScopedConfig<'a> {
+    config: &'a UnresolvedConfig,
+    root: &'a str,
+}
+
+impl<'a> ScopedConfig<'a> {
+    pub(crate) fn new(config: &'a UnresolvedConfig, root: &'a str) -> Self {
+        Self { config, root }
+    }
+
+    /// Returns the dot separated path the view is rooted at.
+    pub fn root(&self) -> &'a str {
+        self.root
+    }
+
+    /// Returns the whole configuration.
+    pub fn config(&self) -> &'a UnresolvedConfig {
+        self.config
+    }
+
+    /// Returns the absolute path of `path`, which is relative to the root of the view.
+    pub fn absolute_path(&self, path: &str) -> String {
+        match (self.root, path) {
+            ("", path) => path.to_string(),
+            (root, "") => root.to_string(),
+            (root, path) => format!("{root}.{path}"),
+        }
+    }
+
+    /// Resolves the value at the relative `path` and deserializes it into `T`, see
+    /// [UnresolvedConfig::get].
+    pub fn get<T>(&self, path: &str) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.config.get(&self.absolute_path(path))
+    }
+
+    /// Like [ScopedConfig::get], resolving with the given [ResolveOptions].
+    pub fn get_with_options<T>(&self, path: &str, options: &ResolveOptions) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.config
+            .get_with_options(&self.absolute_path(path), options)
+    }
+
+    /// Resolves the root of the view and deserializes it into `T`.
+    ///
+    /// # Errors
+    /// - [crate::Error::PathNotFound] if the configuration has nothing at the root of the
+    ///   view.
+    pub fn resolve<T>(&self) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        if self.root.is_empty() {
+            return self.config.clone().resolve();
+        }
+        self.get("")
+    }
+
+    /// Returns where the value at the relative `path` was defined, see
+    /// [UnresolvedConfig::get_origin].
+    pub fn get_origin(&self, path: &str) -> Option<&'a Origin> {
+        self.config.get_origin(&self.absolute_path(path))
+    }
+
+    /// Returns which layer contributed the value at the relative `path`, see
+    /// [UnresolvedConfig::provenance].
+    pub fn provenance(&self, path: &str) -> Option<Provenance> {
+        self.config.provenance(&self.absolute_path(path))
+    }
+
+    /// Returns the documentation of the field at the relative `path`, see
+    /// [UnresolvedConfig::doc_comment].
+    pub fn doc_comment(&self, path: &str) -> Option<String> {
+        self.config.doc_comment(&self.absolute_path(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::Result;
+    use crate::config::Config;
+    use crate::error::Error;
+
+    #[test]
+    fn test_scoped() -> Result<()> {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Settings {
+            retries: u32,
+            backoff: Backoff,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Backoff {
+            max: u32,
+        }
+
+        let config = Config::parse_str_raw(
+            "max = 10\napp.mylib {\n  retries = 3\n  backoff { max = ${max} }\n}",
+            None,
+        )?;
+        let mylib = config.scoped("app.mylib");
+        let settings: Settings = mylib.resolve()?;
+        assert_eq!(
+            settings,
+            Settings {
+                retries: 3,
+                backoff: Backoff { max: 10 }
+            }
+        );
+        assert_eq!(mylib.get::<u32>("backoff.max")?, 10);
+        assert_eq!(
+            mylib.get_origin("retries").unwrap().position.unwrap().line,
+            3
+        );
+        assert!(matches!(
+            mylib.get::<u32>("missing"),
+            Err(Error::PathNotFound(path)) if path == "app.mylib.missing"
+        ));
+        let error = mylib.get::<Vec<u32>>("backoff").unwrap_err();
+        assert!(matches!(&error, Error::AtPath { path, .. } if path == "app.mylib.backoff"));
+        assert!(matches!(
+            config.scoped("other").resolve::<Settings>(),
+            Err(Error::PathNotFound(path)) if path == "other"
+        ));
+        let value: crate::Value = config.scoped("").resolve()?;
+        assert!(value.has_path("app.mylib.retries"));
+        Ok(())
+    }
+}
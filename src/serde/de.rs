@@ -1,10 +1,13 @@
 use std::cell::RefCell;
 
 use crate::merge::value::Value as MValue;
-use crate::value::Value;
+use crate::value::{Map, Value, ValueString};
 use serde::{
-    Deserializer,
-    de::{DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+    de::{
+        DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+        VariantAccess, Visitor, value::BorrowedStrDeserializer,
+    },
     forward_to_deserialize_any,
 };
 
@@ -16,15 +19,20 @@ impl<'de> Deserializer<'de> for Value {
         V: Visitor<'de>,
     {
         match self {
+            Value::Shared(shared) => {
+                std::sync::Arc::unwrap_or_clone(shared).deserialize_any(visitor)
+            }
             Value::Null => visitor.visit_unit(),
             Value::Boolean(b) => visitor.visit_bool(b),
-            Value::String(s) => visitor.visit_string(s),
+            Value::String(s) => s.visit(visitor),
             Value::Number(n) => n
                 .deserialize_any(visitor)
                 .map_err(|e| crate::error::Error::Deserialize(e.to_string())),
+            Value::NonFinite(n) => visitor.visit_f64(n.as_f64()),
             Value::Array(arr) => {
                 struct SeqDeserializer {
                     iter: std::vec::IntoIter<Value>,
+                    index: usize,
                 }
                 impl<'de> SeqAccess<'de> for SeqDeserializer {
                     type Error = crate::error::Error;
@@ -36,19 +44,26 @@ impl<'de> Deserializer<'de> for Value {
                         T: DeserializeSeed<'de>,
                     {
                         match self.iter.next() {
-                            Some(val) => seed.deserialize(val).map(Some),
+                            Some(val) => {
+                                let index = self.index;
+                                self.index += 1;
+                                seed.deserialize(val)
+                                    .map(Some)
+                                    .map_err(|e| e.at_index(index))
+                            }
                             None => Ok(None),
                         }
                     }
                 }
                 visitor.visit_seq(SeqDeserializer {
                     iter: arr.into_iter(),
+                    index: 0,
                 })
             }
             Value::Object(map) => {
                 struct MapDeserializer {
-                    iter: std::collections::hash_map::IntoIter<String, Value>,
-                    value: Option<Value>,
+                    iter: <Map as IntoIterator>::IntoIter,
+                    value: Option<(ValueString, Value)>,
                 }
                 impl<'de> MapAccess<'de> for MapDeserializer {
                     type Error = crate::error::Error;
@@ -58,8 +73,8 @@ impl<'de> Deserializer<'de> for Value {
                     {
                         match self.iter.next() {
                             Some((k, v)) => {
-                                self.value = Some(v);
-                                seed.deserialize(k.into_deserializer()).map(Some)
+                                let (k, _) = self.value.insert((k, v));
+                                seed.deserialize(k.as_str().into_deserializer()).map(Some)
                             }
                             None => Ok(None),
                         }
@@ -68,7 +83,8 @@ impl<'de> Deserializer<'de> for Value {
                     where
                         V: DeserializeSeed<'de>,
                     {
-                        seed.deserialize(self.value.take().unwrap())
+                        let (key, value) = self.value.take().unwrap();
+                        seed.deserialize(value).map_err(|e| e.at_key(&key))
                     }
                 }
                 visitor.visit_map(MapDeserializer {
@@ -79,12 +95,249 @@ impl<'de> Deserializer<'de> for Value {
         }
     }
 
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Shared(shared) => {
+                std::sync::Arc::unwrap_or_clone(shared).deserialize_enum(name, variants, visitor)
+            }
+            Value::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant: variant.as_str().into_deserializer(),
+                value: None::<Value>,
+            }),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("the object has one entry");
+                visitor
+                    .visit_enum(EnumDeserializer {
+                        variant: variant.as_str().into_deserializer(),
+                        value: Some(value),
+                    })
+                    .map_err(|e| e.at_key(&variant))
+            }
+            other => Err(invalid_enum(other.ty())),
+        }
+    }
+
     // 我们只需要实现 `deserialize_any`，其他都用默认的转发实现即可
     forward_to_deserialize_any! {
         <W: Visitor<'de>>
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
         bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Deserializes a borrowed value without copying it: strings, object keys and enum tags
+/// are handed to serde as `&'de str`, so types like `HashMap<&str, &str>` borrow from
+/// the value.
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use hocon_rs::{Config, Value};
+/// use serde::Deserialize;
+///
+/// let value: Value = Config::parse_str("a = x, b = y", None).unwrap();
+/// let map = HashMap::<&str, &str>::deserialize(&value).unwrap();
+/// assert_eq!(map["a"], "x");
+/// ```
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = crate::error::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Shared(shared) => shared.as_ref().deserialize_any(visitor),
+            Value::Null => visitor.visit_unit(),
+            Value::Boolean(b) => visitor.visit_bool(*b),
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Number(n) => n
+                .deserialize_any(visitor)
+                .map_err(|e| crate::error::Error::Deserialize(e.to_string())),
+            Value::NonFinite(n) => visitor.visit_f64(n.as_f64()),
+            Value::Array(arr) => {
+                struct SeqDeserializer<'de> {
+                    iter: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+                }
+                impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+                    type Error = crate::error::Error;
+                    fn next_element_seed<T>(
+                        &mut self,
+                        seed: T,
+                    ) -> Result<Option<T::Value>, Self::Error>
+                    where
+                        T: DeserializeSeed<'de>,
+                    {
+                        match self.iter.next() {
+                            Some((index, val)) => seed
+                                .deserialize(val)
+                                .map(Some)
+                                .map_err(|e| e.at_index(index)),
+                            None => Ok(None),
+                        }
+                    }
+                }
+                visitor.visit_seq(SeqDeserializer {
+                    iter: arr.iter().enumerate(),
+                })
+            }
+            Value::Object(map) => {
+                struct MapDeserializer<'de> {
+                    iter: <&'de Map as IntoIterator>::IntoIter,
+                    value: Option<(&'de str, &'de Value)>,
+                }
+                impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+                    type Error = crate::error::Error;
+                    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+                    where
+                        K: DeserializeSeed<'de>,
+                    {
+                        match self.iter.next() {
+                            Some((k, v)) => {
+                                self.value = Some((k, v));
+                                seed.deserialize(BorrowedStrDeserializer::new(k)).map(Some)
+                            }
+                            None => Ok(None),
+                        }
+                    }
+                    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+                    where
+                        V: DeserializeSeed<'de>,
+                    {
+                        let (key, value) = self.value.take().unwrap();
+                        seed.deserialize(value).map_err(|e| e.at_key(key))
+                    }
+                }
+                visitor.visit_map(MapDeserializer {
+                    iter: map.iter(),
+                    value: None,
+                })
+            }
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Shared(shared) => shared.as_ref().deserialize_enum(name, variants, visitor),
+            Value::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant: BorrowedStrDeserializer::new(variant),
+                value: None::<&Value>,
+            }),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().expect("the object has one entry");
+                visitor
+                    .visit_enum(EnumDeserializer {
+                        variant: BorrowedStrDeserializer::new(variant),
+                        value: Some(value),
+                    })
+                    .map_err(|e| e.at_key(variant))
+            }
+            other => Err(invalid_enum(other.ty())),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        <W: Visitor<'de>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+fn invalid_enum(ty: &str) -> crate::error::Error {
+    crate::error::Error::Deserialize(format!(
+        "invalid type: {ty}, expected a string or an object with a single key for an enum"
+    ))
+}
+
+/// An externally tagged enum: either just the variant name, or an object with the variant
+/// name as its only key and the variant's data as value.
+struct EnumDeserializer<K, V> {
+    variant: K,
+    value: Option<V>,
+}
+
+impl<'de, K, V> EnumAccess<'de> for EnumDeserializer<K, V>
+where
+    K: Deserializer<'de, Error = crate::error::Error>,
+    V: Deserializer<'de, Error = crate::error::Error>,
+{
+    type Error = crate::error::Error;
+    type Variant = VariantDeserializer<V>;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, Self::Variant), Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant)?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer<V> {
+    value: Option<V>,
+}
+
+impl<V> VariantDeserializer<V> {
+    fn data(self, expected: &'static str) -> Result<V, crate::error::Error> {
+        self.value
+            .ok_or_else(|| serde::de::Error::invalid_type(Unexpected::UnitVariant, &expected))
+    }
+}
+
+impl<'de, V> VariantAccess<'de> for VariantDeserializer<V>
+where
+    V: Deserializer<'de, Error = crate::error::Error>,
+{
+    type Error = crate::error::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            Some(value) => serde::de::IgnoredAny::deserialize(value).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.data("newtype variant")?)
+    }
+
+    fn tuple_variant<W>(self, _len: usize, visitor: W) -> Result<W::Value, Self::Error>
+    where
+        W: Visitor<'de>,
+    {
+        self.data("tuple variant")?.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<W>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: W,
+    ) -> Result<W::Value, Self::Error>
+    where
+        W: Visitor<'de>,
+    {
+        self.data("struct variant")?.deserialize_map(visitor)
     }
 }
 
@@ -98,14 +351,16 @@ impl<'de> Deserializer<'de> for MValue {
         match self {
             MValue::Null | MValue::None => visitor.visit_unit(),
             MValue::Boolean(b) => visitor.visit_bool(b),
-            MValue::String(s) => visitor.visit_string(s),
+            MValue::String(s) => s.visit(visitor),
             MValue::Number(n) => {
                 let n = n.deserialize_any(visitor)?;
                 Ok(n)
             }
+            MValue::NonFinite(n) => visitor.visit_f64(n.as_f64()),
             MValue::Array(arr) => {
                 struct SeqDeserializer {
                     iter: std::vec::IntoIter<RefCell<MValue>>,
+                    index: usize,
                 }
                 impl<'de> SeqAccess<'de> for SeqDeserializer {
                     type Error = crate::error::Error;
@@ -117,19 +372,26 @@ impl<'de> Deserializer<'de> for MValue {
                         T: DeserializeSeed<'de>,
                     {
                         match self.iter.next() {
-                            Some(val) => seed.deserialize(val.into_inner()).map(Some),
+                            Some(val) => {
+                                let index = self.index;
+                                self.index += 1;
+                                seed.deserialize(val.into_inner())
+                                    .map(Some)
+                                    .map_err(|e| e.at_index(index))
+                            }
                             None => Ok(None),
                         }
                     }
                 }
                 visitor.visit_seq(SeqDeserializer {
                     iter: arr.into_inner().into_iter(),
+                    index: 0,
                 })
             }
             MValue::Object(map) => {
                 struct MapDeserializer {
-                    iter: std::collections::btree_map::IntoIter<String, RefCell<MValue>>,
-                    value: Option<RefCell<MValue>>,
+                    iter: <crate::merge::object::Fields as IntoIterator>::IntoIter,
+                    value: Option<(ValueString, RefCell<MValue>)>,
                 }
                 impl<'de> MapAccess<'de> for MapDeserializer {
                     type Error = crate::error::Error;
@@ -142,8 +404,8 @@ impl<'de> Deserializer<'de> for MValue {
                                 if matches!(v.get_mut(), MValue::None) {
                                     self.next_key_seed(seed)
                                 } else {
-                                    self.value = Some(v);
-                                    seed.deserialize(k.into_deserializer()).map(Some)
+                                    let (k, _) = self.value.insert((k, v));
+                                    seed.deserialize(k.as_str().into_deserializer()).map(Some)
                                 }
                             }
                             None => Ok(None),
@@ -153,7 +415,9 @@ impl<'de> Deserializer<'de> for MValue {
                     where
                         V: DeserializeSeed<'de>,
                     {
-                        seed.deserialize(self.value.take().unwrap().into_inner())
+                        let (key, value) = self.value.take().unwrap();
+                        seed.deserialize(value.into_inner())
+                            .map_err(|e| e.at_key(&key))
                     }
                 }
                 visitor.visit_map(MapDeserializer {
@@ -168,16 +432,56 @@ impl<'de> Deserializer<'de> for MValue {
         }
     }
 
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            MValue::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant: variant.as_str().into_deserializer(),
+                value: None::<MValue>,
+            }),
+            MValue::Object(map) => {
+                let mut entries = map
+                    .into_inner()
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_inner()))
+                    .filter(|(_, value)| !matches!(value, MValue::None));
+                match (entries.next(), entries.next()) {
+                    (Some((variant, value)), None) => visitor
+                        .visit_enum(EnumDeserializer {
+                            variant: variant.as_str().into_deserializer(),
+                            value: Some(value),
+                        })
+                        .map_err(|e| e.at_key(&variant)),
+                    _ => Err(invalid_enum("Object")),
+                }
+            }
+            MValue::Substitution(_)
+            | MValue::Concat(_)
+            | MValue::AddAssign(_)
+            | MValue::DelayReplacement(_) => Err(crate::error::Error::ResolveIncomplete),
+            other => Err(invalid_enum(other.ty())),
+        }
+    }
+
     forward_to_deserialize_any! {
         <W: Visitor<'de>>
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
         bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        tuple_struct map struct identifier ignored_any
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use serde::Deserialize;
 
     #[derive(Debug, Deserialize, Eq, PartialEq)]
@@ -237,4 +541,88 @@ mod tests {
         assert_eq!(config_hocon, config_json);
         Ok(())
     }
+    #[derive(Debug, Deserialize, Eq, PartialEq)]
+    enum Mode {
+        Off,
+        Fixed(u32),
+        Range { min: u32, max: u32 },
+    }
+
+    #[test]
+    fn test_de_enum() -> crate::Result<()> {
+        let input = "modes = [Off, { Fixed = 3 }, { Range { min = 1, max = 2 } }]";
+        let expected = vec![Mode::Off, Mode::Fixed(3), Mode::Range { min: 1, max: 2 }];
+        let config = crate::config::Config::parse_str_raw(input, None)?;
+        let modes: Vec<Mode> = config.get("modes")?;
+        assert_eq!(modes, expected);
+        let value = config.resolve_value()?;
+        let modes = value.get_by_path(["modes"]).unwrap();
+        assert_eq!(Vec::<Mode>::deserialize(modes)?, expected);
+        assert_eq!(Vec::<Mode>::deserialize(modes.clone())?, expected);
+
+        let value: crate::Value = crate::config::Config::parse_str("mode { Fixed = x }", None)?;
+        let error = HashMap::<String, Mode>::deserialize(&value).unwrap_err();
+        assert!(
+            matches!(&error, crate::Error::AtPath { path, .. } if path == "mode.Fixed"),
+            "{error:?}"
+        );
+        assert!(Mode::deserialize(&crate::Value::from(1)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_borrowed() -> crate::Result<()> {
+        let value: crate::Value =
+            crate::config::Config::parse_str("names { first = a, second = b }", None)?;
+        let names = value.get_by_path(["names"]).unwrap();
+        let borrowed = HashMap::<&str, &str>::deserialize(names)?;
+        assert_eq!(borrowed, HashMap::from([("first", "a"), ("second", "b")]));
+        let (key, _) = names.as_object().unwrap().get_key_value("first").unwrap();
+        let (borrowed_key, _) = borrowed.get_key_value("first").unwrap();
+        assert_eq!(key.as_ptr(), borrowed_key.as_ptr());
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_error_path() -> crate::Result<()> {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Servers {
+            servers: Vec<Server>,
+        }
+
+        let input = "servers = [\n  { host = a, roles = [] }\n  {\n    host = b\n    roles = [x, {}]\n  }\n]";
+        let config = crate::config::Config::parse_str_raw(input, None)?;
+        let error = config.clone().resolve::<Servers>().unwrap_err();
+        let crate::Error::AtPath { path, origin, .. } = &error else {
+            panic!("expected an error with a path, got {error:?}");
+        };
+        assert_eq!(path, "servers[1].roles[1]");
+        assert!(origin.is_none());
+
+        let error = config.annotate_error(error);
+        let crate::Error::AtPath { origin, .. } = &error else {
+            panic!("expected an error with a path, got {error:?}");
+        };
+        let span = origin.as_ref().unwrap().span().unwrap();
+        assert_eq!((span.start.line, span.start.column), (5, 16));
+        assert_eq!((span.end.line, span.end.column), (5, 18));
+        assert!(
+            error
+                .to_string()
+                .starts_with("Invalid value at servers[1].roles[1] (<string>:5:16): ")
+        );
+
+        let config =
+            crate::config::Config::parse_str_raw("a.b {\n  ports = [80,\n    x]\n}", None)?;
+        let error = config.get::<Vec<u16>>("a.b.ports").unwrap_err();
+        match error {
+            crate::Error::AtPath { path, origin, .. } => {
+                assert_eq!(path, "a.b.ports[1]");
+                assert_eq!(origin.unwrap().position.unwrap().line, 3);
+            }
+            other => panic!("expected an error with a path, got {other:?}"),
+        }
+        Ok(())
+    }
 }
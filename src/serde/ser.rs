@@ -1,5 +1,9 @@
 use crate::Result;
-use serde::ser::Serialize;
+use crate::error::Error;
+use crate::number::{NonFinite, write_number};
+use crate::path::render_key;
+use serde::ser::{self, Serialize};
+use std::fmt::Write as _;
 use std::io;
 
 #[inline]
@@ -57,3 +61,953 @@ where
     let string = serde_json::to_string_pretty(value)?;
     Ok(string)
 }
+
+const INDENT: &str = "  ";
+
+/// Serializes `value` as HOCON text, see [`Serializer`].
+///
+/// # Example
+/// ```rust
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Server {
+///     host: String,
+///     ports: Vec<u16>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct App {
+///     name: String,
+///     server: Server,
+/// }
+///
+/// let app = App {
+///     name: "demo".to_string(),
+///     server: Server { host: "localhost".to_string(), ports: vec![80, 443] },
+/// };
+/// let hocon = hocon_rs::serde::ser::to_hocon_string(&app).unwrap();
+/// assert_eq!(
+///     hocon,
+///     "name = \"demo\"\nserver {\n  host = \"localhost\"\n  ports = [80, 443]\n}\n"
+/// );
+/// ```
+pub fn to_hocon_string<T>(value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let mut serializer = Serializer::new();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// Serializes `value` as HOCON text into `writer`, see [`Serializer`].
+pub fn to_hocon_writer<W, T>(mut writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    writer.write_all(to_hocon_string(value)?.as_bytes())?;
+    Ok(())
+}
+
+/// A [`serde::Serializer`] that writes HOCON text.
+///
+/// The root object is written without braces, nested objects use the `key { ... }`
+/// syntax and every field goes on its own line, in the order the fields are serialized.
+/// Keys are only quoted when needed, while strings are always quoted, so they never turn
+/// into numbers, booleans or substitutions when parsed again. Arrays of scalars are
+/// written on a single line.
+///
+/// Enums are written externally tagged, like `serde_json` does: `Variant` becomes the
+/// string `"Variant"` and `Variant(value)` becomes `{ Variant = value }`.
+///
+/// Like a HOCON document, the value must be an object, other values fail with
+/// [`Error::Serialize`].
+#[derive(Debug, Default)]
+pub struct Serializer {
+    out: String,
+    /// The indentation level of the line the next value is written on.
+    depth: usize,
+    /// Whether no value was written yet, in which case an object is the root object.
+    root: bool,
+    /// Whether a key was written and is waiting for its separator.
+    pending_key: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ObjectState {
+    root: bool,
+    /// The indentation level of the fields.
+    depth: usize,
+    empty: bool,
+}
+
+#[derive(Debug)]
+struct ArrayState {
+    depth: usize,
+    elements: Vec<String>,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Self {
+            root: true,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the HOCON text written so far.
+    pub fn into_inner(self) -> String {
+        self.out
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// Writes the separator of a pending key, `key { ... }` for objects and `key = value`
+    /// otherwise. Returns whether this is the root value, which must be an object.
+    fn begin_value(&mut self, object: bool) -> Result<bool> {
+        if std::mem::take(&mut self.pending_key) {
+            self.out.push_str(if object { " " } else { " = " });
+        }
+        let root = std::mem::take(&mut self.root);
+        if root && !object {
+            return Err(ser::Error::custom(
+                "the root of a HOCON document must be an object",
+            ));
+        }
+        Ok(root)
+    }
+
+    fn write_scalar(&mut self, value: impl std::fmt::Display) -> Result<()> {
+        self.begin_value(false)?;
+        write!(self.out, "{value}").expect("writing to a String never fails");
+        Ok(())
+    }
+
+    fn write_quoted(&mut self, value: &str) -> Result<()> {
+        self.begin_value(false)?;
+        self.out.push_str(&serde_json::to_string(value)?);
+        Ok(())
+    }
+
+    /// Writes `value` like [`crate::Value::render`] does. `Infinity`, `-Infinity` and `NaN`
+    /// only parse back with [`crate::ConfigOptions::non_finite_numbers`].
+    fn write_float(&mut self, value: f64) -> Result<()> {
+        self.begin_value(false)?;
+        match NonFinite::from_f64(value) {
+            Some(non_finite) => write!(self.out, "{non_finite}"),
+            None => {
+                let number = serde_json::Number::from_f64(value).expect("the float is finite");
+                write_number(&mut self.out, &number)
+            }
+        }
+        .expect("writing to a String never fails");
+        Ok(())
+    }
+
+    fn begin_object(&mut self) -> Result<ObjectState> {
+        let root = self.begin_value(true)?;
+        if root {
+            return Ok(ObjectState {
+                root,
+                depth: 0,
+                empty: true,
+            });
+        }
+        self.out.push('{');
+        Ok(ObjectState {
+            root,
+            depth: self.depth + 1,
+            empty: true,
+        })
+    }
+
+    fn write_key(&mut self, state: &mut ObjectState, key: &str) {
+        if !(state.root && state.empty) {
+            self.out.push('\n');
+        }
+        state.empty = false;
+        self.indent(state.depth);
+        self.out.push_str(&render_key(key));
+        self.pending_key = true;
+        self.depth = state.depth;
+    }
+
+    fn end_object(&mut self, state: ObjectState) {
+        if state.root {
+            if !state.empty {
+                self.out.push('\n');
+            }
+        } else if state.empty {
+            self.out.push('}');
+        } else {
+            self.out.push('\n');
+            self.indent(state.depth - 1);
+            self.out.push('}');
+        }
+        self.depth = state.depth.saturating_sub(1);
+    }
+
+    fn begin_array(&mut self) -> Result<ArrayState> {
+        self.begin_value(false)?;
+        Ok(ArrayState {
+            depth: self.depth,
+            elements: vec![],
+        })
+    }
+
+    /// Serializes an element on its own, so the array can be written on a single line
+    /// if none of its elements spans multiple lines.
+    fn write_element<T>(&mut self, state: &mut ArrayState, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let out = std::mem::take(&mut self.out);
+        self.depth = state.depth + 1;
+        let result = value.serialize(&mut *self);
+        state.elements.push(std::mem::replace(&mut self.out, out));
+        result
+    }
+
+    fn end_array(&mut self, state: ArrayState) {
+        self.depth = state.depth;
+        if state.elements.iter().all(|element| !element.contains('\n')) {
+            self.out.push('[');
+            self.out.push_str(&state.elements.join(", "));
+            self.out.push(']');
+            return;
+        }
+        self.out.push_str("[\n");
+        let len = state.elements.len();
+        for (index, element) in state.elements.into_iter().enumerate() {
+            self.indent(state.depth + 1);
+            self.out.push_str(&element);
+            if index + 1 < len {
+                self.out.push(',');
+            }
+            self.out.push('\n');
+        }
+        self.indent(state.depth);
+        self.out.push(']');
+    }
+
+    /// Opens the `{ Variant = ... }` object of an enum variant with data.
+    fn begin_variant(&mut self, variant: &str) -> Result<ObjectState> {
+        let mut state = self.begin_object()?;
+        self.write_key(&mut state, variant);
+        Ok(state)
+    }
+}
+
+enum State {
+    Object(ObjectState),
+    Array(ArrayState),
+}
+
+/// Serializes the elements of an array or the fields of an object, see [`Serializer`].
+pub struct Compound<'a> {
+    ser: &'a mut Serializer,
+    state: State,
+    /// The object wrapping an enum variant, closed after the variant's data.
+    variant: Option<ObjectState>,
+}
+
+impl Compound<'_> {
+    fn element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match &mut self.state {
+            State::Array(state) => self.ser.write_element(state, value),
+            State::Object(_) => unreachable!("elements are only serialized into arrays"),
+        }
+    }
+
+    fn field<T>(&mut self, key: &str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match &mut self.state {
+            State::Object(state) => {
+                self.ser.write_key(state, key);
+                value.serialize(&mut *self.ser)
+            }
+            State::Array(_) => unreachable!("fields are only serialized into objects"),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.state {
+            State::Object(state) => self.ser.end_object(state),
+            State::Array(state) => self.ser.end_array(state),
+        }
+        if let Some(variant) = self.variant {
+            self.ser.end_object(variant);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.write_scalar(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.write_float(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.write_float(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write_quoted(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.write_quoted(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            ser::SerializeSeq::serialize_element(&mut seq, byte)?;
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.write_scalar("null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.write_quoted(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let state = self.begin_variant(variant)?;
+        value.serialize(&mut *self)?;
+        self.end_object(state);
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        let state = State::Array(self.begin_array()?);
+        Ok(Compound {
+            ser: self,
+            state,
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        let variant = Some(self.begin_variant(variant)?);
+        let state = State::Array(self.begin_array()?);
+        Ok(Compound {
+            ser: self,
+            state,
+            variant,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        let state = State::Object(self.begin_object()?);
+        Ok(Compound {
+            ser: self,
+            state,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        let variant = Some(self.begin_variant(variant)?);
+        let state = State::Object(self.begin_object()?);
+        Ok(Compound {
+            ser: self,
+            state,
+            variant,
+        })
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = key.serialize(KeySerializer)?;
+        match &mut self.state {
+            State::Object(state) => {
+                self.ser.write_key(state, &key);
+                Ok(())
+            }
+            State::Array(_) => unreachable!("keys are only serialized into objects"),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Turns map keys into strings. Like in JSON, only strings, numbers, booleans and unit
+/// variants can be keys.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn unsupported<T>(ty: &str) -> Result<T> {
+        Err(ser::Error::custom(format!(
+            "{ty} cannot be used as a map key, keys must be strings"
+        )))
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = ser::Impossible<String, Error>;
+    type SerializeTuple = ser::Impossible<String, Error>;
+    type SerializeTupleStruct = ser::Impossible<String, Error>;
+    type SerializeTupleVariant = ser::Impossible<String, Error>;
+    type SerializeMap = ser::Impossible<String, Error>;
+    type SerializeStruct = ser::Impossible<String, Error>;
+    type SerializeStructVariant = ser::Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String> {
+        Self::unsupported("a float")
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String> {
+        Self::unsupported("a float")
+    }
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String> {
+        Self::unsupported("a byte array")
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Self::unsupported("null")
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Self::unsupported("null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Self::unsupported("null")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Self::unsupported("an enum variant with data")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Self::unsupported("an array")
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Self::unsupported("an array")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Self::unsupported("an array")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Self::unsupported("an enum variant with data")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Self::unsupported("an object")
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Self::unsupported("an object")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Self::unsupported("an enum variant with data")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::Result;
+    use crate::config::Config;
+    use crate::config_options::ConfigOptions;
+
+    use super::to_hocon_string;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Endpoint {
+        path: String,
+        methods: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct App {
+        name: String,
+        version: Option<String>,
+        debug: bool,
+        ratio: f64,
+        limits: (u32, i64),
+        labels: BTreeMap<String, String>,
+        endpoints: Vec<Endpoint>,
+        matrix: Vec<Vec<u8>>,
+        empty: BTreeMap<String, u8>,
+    }
+
+    #[test]
+    fn test_to_hocon_string() -> Result<()> {
+        let app = App {
+            name: "${not.a.substitution}".to_string(),
+            version: None,
+            debug: true,
+            ratio: 0.5,
+            limits: (10, -1),
+            labels: BTreeMap::from([
+                ("a.b".to_string(), "true".to_string()),
+                ("plain".to_string(), "multi\nline \"quoted\"".to_string()),
+            ]),
+            endpoints: vec![
+                Endpoint {
+                    path: "/".to_string(),
+                    methods: vec!["GET".to_string()],
+                },
+                Endpoint {
+                    path: "/items".to_string(),
+                    methods: vec![],
+                },
+            ],
+            matrix: vec![vec![1, 2], vec![]],
+            empty: BTreeMap::new(),
+        };
+        let hocon = to_hocon_string(&app)?;
+        let expected = r#"name = "${not.a.substitution}"
+version = null
+debug = true
+ratio = 0.5
+limits = [10, -1]
+labels {
+  "a.b" = "true"
+  plain = "multi\nline \"quoted\""
+}
+endpoints = [
+  {
+    path = "/"
+    methods = ["GET"]
+  },
+  {
+    path = "/items"
+    methods = []
+  }
+]
+matrix = [[1, 2], []]
+empty {}
+"#;
+        assert_eq!(hocon, expected);
+        let parsed: App = Config::parse_str(&hocon, None)?;
+        assert_eq!(parsed, app);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_hocon_string_enums_and_scalars() -> Result<()> {
+        #[derive(Serialize)]
+        enum Mode {
+            Off,
+            Fixed(u8),
+            Range(u8, u8),
+            Custom { level: u8 },
+        }
+        let modes = BTreeMap::from([(
+            "modes",
+            vec![
+                Mode::Off,
+                Mode::Fixed(1),
+                Mode::Range(1, 2),
+                Mode::Custom { level: 3 },
+            ],
+        )]);
+        let hocon = to_hocon_string(&modes)?;
+        assert_eq!(
+            hocon,
+            "modes = [\n  \"Off\",\n  {\n    Fixed = 1\n  },\n  {\n    Range = [1, 2]\n  },\n  {\n    Custom {\n      level = 3\n    }\n  }\n]\n"
+        );
+        assert_eq!(to_hocon_string(&Mode::Fixed(1))?, "Fixed = 1\n");
+        assert!(to_hocon_string(&42).is_err());
+        assert!(to_hocon_string(&Mode::Off).is_err());
+        assert!(to_hocon_string(&[1, 2]).is_err());
+        assert!(to_hocon_string(&BTreeMap::from([(vec![1], 1)])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_hocon_string_floats() -> Result<()> {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Floats {
+            large: f64,
+            small: f64,
+            negative: f64,
+            single: f32,
+            infinity: f64,
+            negative_infinity: f64,
+            nan: f64,
+        }
+        let floats = Floats {
+            large: 1e300,
+            small: 1e-300,
+            negative: -2.5e-7,
+            single: 0.1,
+            infinity: f64::INFINITY,
+            negative_infinity: f64::NEG_INFINITY,
+            nan: f64::NAN,
+        };
+        let hocon = to_hocon_string(&floats)?;
+        assert!(hocon.contains("large = 1e300\n"), "{hocon}");
+        assert!(hocon.contains("infinity = Infinity\n"), "{hocon}");
+        let options = ConfigOptions {
+            non_finite_numbers: true,
+            ..Default::default()
+        };
+        let parsed: Floats = Config::parse_str(&hocon, Some(options))?;
+        assert_eq!(parsed.large, floats.large);
+        assert_eq!(parsed.small, floats.small);
+        assert_eq!(parsed.negative, floats.negative);
+        assert_eq!(parsed.single, floats.single);
+        assert_eq!(parsed.infinity, f64::INFINITY);
+        assert_eq!(parsed.negative_infinity, f64::NEG_INFINITY);
+        assert!(parsed.nan.is_nan());
+        Ok(())
+    }
+}
@@ -7,6 +7,18 @@ pub enum Syntax {
     Properties,
 }
 
+impl Syntax {
+    /// Returns the syntax of files with the given extension, e.g. `"conf"`.
+    pub fn from_extension(extension: &str) -> Option<Syntax> {
+        match extension {
+            "conf" => Some(Syntax::Hocon),
+            "json" => Some(Syntax::Json),
+            "properties" => Some(Syntax::Properties),
+            _ => None,
+        }
+    }
+}
+
 impl Display for Syntax {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -1,6 +1,46 @@
-use crate::value::Value;
+use crate::config_options::ConfigOptions;
+use crate::parser::Context;
+use crate::parser::loader::parse_hocon;
+use crate::parser::read::StrRead;
+use crate::render::Renderer;
+use crate::value::{Map, Value};
 use serde_json::Number;
-use std::{collections::HashMap, iter::once};
+use std::iter::once;
+
+/// Re-emits the HOCON text `input` in a canonical style, e.g. for a `hocon fmt` command.
+///
+/// Comments, field order, include statements, substitutions and the way values are written
+/// are kept, so the result means the same. The rest is normalized: nesting is indented by
+/// `indent` spaces, fields are written as `key = value`, `key += value` or `key { ... }`,
+/// keys are quoted only where needed, the root braces are omitted and runs of blank lines
+/// are collapsed. Arrays go on one line unless they hold objects or arrays, and arrays with
+/// comments inside are kept as written. Included files are not read.
+///
+/// # Errors
+/// Fails if `input` is not valid HOCON.
+///
+/// # Example
+/// ```rust
+/// use hocon_rs::transform::format;
+///
+/// let input = "{\n# Server settings\n\"server\" : {port:80,   hosts=[a,b]}\n\n\n\"a.b\" = ${x}}";
+/// assert_eq!(
+///     format(input, 2).unwrap(),
+///     "# Server settings\nserver {\n  port = 80\n  hosts = [a, b]\n}\n\n\"a.b\" = ${x}\n"
+/// );
+/// ```
+pub fn format(input: &str, indent: usize) -> crate::Result<String> {
+    let ctx = Context {
+        skip_includes: true,
+        ..Default::default()
+    };
+    let object = parse_hocon(StrRead::new(input), ConfigOptions::default(), Some(ctx))?;
+    let mut formatted = String::new();
+    Renderer::canonical(&mut formatted, indent)
+        .render_root_raw(&object)
+        .expect("writing to a String never fails");
+    Ok(formatted)
+}
 
 impl From<i64> for Value {
     fn from(value: i64) -> Self {
@@ -16,13 +56,13 @@ impl From<i32> for Value {
 
 impl From<String> for Value {
     fn from(value: String) -> Self {
-        Value::String(value)
+        Value::new_string(value)
     }
 }
 
 impl From<&str> for Value {
     fn from(value: &str) -> Self {
-        Value::String(value.to_string())
+        Value::new_string(value)
     }
 }
 
@@ -38,28 +78,34 @@ impl From<f64> for Value {
     }
 }
 
-impl From<HashMap<String, Value>> for Value {
-    fn from(value: HashMap<String, Value>) -> Self {
+impl From<Map> for Value {
+    fn from(value: Map) -> Self {
         Value::Object(value)
     }
 }
 
+impl From<std::collections::HashMap<String, Value>> for Value {
+    fn from(value: std::collections::HashMap<String, Value>) -> Self {
+        Value::Object(value.into())
+    }
+}
+
 impl From<(String, Value)> for Value {
     fn from(value: (String, Value)) -> Self {
-        Value::Object(HashMap::from_iter(once(value)))
+        Value::Object(Map::from_iter(once(value)))
     }
 }
 
 impl From<(&str, Value)> for Value {
     fn from(value: (&str, Value)) -> Self {
         let (k, v) = value;
-        Value::Object(HashMap::from_iter(once((k.to_string(), v))))
+        Value::Object(Map::from_iter(once((k.to_string(), v))))
     }
 }
 
 impl From<Vec<(String, Value)>> for Value {
     fn from(value: Vec<(String, Value)>) -> Self {
-        Value::Object(HashMap::from_iter(value))
+        Value::Object(Map::from_iter(value))
     }
 }
 
@@ -87,7 +133,7 @@ impl From<serde_json::Value> for Value {
             serde_json::Value::Null => Value::Null,
             serde_json::Value::Bool(boolean) => Value::Boolean(boolean),
             serde_json::Value::Number(number) => Value::Number(number),
-            serde_json::Value::String(string) => Value::String(string),
+            serde_json::Value::String(string) => Value::new_string(string),
             serde_json::Value::Array(array) => {
                 Value::array_from_iter(array.into_iter().map(Into::into))
             }
@@ -98,12 +144,16 @@ impl From<serde_json::Value> for Value {
     }
 }
 
+/// Converts to a JSON value. Infinite and NaN floats become [`serde_json::Value::Null`], as
+/// JSON has no representation for them.
 impl From<Value> for serde_json::Value {
     fn from(val: Value) -> Self {
         match val {
             Value::Object(object) => {
                 let map = serde_json::Map::from_iter(
-                    object.into_iter().map(|(key, value)| (key, value.into())),
+                    object
+                        .into_iter()
+                        .map(|(key, value)| (key.into_string(), value.into())),
                 );
                 serde_json::Value::Object(map)
             }
@@ -112,8 +162,174 @@ impl From<Value> for serde_json::Value {
             }
             Value::Boolean(boolean) => serde_json::Value::Bool(boolean),
             Value::Null => serde_json::Value::Null,
-            Value::String(string) => serde_json::Value::String(string),
+            Value::String(string) => serde_json::Value::String(string.into_string()),
             Value::Number(number) => serde_json::Value::Number(number),
+            Value::NonFinite(_) => serde_json::Value::Null,
+            Value::Shared(shared) => std::sync::Arc::unwrap_or_clone(shared).into(),
+        }
+    }
+}
+
+/// Converts a TOML value. Datetimes become strings in RFC 3339 format, and floats that are
+/// infinite or NaN become [`Value::Null`], since HOCON has no representation for them.
+#[cfg(feature = "toml")]
+impl From<toml::Value> for Value {
+    fn from(val: toml::Value) -> Self {
+        match val {
+            toml::Value::String(string) => Value::new_string(string),
+            toml::Value::Integer(integer) => Value::from(integer),
+            toml::Value::Float(float) => Value::from(float),
+            toml::Value::Boolean(boolean) => Value::Boolean(boolean),
+            toml::Value::Datetime(datetime) => Value::new_string(datetime.to_string()),
+            toml::Value::Array(array) => Value::array_from_iter(array.into_iter().map(Into::into)),
+            toml::Value::Table(table) => {
+                Value::object_from_iter(table.into_iter().map(|(key, value)| (key, value.into())))
+            }
+        }
+    }
+}
+
+/// Converts to a TOML value. Fails with [`crate::Error::InvalidConversion`] on nulls, which
+/// TOML doesn't have, and on integers outside of the `i64` range, wrapped in
+/// [`crate::Error::AtPath`] with the path of the offending value.
+///
+/// Call it as `value.try_into()`: `toml::Value::try_from` is an inherent method of `toml`
+/// that serializes any value with serde instead.
+#[cfg(feature = "toml")]
+impl TryFrom<Value> for toml::Value {
+    type Error = crate::Error;
+
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        let value = match val {
+            Value::Object(object) => {
+                let mut table = toml::Table::new();
+                for (key, value) in object {
+                    match value.try_into() {
+                        Ok(value) => {
+                            table.insert(key.into_string(), value);
+                        }
+                        Err(error) => return Err(error.at_key(&key)),
+                    }
+                }
+                toml::Value::Table(table)
+            }
+            Value::Array(array) => {
+                let array = array
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        value
+                            .try_into()
+                            .map_err(|error: crate::Error| error.at_index(index))
+                    })
+                    .collect::<crate::Result<_>>()?;
+                toml::Value::Array(array)
+            }
+            Value::Boolean(boolean) => toml::Value::Boolean(boolean),
+            Value::String(string) => toml::Value::String(string.into_string()),
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => toml::Value::Integer(integer),
+                None if number.is_f64() => toml::Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+                None => {
+                    return Err(crate::Error::InvalidConversion {
+                        from: "Number",
+                        to: "TOML integer",
+                    });
+                }
+            },
+            Value::NonFinite(non_finite) => toml::Value::Float(non_finite.as_f64()),
+            Value::Null => {
+                return Err(crate::Error::InvalidConversion {
+                    from: "Null",
+                    to: "TOML value",
+                });
+            }
+            Value::Shared(shared) => std::sync::Arc::unwrap_or_clone(shared).try_into()?,
+        };
+        Ok(value)
+    }
+}
+
+/// Converts a YAML value. Mapping keys that aren't strings are stringified: scalars as
+/// written, e.g. `1` or `true`, sequences and mappings as compact HOCON. Tags are dropped
+/// and floats that are infinite or NaN become [`Value::Null`].
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Value> for Value {
+    fn from(val: serde_yaml::Value) -> Self {
+        fn stringify_key(key: serde_yaml::Value) -> String {
+            match key {
+                serde_yaml::Value::String(string) => string,
+                serde_yaml::Value::Null => "null".to_string(),
+                serde_yaml::Value::Bool(boolean) => boolean.to_string(),
+                serde_yaml::Value::Number(number) => number.to_string(),
+                serde_yaml::Value::Tagged(tagged) => stringify_key(tagged.value),
+                key @ (serde_yaml::Value::Sequence(_) | serde_yaml::Value::Mapping(_)) => {
+                    Value::from(key).to_hocon_string()
+                }
+            }
+        }
+        match val {
+            serde_yaml::Value::Null => Value::Null,
+            serde_yaml::Value::Bool(boolean) => Value::Boolean(boolean),
+            serde_yaml::Value::Number(number) => {
+                if let Some(integer) = number.as_i64() {
+                    Value::from(integer)
+                } else if let Some(integer) = number.as_u64() {
+                    Value::Number(integer.into())
+                } else {
+                    Value::from(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_yaml::Value::String(string) => Value::new_string(string),
+            serde_yaml::Value::Sequence(sequence) => {
+                Value::array_from_iter(sequence.into_iter().map(Into::into))
+            }
+            serde_yaml::Value::Mapping(mapping) => Value::object_from_iter(
+                mapping
+                    .into_iter()
+                    .map(|(key, value)| (stringify_key(key), value.into())),
+            ),
+            serde_yaml::Value::Tagged(tagged) => tagged.value.into(),
+        }
+    }
+}
+
+/// Converts to a YAML value, with the keys of objects sorted.
+#[cfg(feature = "yaml")]
+impl From<Value> for serde_yaml::Value {
+    fn from(val: Value) -> Self {
+        match val {
+            Value::Object(object) => {
+                let mut entries = object.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                serde_yaml::Value::Mapping(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| {
+                            (serde_yaml::Value::String(key.into_string()), value.into())
+                        })
+                        .collect(),
+                )
+            }
+            Value::Array(array) => {
+                serde_yaml::Value::Sequence(array.into_iter().map(Into::into).collect())
+            }
+            Value::Boolean(boolean) => serde_yaml::Value::Bool(boolean),
+            Value::Null => serde_yaml::Value::Null,
+            Value::String(string) => serde_yaml::Value::String(string.into_string()),
+            Value::Number(number) => {
+                if let Some(integer) = number.as_i64() {
+                    serde_yaml::Value::Number(integer.into())
+                } else if let Some(integer) = number.as_u64() {
+                    serde_yaml::Value::Number(integer.into())
+                } else if let Some(float) = number.as_f64() {
+                    serde_yaml::Value::Number(float.into())
+                } else {
+                    serde_yaml::Value::String(number.to_string())
+                }
+            }
+            Value::NonFinite(non_finite) => serde_yaml::Value::Number(non_finite.as_f64().into()),
+            Value::Shared(shared) => std::sync::Arc::unwrap_or_clone(shared).into(),
         }
     }
 }
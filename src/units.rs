@@ -0,0 +1,126 @@
+//! Parsing of durations and sizes in bytes, with the units HOCON defines for them.
+//!
+//! These are the same rules [`Value::as_duration`](crate::Value::as_duration) and
+//! [`Value::as_bytes`](crate::Value::as_bytes) apply to strings, so values that arrive
+//! outside of a config file, e.g. from a command line flag, can be read the same way.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use bigdecimal::BigDecimal;
+use num_bigint::{BigUint, ToBigInt};
+
+/// Parses a duration such as `"1.5h"`, `"30seconds"` or `"250"`.
+///
+/// A number without unit is read as milliseconds. Supported units are `ns`, `us`, `ms`,
+/// `s`, `m`, `h` and `d`, as well as their long forms like `seconds`. Returns `None` if
+/// the number or the unit is invalid.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// assert_eq!(hocon_rs::parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+/// assert_eq!(hocon_rs::parse_duration("250"), Some(Duration::from_millis(250)));
+/// assert_eq!(hocon_rs::parse_duration("1 fortnight"), None);
+/// ```
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let idx = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(idx);
+    match unit {
+        "ns" | "nano" | "nanos" | "nanosecond" | "nanoseconds" => {
+            Some(Duration::from_nanos(num.parse().ok()?))
+        }
+        "us" | "micro" | "micros" | "microsecond" | "microseconds" => {
+            Some(Duration::from_micros(num.parse().ok()?))
+        }
+        "" | "ms" | "milli" | "millis" | "millisecond" | "milliseconds" => {
+            Some(duration_from_millis_f64(num.parse().ok()?))
+        }
+        "s" | "second" | "seconds" => {
+            let s: f64 = num.parse().ok()?;
+            Some(duration_from_millis_f64(s * 1000.0))
+        }
+        "m" | "minute" | "minutes" => Some(duration_from_minutes(num.parse().ok()?)),
+        "h" | "hour" | "hours" => {
+            let h: f64 = num.parse().ok()?;
+            Some(duration_from_minutes(h * 60.0))
+        }
+        "d" | "day" | "days" => {
+            let d: f64 = num.parse().ok()?;
+            Some(duration_from_minutes(d * 60.0 * 24.0))
+        }
+        _ => None,
+    }
+}
+
+/// Parses a size in bytes such as `"2GiB"`, `"512 kB"` or `"10"`.
+///
+/// A number without unit is read as bytes. Powers of ten use SI units like `kB` and `MB`,
+/// powers of two use units like `K`, `Ki` and `KiB`. Fractional sizes are truncated to
+/// whole bytes. Returns `None` if the number or the unit is invalid.
+///
+/// # Example
+/// ```rust
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(hocon_rs::parse_bytes("2GiB"), Some(BigUint::from(2u64 << 30)));
+/// assert_eq!(hocon_rs::parse_bytes("1.5kB"), Some(BigUint::from(1500u32)));
+/// assert_eq!(hocon_rs::parse_bytes("-1B"), None);
+/// ```
+pub fn parse_bytes(s: &str) -> Option<BigUint> {
+    let s = s.trim();
+    let idx = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(idx);
+    let bytes = match unit.trim() {
+        "" | "B" | "b" | "byte" | "bytes" => Some(BigUint::from(1u32)),
+        "kB" | "kilobyte" | "kilobytes" => Some(BigUint::from(10u32).pow(3u32)),
+        "MB" | "megabyte" | "megabytes" => Some(BigUint::from(10u32).pow(6u32)),
+        "GB" | "gigabyte" | "gigabytes" => Some(BigUint::from(10u32).pow(9u32)),
+        "TB" | "terabyte" | "terabytes" => Some(BigUint::from(10u32).pow(12u32)),
+        "PB" | "petabyte" | "petabytes" => Some(BigUint::from(10u32).pow(15u32)),
+        "EB" | "exabyte" | "exabytes" => Some(BigUint::from(10u32).pow(18u32)),
+        "ZB" | "zettabyte" | "zettabytes" => Some(BigUint::from(10u32).pow(21u32)),
+        "YB" | "yottabyte" | "yottabytes" => Some(BigUint::from(10u32).pow(24u32)),
+
+        "K" | "k" | "Ki" | "KiB" | "kibibyte" | "kibibytes" => Some(BigUint::from(2u32).pow(10u32)),
+        "M" | "m" | "Mi" | "MiB" | "mebibyte" | "mebibytes" => Some(BigUint::from(2u32).pow(20u32)),
+        "G" | "g" | "Gi" | "GiB" | "gibibyte" | "gibibytes" => Some(BigUint::from(2u32).pow(30u32)),
+        "T" | "t" | "Ti" | "TiB" | "tebibyte" | "tebibytes" => Some(BigUint::from(2u32).pow(40u32)),
+        "P" | "p" | "Pi" | "PiB" | "pebibyte" | "pebibytes" => Some(BigUint::from(2u32).pow(50u32)),
+        "E" | "e" | "Ei" | "EiB" | "exbibyte" | "exbibytes" => Some(BigUint::from(2u32).pow(60u32)),
+        "Z" | "z" | "Zi" | "ZiB" | "zebibyte" | "zebibytes" => Some(BigUint::from(2u32).pow(70u32)),
+        "Y" | "y" | "Yi" | "YiB" | "yobibyte" | "yobibytes" => Some(BigUint::from(2u32).pow(80u32)),
+
+        _ => None,
+    }?;
+    match BigUint::from_str(num) {
+        Ok(num) => Some(&num * &bytes),
+        Err(_) => match BigDecimal::from_str(num) {
+            Ok(num) => {
+                let num = &num * &bytes.to_bigint()?;
+                let (num, _) = num.with_scale(0).into_bigint_and_exponent();
+                BigUint::try_from(num).ok()
+            }
+            Err(_) => None,
+        },
+    }
+}
+
+fn duration_from_minutes(min: f64) -> Duration {
+    let secs = min * 60.0;
+    let whole = secs.trunc() as u64;
+    let nanos = (secs.fract() * 1_000_000_000.0).round() as u32;
+    Duration::new(whole, nanos)
+}
+
+pub(crate) fn duration_from_millis_f64(ms: f64) -> Duration {
+    let secs = (ms / 1000.0) as u64;
+    let nanos = ((ms % 1000.0) * 1_000_000.0) as u32;
+    Duration::new(secs, nanos)
+}
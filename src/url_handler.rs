@@ -0,0 +1,166 @@
+//! Handlers for custom URL schemes, e.g. `s3://` or `consul://`, registered in
+//! [`crate::ConfigOptions::url_handlers`] so `include url(...)` and
+//! [`crate::Config::parse_url`] read such URLs without downloading them first.
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+/// Reads the contents of URLs with the scheme it is registered for.
+///
+/// The syntax of the contents is told by the extension of the URL path and falls back to
+/// HOCON. With the `tokio` feature, the async loading functions call
+/// `UrlHandler::read_async` instead, so a handler that reads over the network can do so
+/// without blocking.
+///
+/// # Example
+/// ```rust
+/// use std::borrow::Cow;
+/// use hocon_rs::url_handler::UrlHandler;
+/// use hocon_rs::{Config, ConfigOptions, Value};
+///
+/// #[derive(Debug)]
+/// struct Memory;
+///
+/// impl UrlHandler for Memory {
+///     fn read(&self, url: &url::Url) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+///         match url.path() {
+///             "/app.conf" => Ok(Some(Cow::Borrowed(b"port = 8080"))),
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+///
+/// let mut options = ConfigOptions::default();
+/// options.register_url_handler("memory", Memory);
+/// let input = r#"include url("memory://host/app.conf")"#;
+/// let value: Value = Config::parse_str(input, Some(options)).unwrap();
+/// assert_eq!(value.get_by_path(["port"]), Some(&Value::from(8080)));
+/// ```
+pub trait UrlHandler: Debug {
+    /// Returns the contents at `url`, or `None` if there are none.
+    fn read(&self, url: &url::Url) -> std::io::Result<Option<Cow<'static, [u8]>>>;
+
+    /// Like [`UrlHandler::read`], for the async loading functions. By default, it calls
+    /// [`UrlHandler::read`], blocking the task until it returns.
+    #[cfg(feature = "tokio")]
+    fn read_async<'a>(&'a self, url: &'a url::Url) -> ReadFuture<'a> {
+        Box::pin(std::future::ready(self.read(url)))
+    }
+}
+
+/// The contents [`UrlHandler::read_async`] reads.
+#[cfg(feature = "tokio")]
+pub type ReadFuture<'a> = std::pin::Pin<
+    Box<dyn std::future::Future<Output = std::io::Result<Option<Cow<'static, [u8]>>>> + 'a>,
+>;
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::url_handler::UrlHandler;
+    use crate::{Config, ConfigOptions, Error, Result, Value};
+
+    #[derive(Debug, Default)]
+    struct Bucket {
+        reads: Rc<Cell<usize>>,
+    }
+
+    impl UrlHandler for Bucket {
+        fn read(&self, url: &url::Url) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+            self.reads.set(self.reads.get() + 1);
+            let contents: &'static [u8] = match url.path() {
+                "/app.conf" => b"include \"s3://bucket/defaults.json\"\nport = ${defaults.port}",
+                "/defaults.json" => br#"{"defaults": {"port": 8080}}"#,
+                _ => return Ok(None),
+            };
+            Ok(Some(Cow::Borrowed(contents)))
+        }
+    }
+
+    #[test]
+    fn test_url_handlers() -> Result<()> {
+        let bucket = Bucket::default();
+        let reads = bucket.reads.clone();
+        let mut options = ConfigOptions::default();
+        options.register_url_handler("S3", bucket);
+        let input = "include url(\"s3://bucket/app.conf\")\ninclude \"s3://bucket/app.conf\"";
+        let value: Value = Config::parse_str(input, Some(options.clone()))?;
+        let expected: Value = Config::parse_str("defaults.port = 8080, port = 8080", None)?;
+        assert_eq!(value, expected);
+        // Every location is read once per load.
+        assert_eq!(reads.get(), 2);
+
+        let input = "include required(url(\"s3://bucket/missing.conf\"))";
+        let result = Config::parse_str::<Value>(input, Some(options.clone()));
+        assert!(matches!(result, Err(Error::Include { .. })));
+        assert_eq!(
+            Config::parse_url::<Value>("s3://bucket/app.conf", Some(options))?,
+            expected
+        );
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct Fixed(&'static str);
+
+    impl UrlHandler for Fixed {
+        fn read(&self, _: &url::Url) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+            Ok(Some(Cow::Borrowed(self.0.as_bytes())))
+        }
+    }
+
+    #[test]
+    fn test_url_handlers_not_cached() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.conf");
+        std::fs::write(&path, "include url(\"memory://host/app.conf\")")?;
+        for port in ["8080", "9090"] {
+            let mut options = ConfigOptions {
+                cache: true,
+                ..Default::default()
+            };
+            options.register_url_handler("memory", Fixed(format!("port = {port}").leak()));
+            let value: Value = Config::load(&path, Some(options))?;
+            let expected: Value = Config::parse_str(&format!("port = {port}"), None)?;
+            assert_eq!(value, expected);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_url_handlers_async() -> Result<()> {
+        use crate::url_handler::ReadFuture;
+
+        #[derive(Debug)]
+        struct Remote;
+
+        impl UrlHandler for Remote {
+            fn read(&self, _: &url::Url) -> std::io::Result<Option<Cow<'static, [u8]>>> {
+                panic!("the async loaders read without blocking");
+            }
+
+            fn read_async<'a>(&'a self, url: &'a url::Url) -> ReadFuture<'a> {
+                Box::pin(async move {
+                    tokio::task::yield_now().await;
+                    let contents: &'static [u8] = match url.path() {
+                        "/app.conf" => b"include \"remote://host/port.conf\"\nname = app",
+                        "/port.conf" => b"port = 8080",
+                        _ => return Ok(None),
+                    };
+                    Ok(Some(Cow::Borrowed(contents)))
+                })
+            }
+        }
+
+        let mut options = ConfigOptions::default();
+        options.register_url_handler("remote", Remote);
+        let value: Value = Config::parse_url_async("remote://host/app.conf", Some(options)).await?;
+        let expected: Value = Config::parse_str("name = app, port = 8080", None)?;
+        assert_eq!(value, expected);
+        Ok(())
+    }
+}
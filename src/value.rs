@@ -1,28 +1,63 @@
-use bigdecimal::BigDecimal;
-use num_bigint::{BigUint, ToBigInt};
+use num_bigint::BigUint;
+use serde::de::DeserializeOwned;
 use serde::de::{Error, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Number;
+#[cfg(not(feature = "preserve_order"))]
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::number::NonFinite;
+use crate::path::{Selector, render_path};
+use crate::policy::{Policy, Violation};
 use crate::{join, join_format};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg(feature = "preserve_order")]
+use indexmap::map::Entry;
+
+pub use crate::map::Map;
+
+/// Returns the entries of `object` in the order the flattening APIs visit them, see [`Map`].
+#[cfg(not(feature = "preserve_order"))]
+pub(crate) fn ordered_entries(object: &Map) -> Vec<(&ValueString, &Value)> {
+    let mut entries = object.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Returns the entries of `object` in the order the flattening APIs visit them, see [`Map`].
+#[cfg(feature = "preserve_order")]
+pub(crate) fn ordered_entries(object: &Map) -> Vec<(&ValueString, &Value)> {
+    object.iter().collect()
+}
+
+pub use crate::value_string::ValueString;
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Value {
-    Object(HashMap<String, Value>),
+    Object(Map),
     Array(Vec<Value>),
     Boolean(bool),
     Null,
-    String(String),
+    String(ValueString),
     Number(Number),
+    /// `Infinity`, `-Infinity` or `NaN`, which a [`Number`] can't hold. Only parsed with
+    /// [`crate::ConfigOptions::non_finite_numbers`].
+    NonFinite(NonFinite),
+    /// A value shared with other parts of the tree, see [`Value::dedup`].
+    ///
+    /// It behaves like the value it points to: accessors and comparisons look through it,
+    /// and mutable accessors copy it on write.
+    Shared(Arc<Value>),
 }
 
 impl Value {
-    pub fn object(obj: HashMap<String, Value>) -> Value {
+    pub fn object(obj: Map) -> Value {
         Value::Object(obj)
     }
 
@@ -30,7 +65,7 @@ impl Value {
     where
         I: IntoIterator<Item = (String, Value)>,
     {
-        Value::Object(HashMap::from_iter(iter))
+        Value::Object(Map::from_iter(iter))
     }
 
     pub fn array(values: Vec<Value>) -> Value {
@@ -52,35 +87,63 @@ impl Value {
         Value::Null
     }
 
-    pub fn new_string(string: impl Into<String>) -> Value {
+    pub fn new_string(string: impl Into<ValueString>) -> Value {
         Value::String(string.into())
     }
 }
 
 impl Value {
-    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+    /// Returns the value behind any [`Value::Shared`] indirection.
+    pub fn unshared(&self) -> &Value {
+        let mut value = self;
+        while let Value::Shared(shared) = value {
+            value = shared;
+        }
+        value
+    }
+
+    /// Returns the value behind any [`Value::Shared`] indirection for mutation, copying it
+    /// first if it is still shared. Nested shared values are copied only when they are
+    /// accessed mutably themselves.
+    pub fn make_mut(&mut self) -> &mut Value {
+        if let Value::Shared(_) = self {
+            *self = std::mem::replace(self, Value::Null).into_unshared();
+        }
+        self
+    }
+
+    /// Consumes the value and returns the value behind any [`Value::Shared`] indirection,
+    /// copying it only if it is still shared.
+    pub fn into_unshared(self) -> Value {
         match self {
+            Value::Shared(shared) => Arc::unwrap_or_clone(shared).into_unshared(),
+            value => value,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map> {
+        match self.unshared() {
             Value::Object(object) => Some(object),
             _ => None,
         }
     }
 
-    pub fn as_object_mut(&mut self) -> Option<&mut HashMap<String, Value>> {
-        match self {
+    pub fn as_object_mut(&mut self) -> Option<&mut Map> {
+        match self.make_mut() {
             Value::Object(object) => Some(object),
             _ => None,
         }
     }
 
     pub fn as_array(&self) -> Option<&Vec<Value>> {
-        match self {
+        match self.unshared() {
             Value::Array(array) => Some(array),
             _ => None,
         }
     }
 
     pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
-        match self {
+        match self.make_mut() {
             Value::Array(array) => Some(array),
             _ => None,
         }
@@ -119,7 +182,7 @@ impl Value {
     /// [ "first", "second", "third" ]
     /// ```
     pub fn as_array_numerically(&self) -> Option<Vec<&Value>> {
-        match self {
+        match self.unshared() {
             // Already an array → just return the elements.
             Value::Array(array) => Some(array.iter().collect()),
 
@@ -149,112 +212,112 @@ impl Value {
     }
 
     /// Attempts to interpret the current [`Value`] as a boolean, following
-    /// HOCON's relaxed truthy/falsey rules.
-    ///
-    /// # Behavior
-    ///
-    /// - If the value is a `Value::Boolean`, returns the inner `bool`.
-    ///
-    /// - If the value is a `Value::String`, accepts several textual
-    ///   representations:
-    ///   - `"true"`, `"on"`, `"yes"` → `Some(true)`
-    ///   - `"false"`, `"off"`, `"no"` → `Some(false)`
-    ///
-    /// - For all other values (numbers, arrays, objects, or strings that
-    ///   don't match the above), returns `None`.
+    /// HOCON's relaxed truthy/falsey rules, see [`Lenient::as_boolean`].
     ///
-    /// # Notes
-    /// - The matching is **case-sensitive** (`"True"` will not be recognized).
-    /// - This conversion is specific to HOCON and goes beyond JSON’s strict
-    ///   boolean representation.
+    /// Use [`Value::as_boolean_strict`] to accept only boolean values.
     pub fn as_boolean(&self) -> Option<bool> {
-        match self {
-            // Direct boolean value
-            Value::Boolean(boolean) => Some(*boolean),
-
-            // String representations of truthy values
-            Value::String(boolean) if boolean == "true" || boolean == "on" || boolean == "yes" => {
-                Some(true)
-            }
-
-            // String representations of falsey values
-            Value::String(boolean) if boolean == "false" || boolean == "off" || boolean == "no" => {
-                Some(false)
-            }
-
-            // Everything else → not interpretable as boolean
-            _ => None,
-        }
+        self.lenient().as_boolean()
     }
 
     pub fn as_str(&self) -> Option<&str> {
-        match self {
+        match self.unshared() {
             Value::String(string) => Some(string),
             _ => None,
         }
     }
 
+    /// Returns the number as `f64`, parsing strings, see [`Lenient`].
     pub fn as_f64(&self) -> Option<f64> {
-        match self {
-            Value::Number(number) => number.as_f64(),
-            Value::String(number) => number.parse().ok(),
-            _ => None,
-        }
+        self.lenient().as_f64()
     }
 
+    /// Returns the number as `i64`, parsing strings, see [`Lenient`].
     pub fn as_i64(&self) -> Option<i64> {
-        match self {
-            Value::Number(number) => number.as_i64(),
-            Value::String(number) => number.parse().ok(),
-            _ => None,
-        }
+        self.lenient().as_i64()
     }
 
+    /// Returns the number as `i128`, parsing strings, see [`Lenient`].
     pub fn as_i128(&self) -> Option<i128> {
-        match self {
-            Value::Number(number) => number.as_i128(),
-            Value::String(number) => number.parse().ok(),
-            _ => None,
-        }
+        self.lenient().as_i128()
     }
 
+    /// Returns the number as `u128`, parsing strings, see [`Lenient`].
     pub fn as_u128(&self) -> Option<u128> {
-        match self {
-            Value::Number(number) => number.as_u128(),
-            Value::String(number) => number.parse().ok(),
-            _ => None,
-        }
+        self.lenient().as_u128()
     }
 
+    /// Returns the number as `u64`, parsing strings, see [`Lenient`].
     pub fn as_u64(&self) -> Option<u64> {
-        match self {
-            Value::Number(number) => number.as_u64(),
-            Value::String(number) => number.parse().ok(),
-            _ => None,
-        }
+        self.lenient().as_u64()
     }
 
-    /// Checks whether the current [`Value`] represents `null` in HOCON.
+    /// Checks whether the current [`Value`] represents `null` in HOCON, which includes
+    /// the string `"null"`, see [`Lenient::is_null`].
     ///
-    /// # Behavior
-    ///
-    /// - Returns `true` if the value is explicitly `Value::Null`.
-    /// - Returns `true` if the value is a `Value::String` equal to `"null"`.
-    /// - Otherwise, returns `false`.
-    ///
-    /// # Notes
-    /// - The check for `"null"` is **case-sensitive**. `"Null"` or `"NULL"`
-    ///   will not be considered null.
-    /// - This deviates from strict JSON, where only a literal `null` is valid.
-    ///   HOCON allows the string `"null"` to be treated as a null value.
+    /// Use [`Value::is_null_strict`] to accept only `null` values.
     pub fn is_null(&self) -> bool {
-        match self {
-            Value::Null => true,
-            Value::String(s) if s == "null" => true,
-            _ => false,
+        self.lenient().is_null()
+    }
+
+    /// Returns the boolean if this is a [`Value::Boolean`], without looking into strings.
+    pub fn as_boolean_strict(&self) -> Option<bool> {
+        match self.unshared() {
+            Value::Boolean(boolean) => Some(*boolean),
+            _ => None,
         }
     }
 
+    /// Returns the number as `f64` if this is a [`Value::Number`] or [`Value::NonFinite`],
+    /// without parsing strings.
+    pub fn as_f64_strict(&self) -> Option<f64> {
+        match self.unshared() {
+            Value::NonFinite(non_finite) => Some(non_finite.as_f64()),
+            _ => self.as_number()?.as_f64(),
+        }
+    }
+
+    /// Returns the number as `i64` if this is a [`Value::Number`] that fits, without
+    /// parsing strings.
+    pub fn as_i64_strict(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    /// Returns the number as `i128` if this is a [`Value::Number`] that fits, without
+    /// parsing strings.
+    pub fn as_i128_strict(&self) -> Option<i128> {
+        self.as_number()?.as_i128()
+    }
+
+    /// Returns the number as `u128` if this is a [`Value::Number`] that fits, without
+    /// parsing strings.
+    pub fn as_u128_strict(&self) -> Option<u128> {
+        self.as_number()?.as_u128()
+    }
+
+    /// Returns the number as `u64` if this is a [`Value::Number`] that fits, without
+    /// parsing strings.
+    pub fn as_u64_strict(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    /// Returns `true` only for [`Value::Null`].
+    pub fn is_null_strict(&self) -> bool {
+        matches!(self.unshared(), Value::Null)
+    }
+
+    fn as_number(&self) -> Option<&Number> {
+        match self.unshared() {
+            Value::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// Returns a view of this value whose accessors coerce strings, e.g. `"42"` to a number
+    /// or `"yes"` to `true`.
+    pub fn lenient(&self) -> Lenient<'_> {
+        Lenient(self)
+    }
+
     pub fn ty(&self) -> &'static str {
         match self {
             Value::Object(_) => "Object",
@@ -262,45 +325,125 @@ impl Value {
             Value::Boolean(_) => "Boolean",
             Value::Null => "Null",
             Value::String(_) => "String",
-            Value::Number(_) => "Number",
+            Value::Number(_) | Value::NonFinite(_) => "Number",
+            Value::Shared(shared) => shared.ty(),
         }
     }
 
-    pub fn into_object(self) -> Option<HashMap<String, Value>> {
-        match self {
+    pub fn into_object(self) -> Option<Map> {
+        match self.into_unshared() {
             Value::Object(object) => Some(object),
             _ => None,
         }
     }
 
     pub fn into_array(self) -> Option<Vec<Value>> {
-        match self {
+        match self.into_unshared() {
             Value::Array(array) => Some(array),
             _ => None,
         }
     }
 
     pub fn into_boolean(self) -> Option<bool> {
-        match self {
+        match self.into_unshared() {
             Value::Boolean(boolean) => Some(boolean),
             _ => None,
         }
     }
 
     pub fn into_string(self) -> Option<String> {
-        match self {
-            Value::String(string) => Some(string),
+        match self.into_unshared() {
+            Value::String(string) => Some(string.into_string()),
             _ => None,
         }
     }
 
     pub fn into_number(self) -> Option<Number> {
-        match self {
+        match self.into_unshared() {
             Value::Number(number) => Some(number),
             _ => None,
         }
     }
 
+    /// Deserializes every entry of an object into `T`, e.g. for a map of named sections.
+    ///
+    /// # Errors
+    /// - [`crate::Error::InvalidConversion`] if `self` is not an object.
+    /// - [`crate::Error::AtPath`] with the offending key if an entry can't be deserialized.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("a.port = 1, b.port = 2", None).unwrap();
+    /// let ports = value.into_typed_map::<Value>().unwrap();
+    /// assert_eq!(ports["b"].get_by_path(["port"]), Some(&Value::from(2)));
+    /// ```
+    pub fn into_typed_map<T>(self) -> crate::Result<HashMap<String, T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.into_typed_entries()
+    }
+
+    /// Like [`Value::into_typed_map`], but collects into a [`BTreeMap`] sorted by key.
+    pub fn into_typed_btree_map<T>(self) -> crate::Result<BTreeMap<String, T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.into_typed_entries()
+    }
+
+    /// Deserializes `self` into `T` after filling in what it's missing from `reference`, see
+    /// [`Value::with_fallback`]. A partial override can be loaded into a complete struct
+    /// without an `Option` on every field, as long as the reference provides them.
+    ///
+    /// # Errors
+    /// Fails if the merged value can't be deserialized into `T`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let reference: Value = Config::parse_str("host = localhost, port = 80", None).unwrap();
+    /// let overrides: Value = Config::parse_str("port = 8080", None).unwrap();
+    /// let server = overrides.project::<Server>(&reference).unwrap();
+    /// assert_eq!((server.host.as_str(), server.port), ("localhost", 8080));
+    /// ```
+    pub fn project<T>(self, reference: &Value) -> crate::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        T::deserialize(self.with_fallback(reference.clone()))
+    }
+
+    fn into_typed_entries<T, M>(self) -> crate::Result<M>
+    where
+        T: DeserializeOwned,
+        M: FromIterator<(String, T)>,
+    {
+        match self.into_unshared() {
+            Value::Object(object) => object
+                .into_iter()
+                .map(|(key, value)| match T::deserialize(value) {
+                    Ok(value) => Ok((key.into_string(), value)),
+                    Err(error) => Err(error.at_key(&key)),
+                })
+                .collect(),
+            other => Err(crate::Error::InvalidConversion {
+                from: other.ty(),
+                to: "object",
+            }),
+        }
+    }
+
     /// Retrieves a value from a nested `Value::Object` by following a HOCON-style path.
     ///
     /// # Arguments
@@ -342,7 +485,7 @@ impl Value {
 
         // Traverse the object tree step by step
         for &path in paths {
-            if let Value::Object(obj) = current {
+            if let Value::Object(obj) = current.unshared() {
                 if let Some(val) = obj.get(path) {
                     current = val;
                 } else {
@@ -365,17 +508,251 @@ impl Value {
         }
         let mut current = self;
         for &path in paths {
-            if let Value::Object(obj) = current {
-                if let Some(val) = obj.get_mut(path) {
-                    current = val;
-                } else {
-                    return None;
-                }
+            match current.make_mut() {
+                Value::Object(obj) => current = obj.get_mut(path)?,
+                _ => return None,
             }
         }
         Some(current)
     }
 
+    /// Returns all leaf entries (every value that is not an object) as `(path, value)` pairs,
+    /// in the order described on [`Map`].
+    ///
+    /// Paths are rendered as HOCON path expressions, keys that contain dots or other special
+    /// characters are quoted, so `{"a.b": {c: 1}}` yields `"a.b".c`. Arrays and nulls are
+    /// leaves, empty objects produce no entry. If `self` is not an object, the result is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Config;
+    /// use hocon_rs::Value;
+    ///
+    /// let value: Value = Config::parse_str("a { b = 1, c = [2] }", None).unwrap();
+    /// let entries = value.entry_set();
+    /// assert_eq!(entries[0], ("a.b".to_string(), &Value::from(1)));
+    /// assert_eq!(entries[1].0, "a.c");
+    /// ```
+    pub fn entry_set(&self) -> Vec<(String, &Value)> {
+        fn collect<'a>(
+            value: &'a Value,
+            path: &mut Vec<&'a str>,
+            entries: &mut Vec<(String, &'a Value)>,
+        ) {
+            match value.unshared() {
+                Value::Object(object) => {
+                    for (key, value) in ordered_entries(object) {
+                        path.push(key);
+                        collect(value, path, entries);
+                        path.pop();
+                    }
+                }
+                leaf => entries.push((render_path(path.iter().copied()), leaf)),
+            }
+        }
+        let mut entries = vec![];
+        if let Value::Object(_) = self.unshared() {
+            collect(self, &mut vec![], &mut entries);
+        }
+        entries
+    }
+
+    /// Returns every value whose path matches `selector` as `(path, value)` pairs, in the
+    /// order described on [`Map`] with objects before the values they contain.
+    ///
+    /// The selector is a HOCON path expression where the unquoted keys `*` and `**` match
+    /// any single key and any number of keys, including none, and keys containing dots are
+    /// quoted like `a."b.c"`. Paths are rendered like in [`Value::entry_set`], so they select
+    /// their value again. Only object keys are matched, arrays are not searched. An invalid
+    /// selector matches nothing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("db.timeout = 5s, http { client.timeout = 1s }", None).unwrap();
+    /// let timeouts = value.select("**.timeout");
+    /// assert_eq!(timeouts[0], ("db.timeout".to_string(), &Value::from("5s")));
+    /// assert_eq!(timeouts[1].0, "http.client.timeout");
+    /// ```
+    pub fn select(&self, selector: &str) -> Vec<(String, &Value)> {
+        fn collect<'a>(
+            object: &'a Map,
+            selector: &Selector,
+            states: &[usize],
+            path: &mut Vec<&'a str>,
+            selections: &mut Vec<(String, &'a Value)>,
+        ) {
+            for (key, value) in ordered_entries(object) {
+                let states = selector.step(states, key);
+                if states.is_empty() {
+                    continue;
+                }
+                path.push(key);
+                if selector.is_match(&states) {
+                    selections.push((render_path(path.iter().copied()), value));
+                }
+                if let Value::Object(object) = value.unshared() {
+                    collect(object, selector, &states, path, selections);
+                }
+                path.pop();
+            }
+        }
+        let selector = Selector::new(selector);
+        let mut selections = vec![];
+        if let Value::Object(object) = self.unshared() {
+            collect(
+                object,
+                &selector,
+                &selector.start(),
+                &mut vec![],
+                &mut selections,
+            );
+        }
+        selections
+    }
+
+    /// Wraps this value into objects nested under the dot separated `path`, a HOCON path
+    /// expression where keys containing dots are quoted like `a."b.c"`.
+    ///
+    /// Fails with [`crate::Error::InvalidPathExpression`] if `path` isn't a path
+    /// expression.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::Value;
+    ///
+    /// let value = Value::from(1).at_path("a.b").unwrap();
+    /// assert_eq!(value.get_by_path(["a", "b"]), Some(&Value::from(1)));
+    /// ```
+    pub fn at_path(self, path: &str) -> crate::Result<Value> {
+        let keys = crate::path::Path::from_str(path)?.keys();
+        Ok(keys
+            .into_iter()
+            .rev()
+            .fold(self, |value, key| value.at_key(key)))
+    }
+
+    /// Wraps this value into an object with the single entry `key`. Unlike
+    /// [`Value::at_path`], the key is taken literally, dots included.
+    pub fn at_key(self, key: impl Into<String>) -> Value {
+        Value::Object(Map::from([(key.into(), self)]))
+    }
+
+    /// Returns `true` if a value exists at `path` and it is not null (see [`Value::is_null`]).
+    /// `path` is a HOCON path expression, so keys containing dots are quoted like
+    /// `a."b.c"`. An invalid path exists nowhere.
+    ///
+    /// Use [`Value::has_path_or_null`] to also accept null values, and
+    /// [`Value::get_is_null`] to tell a missing path from a null one.
+    pub fn has_path(&self, path: &str) -> bool {
+        self.find(path).ok().flatten().is_some_and(|v| !v.is_null())
+    }
+
+    /// Returns `true` if a value exists at `path`, including null values.
+    ///
+    /// Keys removed by an optional substitution that resolved to nothing don't exist,
+    /// while `a = null` does.
+    pub fn has_path_or_null(&self, path: &str) -> bool {
+        self.find(path).ok().flatten().is_some()
+    }
+
+    /// Returns whether the value at the path expression `path` is null.
+    ///
+    /// # Errors
+    /// - [`crate::Error::InvalidPathExpression`] if `path` isn't a path expression.
+    /// - [`crate::Error::PathNotFound`] if there is no value at `path`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("a = null, b = ${?MISSING_VAR}", None).unwrap();
+    /// assert!(value.get_is_null("a").unwrap());
+    /// assert!(value.get_is_null("b").is_err());
+    /// ```
+    pub fn get_is_null(&self, path: &str) -> crate::Result<bool> {
+        match self.find(path)? {
+            Some(value) => Ok(value.is_null()),
+            None => Err(crate::Error::PathNotFound(path.to_string())),
+        }
+    }
+
+    /// Returns the value at the path expression `path`.
+    fn find(&self, path: &str) -> crate::Result<Option<&Value>> {
+        let keys = crate::path::Path::from_str(path)?.keys();
+        Ok(self.get_by_path(keys.iter().map(String::as_str).collect::<Vec<_>>()))
+    }
+
+    /// Returns a copy of this value with `value` set at the path expression `path`.
+    ///
+    /// Missing intermediate objects are created and non-object values on the way are
+    /// replaced by objects. An existing value at `path` is replaced, not merged.
+    ///
+    /// Fails with [`crate::Error::InvalidPathExpression`] if `path` isn't a path
+    /// expression.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("a = 1", None).unwrap();
+    /// let value = value.with_value(r#"b."c.d""#, 2).unwrap();
+    /// assert_eq!(value.get_by_path(["a"]), Some(&Value::from(1)));
+    /// assert_eq!(value.get_by_path(["b", "c.d"]), Some(&Value::from(2)));
+    /// ```
+    pub fn with_value(self, path: &str, value: impl Into<Value>) -> crate::Result<Value> {
+        fn with_value(current: Value, keys: &[String], value: Value) -> Value {
+            match keys.split_first() {
+                Some((first, rest)) => {
+                    let mut object = match current.into_unshared() {
+                        Value::Object(object) => object,
+                        _ => Map::new(),
+                    };
+                    let child = object.get_or_insert_with(first.as_str(), || Value::Null);
+                    *child = with_value(std::mem::replace(child, Value::Null), rest, value);
+                    Value::Object(object)
+                }
+                None => value,
+            }
+        }
+        let keys = crate::path::Path::from_str(path)?.keys();
+        Ok(with_value(self, &keys, value.into()))
+    }
+
+    /// Returns a copy of this value with the entry at the path expression `path` removed.
+    ///
+    /// Parent objects that become empty because of the removal are removed as well. The
+    /// value is returned unchanged if there is nothing at `path`.
+    ///
+    /// Fails with [`crate::Error::InvalidPathExpression`] if `path` isn't a path
+    /// expression.
+    pub fn without_path(mut self, path: &str) -> crate::Result<Value> {
+        fn remove(object: &mut Map, keys: &[String]) -> bool {
+            let Some((first, rest)) = keys.split_first() else {
+                return false;
+            };
+            if rest.is_empty() {
+                return object.remove(first).is_some();
+            }
+            match object.get_mut(first).map(Value::make_mut) {
+                Some(Value::Object(child)) => {
+                    let removed = remove(child, rest);
+                    if removed && child.is_empty() {
+                        object.remove(first);
+                    }
+                    removed
+                }
+                _ => false,
+            }
+        }
+        let keys = crate::path::Path::from_str(path)?.keys();
+        if let Value::Object(object) = self.make_mut() {
+            remove(object, &keys);
+        }
+        Ok(self)
+    }
+
     /// Merge this `Value` with a fallback `Value`, following HOCON's `withFallback` semantics.
     ///
     /// - If both `self` and `fallback` are `Object`s, they are merged key by key:
@@ -386,17 +763,19 @@ impl Value {
     /// - For all other cases (non-object values), `self` takes precedence
     ///   and the fallback is ignored.
     pub fn with_fallback(self, fallback: Value) -> Value {
-        match (self, fallback) {
+        match (self.into_unshared(), fallback.into_unshared()) {
             // Case 1: Both values are objects -> perform deep merge
             (Value::Object(mut obj), Value::Object(fb_obj)) => {
                 for (k, fb_val) in fb_obj {
-                    match obj.entry(k) {
+                    match obj.0.entry(k) {
                         // If key already exists in `self`
                         Entry::Occupied(mut occupied_entry) => {
                             let existing_val = occupied_entry.get_mut();
 
                             // If both values are objects -> merge recursively
-                            if let (Value::Object(_), Value::Object(_)) = (&existing_val, &fb_val) {
+                            if let (Value::Object(_), Value::Object(_)) =
+                                (existing_val.unshared(), fb_val.unshared())
+                            {
                                 // Temporarily move out the existing value to avoid borrow conflicts
                                 let mut temp = Value::Null;
                                 std::mem::swap(&mut temp, existing_val);
@@ -420,70 +799,129 @@ impl Value {
             (other, _) => other,
         }
     }
+
+    /// Shares identical sub-values behind [`Value::Shared`], so repeated strings, arrays and
+    /// objects, e.g. from expanding a template many times, are stored only once.
+    ///
+    /// The result compares equal to `self`. Mutating a shared sub-value, e.g. through
+    /// [`Value::as_object_mut`], copies it first and leaves its other occurrences untouched.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("a { x = 1 }, b { x = 1 }", None).unwrap();
+    /// let deduped = value.clone().dedup();
+    /// assert_eq!(deduped, value);
+    /// assert!(matches!(deduped.as_object().unwrap()["b"], Value::Shared(_)));
+    /// ```
+    pub fn dedup(self) -> Value {
+        Interner::default().dedup_children(self).0
+    }
+
+    /// Runs `policies` against this value and returns all violations they found, in the
+    /// order of the policies.
+    pub fn check_policies(&self, policies: &[&dyn Policy]) -> Vec<Violation> {
+        policies
+            .iter()
+            .flat_map(|policy| policy.check(self))
+            .collect()
+    }
+
+    /// Converts the value to a [serde_json::Value], without going through serde.
+    /// [`Value::NonFinite`] numbers, which JSON can't represent, become `null`.
+    pub fn into_json(self) -> serde_json::Value {
+        self.into()
+    }
+
+    /// Renders the value as HOCON or JSON, see [`crate::RenderOptions`].
+    pub fn render(&self, options: &crate::RenderOptions) -> String {
+        let mut rendered = String::new();
+        crate::render::Renderer::with_options(&mut rendered, options.clone())
+            .render_root_value(self)
+            .expect("writing to a String never fails");
+        rendered
+    }
+
+    /// Renders the value as HOCON on a single line, e.g. `{a = 1, b {c = ["x y"]}}`.
+    ///
+    /// Unlike [Display], the result always parses back to the same value: strings are
+    /// quoted and escaped, keys are quoted if they contain dots or other special characters.
+    /// Object keys are sorted.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("\"a.b\" = \"x: y\", c = [1, true]", None).unwrap();
+    /// let hocon = value.to_hocon_string();
+    /// assert_eq!(hocon, "{\"a.b\" = \"x: y\", c = [1, true]}");
+    /// assert_eq!(Config::parse_str::<Value>(&hocon, None).unwrap(), value);
+    /// ```
+    pub fn to_hocon_string(&self) -> String {
+        let mut rendered = String::new();
+        crate::render::Renderer::new(&mut rendered)
+            .render_compact_value(self)
+            .expect("writing to a String never fails");
+        rendered
+    }
+
+    /// Flattens the value into the Java `.properties` format, one `path=value` line per
+    /// leaf, in the order described on [`Map`].
+    ///
+    /// Array elements are keyed by their index and nulls are left out. Keys and values are
+    /// escaped, non-ASCII characters as `\uXXXX`. If `self` is not an object, the result is
+    /// empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hocon_rs::{Config, Value};
+    ///
+    /// let value: Value = Config::parse_str("db { hosts = [a, b], url = \"jdbc:h2:mem\" }", None).unwrap();
+    /// assert_eq!(
+    ///     value.to_properties_string(),
+    ///     "db.hosts.0=a\ndb.hosts.1=b\ndb.url=jdbc\\:h2\\:mem\n"
+    /// );
+    /// ```
+    pub fn to_properties_string(&self) -> String {
+        crate::properties::render_properties(self)
+    }
+
+    /// Flattens the value into a dotenv (`.env`) file, one `NAME=value` line per leaf, in
+    /// the order described on [`Map`]. By default the names read back into the same paths with
+    /// [`crate::ConfigBuilder::add_env_prefix`], see [`crate::DotenvOptions`].
+    ///
+    /// Array elements are keyed by their index and nulls are left out. If `self` is not an
+    /// object, the result is empty.
+    pub fn to_dotenv_string(&self, options: &crate::DotenvOptions) -> String {
+        crate::dotenv::render_dotenv(self, options)
+    }
+
+    /// Renders the value as indented HOCON, one field per line, the same as
+    /// [Value::render] with the default [`crate::RenderOptions`].
+    pub fn to_hocon_string_pretty(&self) -> String {
+        self.render(&crate::RenderOptions::default())
+    }
+
+    /// Renders the value as HOCON and writes it to `path` through a temporary file that is
+    /// renamed over it, see [`crate::Config::render_to_file`]. Object keys are sorted.
+    pub fn render_to_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        backup: bool,
+    ) -> crate::Result<()> {
+        let rendered = self.render(&crate::RenderOptions::default());
+        crate::render::write_atomic(path.as_ref(), rendered.as_bytes(), backup, false)
+    }
 }
 
 impl Value {
     pub fn as_bytes(&self) -> Option<BigUint> {
-        fn str_to_bytes(s: &str) -> Option<BigUint> {
-            let idx = s
-                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
-                .unwrap_or(s.len());
-            let (num, unit) = s.split_at(idx);
-            let bytes = match unit.trim() {
-                "" | "B" | "b" | "byte" | "bytes" => Some(BigUint::from(1u32)),
-                "kB" | "kilobyte" | "kilobytes" => Some(BigUint::from(10u32).pow(3u32)),
-                "MB" | "megabyte" | "megabytes" => Some(BigUint::from(10u32).pow(6u32)),
-                "GB" | "gigabyte" | "gigabytes" => Some(BigUint::from(10u32).pow(9u32)),
-                "TB" | "terabyte" | "terabytes" => Some(BigUint::from(10u32).pow(12u32)),
-                "PB" | "petabyte" | "petabytes" => Some(BigUint::from(10u32).pow(15u32)),
-                "EB" | "exabyte" | "exabytes" => Some(BigUint::from(10u32).pow(18u32)),
-                "ZB" | "zettabyte" | "zettabytes" => Some(BigUint::from(10u32).pow(21u32)),
-                "YB" | "yottabyte" | "yottabytes" => Some(BigUint::from(10u32).pow(24u32)),
-
-                "K" | "k" | "Ki" | "KiB" | "kibibyte" | "kibibytes" => {
-                    Some(BigUint::from(2u32).pow(10u32))
-                }
-                "M" | "m" | "Mi" | "MiB" | "mebibyte" | "mebibytes" => {
-                    Some(BigUint::from(2u32).pow(20u32))
-                }
-                "G" | "g" | "Gi" | "GiB" | "gibibyte" | "gibibytes" => {
-                    Some(BigUint::from(2u32).pow(30u32))
-                }
-                "T" | "t" | "Ti" | "TiB" | "tebibyte" | "tebibytes" => {
-                    Some(BigUint::from(2u32).pow(40u32))
-                }
-                "P" | "p" | "Pi" | "PiB" | "pebibyte" | "pebibytes" => {
-                    Some(BigUint::from(2u32).pow(50u32))
-                }
-                "E" | "e" | "Ei" | "EiB" | "exbibyte" | "exbibytes" => {
-                    Some(BigUint::from(2u32).pow(60u32))
-                }
-                "Z" | "z" | "Zi" | "ZiB" | "zebibyte" | "zebibytes" => {
-                    Some(BigUint::from(2u32).pow(70u32))
-                }
-                "Y" | "y" | "Yi" | "YiB" | "yobibyte" | "yobibytes" => {
-                    Some(BigUint::from(2u32).pow(80u32))
-                }
-
-                _ => None,
-            }?;
-            match BigUint::from_str(num) {
-                Ok(num) => Some(&num * &bytes),
-                Err(_) => match BigDecimal::from_str(num) {
-                    Ok(num) => {
-                        let num = &num * &bytes.to_bigint()?;
-                        let (num, _) = num.with_scale(0).into_bigint_and_exponent();
-                        BigUint::try_from(num).ok()
-                    }
-                    Err(_) => None,
-                },
-            }
-        }
-        match self {
+        match self.unshared() {
             #[cfg(not(feature = "json_arbitrary_precision"))]
             Value::Number(num) => match num.as_u64().map(BigUint::from) {
                 None => {
-                    use bigdecimal::FromPrimitive;
+                    use bigdecimal::{BigDecimal, FromPrimitive};
                     let (num, _) = num
                         .as_f64()
                         .and_then(BigDecimal::from_f64)?
@@ -494,70 +932,25 @@ impl Value {
                 Some(i) => Some(i),
             },
             #[cfg(feature = "json_arbitrary_precision")]
-            Value::Number(i) => str_to_bytes(i.as_str()),
-            Value::String(s) => str_to_bytes(s.as_str().trim()),
+            Value::Number(i) => crate::units::parse_bytes(i.as_str()),
+            Value::String(s) => crate::units::parse_bytes(s.as_str()),
             _ => None,
         }
     }
 
     pub fn as_duration(&self) -> Option<Duration> {
-        fn duration_from_minutes(min: f64) -> Duration {
-            let secs = min * 60.0;
-            let whole = secs.trunc() as u64;
-            let nanos = (secs.fract() * 1_000_000_000.0).round() as u32;
-            Duration::new(whole, nanos)
-        }
-
-        fn duration_from_millis_f64(ms: f64) -> Duration {
-            let secs = (ms / 1000.0) as u64;
-            let nanos = ((ms % 1000.0) * 1_000_000.0) as u32;
-            Duration::new(secs, nanos)
-        }
-
-        fn str_to_duration(s: &str) -> Option<Duration> {
-            let idx = s
-                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
-                .unwrap_or(s.len());
-            let (num, unit) = s.split_at(idx);
-            match unit {
-                "ns" | "nano" | "nanos" | "nanosecond" | "nanoseconds" => {
-                    Some(Duration::from_nanos(num.parse().ok()?))
-                }
-                "us" | "micro" | "micros" | "microsecond" | "microseconds" => {
-                    Some(Duration::from_micros(num.parse().ok()?))
-                }
-                "" | "ms" | "milli" | "millis" | "millisecond" | "milliseconds" => {
-                    Some(duration_from_millis_f64(num.parse().ok()?))
-                }
-                "s" | "second" | "seconds" => {
-                    let s: f64 = num.parse().ok()?;
-                    Some(duration_from_millis_f64(s * 1000.0))
-                }
-                "m" | "minute" | "minutes" => Some(duration_from_minutes(num.parse().ok()?)),
-                "h" | "hour" | "hours" => {
-                    let h: f64 = num.parse().ok()?;
-                    Some(duration_from_minutes(h * 60.0))
-                }
-                "d" | "day" | "days" => {
-                    let d: f64 = num.parse().ok()?;
-                    Some(duration_from_minutes(d * 60.0 * 24.0))
-                }
-                _ => None,
-            }
-        }
-
-        match self {
+        match self.unshared() {
             #[cfg(not(feature = "json_arbitrary_precision"))]
             Value::Number(millis) => match millis.as_u64() {
                 Some(millis) => {
                     let duration = Duration::from_millis(millis);
                     Some(duration)
                 }
-                None => millis.as_f64().map(duration_from_millis_f64),
+                None => millis.as_f64().map(crate::units::duration_from_millis_f64),
             },
             #[cfg(feature = "json_arbitrary_precision")]
-            Value::Number(i) => str_to_duration(i.as_str()),
-            Value::String(s) => str_to_duration(s.as_str().trim()),
+            Value::Number(i) => crate::units::parse_duration(i.as_str()),
+            Value::String(s) => crate::units::parse_duration(s.as_str()),
             _ => None,
         }
     }
@@ -615,17 +1008,218 @@ impl Display for Value {
             Value::Number(number) => {
                 write!(f, "{}", number)
             }
+            Value::NonFinite(non_finite) => {
+                write!(f, "{}", non_finite)
+            }
+            Value::Shared(shared) => {
+                write!(f, "{}", shared)
+            }
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Shared(left), Value::Shared(right)) if Arc::ptr_eq(left, right) => true,
+            (Value::Shared(left), right) => **left == *right,
+            (left, Value::Shared(right)) => *left == **right,
+            (Value::Object(left), Value::Object(right)) => left == right,
+            (Value::Array(left), Value::Array(right)) => left == right,
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::Null, Value::Null) => true,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::NonFinite(left), Value::NonFinite(right)) => left == right,
+            _ => false,
         }
     }
 }
 
+impl Eq for Value {}
+
+/// Deduplicates values bottom-up, see [`Value::dedup`]. Values are bucketed by a structural
+/// hash, so children are compared by pointer once they have been interned.
+#[derive(Default)]
+struct Interner {
+    values: HashMap<u64, Vec<Arc<Value>>>,
+}
+
+impl Interner {
+    /// Interns the children of `value` and returns the rebuilt value with its hash.
+    fn dedup_children(&mut self, value: Value) -> (Value, u64) {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        let value = match value {
+            Value::Object(object) => {
+                let mut entries = Vec::with_capacity(object.len());
+                for (key, value) in object {
+                    let (value, hash) = self.intern(value);
+                    entries.push((key, value, hash));
+                }
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                0u8.hash(&mut hasher);
+                for (key, _, hash) in &entries {
+                    key.hash(&mut hasher);
+                    hash.hash(&mut hasher);
+                }
+                Value::Object(entries.into_iter().map(|(k, v, _)| (k, v)).collect())
+            }
+            Value::Array(array) => {
+                1u8.hash(&mut hasher);
+                let array = array
+                    .into_iter()
+                    .map(|value| {
+                        let (value, hash) = self.intern(value);
+                        hash.hash(&mut hasher);
+                        value
+                    })
+                    .collect();
+                Value::Array(array)
+            }
+            Value::Boolean(boolean) => {
+                2u8.hash(&mut hasher);
+                boolean.hash(&mut hasher);
+                Value::Boolean(boolean)
+            }
+            Value::Null => {
+                3u8.hash(&mut hasher);
+                Value::Null
+            }
+            Value::String(string) => {
+                4u8.hash(&mut hasher);
+                string.hash(&mut hasher);
+                Value::String(string)
+            }
+            Value::Number(number) => {
+                5u8.hash(&mut hasher);
+                number.hash(&mut hasher);
+                Value::Number(number)
+            }
+            Value::NonFinite(non_finite) => {
+                6u8.hash(&mut hasher);
+                non_finite.hash(&mut hasher);
+                Value::NonFinite(non_finite)
+            }
+            Value::Shared(shared) => return self.dedup_children(Arc::unwrap_or_clone(shared)),
+        };
+        (value, hasher.finish())
+    }
+
+    /// Returns a [`Value::Shared`] pointing to the first occurrence of `value`. Scalars
+    /// without heap allocations are returned as they are.
+    fn intern(&mut self, value: Value) -> (Value, u64) {
+        let (value, hash) = self.dedup_children(value);
+        let shareable = match &value {
+            Value::Object(object) => !object.is_empty(),
+            Value::Array(array) => !array.is_empty(),
+            Value::String(string) => !string.is_empty(),
+            _ => false,
+        };
+        if !shareable {
+            return (value, hash);
+        }
+        let bucket = self.values.entry(hash).or_default();
+        let shared = match bucket.iter().find(|shared| ***shared == value) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared = Arc::new(value);
+                bucket.push(shared.clone());
+                shared
+            }
+        };
+        (Value::Shared(shared), hash)
+    }
+}
+
+/// A view of a [`Value`] whose accessors coerce strings the way HOCON does, created with
+/// [`Value::lenient`].
+///
+/// The plain accessors of [`Value`], like [`Value::as_i64`], behave the same way. The
+/// `_strict` variants, like [`Value::as_i64_strict`], never look into strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lenient<'a>(pub &'a Value);
+
+impl Lenient<'_> {
+    /// Interprets the value as a boolean, following HOCON's relaxed truthy/falsey rules.
+    ///
+    /// # Behavior
+    ///
+    /// - If the value is a `Value::Boolean`, returns the inner `bool`.
+    ///
+    /// - If the value is a `Value::String`, accepts several textual
+    ///   representations:
+    ///   - `"true"`, `"on"`, `"yes"` → `Some(true)`
+    ///   - `"false"`, `"off"`, `"no"` → `Some(false)`
+    ///
+    /// - For all other values (numbers, arrays, objects, or strings that
+    ///   don't match the above), returns `None`.
+    ///
+    /// # Notes
+    /// - The matching is **case-sensitive** (`"True"` will not be recognized).
+    /// - This conversion is specific to HOCON and goes beyond JSON’s strict
+    ///   boolean representation.
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self.0.unshared() {
+            Value::String(boolean) if boolean == "true" || boolean == "on" || boolean == "yes" => {
+                Some(true)
+            }
+            Value::String(boolean) if boolean == "false" || boolean == "off" || boolean == "no" => {
+                Some(false)
+            }
+            value => value.as_boolean_strict(),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.0.as_f64_strict().or_else(|| self.parse())
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.0.as_i64_strict().or_else(|| self.parse())
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        self.0.as_i128_strict().or_else(|| self.parse())
+    }
+
+    pub fn as_u128(&self) -> Option<u128> {
+        self.0.as_u128_strict().or_else(|| self.parse())
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.0.as_u64_strict().or_else(|| self.parse())
+    }
+
+    /// Checks whether the value represents `null` in HOCON.
+    ///
+    /// # Behavior
+    ///
+    /// - Returns `true` if the value is explicitly `Value::Null`.
+    /// - Returns `true` if the value is a `Value::String` equal to `"null"`.
+    /// - Otherwise, returns `false`.
+    ///
+    /// # Notes
+    /// - The check for `"null"` is **case-sensitive**. `"Null"` or `"NULL"`
+    ///   will not be considered null.
+    /// - This deviates from strict JSON, where only a literal `null` is valid.
+    ///   HOCON allows the string `"null"` to be treated as a null value.
+    pub fn is_null(&self) -> bool {
+        self.0.is_null_strict() || self.0.as_str() == Some("null")
+    }
+
+    fn parse<T: FromStr>(&self) -> Option<T> {
+        self.0.as_str()?.parse().ok()
+    }
+}
+
 impl TryFrom<crate::merge::value::Value> for Value {
     type Error = crate::error::Error;
 
     fn try_from(value: crate::merge::value::Value) -> Result<Self, Self::Error> {
         fn from_object(object: crate::merge::object::Object) -> crate::Result<Value> {
-            let inner: BTreeMap<_, _> = object.into();
-            let mut object = HashMap::with_capacity(inner.len());
+            let inner = object.into_inner();
+            let mut object = Map::with_capacity(inner.len());
             for (k, v) in inner.into_iter() {
                 let v = v.into_inner();
                 if !matches!(v, crate::merge::value::Value::None) {
@@ -657,6 +1251,7 @@ impl TryFrom<crate::merge::value::Value> for Value {
             crate::merge::value::Value::Null | crate::merge::value::Value::None => Value::Null,
             crate::merge::value::Value::String(string) => Value::String(string),
             crate::merge::value::Value::Number(number) => Value::Number(number),
+            crate::merge::value::Value::NonFinite(non_finite) => Value::NonFinite(non_finite),
             crate::merge::value::Value::Substitution(_)
             | crate::merge::value::Value::Concat(_)
             | crate::merge::value::Value::AddAssign(_)
@@ -668,6 +1263,60 @@ impl TryFrom<crate::merge::value::Value> for Value {
     }
 }
 
+impl Value {
+    /// Converts a value resolved with [`crate::ResolveOptions::allow_unresolved`], leaving out
+    /// what still depends on a missing substitution and adding its path to `unresolved`.
+    /// Unresolved array elements become nulls, so the indices of the others don't change.
+    pub(crate) fn from_partially_resolved(
+        value: crate::merge::value::Value,
+        path: &mut Vec<String>,
+        unresolved: &mut Vec<String>,
+    ) -> Option<Value> {
+        let value = match value {
+            crate::merge::value::Value::Object(object) => {
+                let inner = object.into_inner();
+                let mut object = Map::with_capacity(inner.len());
+                for (k, v) in inner.into_iter() {
+                    let v = v.into_inner();
+                    if matches!(v, crate::merge::value::Value::None) {
+                        continue;
+                    }
+                    path.push(k.to_string());
+                    let v = Self::from_partially_resolved(v, path, unresolved);
+                    path.pop();
+                    if let Some(v) = v {
+                        object.insert(k, v);
+                    }
+                }
+                Value::Object(object)
+            }
+            crate::merge::value::Value::Array(array) => {
+                let mut result = Vec::with_capacity(array.len());
+                for (index, ele) in array.into_inner().into_iter().enumerate() {
+                    path.push(index.to_string());
+                    let v = Self::from_partially_resolved(ele.into_inner(), path, unresolved);
+                    path.pop();
+                    result.push(v.unwrap_or(Value::Null));
+                }
+                Value::Array(result)
+            }
+            crate::merge::value::Value::Boolean(boolean) => Value::Boolean(boolean),
+            crate::merge::value::Value::Null | crate::merge::value::Value::None => Value::Null,
+            crate::merge::value::Value::String(string) => Value::String(string),
+            crate::merge::value::Value::Number(number) => Value::Number(number),
+            crate::merge::value::Value::NonFinite(non_finite) => Value::NonFinite(non_finite),
+            crate::merge::value::Value::Substitution(_)
+            | crate::merge::value::Value::Concat(_)
+            | crate::merge::value::Value::AddAssign(_)
+            | crate::merge::value::Value::DelayReplacement(_) => {
+                unresolved.push(render_path(path.iter().map(String::as_str)));
+                return None;
+            }
+        };
+        Some(value)
+    }
+}
+
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -680,6 +1329,8 @@ impl Serialize for Value {
             Value::Null => serializer.serialize_none(),
             Value::String(s) => s.serialize(serializer),
             Value::Number(num) => num.serialize(serializer),
+            Value::NonFinite(non_finite) => serializer.serialize_f64(non_finite.as_f64()),
+            Value::Shared(shared) => shared.serialize(serializer),
         }
     }
 }
@@ -716,15 +1367,16 @@ impl<'de> Deserialize<'de> for Value {
             {
                 Ok(Number::from_f64(v)
                     .map(Value::Number)
+                    .or_else(|| NonFinite::from_f64(v).map(Value::NonFinite))
                     .unwrap_or(Value::Null))
             }
 
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
-                Ok(Value::String(v.to_owned()))
+                Ok(Value::new_string(v))
             }
 
             fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
-                Ok(Value::String(v))
+                Ok(Value::new_string(v))
             }
 
             fn visit_none<E>(self) -> Result<Self::Value, E> {
@@ -751,7 +1403,7 @@ impl<'de> Deserialize<'de> for Value {
                 M: MapAccess<'de>,
             {
                 match map.next_key::<String>()? {
-                    None => Ok(Value::Object(HashMap::new())),
+                    None => Ok(Value::Object(Map::new())),
                     Some(first_key) => match first_key.as_str() {
                         #[cfg(feature = "json_arbitrary_precision")]
                         "$serde_json::private::Number" => {
@@ -760,10 +1412,10 @@ impl<'de> Deserialize<'de> for Value {
                             Ok(Value::Number(n))
                         }
                         _ => {
-                            let mut values = HashMap::new();
+                            let mut values = Map::new();
                             let value = map.next_value()?;
                             values.insert(first_key, value);
-                            while let Some((k, v)) = map.next_entry()? {
+                            while let Some((k, v)) = map.next_entry::<String, Value>()? {
                                 values.insert(k, v);
                             }
                             Ok(Value::Object(values))
@@ -904,10 +1556,26 @@ mod tests {
     #[case("yobibyte", bin_factor(80))]
     #[case("yobibytes", bin_factor(80))]
     fn test_as_bytes_all_units(#[case] unit: &str, #[case] factor: BigUint) {
-        let input = Value::String(format!("1{}", unit));
+        let input = Value::new_string(format!("1{}", unit));
         assert_eq!(input.as_bytes(), Some(factor));
     }
 
+    #[cfg(feature = "compact_str")]
+    #[test]
+    fn test_compact_strings() -> crate::Result<()> {
+        let value: Value =
+            crate::Config::parse_str("a { host = localhost, port = \"8080\" }", None)?;
+        let Some(Value::String(host)) = value.get_by_path(["a", "host"]) else {
+            panic!("expected a string");
+        };
+        assert_eq!(host, "localhost");
+        assert!(host.is_inline());
+        let a = value.get_by_path(["a"]).and_then(Value::as_object).unwrap();
+        let (key, _) = a.get_key_value("host").unwrap();
+        assert!(key.is_inline());
+        Ok(())
+    }
+
     #[rstest]
     #[case(Value::String("123 B".into()), Some(BigUint::from(123u32)))]
     #[case(Value::String("1 kB".into()), Some(BigUint::from(1000u32)))]
@@ -1002,7 +1670,7 @@ mod tests {
     }
 
     fn obj(entries: Vec<(&str, Value)>) -> Value {
-        let mut map = HashMap::new();
+        let mut map = Map::new();
         for (k, v) in entries {
             map.insert(k.to_string(), v);
         }
@@ -1060,6 +1728,40 @@ mod tests {
         assert_eq!(input.is_null(), expected);
     }
 
+    #[rstest]
+    #[case(Value::Number(42.into()), Some(42), Some(42))]
+    #[case(Value::String("42".into()), None, Some(42))]
+    #[case(Value::String("4x".into()), None, None)]
+    #[case(Value::Boolean(true), None, None)]
+    #[case(Value::Shared(Arc::new(Value::Number((-1).into()))), Some(-1), Some(-1))]
+    fn test_strict_and_lenient_numbers(
+        #[case] input: Value,
+        #[case] strict: Option<i64>,
+        #[case] lenient: Option<i64>,
+    ) {
+        assert_eq!(input.as_i64_strict(), strict);
+        assert_eq!(input.lenient().as_i64(), lenient);
+        assert_eq!(input.as_i64(), lenient);
+        assert_eq!(input.as_f64_strict(), strict.map(|n| n as f64));
+        assert_eq!(input.lenient().as_f64(), lenient.map(|n| n as f64));
+    }
+
+    #[rstest]
+    #[case(Value::Boolean(true), Some(true), Some(true))]
+    #[case(Value::String("yes".into()), None, Some(true))]
+    #[case(Value::String("false".into()), None, Some(false))]
+    #[case(Value::Null, None, None)]
+    fn test_strict_and_lenient_booleans(
+        #[case] input: Value,
+        #[case] strict: Option<bool>,
+        #[case] lenient: Option<bool>,
+    ) {
+        assert_eq!(input.as_boolean_strict(), strict);
+        assert_eq!(input.lenient().as_boolean(), lenient);
+        assert_eq!(input.is_null_strict(), input == Value::Null);
+        assert!(!Value::String("null".into()).is_null_strict());
+    }
+
     #[rstest]
     // Case 1: Simple fallback
     #[case(
@@ -1121,11 +1823,11 @@ mod tests {
 
     #[test]
     fn test_as_mut() {
-        let mut object = HashMap::new();
-        object.insert("hello".into(), Value::String("world".into()));
+        let mut object = Map::new();
+        object.insert("hello", Value::String("world".into()));
         let mut value = Value::Object(object);
         let object = value.as_object_mut().unwrap();
-        object.insert("array".into(), Value::Array(vec![]));
+        object.insert("array", Value::Array(vec![]));
         let array = object.get_mut("array").unwrap();
         let array = array.as_array_mut().unwrap();
         array.push(Value::Null);
@@ -1133,7 +1835,7 @@ mod tests {
 
     #[test]
     fn test_into() {
-        let value = Value::Object(HashMap::default());
+        let value = Value::Object(Map::default());
         let _ = value.into_object().unwrap();
         let value = Value::Array(vec![]);
         let _ = value.into_array().unwrap();
@@ -1144,4 +1846,383 @@ mod tests {
         let value = Value::String("hello".into());
         let _ = value.into_string().unwrap();
     }
+
+    #[test]
+    fn test_entry_set() {
+        let value = obj(vec![
+            (
+                "a",
+                obj(vec![("b", Value::from(1)), ("c", Value::Array(vec![]))]),
+            ),
+            ("d.e", obj(vec![("f g", Value::Null)])),
+            ("empty", obj(vec![])),
+            ("include", Value::from(true)),
+        ]);
+        let entries = value.entry_set();
+        let paths: Vec<_> = entries.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["a.b", "a.c", "\"d.e\".\"f g\"", "\"include\""]);
+        assert_eq!(entries[0].1, &Value::from(1));
+        assert!(Value::from(1).entry_set().is_empty());
+    }
+
+    #[test]
+    fn test_flatten_order() {
+        let value = obj(vec![
+            ("z", Value::from(0)),
+            ("a-c", Value::from(1)),
+            ("a", obj(vec![("d", Value::from(2)), ("b", Value::from(3))])),
+        ]);
+        #[cfg(not(feature = "preserve_order"))]
+        let expected = ["a.b", "a.d", "a-c", "z"];
+        #[cfg(feature = "preserve_order")]
+        let expected = ["z", "a-c", "a.d", "a.b"];
+        let paths = value.entry_set().into_iter().map(|(path, _)| path);
+        assert_eq!(paths.collect::<Vec<_>>(), expected);
+        let paths = value.select("**").into_iter().map(|(path, _)| path);
+        let leaves = paths.filter(|path| path != "a").collect::<Vec<_>>();
+        assert_eq!(leaves, expected);
+        let properties = value.to_properties_string();
+        let keys = properties
+            .lines()
+            .map(|line| line.split('=').next().unwrap());
+        assert_eq!(keys.collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_into_typed_map() {
+        let value = obj(vec![("a", Value::from(1)), ("b", Value::from(2))]);
+        let map = value.clone().into_typed_map::<i64>().unwrap();
+        assert_eq!(
+            map,
+            HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)])
+        );
+        let map = value.into_typed_btree_map::<Value>().unwrap();
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+
+        let value = obj(vec![("a", Value::from(1)), ("b.c", Value::from("x"))]);
+        match value.into_typed_map::<i64>() {
+            Err(crate::Error::AtPath { path, .. }) => assert_eq!(path, "\"b.c\""),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(matches!(
+            Value::from(1).into_typed_map::<i64>(),
+            Err(crate::Error::InvalidConversion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_project() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pool {
+            size: i64,
+            timeout: String,
+        }
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Db {
+            url: String,
+            pool: Pool,
+        }
+        let reference = obj(vec![
+            ("url", Value::from("jdbc:h2")),
+            (
+                "pool",
+                obj(vec![
+                    ("size", Value::from(4)),
+                    ("timeout", Value::from("1s")),
+                ]),
+            ),
+        ]);
+        let overrides = obj(vec![("pool", obj(vec![("size", Value::from(16))]))]);
+        let db = overrides.project::<Db>(&reference).unwrap();
+        assert_eq!(
+            db,
+            Db {
+                url: "jdbc:h2".to_string(),
+                pool: Pool {
+                    size: 16,
+                    timeout: "1s".to_string(),
+                },
+            }
+        );
+        assert!(
+            Value::Object(Map::new())
+                .project::<Db>(&Value::Null)
+                .is_err()
+        );
+    }
+
+    #[rstest]
+    #[case("a", true, true, Some(false))]
+    #[case("b", false, true, Some(true))]
+    #[case("c.d", true, true, Some(false))]
+    #[case("c.e", false, false, None)]
+    #[case("a.b", false, false, None)]
+    #[case(r#""c.d""#, true, true, Some(false))]
+    #[case("c..d", false, false, None)]
+    fn test_has_path(
+        #[case] path: &str,
+        #[case] has_path: bool,
+        #[case] has_path_or_null: bool,
+        #[case] is_null: Option<bool>,
+    ) {
+        let value = obj(vec![
+            ("a", Value::from(1)),
+            ("b", Value::Null),
+            ("c", obj(vec![("d", Value::from("x"))])),
+            ("c.d", Value::from("y")),
+        ]);
+        assert_eq!(value.has_path(path), has_path);
+        assert_eq!(value.has_path_or_null(path), has_path_or_null);
+        assert_eq!(value.get_is_null(path).ok(), is_null);
+    }
+
+    #[test]
+    fn test_at_path() -> crate::Result<()> {
+        let value = Value::from(1).at_path("a.b")?;
+        assert_eq!(value, obj(vec![("a", obj(vec![("b", Value::from(1))]))]));
+        let value = Value::from(1).at_path(r#"a."b.c""#)?;
+        assert_eq!(value, obj(vec![("a", obj(vec![("b.c", Value::from(1))]))]));
+        let value = Value::from(1).at_key("a.b");
+        assert_eq!(value, obj(vec![("a.b", Value::from(1))]));
+        for path in ["", "a..b", "a.", ".a", "a: b"] {
+            assert!(matches!(
+                Value::from(1).at_path(path),
+                Err(crate::Error::InvalidPathExpression(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_value_and_without_path() -> crate::Result<()> {
+        let value = obj(vec![
+            ("a", obj(vec![("b", Value::from(1))])),
+            ("c", Value::from(2)),
+        ]);
+        let value = value.with_value("a.d", 3)?.with_value("c.e", 4)?;
+        assert_eq!(value.get_by_path(["a", "b"]), Some(&Value::from(1)));
+        assert_eq!(value.get_by_path(["a", "d"]), Some(&Value::from(3)));
+        assert_eq!(value.get_by_path(["c", "e"]), Some(&Value::from(4)));
+
+        let value = value.without_path("c.e")?.without_path("a.x")?;
+        assert!(!value.has_path_or_null("c"));
+        let value = value.without_path("a.b")?;
+        assert_eq!(value, obj(vec![("a", obj(vec![("d", Value::from(3))]))]));
+
+        let value = value.with_value(r#""x.y""#, 5)?;
+        assert_eq!(value.get_by_path(["x.y"]), Some(&Value::from(5)));
+        let value = value.without_path(r#""x.y""#)?;
+        assert_eq!(value, obj(vec![("a", obj(vec![("d", Value::from(3))]))]));
+        for path in ["", "a..b"] {
+            assert!(matches!(
+                value.clone().with_value(path, 1),
+                Err(crate::Error::InvalidPathExpression(_))
+            ));
+            assert!(matches!(
+                value.clone().without_path(path),
+                Err(crate::Error::InvalidPathExpression(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup() -> crate::Result<()> {
+        let template = obj(vec![
+            ("host", Value::from("localhost")),
+            (
+                "ports",
+                Value::array(vec![Value::from(80), Value::from(443)]),
+            ),
+        ]);
+        let value = obj(vec![
+            ("a", template.clone()),
+            ("b", template.clone()),
+            ("c", obj(vec![("host", Value::from("localhost"))])),
+        ]);
+        let mut deduped = value.clone().dedup();
+        assert_eq!(deduped, value);
+
+        let object = deduped.as_object().unwrap();
+        match (&object["a"], &object["b"]) {
+            (Value::Shared(a), Value::Shared(b)) => assert!(Arc::ptr_eq(a, b)),
+            other => panic!("expected shared values, got {other:?}"),
+        }
+        assert_eq!(
+            deduped.get_by_path(["c", "host"]).and_then(Value::as_str),
+            Some("localhost")
+        );
+        assert_eq!(deduped.to_string().len(), value.to_string().len());
+        assert_eq!(crate::from_value::<Value>(deduped.clone())?, value);
+
+        *deduped.get_by_path_mut(["b", "host"]).unwrap() = Value::from("example.com");
+        assert_eq!(
+            deduped.get_by_path(["a", "host"]).and_then(Value::as_str),
+            Some("localhost")
+        );
+        assert_eq!(
+            deduped.get_by_path(["b", "host"]).and_then(Value::as_str),
+            Some("example.com")
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case("*.timeout", &["db.timeout", "http.timeout"])]
+    #[case("**.timeout", &["db.timeout", "http.client.timeout", "http.timeout"])]
+    #[case("http.**", &["http", "http.client", "http.client.timeout", "http.timeout"])]
+    #[case("*.*.timeout", &["http.client.timeout"])]
+    #[case("timeout", &[])]
+    fn test_select(#[case] selector: &str, #[case] expected: &[&str]) {
+        let value = obj(vec![
+            ("db", obj(vec![("timeout", Value::from("5s"))])),
+            (
+                "http",
+                obj(vec![
+                    ("client", obj(vec![("timeout", Value::from("2s"))])),
+                    ("timeout", Value::from("1s")),
+                ]),
+            ),
+            ("name", Value::from("app")),
+        ]);
+        let selections = value.select(selector);
+        let paths = selections
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_select_quoted_keys() {
+        let value = obj(vec![
+            ("db.url", obj(vec![("*", Value::from(1))])),
+            ("db", obj(vec![("url", Value::from(2))])),
+        ]);
+        for (path, selected) in value.select("**") {
+            let selections = value.select(&path);
+            assert_eq!(selections, [(path.clone(), selected)], "{path}");
+        }
+        let values = |selector| {
+            value
+                .select(selector)
+                .into_iter()
+                .map(|(_, value)| value.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values("\"db.url\".\"*\""), [Value::from(1)]);
+        assert_eq!(values("*.url"), [Value::from(2)]);
+        assert_eq!(values("\"db.url\".*"), [Value::from(1)]);
+        assert!(values("a..b").is_empty());
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn test_preserve_order() -> crate::Result<()> {
+        let mut value = Value::object_from_iter([
+            ("c".to_string(), Value::from(1)),
+            ("a".to_string(), Value::from(2)),
+            ("b".to_string(), Value::from(3)),
+        ]);
+        value = value.with_value("a", 4)?.without_path("c")?;
+        assert_eq!(serde_json::to_string(&value)?, r#"{"a":4,"b":3}"#);
+        let options = crate::RenderOptions {
+            sort_keys: false,
+            ..Default::default()
+        };
+        let value = value.with_value("0", 5)?;
+        assert_eq!(value.render(&options), "a = 4\nb = 3\n0 = 5\n");
+
+        let resolved: Value =
+            crate::Config::parse_str("c = 1, b { y = 1, x = 2 }, a = ${c}", None)?;
+        assert_eq!(
+            serde_json::to_string(&resolved.into_json())?,
+            r#"{"c":1,"b":{"y":1,"x":2},"a":1}"#
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_conversion() -> crate::Result<()> {
+        let toml: toml::Value = toml::from_str(
+            "name = \"app\"\nport = 80\nratio = 0.5\nstarted = 1979-05-27T07:32:00Z\n[db]\nhosts = [\"a\", \"b\"]\n",
+        )
+        .map_err(|error| crate::Error::Deserialize(error.to_string()))?;
+        let value = Value::from(toml.clone());
+        let expected: Value = crate::Config::parse_str(
+            "name = app, port = 80, ratio = 0.5, started = \"1979-05-27T07:32:00Z\", db.hosts = [a, b]",
+            None,
+        )?;
+        assert_eq!(value, expected);
+        let mut round_trip: toml::Value = value.try_into()?;
+        round_trip["started"] = toml["started"].clone();
+        assert_eq!(round_trip, toml);
+
+        let value: Value = crate::Config::parse_str("a { b = [1, null] }", None)?;
+        let error = TryInto::<toml::Value>::try_into(value).unwrap_err();
+        assert!(matches!(&error, crate::Error::AtPath { path, .. } if path == "a.b[1]"));
+        Ok(())
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_conversion() -> crate::Result<()> {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(
+            "name: app\nport: 80\nratio: 0.5\ntags: [a, !custom b]\ncodes:\n  404: missing\n  true: yes\n  [1, 2]: pair\n",
+        )
+        .map_err(|error| crate::Error::Deserialize(error.to_string()))?;
+        let value = Value::from(yaml);
+        let expected: Value = crate::Config::parse_str(
+            "name = app, port = 80, ratio = 0.5, tags = [a, b], codes { \"404\" = missing, \"true\" = yes, \"[1, 2]\" = pair }",
+            None,
+        )?;
+        assert_eq!(value, expected);
+
+        let yaml = serde_yaml::Value::from(value.clone());
+        assert_eq!(Value::from(yaml), value);
+        let yaml = serde_yaml::to_string(&serde_yaml::Value::from(obj(vec![
+            ("b", Value::Null),
+            ("a", Value::from(1)),
+        ])))
+        .map_err(|error| crate::Error::Serialize(error.to_string()))?;
+        assert_eq!(yaml, "a: 1\nb: null\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_hocon_string_round_trip() {
+        let value = obj(vec![
+            ("plain", Value::from("text")),
+            ("a.b", Value::from("dotted key")),
+            ("", Value::from("empty key")),
+            ("quotes", Value::from("say \"hi\"\n\tand \\ leave")),
+            ("substitution", Value::from("${not.a.substitution}")),
+            ("comment", Value::from("// not # a comment")),
+            (
+                "keywords",
+                Value::Array(vec![Value::from("true"), Value::from("null")]),
+            ),
+            ("numeric", Value::from("42")),
+            ("unicode", Value::from("héllo ✓")),
+            (
+                "nested",
+                obj(vec![(
+                    "list",
+                    Value::Array(vec![obj(vec![("x", Value::from(1))]), Value::Array(vec![])]),
+                )]),
+            ),
+            ("empty", obj(vec![])),
+            ("null", Value::Null),
+            ("flag", Value::from(false)),
+        ]);
+        let compact = value.to_hocon_string();
+        assert!(!compact.contains('\n'), "{compact}");
+        let parsed: Value = crate::Config::parse_str(&compact, None).unwrap();
+        assert_eq!(parsed, value, "{compact}");
+
+        let pretty = value.to_hocon_string_pretty();
+        let parsed: Value = crate::Config::parse_str(&pretty, None).unwrap();
+        assert_eq!(parsed, value, "{pretty}");
+    }
 }
@@ -0,0 +1,293 @@
+//! The string of a [`crate::Value::String`] and of the keys of a [`crate::value::Map`].
+
+use std::borrow::{Borrow, Cow};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde::de::Visitor;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(not(feature = "compact_str"))]
+type Inner = String;
+#[cfg(feature = "compact_str")]
+type Inner = compact_str::CompactString;
+
+/// The string of a [`crate::Value::String`], of the keys of a [`crate::value::Map`] and of the
+/// strings of a [`crate::raw::raw_string::RawString`] that don't borrow from the input.
+///
+/// With the `compact_str` feature strings of up to 24 bytes are stored inline instead of
+/// allocating them. Most keys and scalar values of a configuration are that short. The API
+/// is the same either way.
+///
+/// Equal keys share one allocation if they were interned, see
+/// [`crate::ResolveOptions::intern_keys`].
+#[derive(Clone)]
+pub struct ValueString(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Owned(Inner),
+    Shared(Arc<str>),
+}
+
+impl ValueString {
+    /// Creates an empty string.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the string as a `&str`.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Owned(string) => string,
+            Repr::Shared(string) => string,
+        }
+    }
+
+    /// Converts the string into a `String`, which only copies strings stored inline or
+    /// shared with other keys.
+    pub fn into_string(self) -> String {
+        match self.0 {
+            #[cfg(not(feature = "compact_str"))]
+            Repr::Owned(string) => string,
+            #[cfg(feature = "compact_str")]
+            Repr::Owned(string) => string.into_string(),
+            Repr::Shared(string) => string.to_string(),
+        }
+    }
+
+    /// Returns `true` if the string is stored inline, which is never the case without the
+    /// `compact_str` feature.
+    pub fn is_inline(&self) -> bool {
+        match &self.0 {
+            #[cfg(not(feature = "compact_str"))]
+            Repr::Owned(_) => false,
+            #[cfg(feature = "compact_str")]
+            Repr::Owned(string) => !string.is_heap_allocated(),
+            Repr::Shared(_) => false,
+        }
+    }
+
+    /// Returns `true` if the string shares its allocation with equal strings.
+    pub(crate) fn is_shared(&self) -> bool {
+        matches!(self.0, Repr::Shared(_))
+    }
+
+    /// Returns the string in a form that clones share the allocation of, unless it's stored
+    /// inline, which is cheaper to copy.
+    pub(crate) fn into_shared(self) -> Self {
+        if self.is_inline() || self.is_shared() {
+            return self;
+        }
+        Self(Repr::Shared(Arc::from(self.as_str())))
+    }
+
+    /// Passes the string to `visitor`, by value unless that would copy it.
+    pub(crate) fn visit<'de, V, E>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+        E: serde::de::Error,
+    {
+        if self.is_inline() || self.is_shared() {
+            visitor.visit_str(&self)
+        } else {
+            visitor.visit_string(self.into_string())
+        }
+    }
+}
+
+impl Default for ValueString {
+    fn default() -> Self {
+        Self(Repr::Owned(Inner::default()))
+    }
+}
+
+impl Deref for ValueString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for ValueString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for ValueString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for ValueString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ValueString {}
+
+impl PartialOrd for ValueString {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ValueString {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for ValueString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Display for ValueString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl Debug for ValueString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl From<&str> for ValueString {
+    fn from(string: &str) -> Self {
+        Self(Repr::Owned(Inner::from(string)))
+    }
+}
+
+impl From<&String> for ValueString {
+    fn from(string: &String) -> Self {
+        Self(Repr::Owned(Inner::from(string.as_str())))
+    }
+}
+
+impl From<String> for ValueString {
+    fn from(string: String) -> Self {
+        Self(Repr::Owned(Inner::from(string)))
+    }
+}
+
+impl From<Box<str>> for ValueString {
+    fn from(string: Box<str>) -> Self {
+        Self(Repr::Owned(Inner::from(string)))
+    }
+}
+
+impl From<Cow<'_, str>> for ValueString {
+    fn from(string: Cow<'_, str>) -> Self {
+        match string {
+            Cow::Borrowed(string) => string.into(),
+            Cow::Owned(string) => string.into(),
+        }
+    }
+}
+
+impl From<&Rc<str>> for ValueString {
+    fn from(string: &Rc<str>) -> Self {
+        Self(Repr::Owned(Inner::from(&**string)))
+    }
+}
+
+impl From<char> for ValueString {
+    fn from(c: char) -> Self {
+        Self(Repr::Owned(Inner::from(c.encode_utf8(&mut [0; 4]) as &str)))
+    }
+}
+
+impl From<ValueString> for String {
+    fn from(string: ValueString) -> Self {
+        string.into_string()
+    }
+}
+
+impl FromIterator<char> for ValueString {
+    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+        Self(Repr::Owned(Inner::from_iter(iter)))
+    }
+}
+
+impl<'a> FromIterator<&'a str> for ValueString {
+    fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
+        Self(Repr::Owned(Inner::from_iter(iter)))
+    }
+}
+
+impl PartialEq<str> for ValueString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ValueString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for ValueString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<ValueString> for str {
+    fn eq(&self, other: &ValueString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<ValueString> for &str {
+    fn eq(&self, other: &ValueString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<ValueString> for String {
+    fn eq(&self, other: &ValueString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl Serialize for ValueString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueStringVisitor;
+
+        impl Visitor<'_> for ValueStringVisitor {
+            type Value = ValueString;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<ValueString, E> {
+                Ok(v.into())
+            }
+
+            fn visit_string<E: serde::de::Error>(self, v: String) -> Result<ValueString, E> {
+                Ok(v.into())
+            }
+        }
+
+        deserializer.deserialize_string(ValueStringVisitor)
+    }
+}